@@ -0,0 +1,294 @@
+//! Options for compiling CUDA programs with NVRTC or nvcc.
+//!
+//! `CompileOptions` renders to the option strings NVRTC takes, which nvcc
+//! takes as well, always in the same order for the same settings, so that
+//! the strings can also be used as part of a cache key. The optix crate
+//! re-exports these types from `optix::cuda::nvrtc`.
+//!
+//! ```
+//! use optix_build::{Arch, CompileOptions, CppStandard};
+//!
+//! let options = CompileOptions::new()
+//!     .arch(Arch::Compute(70))
+//!     .std(CppStandard::Cpp14)
+//!     .relocatable_device_code(true)
+//!     .include_dir("/opt/optix/include")
+//!     .define("__x86_64");
+//! assert_eq!(
+//!     options.to_strings(),
+//!     [
+//!         "-arch=compute_70",
+//!         "-std=c++14",
+//!         "-rdc=true",
+//!         "-I/opt/optix/include",
+//!         "-D__x86_64",
+//!     ]
+//! );
+//! ```
+use std::collections::BTreeMap;
+use std::fmt;
+use std::path::{Path, PathBuf};
+
+/// The GPU architecture to compile for, given as major * 10 + minor version,
+/// e.g. `Compute(70)` for compute capability 7.0.
+#[derive(Debug, Copy, Clone, PartialEq, Eq, Hash)]
+pub enum Arch {
+    /// A virtual architecture, producing PTX that runs on this and later
+    /// architectures. This is what OptiX modules are compiled for.
+    Compute(u32),
+    /// A real architecture
+    Sm(u32),
+}
+
+impl fmt::Display for Arch {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            Arch::Compute(v) => write!(f, "compute_{}", v),
+            Arch::Sm(v) => write!(f, "sm_{}", v),
+        }
+    }
+}
+
+#[derive(Debug, Copy, Clone, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub enum CppStandard {
+    Cpp03,
+    Cpp11,
+    Cpp14,
+    Cpp17,
+}
+
+impl fmt::Display for CppStandard {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        f.write_str(match self {
+            CppStandard::Cpp03 => "c++03",
+            CppStandard::Cpp11 => "c++11",
+            CppStandard::Cpp14 => "c++14",
+            CppStandard::Cpp17 => "c++17",
+        })
+    }
+}
+
+/// Options for `optix::cuda::nvrtc::Program::compile()` or `PtxBuilder`.
+/// Anything left unset is not passed, so the compiler's default applies.
+#[derive(Debug, Clone, Default, PartialEq, Eq, Hash)]
+pub struct CompileOptions {
+    arch: Option<Arch>,
+    std: Option<CppStandard>,
+    relocatable_device_code: Option<bool>,
+    line_info: bool,
+    use_fast_math: bool,
+    ftz: Option<bool>,
+    prec_sqrt: Option<bool>,
+    prec_div: Option<bool>,
+    fmad: Option<bool>,
+    include_dirs: Vec<PathBuf>,
+    // sorted by name so that the order they're defined in doesn't matter
+    defines: BTreeMap<String, Option<String>>,
+    flags: Vec<String>,
+}
+
+impl CompileOptions {
+    pub fn new() -> CompileOptions {
+        CompileOptions::default()
+    }
+
+    /// `-arch`
+    pub fn arch(mut self, arch: Arch) -> Self {
+        self.arch = Some(arch);
+        self
+    }
+
+    /// `-std`
+    pub fn std(mut self, std: CppStandard) -> Self {
+        self.std = Some(std);
+        self
+    }
+
+    /// `-rdc`, needed for programs that call functions in other
+    /// translation units, and by OptiX for programs using `optixTrace()`
+    pub fn relocatable_device_code(mut self, rdc: bool) -> Self {
+        self.relocatable_device_code = Some(rdc);
+        self
+    }
+
+    /// `-lineinfo`, to map the PTX back to the source in profilers and
+    /// debuggers
+    pub fn line_info(mut self, line_info: bool) -> Self {
+        self.line_info = line_info;
+        self
+    }
+
+    /// `--use_fast_math`, which implies flushing denormals to zero, less
+    /// precise division and square roots, and fused multiply-adds. These can
+    /// be overridden individually with `ftz()`, `prec_div()`, `prec_sqrt()`
+    /// and `fmad()`.
+    pub fn use_fast_math(mut self, use_fast_math: bool) -> Self {
+        self.use_fast_math = use_fast_math;
+        self
+    }
+
+    /// `--ftz`: flush single precision denormals to zero
+    pub fn ftz(mut self, ftz: bool) -> Self {
+        self.ftz = Some(ftz);
+        self
+    }
+
+    /// `--prec-sqrt`: use IEEE round-to-nearest single precision square
+    /// roots
+    pub fn prec_sqrt(mut self, prec_sqrt: bool) -> Self {
+        self.prec_sqrt = Some(prec_sqrt);
+        self
+    }
+
+    /// `--prec-div`: use IEEE round-to-nearest single precision division
+    pub fn prec_div(mut self, prec_div: bool) -> Self {
+        self.prec_div = Some(prec_div);
+        self
+    }
+
+    /// `--fmad`: contract multiplies and adds into fused multiply-adds
+    pub fn fmad(mut self, fmad: bool) -> Self {
+        self.fmad = Some(fmad);
+        self
+    }
+
+    /// `-I`: add a directory to search for included headers in. Directories
+    /// are searched in the order they're added.
+    pub fn include_dir<P: AsRef<Path>>(mut self, dir: P) -> Self {
+        self.include_dirs.push(dir.as_ref().to_path_buf());
+        self
+    }
+
+    /// `-D`: define the macro `name`
+    pub fn define<S: Into<String>>(mut self, name: S) -> Self {
+        self.defines.insert(name.into(), None);
+        self
+    }
+
+    /// `-D`: define the macro `name` as `value`
+    pub fn define_value<S: Into<String>, V: Into<String>>(
+        mut self,
+        name: S,
+        value: V,
+    ) -> Self {
+        self.defines.insert(name.into(), Some(value.into()));
+        self
+    }
+
+    /// Pass `flag` to the compiler as it is, after all other options.
+    pub fn flag<S: Into<String>>(mut self, flag: S) -> Self {
+        self.flags.push(flag.into());
+        self
+    }
+
+    pub fn include_dirs(&self) -> &[PathBuf] {
+        &self.include_dirs
+    }
+
+    /// The options as NVRTC takes them.
+    ///
+    /// The typed options come first, in a fixed order, then the include
+    /// directories in the order they were added, the macros sorted by name,
+    /// and finally the raw flags in the order they were added.
+    pub fn to_strings(&self) -> Vec<String> {
+        let mut options = Vec::new();
+        if let Some(arch) = self.arch {
+            options.push(format!("-arch={}", arch));
+        }
+        if let Some(std) = self.std {
+            options.push(format!("-std={}", std));
+        }
+        if let Some(rdc) = self.relocatable_device_code {
+            options.push(format!("-rdc={}", rdc));
+        }
+        if self.line_info {
+            options.push("-lineinfo".to_string());
+        }
+        if self.use_fast_math {
+            options.push("--use_fast_math".to_string());
+        }
+        let toggles = [
+            ("--ftz", self.ftz),
+            ("--prec-sqrt", self.prec_sqrt),
+            ("--prec-div", self.prec_div),
+            ("--fmad", self.fmad),
+        ];
+        for (name, value) in toggles.iter() {
+            if let Some(value) = value {
+                options.push(format!("{}={}", name, value));
+            }
+        }
+        for dir in &self.include_dirs {
+            options.push(format!("-I{}", dir.display()));
+        }
+        for (name, value) in &self.defines {
+            options.push(match value {
+                Some(value) => format!("-D{}={}", name, value),
+                None => format!("-D{}", name),
+            });
+        }
+        options.extend(self.flags.iter().cloned());
+        options
+    }
+}
+
+impl fmt::Display for CompileOptions {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        f.write_str(&self.to_strings().join(" "))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{Arch, CompileOptions, CppStandard};
+
+    #[test]
+    fn render() {
+        assert!(CompileOptions::new().to_strings().is_empty());
+
+        let options = CompileOptions::new()
+            .flag("-default-device")
+            .define_value("__CUDA_INCLUDE_COMPILER_INTERNAL_HEADERS__", "1")
+            .include_dir("/opt/optix/include")
+            .fmad(false)
+            .use_fast_math(true)
+            .line_info(true)
+            .define("__x86_64")
+            .include_dir("/usr/local/cuda/include")
+            .arch(Arch::Sm(86))
+            .std(CppStandard::Cpp17);
+        assert_eq!(
+            options.to_strings(),
+            [
+                "-arch=sm_86",
+                "-std=c++17",
+                "-lineinfo",
+                "--use_fast_math",
+                "--fmad=false",
+                "-I/opt/optix/include",
+                "-I/usr/local/cuda/include",
+                "-D__CUDA_INCLUDE_COMPILER_INTERNAL_HEADERS__=1",
+                "-D__x86_64",
+                "-default-device",
+            ]
+        );
+        assert!(options.to_string().starts_with("-arch=sm_86 -std=c++17 "));
+    }
+
+    #[test]
+    fn macro_order_does_not_matter() {
+        let a = CompileOptions::new().define("A").define_value("B", "2");
+        let b = CompileOptions::new().define_value("B", "2").define("A");
+        assert_eq!(a.to_strings(), b.to_strings());
+        assert_eq!(a, b);
+
+        // but include directories are searched in order
+        let a = CompileOptions::new().include_dir("a").include_dir("b");
+        let b = CompileOptions::new().include_dir("b").include_dir("a");
+        assert_ne!(a.to_strings(), b.to_strings());
+
+        // a macro defined again takes the last value
+        let c = CompileOptions::new().define("A").define_value("A", "0");
+        assert_eq!(c.to_strings(), ["-DA=0"]);
+    }
+}
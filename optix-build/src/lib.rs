@@ -0,0 +1,506 @@
+//! Compiling CUDA programs to PTX in build scripts, so that applications
+//! can embed the PTX and don't need a CUDA toolkit where they run.
+//!
+//! In the `main()` of `build.rs`, with `optix-build` as a build dependency:
+//!
+//! ```no_run
+//! use optix_build::{Arch, CompileOptions, PtxBuilder};
+//!
+//! PtxBuilder::new()
+//!     .file("src/devicePrograms.cu")
+//!     .optix_include()
+//!     .options(
+//!         CompileOptions::new()
+//!             .arch(Arch::Compute(70))
+//!             .include_dir("src")
+//!             .define("__x86_64"),
+//!     )
+//!     .build()
+//!     .unwrap();
+//! ```
+//!
+//! Each file is compiled to `<file stem>.ptx` in `OUT_DIR`, from where
+//! `optix::include_ptx!("devicePrograms")` embeds it. The options are the
+//! same `CompileOptions` that `optix::cuda::nvrtc` compiles with at runtime.
+//!
+//! By default files are compiled with nvcc, found from the `NVCC` or
+//! `CUDA_ROOT` environment variables, or on the `PATH`. Any command that
+//! takes nvcc's options can be used instead with `Compiler::command()`, and
+//! `Compiler::function()` compiles in process, e.g. with NVRTC.
+use std::path::{Path, PathBuf};
+use std::process::Command;
+
+mod compile_options;
+pub use compile_options::{Arch, CompileOptions, CppStandard};
+
+#[derive(Debug, thiserror::Error)]
+pub enum Error {
+    #[error("OUT_DIR is not set, and no output directory was given")]
+    NoOutDir,
+    #[error(
+        "'{}' and '{}' would both be compiled to '{}'",
+        .path.display(),
+        .other.display(),
+        .ptx.display()
+    )]
+    DuplicateOutput {
+        path: PathBuf,
+        other: PathBuf,
+        ptx: PathBuf,
+    },
+    #[error("Could not run '{}': {source:}", .program.display())]
+    CompilerNotRun {
+        program: PathBuf,
+        source: std::io::Error,
+    },
+    #[error("Compiling '{}' to PTX failed:\n{log:}", .path.display())]
+    CompilationFailed { path: PathBuf, log: String },
+    #[error("Failed to write '{}': {source:}", .path.display())]
+    WriteFailed {
+        path: PathBuf,
+        source: std::io::Error,
+    },
+}
+type Result<T, E = Error> = std::result::Result<T, E>;
+
+/// Compiles the CUDA source file at the given path, passing it the given
+/// options, and returns the PTX or the compiler's log.
+pub type CompileFn =
+    Box<dyn Fn(&Path, &[String]) -> std::result::Result<String, String>>;
+
+/// How to compile CUDA source to PTX.
+pub enum Compiler {
+    /// Run `program` with `args`, then the compile options, then
+    /// `--ptx -o <output> <source>`, as for nvcc
+    Command { program: PathBuf, args: Vec<String> },
+    /// Call a function to compile in process
+    Function(CompileFn),
+}
+
+impl Compiler {
+    /// nvcc from the toolkit in `CUDA_ROOT`, or the one given by the `NVCC`
+    /// environment variable, or else the one on the `PATH`.
+    pub fn nvcc() -> Compiler {
+        let program = match (std::env::var_os("NVCC"), nvcc_in_cuda_root()) {
+            (Some(nvcc), _) => PathBuf::from(nvcc),
+            (None, Some(nvcc)) => nvcc,
+            (None, None) => PathBuf::from("nvcc"),
+        };
+        Compiler::command(program)
+    }
+
+    /// Run `program` to compile. It must take nvcc's options.
+    pub fn command<P: AsRef<Path>>(program: P) -> Compiler {
+        Compiler::Command {
+            program: program.as_ref().to_path_buf(),
+            args: Vec::new(),
+        }
+    }
+
+    /// Pass `arg` to the compiler command before the other options. Has no
+    /// effect on `Compiler::Function`.
+    pub fn arg<S: Into<String>>(mut self, arg: S) -> Compiler {
+        if let Compiler::Command { args, .. } = &mut self {
+            args.push(arg.into());
+        }
+        self
+    }
+
+    /// Compile by calling `f` with the path of each file and the compile
+    /// options, e.g. to compile with NVRTC, which takes the same `-I`, `-D`
+    /// and `-arch` options as nvcc.
+    pub fn function<F>(f: F) -> Compiler
+    where
+        F: Fn(&Path, &[String]) -> std::result::Result<String, String>
+            + 'static,
+    {
+        Compiler::Function(Box::new(f))
+    }
+}
+
+fn nvcc_in_cuda_root() -> Option<PathBuf> {
+    let root = PathBuf::from(std::env::var_os("CUDA_ROOT")?);
+    let nvcc = root.join("bin").join("nvcc");
+    if cfg!(windows) {
+        Some(nvcc.with_extension("exe"))
+    } else {
+        Some(nvcc)
+    }
+}
+
+/// Compiles a set of CUDA source files to PTX.
+pub struct PtxBuilder {
+    files: Vec<PathBuf>,
+    compiler: Option<Compiler>,
+    options: CompileOptions,
+    optix_include: bool,
+    out_dir: Option<PathBuf>,
+    cargo_metadata: bool,
+}
+
+impl Default for PtxBuilder {
+    fn default() -> PtxBuilder {
+        PtxBuilder {
+            files: Vec::new(),
+            compiler: None,
+            options: CompileOptions::new(),
+            optix_include: false,
+            out_dir: None,
+            cargo_metadata: true,
+        }
+    }
+}
+
+impl PtxBuilder {
+    pub fn new() -> PtxBuilder {
+        PtxBuilder::default()
+    }
+
+    /// Compile the CUDA source file at `path`.
+    pub fn file<P: AsRef<Path>>(mut self, path: P) -> Self {
+        self.files.push(path.as_ref().to_path_buf());
+        self
+    }
+
+    /// Compile with `compiler` instead of `Compiler::nvcc()`.
+    pub fn compiler(mut self, compiler: Compiler) -> Self {
+        self.compiler = Some(compiler);
+        self
+    }
+
+    /// Compile every file with `options`. The files in their include
+    /// directories are also watched for changes, unless
+    /// `cargo_metadata(false)`.
+    pub fn options(mut self, options: CompileOptions) -> Self {
+        self.options = options;
+        self
+    }
+
+    /// Also search the `include` directory of the OptiX SDK given by the
+    /// `OPTIX_ROOT` environment variable, if it is set, after the include
+    /// directories of the options.
+    pub fn optix_include(mut self) -> Self {
+        self.optix_include = true;
+        self
+    }
+
+    /// Write the PTX to `dir` instead of `OUT_DIR`.
+    pub fn out_dir<P: AsRef<Path>>(mut self, dir: P) -> Self {
+        self.out_dir = Some(dir.as_ref().to_path_buf());
+        self
+    }
+
+    /// Whether to print the `cargo:rerun-if-*` lines that make cargo run
+    /// the build script again when the sources change. On by default.
+    pub fn cargo_metadata(mut self, cargo_metadata: bool) -> Self {
+        self.cargo_metadata = cargo_metadata;
+        self
+    }
+
+    /// The options passed to the compiler for every file, including the
+    /// OptiX include directory if it was asked for.
+    pub fn compile_options(&self) -> CompileOptions {
+        let optix_root =
+            std::env::var_os("OPTIX_ROOT").filter(|_| self.optix_include);
+        match optix_root {
+            Some(root) => self
+                .options
+                .clone()
+                .include_dir(PathBuf::from(root).join("include")),
+            None => self.options.clone(),
+        }
+    }
+
+    /// The files and directories cargo should watch: the sources, the
+    /// directories they're in, for the headers they include from there, and
+    /// the include directories.
+    fn rerun_if_changed(&self, options: &CompileOptions) -> Vec<PathBuf> {
+        let mut paths: Vec<PathBuf> = Vec::new();
+        let dirs = self.files.iter().map(|path| match path.parent() {
+            Some(dir) if dir != Path::new("") => dir.to_path_buf(),
+            _ => PathBuf::from("."),
+        });
+        let all = self
+            .files
+            .iter()
+            .cloned()
+            .chain(dirs)
+            .chain(options.include_dirs().iter().cloned());
+        for path in all {
+            if !paths.contains(&path) {
+                paths.push(path);
+            }
+        }
+        paths
+    }
+
+    /// The path the PTX for the source file at `path` is written to.
+    fn ptx_path(&self, out_dir: &Path, path: &Path) -> PathBuf {
+        let stem = path.file_stem().unwrap_or(path.as_os_str());
+        out_dir.join(stem).with_extension("ptx")
+    }
+
+    /// Compile all the files, returning the paths of the PTX files written.
+    ///
+    /// # Errors
+    /// If there is no output directory, two files have the same stem, the
+    /// compiler can't be run, or compiling any of the files fails.
+    pub fn build(&self) -> Result<Vec<PathBuf>> {
+        let out_dir = match &self.out_dir {
+            Some(dir) => dir.clone(),
+            None => PathBuf::from(
+                std::env::var_os("OUT_DIR").ok_or(Error::NoOutDir)?,
+            ),
+        };
+        let nvcc;
+        let compiler = match &self.compiler {
+            Some(compiler) => compiler,
+            None => {
+                if self.cargo_metadata {
+                    println!("cargo:rerun-if-env-changed=NVCC");
+                    println!("cargo:rerun-if-env-changed=CUDA_ROOT");
+                }
+                nvcc = Compiler::nvcc();
+                &nvcc
+            }
+        };
+        let options = self.compile_options();
+        if self.cargo_metadata {
+            if self.optix_include {
+                println!("cargo:rerun-if-env-changed=OPTIX_ROOT");
+            }
+            for path in self.rerun_if_changed(&options) {
+                println!("cargo:rerun-if-changed={}", path.display());
+            }
+        }
+
+        let outputs: Vec<PathBuf> = self
+            .files
+            .iter()
+            .map(|path| self.ptx_path(&out_dir, path))
+            .collect();
+        for (i, ptx) in outputs.iter().enumerate() {
+            if let Some(j) = outputs[..i].iter().position(|p| p == ptx) {
+                return Err(Error::DuplicateOutput {
+                    path: self.files[i].clone(),
+                    other: self.files[j].clone(),
+                    ptx: ptx.clone(),
+                });
+            }
+        }
+
+        std::fs::create_dir_all(&out_dir).map_err(|source| {
+            Error::WriteFailed {
+                path: out_dir.clone(),
+                source,
+            }
+        })?;
+        let options = options.to_strings();
+        for (path, ptx) in self.files.iter().zip(&outputs) {
+            compile(compiler, path, &options, ptx)?;
+        }
+        Ok(outputs)
+    }
+}
+
+fn compile(
+    compiler: &Compiler,
+    path: &Path,
+    options: &[String],
+    ptx: &Path,
+) -> Result<()> {
+    match compiler {
+        Compiler::Command { program, args } => {
+            let output = Command::new(program)
+                .args(args)
+                .args(options)
+                .arg("--ptx")
+                .arg("-o")
+                .arg(ptx)
+                .arg(path)
+                .output()
+                .map_err(|source| Error::CompilerNotRun {
+                    program: program.clone(),
+                    source,
+                })?;
+            if !output.status.success() {
+                let mut log =
+                    String::from_utf8_lossy(&output.stderr).into_owned();
+                log.push_str(&String::from_utf8_lossy(&output.stdout));
+                return Err(Error::CompilationFailed {
+                    path: path.to_path_buf(),
+                    log,
+                });
+            }
+            Ok(())
+        }
+        Compiler::Function(f) => {
+            let contents =
+                f(path, options).map_err(|log| Error::CompilationFailed {
+                    path: path.to_path_buf(),
+                    log,
+                })?;
+            std::fs::write(ptx, contents).map_err(|source| Error::WriteFailed {
+                path: ptx.to_path_buf(),
+                source,
+            })
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{Arch, CompileOptions, Compiler, Error, PtxBuilder};
+    use std::fs;
+    use std::path::PathBuf;
+
+    fn temp_dir(name: &str) -> PathBuf {
+        let dir = std::env::temp_dir().join(format!(
+            "optix-build-{}-{}",
+            name,
+            std::process::id()
+        ));
+        let _ = fs::remove_dir_all(&dir);
+        fs::create_dir_all(&dir).unwrap();
+        dir
+    }
+
+    #[test]
+    fn watched_paths() {
+        let options = CompileOptions::new().include_dir("/opt/optix/include");
+        let builder = PtxBuilder::new()
+            .file("src/devicePrograms.cu")
+            .file("src/shadow.cu")
+            .file("other.cu")
+            .options(options.clone());
+        // headers next to the sources are included without -I
+        assert_eq!(
+            builder.rerun_if_changed(&options),
+            [
+                PathBuf::from("src/devicePrograms.cu"),
+                PathBuf::from("src/shadow.cu"),
+                PathBuf::from("other.cu"),
+                PathBuf::from("src"),
+                PathBuf::from("."),
+                PathBuf::from("/opt/optix/include"),
+            ]
+        );
+    }
+
+    #[test]
+    fn compile_with_function() {
+        let dir = temp_dir("function");
+        let source = dir.join("devicePrograms.cu");
+        fs::write(&source, "__global__ void f() {}").unwrap();
+
+        let builder = PtxBuilder::new()
+            .file(&source)
+            .options(CompileOptions::new().arch(Arch::Compute(70)))
+            .out_dir(dir.join("out"))
+            .cargo_metadata(false)
+            .compiler(Compiler::function(|path, options| {
+                let source = fs::read_to_string(path).unwrap();
+                if source.contains("error") {
+                    Err(format!("{}(1): error: broken", path.display()))
+                } else {
+                    Ok(format!("// {}\n// {}\n", options.join(" "), source))
+                }
+            }));
+        let outputs = builder.build().unwrap();
+        assert_eq!(outputs, [dir.join("out").join("devicePrograms.ptx")]);
+        assert_eq!(
+            fs::read_to_string(&outputs[0]).unwrap(),
+            "// -arch=compute_70\n// __global__ void f() {}\n"
+        );
+
+        fs::write(&source, "error").unwrap();
+        match builder.build() {
+            Err(Error::CompilationFailed { path, log }) => {
+                assert_eq!(path, source);
+                assert!(log.ends_with("error: broken"));
+            }
+            r => panic!("unexpected result {:?}", r),
+        }
+
+        // both would be written to devicePrograms.ptx
+        match builder
+            .file(dir.join("other").join("devicePrograms.cu"))
+            .build()
+        {
+            Err(Error::DuplicateOutput { .. }) => (),
+            r => panic!("unexpected result {:?}", r),
+        }
+
+        fs::remove_dir_all(&dir).unwrap();
+    }
+
+    #[cfg(unix)]
+    #[test]
+    fn compile_with_command() {
+        use std::os::unix::fs::PermissionsExt;
+
+        let dir = temp_dir("command");
+        // takes the options nvcc does, and writes them and the source as the
+        // PTX, or fails if the source contains "error"
+        let stub = dir.join("stub-nvcc");
+        fs::write(
+            &stub,
+            r#"#!/bin/sh
+args="$*"
+while [ $# -gt 0 ]; do
+    case "$1" in
+        -o) out="$2"; shift 2 ;;
+        *) src="$1"; shift ;;
+    esac
+done
+if grep -q error "$src"; then
+    echo "$src(1): error: broken" >&2
+    exit 1
+fi
+{ echo "// $args"; cat "$src"; } > "$out"
+"#,
+        )
+        .unwrap();
+        fs::set_permissions(&stub, fs::Permissions::from_mode(0o755)).unwrap();
+
+        let a = dir.join("a.cu");
+        let b = dir.join("b.cu");
+        fs::write(&a, "// a\n").unwrap();
+        fs::write(&b, "// b\n").unwrap();
+        let builder = PtxBuilder::new()
+            .file(&a)
+            .file(&b)
+            .options(CompileOptions::new().define("A"))
+            .out_dir(&dir)
+            .cargo_metadata(false)
+            .compiler(Compiler::command(&stub).arg("-std=c++14"));
+        let outputs = builder.build().unwrap();
+        assert_eq!(outputs, [dir.join("a.ptx"), dir.join("b.ptx")]);
+        assert_eq!(
+            fs::read_to_string(&outputs[1]).unwrap(),
+            format!(
+                "// -std=c++14 -DA --ptx -o {} {}\n// b\n",
+                outputs[1].display(),
+                b.display()
+            )
+        );
+
+        fs::write(&b, "error\n").unwrap();
+        match builder.build() {
+            Err(Error::CompilationFailed { path, log }) => {
+                assert_eq!(path, b);
+                assert_eq!(log, format!("{}(1): error: broken\n", b.display()));
+            }
+            r => panic!("unexpected result {:?}", r),
+        }
+
+        let builder = PtxBuilder::new()
+            .file(&a)
+            .out_dir(&dir)
+            .cargo_metadata(false)
+            .compiler(Compiler::command(dir.join("missing")));
+        assert!(matches!(builder.build(), Err(Error::CompilerNotRun { .. })));
+
+        fs::remove_dir_all(&dir).unwrap();
+    }
+}
@@ -162,6 +162,7 @@ pub enum OptixVertexFormat {
     OPTIX_VERTEX_FORMAT_SNORM16_2 = 8486,
 }
 #[repr(C)]
+#[derive(Debug, Copy, Clone)]
 pub struct OptixBuildInputTriangleArray {
     pub vertexBuffers: *const CUdeviceptr,
     pub numVertices: ::std::os::raw::c_uint,
@@ -190,6 +191,7 @@ pub struct OptixAabb {
     pub maxZ: f32,
 }
 #[repr(C)]
+#[derive(Debug, Copy, Clone)]
 pub struct OptixBuildInputCustomPrimitiveArray {
     pub aabbBuffers: *const CUdeviceptr,
     pub numPrimitives: ::std::os::raw::c_uint,
@@ -202,6 +204,7 @@ pub struct OptixBuildInputCustomPrimitiveArray {
     pub primitiveIndexOffset: ::std::os::raw::c_uint,
 }
 #[repr(C)]
+#[derive(Debug, Copy, Clone)]
 pub struct OptixBuildInputInstanceArray {
     pub instances: CUdeviceptr,
     pub numInstances: ::std::os::raw::c_uint,
@@ -584,295 +587,6 @@ pub type OptixQueryFunctionTable_t = ::std::option::Option<
         sizeOfTable: usize,
     ) -> OptixResult,
 >;
-extern "C" {
-    pub fn optixGetErrorName(
-        result: OptixResult,
-    ) -> *const ::std::os::raw::c_char;
-}
-extern "C" {
-    pub fn optixGetErrorString(
-        result: OptixResult,
-    ) -> *const ::std::os::raw::c_char;
-}
-extern "C" {
-    pub fn optixDeviceContextCreate(
-        fromContext: CUcontext,
-        options: *const OptixDeviceContextOptions,
-        context: *mut OptixDeviceContext,
-    ) -> OptixResult;
-}
-extern "C" {
-    pub fn optixDeviceContextDestroy(
-        context: OptixDeviceContext,
-    ) -> OptixResult;
-}
-extern "C" {
-    pub fn optixDeviceContextGetProperty(
-        context: OptixDeviceContext,
-        property: OptixDeviceProperty,
-        value: *mut ::std::os::raw::c_void,
-        sizeInBytes: usize,
-    ) -> OptixResult;
-}
-extern "C" {
-    pub fn optixDeviceContextSetLogCallback(
-        context: OptixDeviceContext,
-        callbackFunction: OptixLogCallback,
-        callbackData: *mut ::std::os::raw::c_void,
-        callbackLevel: ::std::os::raw::c_uint,
-    ) -> OptixResult;
-}
-extern "C" {
-    pub fn optixDeviceContextSetCacheEnabled(
-        context: OptixDeviceContext,
-        enabled: ::std::os::raw::c_int,
-    ) -> OptixResult;
-}
-extern "C" {
-    pub fn optixDeviceContextSetCacheLocation(
-        context: OptixDeviceContext,
-        location: *const ::std::os::raw::c_char,
-    ) -> OptixResult;
-}
-extern "C" {
-    pub fn optixDeviceContextSetCacheDatabaseSizes(
-        context: OptixDeviceContext,
-        lowWaterMark: usize,
-        highWaterMark: usize,
-    ) -> OptixResult;
-}
-extern "C" {
-    pub fn optixDeviceContextGetCacheEnabled(
-        context: OptixDeviceContext,
-        enabled: *mut ::std::os::raw::c_int,
-    ) -> OptixResult;
-}
-extern "C" {
-    pub fn optixDeviceContextGetCacheLocation(
-        context: OptixDeviceContext,
-        location: *mut ::std::os::raw::c_char,
-        locationSize: usize,
-    ) -> OptixResult;
-}
-extern "C" {
-    pub fn optixDeviceContextGetCacheDatabaseSizes(
-        context: OptixDeviceContext,
-        lowWaterMark: *mut usize,
-        highWaterMark: *mut usize,
-    ) -> OptixResult;
-}
-extern "C" {
-    pub fn optixPipelineCreate(
-        context: OptixDeviceContext,
-        pipelineCompileOptions: *const OptixPipelineCompileOptions,
-        pipelineLinkOptions: *const OptixPipelineLinkOptions,
-        programGroups: *const OptixProgramGroup,
-        numProgramGroups: ::std::os::raw::c_uint,
-        logString: *mut ::std::os::raw::c_char,
-        logStringSize: *mut usize,
-        pipeline: *mut OptixPipeline,
-    ) -> OptixResult;
-}
-extern "C" {
-    pub fn optixPipelineDestroy(pipeline: OptixPipeline) -> OptixResult;
-}
-extern "C" {
-    pub fn optixPipelineSetStackSize(
-        pipeline: OptixPipeline,
-        directCallableStackSizeFromTraversal: ::std::os::raw::c_uint,
-        directCallableStackSizeFromState: ::std::os::raw::c_uint,
-        continuationStackSize: ::std::os::raw::c_uint,
-        maxTraversableGraphDepth: ::std::os::raw::c_uint,
-    ) -> OptixResult;
-}
-extern "C" {
-    pub fn optixModuleCreateFromPTX(
-        context: OptixDeviceContext,
-        moduleCompileOptions: *const OptixModuleCompileOptions,
-        pipelineCompileOptions: *const OptixPipelineCompileOptions,
-        PTX: *const ::std::os::raw::c_char,
-        PTXsize: usize,
-        logString: *mut ::std::os::raw::c_char,
-        logStringSize: *mut usize,
-        module: *mut OptixModule,
-    ) -> OptixResult;
-}
-extern "C" {
-    pub fn optixModuleDestroy(module: OptixModule) -> OptixResult;
-}
-extern "C" {
-    pub fn optixProgramGroupGetStackSize(
-        programGroup: OptixProgramGroup,
-        stackSizes: *mut OptixStackSizes,
-    ) -> OptixResult;
-}
-extern "C" {
-    pub fn optixProgramGroupCreate(
-        context: OptixDeviceContext,
-        programDescriptions: *const OptixProgramGroupDesc,
-        numProgramGroups: ::std::os::raw::c_uint,
-        options: *const OptixProgramGroupOptions,
-        logString: *mut ::std::os::raw::c_char,
-        logStringSize: *mut usize,
-        programGroups: *mut OptixProgramGroup,
-    ) -> OptixResult;
-}
-extern "C" {
-    pub fn optixProgramGroupDestroy(
-        programGroup: OptixProgramGroup,
-    ) -> OptixResult;
-}
-extern "C" {
-    pub fn optixLaunch(
-        pipeline: OptixPipeline,
-        stream: CUstream,
-        pipelineParams: CUdeviceptr,
-        pipelineParamsSize: usize,
-        sbt: *const OptixShaderBindingTable,
-        width: ::std::os::raw::c_uint,
-        height: ::std::os::raw::c_uint,
-        depth: ::std::os::raw::c_uint,
-    ) -> OptixResult;
-}
-extern "C" {
-    pub fn optixSbtRecordPackHeader(
-        programGroup: OptixProgramGroup,
-        sbtRecordHeaderHostPointer: *mut ::std::os::raw::c_void,
-    ) -> OptixResult;
-}
-extern "C" {
-    pub fn optixAccelComputeMemoryUsage(
-        context: OptixDeviceContext,
-        accelOptions: *const OptixAccelBuildOptions,
-        buildInputs: *const OptixBuildInput,
-        numBuildInputs: ::std::os::raw::c_uint,
-        bufferSizes: *mut OptixAccelBufferSizes,
-    ) -> OptixResult;
-}
-extern "C" {
-    pub fn optixAccelBuild(
-        context: OptixDeviceContext,
-        stream: CUstream,
-        accelOptions: *const OptixAccelBuildOptions,
-        buildInputs: *const OptixBuildInput,
-        numBuildInputs: ::std::os::raw::c_uint,
-        tempBuffer: CUdeviceptr,
-        tempBufferSizeInBytes: usize,
-        outputBuffer: CUdeviceptr,
-        outputBufferSizeInBytes: usize,
-        outputHandle: *mut OptixTraversableHandle,
-        emittedProperties: *const OptixAccelEmitDesc,
-        numEmittedProperties: ::std::os::raw::c_uint,
-    ) -> OptixResult;
-}
-extern "C" {
-    pub fn optixAccelGetRelocationInfo(
-        context: OptixDeviceContext,
-        handle: OptixTraversableHandle,
-        info: *mut OptixAccelRelocationInfo,
-    ) -> OptixResult;
-}
-extern "C" {
-    pub fn optixAccelCheckRelocationCompatibility(
-        context: OptixDeviceContext,
-        info: *const OptixAccelRelocationInfo,
-        compatible: *mut ::std::os::raw::c_int,
-    ) -> OptixResult;
-}
-extern "C" {
-    pub fn optixAccelRelocate(
-        context: OptixDeviceContext,
-        stream: CUstream,
-        info: *const OptixAccelRelocationInfo,
-        instanceTraversableHandles: CUdeviceptr,
-        numInstanceTraversableHandles: usize,
-        targetAccel: CUdeviceptr,
-        targetAccelSizeInBytes: usize,
-        targetHandle: *mut OptixTraversableHandle,
-    ) -> OptixResult;
-}
-extern "C" {
-    pub fn optixAccelCompact(
-        context: OptixDeviceContext,
-        stream: CUstream,
-        inputHandle: OptixTraversableHandle,
-        outputBuffer: CUdeviceptr,
-        outputBufferSizeInBytes: usize,
-        outputHandle: *mut OptixTraversableHandle,
-    ) -> OptixResult;
-}
-extern "C" {
-    pub fn optixConvertPointerToTraversableHandle(
-        onDevice: OptixDeviceContext,
-        pointer: CUdeviceptr,
-        traversableType: OptixTraversableType,
-        traversableHandle: *mut OptixTraversableHandle,
-    ) -> OptixResult;
-}
-extern "C" {
-    pub fn optixDenoiserCreate(
-        context: OptixDeviceContext,
-        options: *const OptixDenoiserOptions,
-        denoiser: *mut OptixDenoiser,
-    ) -> OptixResult;
-}
-extern "C" {
-    pub fn optixDenoiserSetModel(
-        denoiser: OptixDenoiser,
-        kind: OptixDenoiserModelKind,
-        data: *mut ::std::os::raw::c_void,
-        sizeInBytes: usize,
-    ) -> OptixResult;
-}
-extern "C" {
-    pub fn optixDenoiserDestroy(denoiser: OptixDenoiser) -> OptixResult;
-}
-extern "C" {
-    pub fn optixDenoiserComputeMemoryResources(
-        denoiser: OptixDenoiser,
-        outputWidth: ::std::os::raw::c_uint,
-        outputHeight: ::std::os::raw::c_uint,
-        returnSizes: *mut OptixDenoiserSizes,
-    ) -> OptixResult;
-}
-extern "C" {
-    pub fn optixDenoiserSetup(
-        denoiser: OptixDenoiser,
-        stream: CUstream,
-        outputWidth: ::std::os::raw::c_uint,
-        outputHeight: ::std::os::raw::c_uint,
-        denoiserState: CUdeviceptr,
-        denoiserStateSizeInBytes: usize,
-        scratch: CUdeviceptr,
-        scratchSizeInBytes: usize,
-    ) -> OptixResult;
-}
-extern "C" {
-    pub fn optixDenoiserInvoke(
-        denoiser: OptixDenoiser,
-        stream: CUstream,
-        params: *const OptixDenoiserParams,
-        denoiserState: CUdeviceptr,
-        denoiserStateSizeInBytes: usize,
-        inputLayers: *const OptixImage2D,
-        numInputLayers: ::std::os::raw::c_uint,
-        inputOffsetX: ::std::os::raw::c_uint,
-        inputOffsetY: ::std::os::raw::c_uint,
-        outputLayer: *const OptixImage2D,
-        scratch: CUdeviceptr,
-        scratchSizeInBytes: usize,
-    ) -> OptixResult;
-}
-extern "C" {
-    pub fn optixDenoiserComputeIntensity(
-        denoiser: OptixDenoiser,
-        stream: CUstream,
-        inputImage: *const OptixImage2D,
-        outputIntensity: CUdeviceptr,
-        scratch: CUdeviceptr,
-        scratchSizeInBytes: usize,
-    ) -> OptixResult;
-}
 #[repr(C)]
 #[derive(Debug, Copy, Clone)]
 pub struct OptixFunctionTable {
@@ -1169,6 +883,7 @@ pub const OptixInstanceByteAlignment: usize = 16;
 pub const OptixAabbBufferByteAlignment: usize = 8;
 pub const OptixGeometryTransformByteAlignment: usize = 16;
 pub const OptixTransformByteAlignment: usize = 64;
+pub const OptixAbiVersion: ::std::os::raw::c_int = 22;
 #[repr(u32)]
 #[derive(Debug, Copy, Clone, PartialEq, Eq, Hash)]
 pub enum OptixGeometryFlags {
@@ -1,19 +1,175 @@
-use std::path::{Path, PathBuf};
+#[cfg(feature = "bindgen")]
+use std::path::Path;
+use std::path::PathBuf;
+
+/// The OptiX SDK version selected by the `optix-7-*` features, or 7.0 if none
+/// are enabled. Each version has a different ABI, so at most one may be.
+fn optix_bindings_version() -> String {
+    let mut versions: Vec<String> = std::env::vars()
+        .filter_map(|(var, _)| {
+            var.strip_prefix("CARGO_FEATURE_OPTIX_")
+                .map(|version| version.to_lowercase())
+        })
+        .collect();
+    match versions.len() {
+        0 => "7_0".to_string(),
+        1 => versions.remove(0),
+        _ => {
+            versions.sort();
+            panic!(
+                "The optix-{} features are mutually exclusive. Disable default \
+                 features to select an OptiX version other than 7.0.",
+                versions.join(", optix-").replace('_', "-")
+            )
+        }
+    }
+}
+
+/// The OptiX releases after 7.0 that add API. Each gets an `optix_7_x` cfg
+/// set when the selected version is that release or newer, so wrappers can be
+/// gated on the API they need rather than on a single version.
+const OPTIX_VERSIONS: &[&str] = &["7_3", "7_4", "7_5"];
+
+/// Set the `optix_7_x` cfgs for the selected version and pass the version on
+/// to dependents as `DEP_OPTIX_VERSION` so they can do the same.
+fn emit_version_cfgs() {
+    let selected = optix_bindings_version();
+    let minor = |v: &str| v.trim_start_matches("7_").parse::<u32>().unwrap();
+    for version in OPTIX_VERSIONS {
+        println!("cargo:rustc-check-cfg=cfg(optix_{})", version);
+        if minor(version) <= minor(&selected) {
+            println!("cargo:rustc-cfg=optix_{}", version);
+        }
+    }
+    println!("cargo:version={}", selected);
+}
 
 fn main() {
-    let optix_root = std::env::var("OPTIX_ROOT")
-        .expect("Could not get OPTIX_ROOT from environment");
-    let cuda_root = std::env::var("CUDA_ROOT")
-        .expect("Could not get CUDA_ROOT from environment");
+    println!("cargo:rerun-if-env-changed=OPTIX_ROOT");
+    println!("cargo:rerun-if-env-changed=CUDA_ROOT");
+    println!("cargo:rerun-if-env-changed={}", UPDATE_BINDINGS_VAR);
+
+    emit_version_cfgs();
+
+    let optix_root = std::env::var("OPTIX_ROOT").ok();
+    let cuda_root = std::env::var("CUDA_ROOT").ok();
+
+    match (&optix_root, &cuda_root) {
+        #[cfg(all(feature = "bindgen", not(feature = "prebuilt-bindings")))]
+        (Some(optix_root), Some(cuda_root)) => {
+            check_sdk_version(optix_root);
+            bindgen_cuda(cuda_root);
+            bindgen_optix(optix_root, cuda_root);
+        }
+        _ => {
+            if cfg!(feature = "bindgen") && !cfg!(feature = "prebuilt-bindings")
+            {
+                println!(
+                    "cargo:warning=OPTIX_ROOT or CUDA_ROOT not set, using \
+                     pre-generated bindings for OptiX {}",
+                    optix_bindings_version().replace('_', ".")
+                );
+            }
+            copy_prebuilt_bindings();
+        }
+    }
+
+    // The CUDA functions the optix crate calls are loaded at runtime by
+    // `cuda_loader`, so the toolkit only needs to be linked for code that
+    // calls the rest of the bindings directly.
+    match &cuda_root {
+        Some(cuda_root) => link_cuda(cuda_root),
+        None => println!(
+            "cargo:warning=CUDA_ROOT not set, the CUDA libraries will not be \
+             linked"
+        ),
+    }
+}
+
+/// Set this to a non-empty value to copy the bindings generated by the
+/// bindgen feature over the pre-generated ones in bindings/. Otherwise the
+/// build only ever writes to OUT_DIR.
+const UPDATE_BINDINGS_VAR: &str = "OPTIX_SYS_UPDATE_BINDINGS";
+
+fn prebuilt_bindings_path(name: &str) -> PathBuf {
+    PathBuf::from(std::env::var("CARGO_MANIFEST_DIR").unwrap())
+        .join("bindings")
+        .join(name)
+}
+
+fn optix_bindings_name() -> String {
+    format!("optix_wrapper_{}.rs", optix_bindings_version())
+}
+
+/// Check that the SDK in `optix_root` is the version selected by the features
+/// so we don't silently generate bindings the wrappers were not written for.
+#[cfg(feature = "bindgen")]
+fn check_sdk_version(optix_root: &str) {
+    let header = PathBuf::from(optix_root).join("include").join("optix.h");
+    println!("cargo:rerun-if-changed={}", header.display());
+
+    let text = std::fs::read_to_string(&header).unwrap_or_else(|e| {
+        panic!("Could not read {}: {}", header.display(), e)
+    });
 
-    bindgen_cuda(&cuda_root);
-    bindgen_optix(&optix_root, &cuda_root);
+    // OPTIX_VERSION is major * 10000 + minor * 100 + micro
+    let version = text
+        .lines()
+        .filter_map(|l| l.trim().strip_prefix("#define OPTIX_VERSION"))
+        .find_map(|v| v.trim().parse::<u32>().ok())
+        .unwrap_or_else(|| {
+            panic!("Could not find OPTIX_VERSION in {}", header.display())
+        });
+    let sdk_version =
+        format!("{}_{}", version / 10000, (version % 10000) / 100);
+
+    if sdk_version != optix_bindings_version() {
+        panic!(
+            "OPTIX_ROOT points to an OptiX {} SDK but the enabled features \
+             select OptiX {}. Enable the optix-{} feature or point OPTIX_ROOT \
+             at a matching SDK.",
+            sdk_version.replace('_', "."),
+            optix_bindings_version().replace('_', "."),
+            sdk_version.replace('_', "-"),
+        );
+    }
 }
 
+fn copy_prebuilt_bindings() {
+    let out_dir = PathBuf::from(std::env::var("OUT_DIR").unwrap());
+
+    for (src, dst) in &[
+        (optix_bindings_name(), "optix_wrapper.rs"),
+        ("cuda_wrapper.rs".to_string(), "cuda_wrapper.rs"),
+    ] {
+        let src = prebuilt_bindings_path(src);
+        println!("cargo:rerun-if-changed={}", src.display());
+        if !src.is_file() {
+            panic!(
+                "There are no pre-generated bindings for OptiX {} ({}). Set \
+                 OPTIX_ROOT and CUDA_ROOT to an OptiX {} SDK and CUDA toolkit \
+                 with the bindgen feature enabled to generate them.",
+                optix_bindings_version().replace('_', "."),
+                src.display(),
+                optix_bindings_version().replace('_', "."),
+            );
+        }
+        std::fs::copy(&src, out_dir.join(dst)).unwrap_or_else(|e| {
+            panic!(
+                "Could not copy pre-generated bindings {}: {}",
+                src.display(),
+                e
+            )
+        });
+    }
+}
+
+#[cfg(feature = "bindgen")]
 fn get_modified_time(path: &Path) -> std::time::SystemTime {
     std::fs::metadata(path).unwrap().modified().unwrap()
 }
 
+#[cfg(feature = "bindgen")]
 fn bindgen_optix(optix_root: &str, cuda_root: &str) {
     let out_path = PathBuf::from(std::env::var("OUT_DIR").unwrap())
         .join("optix_wrapper.rs");
@@ -22,6 +178,7 @@ fn bindgen_optix(optix_root: &str, cuda_root: &str) {
         PathBuf::from(std::env::var("CARGO_MANIFEST_DIR").unwrap())
             .join("src")
             .join("optix_wrapper.h");
+    println!("cargo:rerun-if-changed={}", header_path.display());
 
     if !out_path.is_file()
         || get_modified_time(&out_path) < get_modified_time(&header_path)
@@ -36,7 +193,6 @@ fn bindgen_optix(optix_root: &str, cuda_root: &str) {
             .whitelist_type("MissRecord")
             .whitelist_type("HitgroupRecord")
             .blacklist_type("OptixBuildInput")
-            .whitelist_function("optix.*")
             .whitelist_var("OptixSbtRecordHeaderSize")
             .whitelist_var("OptixSbtRecordAlignment")
             .whitelist_var("OptixAccelBufferByteAlignment")
@@ -44,6 +200,7 @@ fn bindgen_optix(optix_root: &str, cuda_root: &str) {
             .whitelist_var("OptixAabbBufferByteAlignment")
             .whitelist_var("OptixGeometryTransformByteAlignment")
             .whitelist_var("OptixTransformByteAlignment")
+            .whitelist_var("OptixAbiVersion")
             .layout_tests(false)
             .generate_comments(false)
             .rustified_enum("OptixResult")
@@ -61,28 +218,15 @@ fn bindgen_optix(optix_root: &str, cuda_root: &str) {
             .generate()
             .expect("Unable to generate optix bindings");
 
-        let dbg_path = std::path::PathBuf::from(
-            std::env::var("CARGO_MANIFEST_DIR").unwrap(),
-        );
-        bindings
-            .write_to_file(dbg_path.join("optix_wrapper.rs"))
-            .expect("Couldn't write bindings!");
-
         bindings
-            .write_to_file(out_path)
+            .write_to_file(&out_path)
             .expect("Couldn't write bindings!");
     }
 
-    let dst_capi = cmake::Config::new("optix_stubs-capi")
-        .define("INC_OPTIX", &format!("{}/include", optix_root))
-        .define("INC_CUDA", &format!("{}/include", cuda_root))
-        .always_configure(false)
-        .build();
-
-    println!("cargo:rustc-link-search=native={}", dst_capi.display());
-    println!("cargo:rustc-link-lib=static=optix_stubs-capi");
+    update_prebuilt_bindings(&out_path, &optix_bindings_name());
 }
 
+#[cfg(feature = "bindgen")]
 fn bindgen_cuda(cuda_root: &str) {
     let out_path = std::path::PathBuf::from(std::env::var("OUT_DIR").unwrap())
         .join("cuda_wrapper.rs");
@@ -91,6 +235,7 @@ fn bindgen_cuda(cuda_root: &str) {
         std::path::PathBuf::from(std::env::var("CARGO_MANIFEST_DIR").unwrap())
             .join("src")
             .join("cuda_wrapper.h");
+    println!("cargo:rerun-if-changed={}", header_path.display());
 
     if !out_path.is_file()
         || get_modified_time(&out_path) < get_modified_time(&header_path)
@@ -121,22 +266,41 @@ fn bindgen_cuda(cuda_root: &str) {
             .generate()
             .expect("Unable to generate cuda bindings");
 
-        let dbg_path = std::path::PathBuf::from(
-            std::env::var("CARGO_MANIFEST_DIR").unwrap(),
-        );
         bindings
-            .write_to_file(dbg_path.join("cuda_wrapper.rs"))
+            .write_to_file(&out_path)
             .expect("Couldn't write bindings!");
+    }
 
-        bindings
-            .write_to_file(out_path)
-            .expect("Couldn't write bindings!");
+    update_prebuilt_bindings(&out_path, "cuda_wrapper.rs");
+}
+
+/// Copy the freshly generated bindings at `generated` over the pre-generated
+/// bindings `name` if `UPDATE_BINDINGS_VAR` is set.
+#[cfg(feature = "bindgen")]
+fn update_prebuilt_bindings(generated: &Path, name: &str) {
+    if std::env::var_os(UPDATE_BINDINGS_VAR)
+        .unwrap_or_default()
+        .is_empty()
+    {
+        return;
     }
 
+    let dst = prebuilt_bindings_path(name);
+    std::fs::copy(generated, &dst).unwrap_or_else(|e| {
+        panic!(
+            "Could not update pre-generated bindings {}: {}",
+            dst.display(),
+            e
+        )
+    });
     println!(
-        "cargo:rustc-link-search=native={}",
-        format!("{}/lib64", cuda_root)
+        "cargo:warning=Updated pre-generated bindings {}",
+        dst.display()
     );
+}
+
+fn link_cuda(cuda_root: &str) {
+    println!("cargo:rustc-link-search=native={}/lib64", cuda_root);
     println!("cargo:rustc-link-lib=dylib=cudart");
     println!("cargo:rustc-link-lib=dylib=cuda");
     println!("cargo:rustc-link-lib=dylib=nvrtc");
@@ -1,5 +1,4 @@
 use optix_sys::cuda_sys::{cudaFree, cudaGetDeviceCount};
-use optix_sys::{optixInit, OptixResult};
 
 fn main() {
     unsafe {
@@ -11,9 +10,8 @@ fn main() {
         }
         println!("Found {} CUDA devices", num_devices);
 
-        let result = optixInit();
-        if result != OptixResult::OPTIX_SUCCESS {
-            panic!("OptiX init failed!");
+        if let Err(e) = optix_sys::loader::load() {
+            panic!("OptiX init failed: {}", e);
         }
 
         println!("OptiX initialized successfully! Yay!");
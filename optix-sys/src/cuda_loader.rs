@@ -0,0 +1,156 @@
+//! Runtime loader for the CUDA driver, runtime and NVRTC libraries.
+//!
+//! Like the OptiX function table (see `loader`), the CUDA entry points used by
+//! the safe wrappers are looked up in the installed libraries the first time
+//! they're called rather than linked against, so binaries that never call
+//! into CUDA, such as host-only tests, can be built without a toolkit.
+//! `cuda_sys` exports these functions in place of the `extern` declarations
+//! of the same names in the bindings.
+//!
+//! The libraries are looked for in `$CUDA_ROOT` first if it is set, then on
+//! the system library path. If a library can't be opened or doesn't export
+//! the function, the call returns `CUDA_ERROR_NOT_INITIALIZED`,
+//! `cudaErrorInitializationError` or `NVRTC_ERROR_INTERNAL_ERROR` rather than
+//! calling through a null pointer.
+
+use std::path::PathBuf;
+use std::sync::OnceLock;
+
+/// A library that is opened the first time one of its functions is called.
+pub(crate) struct CudaLibrary {
+    names: &'static [&'static str],
+    library: OnceLock<Option<libloading::Library>>,
+}
+
+#[cfg(target_os = "windows")]
+pub(crate) static DRIVER: CudaLibrary = CudaLibrary::new(&["nvcuda.dll"]);
+#[cfg(not(target_os = "windows"))]
+pub(crate) static DRIVER: CudaLibrary =
+    CudaLibrary::new(&["libcuda.so.1", "libcuda.so"]);
+
+#[cfg(target_os = "windows")]
+pub(crate) static RUNTIME: CudaLibrary = CudaLibrary::new(&[
+    "cudart64_12.dll",
+    "cudart64_110.dll",
+    "cudart64_102.dll",
+]);
+#[cfg(not(target_os = "windows"))]
+pub(crate) static RUNTIME: CudaLibrary = CudaLibrary::new(&[
+    "libcudart.so",
+    "libcudart.so.12",
+    "libcudart.so.11.0",
+    "libcudart.so.10.2",
+]);
+
+#[cfg(target_os = "windows")]
+pub(crate) static NVRTC: CudaLibrary = CudaLibrary::new(&[
+    "nvrtc64_120_0.dll",
+    "nvrtc64_112_0.dll",
+    "nvrtc64_111_0.dll",
+    "nvrtc64_110_0.dll",
+    "nvrtc64_102_0.dll",
+]);
+#[cfg(not(target_os = "windows"))]
+pub(crate) static NVRTC: CudaLibrary = CudaLibrary::new(&[
+    "libnvrtc.so",
+    "libnvrtc.so.12",
+    "libnvrtc.so.11.2",
+    "libnvrtc.so.11.1",
+    "libnvrtc.so.11.0",
+    "libnvrtc.so.10.2",
+]);
+
+impl CudaLibrary {
+    const fn new(names: &'static [&'static str]) -> CudaLibrary {
+        CudaLibrary {
+            names,
+            library: OnceLock::new(),
+        }
+    }
+
+    fn library(&self) -> Option<&libloading::Library> {
+        self.library
+            .get_or_init(|| {
+                let root = std::env::var_os("CUDA_ROOT").map(|root| {
+                    let dir = if cfg!(target_os = "windows") {
+                        "bin"
+                    } else {
+                        "lib64"
+                    };
+                    PathBuf::from(root).join(dir)
+                });
+                self.names
+                    .iter()
+                    .flat_map(|name| {
+                        root.iter()
+                            .map(move |root| root.join(name))
+                            .chain(std::iter::once(PathBuf::from(name)))
+                    })
+                    .find_map(|path| unsafe {
+                        libloading::Library::new(path).ok()
+                    })
+            })
+            .as_ref()
+    }
+
+    /// Look up the function `name` in the library, or `None` if either
+    /// couldn't be found.
+    ///
+    /// # Safety
+    /// `T` must be the type of the function.
+    pub(crate) unsafe fn symbol<T: Copy>(&self, name: &str) -> Option<T> {
+        let library = self.library()?;
+        library.get::<T>(name.as_bytes()).ok().map(|symbol| *symbol)
+    }
+}
+
+/// Define functions that forward to the function of the same name in one of
+/// the libraries above, or return the given value if it can't be loaded.
+macro_rules! cuda_api {
+    ($(
+        $library:ident fn $name:ident($($arg:ident: $ty:ty),* $(,)?)
+            -> $ret:ty = $unavailable:expr;
+    )*) => {
+        $(
+            pub unsafe fn $name($($arg: $ty),*) -> $ret {
+                type Function = unsafe extern "C" fn($($ty),*) -> $ret;
+                static FUNCTION: std::sync::OnceLock<Option<Function>> =
+                    std::sync::OnceLock::new();
+                let function = FUNCTION.get_or_init(|| {
+                    $crate::cuda_loader::$library.symbol(stringify!($name))
+                });
+                match function {
+                    Some(f) => f($($arg),*),
+                    None => $unavailable,
+                }
+            }
+        )*
+    };
+}
+pub(crate) use cuda_api;
+
+#[cfg(test)]
+mod tests {
+    use super::CudaLibrary;
+
+    #[test]
+    fn missing_library() {
+        let library = CudaLibrary::new(&["/nonexistent/libcudart.so"]);
+        let symbol: Option<unsafe extern "C" fn() -> i32> =
+            unsafe { library.symbol("cudaDeviceSynchronize") };
+        assert!(symbol.is_none());
+    }
+
+    #[cfg(target_os = "linux")]
+    #[test]
+    fn missing_symbol() {
+        // libm is always present but certainly doesn't contain CUDA
+        let library = CudaLibrary::new(&["libm.so.6"]);
+        let symbol: Option<unsafe extern "C" fn() -> i32> =
+            unsafe { library.symbol("cudaDeviceSynchronize") };
+        assert!(symbol.is_none());
+        let symbol: Option<unsafe extern "C" fn(f64) -> f64> =
+            unsafe { library.symbol("cos") };
+        assert_eq!(symbol.map(|cos| unsafe { cos(0.0) }), Some(1.0));
+    }
+}
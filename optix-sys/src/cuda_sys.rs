@@ -1,6 +1,142 @@
-include!(concat!(env!("OUT_DIR"), "/cuda_wrapper.rs"));
+#![allow(clippy::missing_safety_doc, clippy::too_many_arguments)]
+
+// the declarations shadowed by the functions below are never used
+#[allow(dead_code)]
+mod bindings {
+    use super::*;
+    include!(concat!(env!("OUT_DIR"), "/cuda_wrapper.rs"));
+}
+pub use bindings::*;
+
+use super::cuda_loader::cuda_api;
+use std::os::raw::{c_char, c_int, c_uint, c_void};
+
+const DRIVER_UNAVAILABLE: CUresult = cudaError_enum::CUDA_ERROR_NOT_INITIALIZED;
+const RUNTIME_UNAVAILABLE: cudaError_t =
+    cudaError::cudaErrorInitializationError;
+const NVRTC_UNAVAILABLE: nvrtcResult::Type =
+    nvrtcResult::NVRTC_ERROR_INTERNAL_ERROR;
+
+// The functions used by the optix crate are loaded at runtime by
+// `cuda_loader`. Defining them here shadows the extern declarations in the
+// bindings, so nothing that only calls these needs to link against CUDA.
+cuda_api! {
+    DRIVER fn cuInit(Flags: c_uint) -> CUresult = DRIVER_UNAVAILABLE;
+    DRIVER fn cuDeviceGet(device: *mut CUdevice, ordinal: c_int) -> CUresult =
+        DRIVER_UNAVAILABLE;
+    DRIVER fn cuCtxGetCurrent(pctx: *mut CUcontext) -> CUresult =
+        DRIVER_UNAVAILABLE;
+    DRIVER fn cuCtxCreate_v2(
+        pctx: *mut CUcontext,
+        flags: c_uint,
+        dev: CUdevice,
+    ) -> CUresult = DRIVER_UNAVAILABLE;
+    DRIVER fn cuCtxDestroy_v2(ctx: CUcontext) -> CUresult = DRIVER_UNAVAILABLE;
+    DRIVER fn cuCtxPushCurrent_v2(ctx: CUcontext) -> CUresult =
+        DRIVER_UNAVAILABLE;
+    DRIVER fn cuCtxPopCurrent_v2(pctx: *mut CUcontext) -> CUresult =
+        DRIVER_UNAVAILABLE;
+    DRIVER fn cuDevicePrimaryCtxRetain(
+        pctx: *mut CUcontext,
+        dev: CUdevice,
+    ) -> CUresult = DRIVER_UNAVAILABLE;
+    DRIVER fn cuDevicePrimaryCtxRelease(dev: CUdevice) -> CUresult =
+        DRIVER_UNAVAILABLE;
+
+    RUNTIME fn cudaGetDeviceCount(count: *mut c_int) -> cudaError_t =
+        RUNTIME_UNAVAILABLE;
+    RUNTIME fn cudaSetDevice(device: c_int) -> cudaError_t =
+        RUNTIME_UNAVAILABLE;
+    RUNTIME fn cudaGetDeviceProperties(
+        prop: *mut cudaDeviceProp,
+        device: c_int,
+    ) -> cudaError_t = RUNTIME_UNAVAILABLE;
+    RUNTIME fn cudaDeviceSynchronize() -> cudaError_t = RUNTIME_UNAVAILABLE;
+    RUNTIME fn cudaGetLastError() -> cudaError_t = RUNTIME_UNAVAILABLE;
+    RUNTIME fn cudaMemGetInfo(free: *mut usize, total: *mut usize)
+        -> cudaError_t = RUNTIME_UNAVAILABLE;
+    RUNTIME fn cudaMalloc(devPtr: *mut *mut c_void, size: usize)
+        -> cudaError_t = RUNTIME_UNAVAILABLE;
+    RUNTIME fn cudaFree(devPtr: *mut c_void) -> cudaError_t =
+        RUNTIME_UNAVAILABLE;
+    RUNTIME fn cudaMemcpy(
+        dst: *mut c_void,
+        src: *const c_void,
+        count: usize,
+        kind: cudaMemcpyKind::Type,
+    ) -> cudaError_t = RUNTIME_UNAVAILABLE;
+    RUNTIME fn cudaMallocArray(
+        array: *mut cudaArray_t,
+        desc: *const cudaChannelFormatDesc,
+        width: usize,
+        height: usize,
+        flags: c_uint,
+    ) -> cudaError_t = RUNTIME_UNAVAILABLE;
+    RUNTIME fn cudaMemcpy2DToArray(
+        dst: cudaArray_t,
+        wOffset: usize,
+        hOffset: usize,
+        src: *const c_void,
+        spitch: usize,
+        width: usize,
+        height: usize,
+        kind: cudaMemcpyKind::Type,
+    ) -> cudaError_t = RUNTIME_UNAVAILABLE;
+    RUNTIME fn cudaFreeArray(array: cudaArray_t) -> cudaError_t =
+        RUNTIME_UNAVAILABLE;
+    RUNTIME fn cudaCreateTextureObject(
+        pTexObject: *mut cudaTextureObject_t,
+        pResDesc: *const cudaResourceDesc,
+        pTexDesc: *const cudaTextureDesc,
+        pResViewDesc: *const cudaResourceViewDesc,
+    ) -> cudaError_t = RUNTIME_UNAVAILABLE;
+    RUNTIME fn cudaDestroyTextureObject(texObject: cudaTextureObject_t)
+        -> cudaError_t = RUNTIME_UNAVAILABLE;
+    RUNTIME fn cudaStreamCreate(pStream: *mut cudaStream_t) -> cudaError_t =
+        RUNTIME_UNAVAILABLE;
+    RUNTIME fn cudaStreamDestroy(stream: cudaStream_t) -> cudaError_t =
+        RUNTIME_UNAVAILABLE;
+
+    NVRTC fn nvrtcGetErrorString(result: nvrtcResult::Type) -> *const c_char =
+        b"NVRTC library not found\0".as_ptr() as *const c_char;
+    NVRTC fn nvrtcVersion(major: *mut c_int, minor: *mut c_int)
+        -> nvrtcResult::Type = NVRTC_UNAVAILABLE;
+    NVRTC fn nvrtcCreateProgram(
+        prog: *mut nvrtcProgram,
+        src: *const c_char,
+        name: *const c_char,
+        numHeaders: c_int,
+        headers: *const *const c_char,
+        includeNames: *const *const c_char,
+    ) -> nvrtcResult::Type = NVRTC_UNAVAILABLE;
+    NVRTC fn nvrtcCompileProgram(
+        prog: nvrtcProgram,
+        numOptions: c_int,
+        options: *const *const c_char,
+    ) -> nvrtcResult::Type = NVRTC_UNAVAILABLE;
+    NVRTC fn nvrtcAddNameExpression(
+        prog: nvrtcProgram,
+        name_expression: *const c_char,
+    ) -> nvrtcResult::Type = NVRTC_UNAVAILABLE;
+    NVRTC fn nvrtcGetLoweredName(
+        prog: nvrtcProgram,
+        name_expression: *const c_char,
+        lowered_name: *mut *const c_char,
+    ) -> nvrtcResult::Type = NVRTC_UNAVAILABLE;
+    NVRTC fn nvrtcGetProgramLogSize(prog: nvrtcProgram, logSizeRet: *mut usize)
+        -> nvrtcResult::Type = NVRTC_UNAVAILABLE;
+    NVRTC fn nvrtcGetProgramLog(prog: nvrtcProgram, log: *mut c_char)
+        -> nvrtcResult::Type = NVRTC_UNAVAILABLE;
+    NVRTC fn nvrtcGetPTXSize(prog: nvrtcProgram, ptxSizeRet: *mut usize)
+        -> nvrtcResult::Type = NVRTC_UNAVAILABLE;
+    NVRTC fn nvrtcGetPTX(prog: nvrtcProgram, ptx: *mut c_char)
+        -> nvrtcResult::Type = NVRTC_UNAVAILABLE;
+    NVRTC fn nvrtcDestroyProgram(prog: *mut nvrtcProgram)
+        -> nvrtcResult::Type = NVRTC_UNAVAILABLE;
+}
 
 #[repr(C)]
+#[derive(Copy, Clone)]
 pub union cudaResourceDescUnion {
     pub array: cudaResourceDescUnionArray,
     pub mipmap: cudaResourceDescUnionMipmap,
@@ -9,16 +145,19 @@ pub union cudaResourceDescUnion {
 }
 
 #[repr(C)]
+#[derive(Copy, Clone)]
 pub struct cudaResourceDescUnionArray {
     pub array: cudaArray_t,
 }
 
 #[repr(C)]
+#[derive(Copy, Clone)]
 pub struct cudaResourceDescUnionMipmap {
     pub mipmap: cudaMipmappedArray_t,
 }
 
 #[repr(C)]
+#[derive(Copy, Clone)]
 pub struct cudaResourceDescUnionLinear {
     pub devPtr: *mut std::os::raw::c_void,
     pub desc: cudaChannelFormatDesc,
@@ -26,6 +165,7 @@ pub struct cudaResourceDescUnionLinear {
 }
 
 #[repr(C)]
+#[derive(Copy, Clone)]
 pub struct cudaResourceDescUnionPitch2D {
     pub devPtr: *mut std::os::raw::c_void,
     pub desc: cudaChannelFormatDesc,
@@ -35,6 +175,7 @@ pub struct cudaResourceDescUnionPitch2D {
 }
 
 #[repr(C)]
+#[derive(Copy, Clone)]
 pub struct cudaResourceDesc {
     pub resType: cudaResourceType,
     pub res: cudaResourceDescUnion,
@@ -338,7 +479,7 @@ impl From<cudaError::Type> for Error {
 
             cudaError::cudaErrorApiFailureBase => Error::ApiFailureBase,
 
-            _ => unreachable!(),
+            code => Error::Unrecognized(code),
         }
     }
 }
@@ -741,4 +882,1095 @@ pub enum Error {
         "Any unhandled CUDA driver error is added to this value and returned via  the runtime. Production releases of CUDA should not return such errors.  deprecated  This error return is deprecated as of CUDA 4.1."
     )]
     ApiFailureBase,
+    /// A code that is not in the headers these bindings were generated from,
+    /// e.g. one added by a newer runtime.
+    #[error("Unrecognized CUDA error code {0}")]
+    Unrecognized(cudaError::Type),
+}
+
+impl Error {
+    /// The `cudaError_t` value this error was created from.
+    pub fn code(&self) -> cudaError::Type {
+        match self {
+            Error::InvalidValue => cudaError::cudaErrorInvalidValue,
+            Error::MemoryAllocation => cudaError::cudaErrorMemoryAllocation,
+            Error::InitializationError => {
+                cudaError::cudaErrorInitializationError
+            }
+            Error::CudartUnloading => cudaError::cudaErrorCudartUnloading,
+            Error::ProfilerDisabled => cudaError::cudaErrorProfilerDisabled,
+            Error::ProfilerNotInitialized => {
+                cudaError::cudaErrorProfilerNotInitialized
+            }
+            Error::ProfilerAlreadyStarted => {
+                cudaError::cudaErrorProfilerAlreadyStarted
+            }
+            Error::ProfilerAlreadyStopped => {
+                cudaError::cudaErrorProfilerAlreadyStopped
+            }
+            Error::InvalidConfiguration => {
+                cudaError::cudaErrorInvalidConfiguration
+            }
+            Error::InvalidPitchValue => cudaError::cudaErrorInvalidPitchValue,
+            Error::InvalidSymbol => cudaError::cudaErrorInvalidSymbol,
+            Error::InvalidHostPointer => cudaError::cudaErrorInvalidHostPointer,
+            Error::InvalidDevicePointer => {
+                cudaError::cudaErrorInvalidDevicePointer
+            }
+            Error::InvalidTexture => cudaError::cudaErrorInvalidTexture,
+            Error::InvalidTextureBinding => {
+                cudaError::cudaErrorInvalidTextureBinding
+            }
+            Error::InvalidChannelDescriptor => {
+                cudaError::cudaErrorInvalidChannelDescriptor
+            }
+            Error::InvalidMemcpyDirection => {
+                cudaError::cudaErrorInvalidMemcpyDirection
+            }
+            Error::AddressOfConstant => cudaError::cudaErrorAddressOfConstant,
+            Error::TextureFetchFailed => cudaError::cudaErrorTextureFetchFailed,
+            Error::TextureNotBound => cudaError::cudaErrorTextureNotBound,
+            Error::SynchronizationError => {
+                cudaError::cudaErrorSynchronizationError
+            }
+            Error::InvalidFilterSetting => {
+                cudaError::cudaErrorInvalidFilterSetting
+            }
+            Error::InvalidNormSetting => cudaError::cudaErrorInvalidNormSetting,
+            Error::MixedDeviceExecution => {
+                cudaError::cudaErrorMixedDeviceExecution
+            }
+            Error::NotYetImplemented => cudaError::cudaErrorNotYetImplemented,
+            Error::MemoryValueTooLarge => {
+                cudaError::cudaErrorMemoryValueTooLarge
+            }
+            Error::InsufficientDriver => cudaError::cudaErrorInsufficientDriver,
+            Error::InvalidSurface => cudaError::cudaErrorInvalidSurface,
+            Error::DuplicateVariableName => {
+                cudaError::cudaErrorDuplicateVariableName
+            }
+            Error::DuplicateTextureName => {
+                cudaError::cudaErrorDuplicateTextureName
+            }
+            Error::DuplicateSurfaceName => {
+                cudaError::cudaErrorDuplicateSurfaceName
+            }
+            Error::DevicesUnavailable => cudaError::cudaErrorDevicesUnavailable,
+            Error::IncompatibleDriverContext => {
+                cudaError::cudaErrorIncompatibleDriverContext
+            }
+            Error::MissingConfiguration => {
+                cudaError::cudaErrorMissingConfiguration
+            }
+            Error::PriorLaunchFailure => cudaError::cudaErrorPriorLaunchFailure,
+            Error::LaunchMaxDepthExceeded => {
+                cudaError::cudaErrorLaunchMaxDepthExceeded
+            }
+            Error::LaunchFileScopedTex => {
+                cudaError::cudaErrorLaunchFileScopedTex
+            }
+            Error::LaunchFileScopedSurf => {
+                cudaError::cudaErrorLaunchFileScopedSurf
+            }
+            Error::SyncDepthExceeded => cudaError::cudaErrorSyncDepthExceeded,
+            Error::LaunchPendingCountExceeded => {
+                cudaError::cudaErrorLaunchPendingCountExceeded
+            }
+            Error::InvalidDeviceFunction => {
+                cudaError::cudaErrorInvalidDeviceFunction
+            }
+            Error::NoDevice => cudaError::cudaErrorNoDevice,
+            Error::InvalidDevice => cudaError::cudaErrorInvalidDevice,
+            Error::StartupFailure => cudaError::cudaErrorStartupFailure,
+            Error::InvalidKernelImage => cudaError::cudaErrorInvalidKernelImage,
+            Error::DeviceUninitilialized => {
+                cudaError::cudaErrorDeviceUninitilialized
+            }
+            Error::MapBufferObjectFailed => {
+                cudaError::cudaErrorMapBufferObjectFailed
+            }
+            Error::UnmapBufferObjectFailed => {
+                cudaError::cudaErrorUnmapBufferObjectFailed
+            }
+            Error::ArrayIsMapped => cudaError::cudaErrorArrayIsMapped,
+            Error::AlreadyMapped => cudaError::cudaErrorAlreadyMapped,
+            Error::NoKernelImageForDevice => {
+                cudaError::cudaErrorNoKernelImageForDevice
+            }
+            Error::AlreadyAcquired => cudaError::cudaErrorAlreadyAcquired,
+            Error::NotMapped => cudaError::cudaErrorNotMapped,
+            Error::NotMappedAsArray => cudaError::cudaErrorNotMappedAsArray,
+            Error::NotMappedAsPointer => cudaError::cudaErrorNotMappedAsPointer,
+            Error::ECCUncorrectable => cudaError::cudaErrorECCUncorrectable,
+            Error::UnsupportedLimit => cudaError::cudaErrorUnsupportedLimit,
+            Error::DeviceAlreadyInUse => cudaError::cudaErrorDeviceAlreadyInUse,
+            Error::PeerAccessUnsupported => {
+                cudaError::cudaErrorPeerAccessUnsupported
+            }
+            Error::InvalidPtx => cudaError::cudaErrorInvalidPtx,
+            Error::InvalidGraphicsContext => {
+                cudaError::cudaErrorInvalidGraphicsContext
+            }
+            Error::NvlinkUncorrectable => {
+                cudaError::cudaErrorNvlinkUncorrectable
+            }
+            Error::JitCompilerNotFound => {
+                cudaError::cudaErrorJitCompilerNotFound
+            }
+            Error::InvalidSource => cudaError::cudaErrorInvalidSource,
+            Error::FileNotFound => cudaError::cudaErrorFileNotFound,
+            Error::SharedObjectSymbolNotFound => {
+                cudaError::cudaErrorSharedObjectSymbolNotFound
+            }
+            Error::SharedObjectInitFailed => {
+                cudaError::cudaErrorSharedObjectInitFailed
+            }
+            Error::OperatingSystem => cudaError::cudaErrorOperatingSystem,
+            Error::InvalidResourceHandle => {
+                cudaError::cudaErrorInvalidResourceHandle
+            }
+            Error::IllegalState => cudaError::cudaErrorIllegalState,
+            Error::SymbolNotFound => cudaError::cudaErrorSymbolNotFound,
+            Error::NotReady => cudaError::cudaErrorNotReady,
+            Error::IllegalAddress => cudaError::cudaErrorIllegalAddress,
+            Error::LaunchOutOfResources => {
+                cudaError::cudaErrorLaunchOutOfResources
+            }
+            Error::LaunchTimeout => cudaError::cudaErrorLaunchTimeout,
+            Error::LaunchIncompatibleTexturing => {
+                cudaError::cudaErrorLaunchIncompatibleTexturing
+            }
+            Error::PeerAccessAlreadyEnabled => {
+                cudaError::cudaErrorPeerAccessAlreadyEnabled
+            }
+            Error::PeerAccessNotEnabled => {
+                cudaError::cudaErrorPeerAccessNotEnabled
+            }
+            Error::SetOnActiveProcess => cudaError::cudaErrorSetOnActiveProcess,
+            Error::ContextIsDestroyed => cudaError::cudaErrorContextIsDestroyed,
+            Error::Assert => cudaError::cudaErrorAssert,
+            Error::TooManyPeers => cudaError::cudaErrorTooManyPeers,
+            Error::HostMemoryAlreadyRegistered => {
+                cudaError::cudaErrorHostMemoryAlreadyRegistered
+            }
+            Error::HostMemoryNotRegistered => {
+                cudaError::cudaErrorHostMemoryNotRegistered
+            }
+            Error::HardwareStackError => cudaError::cudaErrorHardwareStackError,
+            Error::IllegalInstruction => cudaError::cudaErrorIllegalInstruction,
+            Error::MisalignedAddress => cudaError::cudaErrorMisalignedAddress,
+            Error::InvalidAddressSpace => {
+                cudaError::cudaErrorInvalidAddressSpace
+            }
+            Error::InvalidPc => cudaError::cudaErrorInvalidPc,
+            Error::LaunchFailure => cudaError::cudaErrorLaunchFailure,
+            Error::CooperativeLaunchTooLarge => {
+                cudaError::cudaErrorCooperativeLaunchTooLarge
+            }
+            Error::NotPermitted => cudaError::cudaErrorNotPermitted,
+            Error::NotSupported => cudaError::cudaErrorNotSupported,
+            Error::SystemNotReady => cudaError::cudaErrorSystemNotReady,
+            Error::SystemDriverMismatch => {
+                cudaError::cudaErrorSystemDriverMismatch
+            }
+            Error::CompatNotSupportedOnDevice => {
+                cudaError::cudaErrorCompatNotSupportedOnDevice
+            }
+            Error::StreamCaptureUnsupported => {
+                cudaError::cudaErrorStreamCaptureUnsupported
+            }
+            Error::StreamCaptureInvalidated => {
+                cudaError::cudaErrorStreamCaptureInvalidated
+            }
+            Error::StreamCaptureMerge => cudaError::cudaErrorStreamCaptureMerge,
+            Error::StreamCaptureUnmatched => {
+                cudaError::cudaErrorStreamCaptureUnmatched
+            }
+            Error::StreamCaptureUnjoined => {
+                cudaError::cudaErrorStreamCaptureUnjoined
+            }
+            Error::StreamCaptureIsolation => {
+                cudaError::cudaErrorStreamCaptureIsolation
+            }
+            Error::StreamCaptureImplicit => {
+                cudaError::cudaErrorStreamCaptureImplicit
+            }
+            Error::CapturedEvent => cudaError::cudaErrorCapturedEvent,
+            Error::StreamCaptureWrongThread => {
+                cudaError::cudaErrorStreamCaptureWrongThread
+            }
+            Error::Unknown => cudaError::cudaErrorUnknown,
+            Error::ApiFailureBase => cudaError::cudaErrorApiFailureBase,
+            Error::Unrecognized(code) => *code,
+        }
+    }
+
+    /// The name of the error code as it appears in the CUDA headers, e.g.
+    /// "cudaErrorInvalidValue". The description is the `Display` output.
+    pub fn name(&self) -> &'static str {
+        match self {
+            Error::InvalidValue => "cudaErrorInvalidValue",
+            Error::MemoryAllocation => "cudaErrorMemoryAllocation",
+            Error::InitializationError => "cudaErrorInitializationError",
+            Error::CudartUnloading => "cudaErrorCudartUnloading",
+            Error::ProfilerDisabled => "cudaErrorProfilerDisabled",
+            Error::ProfilerNotInitialized => "cudaErrorProfilerNotInitialized",
+            Error::ProfilerAlreadyStarted => "cudaErrorProfilerAlreadyStarted",
+            Error::ProfilerAlreadyStopped => "cudaErrorProfilerAlreadyStopped",
+            Error::InvalidConfiguration => "cudaErrorInvalidConfiguration",
+            Error::InvalidPitchValue => "cudaErrorInvalidPitchValue",
+            Error::InvalidSymbol => "cudaErrorInvalidSymbol",
+            Error::InvalidHostPointer => "cudaErrorInvalidHostPointer",
+            Error::InvalidDevicePointer => "cudaErrorInvalidDevicePointer",
+            Error::InvalidTexture => "cudaErrorInvalidTexture",
+            Error::InvalidTextureBinding => "cudaErrorInvalidTextureBinding",
+            Error::InvalidChannelDescriptor => {
+                "cudaErrorInvalidChannelDescriptor"
+            }
+            Error::InvalidMemcpyDirection => "cudaErrorInvalidMemcpyDirection",
+            Error::AddressOfConstant => "cudaErrorAddressOfConstant",
+            Error::TextureFetchFailed => "cudaErrorTextureFetchFailed",
+            Error::TextureNotBound => "cudaErrorTextureNotBound",
+            Error::SynchronizationError => "cudaErrorSynchronizationError",
+            Error::InvalidFilterSetting => "cudaErrorInvalidFilterSetting",
+            Error::InvalidNormSetting => "cudaErrorInvalidNormSetting",
+            Error::MixedDeviceExecution => "cudaErrorMixedDeviceExecution",
+            Error::NotYetImplemented => "cudaErrorNotYetImplemented",
+            Error::MemoryValueTooLarge => "cudaErrorMemoryValueTooLarge",
+            Error::InsufficientDriver => "cudaErrorInsufficientDriver",
+            Error::InvalidSurface => "cudaErrorInvalidSurface",
+            Error::DuplicateVariableName => "cudaErrorDuplicateVariableName",
+            Error::DuplicateTextureName => "cudaErrorDuplicateTextureName",
+            Error::DuplicateSurfaceName => "cudaErrorDuplicateSurfaceName",
+            Error::DevicesUnavailable => "cudaErrorDevicesUnavailable",
+            Error::IncompatibleDriverContext => {
+                "cudaErrorIncompatibleDriverContext"
+            }
+            Error::MissingConfiguration => "cudaErrorMissingConfiguration",
+            Error::PriorLaunchFailure => "cudaErrorPriorLaunchFailure",
+            Error::LaunchMaxDepthExceeded => "cudaErrorLaunchMaxDepthExceeded",
+            Error::LaunchFileScopedTex => "cudaErrorLaunchFileScopedTex",
+            Error::LaunchFileScopedSurf => "cudaErrorLaunchFileScopedSurf",
+            Error::SyncDepthExceeded => "cudaErrorSyncDepthExceeded",
+            Error::LaunchPendingCountExceeded => {
+                "cudaErrorLaunchPendingCountExceeded"
+            }
+            Error::InvalidDeviceFunction => "cudaErrorInvalidDeviceFunction",
+            Error::NoDevice => "cudaErrorNoDevice",
+            Error::InvalidDevice => "cudaErrorInvalidDevice",
+            Error::StartupFailure => "cudaErrorStartupFailure",
+            Error::InvalidKernelImage => "cudaErrorInvalidKernelImage",
+            Error::DeviceUninitilialized => "cudaErrorDeviceUninitilialized",
+            Error::MapBufferObjectFailed => "cudaErrorMapBufferObjectFailed",
+            Error::UnmapBufferObjectFailed => {
+                "cudaErrorUnmapBufferObjectFailed"
+            }
+            Error::ArrayIsMapped => "cudaErrorArrayIsMapped",
+            Error::AlreadyMapped => "cudaErrorAlreadyMapped",
+            Error::NoKernelImageForDevice => "cudaErrorNoKernelImageForDevice",
+            Error::AlreadyAcquired => "cudaErrorAlreadyAcquired",
+            Error::NotMapped => "cudaErrorNotMapped",
+            Error::NotMappedAsArray => "cudaErrorNotMappedAsArray",
+            Error::NotMappedAsPointer => "cudaErrorNotMappedAsPointer",
+            Error::ECCUncorrectable => "cudaErrorECCUncorrectable",
+            Error::UnsupportedLimit => "cudaErrorUnsupportedLimit",
+            Error::DeviceAlreadyInUse => "cudaErrorDeviceAlreadyInUse",
+            Error::PeerAccessUnsupported => "cudaErrorPeerAccessUnsupported",
+            Error::InvalidPtx => "cudaErrorInvalidPtx",
+            Error::InvalidGraphicsContext => "cudaErrorInvalidGraphicsContext",
+            Error::NvlinkUncorrectable => "cudaErrorNvlinkUncorrectable",
+            Error::JitCompilerNotFound => "cudaErrorJitCompilerNotFound",
+            Error::InvalidSource => "cudaErrorInvalidSource",
+            Error::FileNotFound => "cudaErrorFileNotFound",
+            Error::SharedObjectSymbolNotFound => {
+                "cudaErrorSharedObjectSymbolNotFound"
+            }
+            Error::SharedObjectInitFailed => "cudaErrorSharedObjectInitFailed",
+            Error::OperatingSystem => "cudaErrorOperatingSystem",
+            Error::InvalidResourceHandle => "cudaErrorInvalidResourceHandle",
+            Error::IllegalState => "cudaErrorIllegalState",
+            Error::SymbolNotFound => "cudaErrorSymbolNotFound",
+            Error::NotReady => "cudaErrorNotReady",
+            Error::IllegalAddress => "cudaErrorIllegalAddress",
+            Error::LaunchOutOfResources => "cudaErrorLaunchOutOfResources",
+            Error::LaunchTimeout => "cudaErrorLaunchTimeout",
+            Error::LaunchIncompatibleTexturing => {
+                "cudaErrorLaunchIncompatibleTexturing"
+            }
+            Error::PeerAccessAlreadyEnabled => {
+                "cudaErrorPeerAccessAlreadyEnabled"
+            }
+            Error::PeerAccessNotEnabled => "cudaErrorPeerAccessNotEnabled",
+            Error::SetOnActiveProcess => "cudaErrorSetOnActiveProcess",
+            Error::ContextIsDestroyed => "cudaErrorContextIsDestroyed",
+            Error::Assert => "cudaErrorAssert",
+            Error::TooManyPeers => "cudaErrorTooManyPeers",
+            Error::HostMemoryAlreadyRegistered => {
+                "cudaErrorHostMemoryAlreadyRegistered"
+            }
+            Error::HostMemoryNotRegistered => {
+                "cudaErrorHostMemoryNotRegistered"
+            }
+            Error::HardwareStackError => "cudaErrorHardwareStackError",
+            Error::IllegalInstruction => "cudaErrorIllegalInstruction",
+            Error::MisalignedAddress => "cudaErrorMisalignedAddress",
+            Error::InvalidAddressSpace => "cudaErrorInvalidAddressSpace",
+            Error::InvalidPc => "cudaErrorInvalidPc",
+            Error::LaunchFailure => "cudaErrorLaunchFailure",
+            Error::CooperativeLaunchTooLarge => {
+                "cudaErrorCooperativeLaunchTooLarge"
+            }
+            Error::NotPermitted => "cudaErrorNotPermitted",
+            Error::NotSupported => "cudaErrorNotSupported",
+            Error::SystemNotReady => "cudaErrorSystemNotReady",
+            Error::SystemDriverMismatch => "cudaErrorSystemDriverMismatch",
+            Error::CompatNotSupportedOnDevice => {
+                "cudaErrorCompatNotSupportedOnDevice"
+            }
+            Error::StreamCaptureUnsupported => {
+                "cudaErrorStreamCaptureUnsupported"
+            }
+            Error::StreamCaptureInvalidated => {
+                "cudaErrorStreamCaptureInvalidated"
+            }
+            Error::StreamCaptureMerge => "cudaErrorStreamCaptureMerge",
+            Error::StreamCaptureUnmatched => "cudaErrorStreamCaptureUnmatched",
+            Error::StreamCaptureUnjoined => "cudaErrorStreamCaptureUnjoined",
+            Error::StreamCaptureIsolation => "cudaErrorStreamCaptureIsolation",
+            Error::StreamCaptureImplicit => "cudaErrorStreamCaptureImplicit",
+            Error::CapturedEvent => "cudaErrorCapturedEvent",
+            Error::StreamCaptureWrongThread => {
+                "cudaErrorStreamCaptureWrongThread"
+            }
+            Error::Unknown => "cudaErrorUnknown",
+            Error::ApiFailureBase => "cudaErrorApiFailureBase",
+            Error::Unrecognized(_) => "cudaErrorUnrecognized",
+        }
+    }
+}
+
+impl From<cudaError_enum::Type> for DriverError {
+    fn from(e: cudaError_enum::Type) -> DriverError {
+        match e {
+            cudaError_enum::CUDA_ERROR_INVALID_VALUE => {
+                DriverError::InvalidValue
+            }
+            cudaError_enum::CUDA_ERROR_OUT_OF_MEMORY => {
+                DriverError::OutOfMemory
+            }
+            cudaError_enum::CUDA_ERROR_NOT_INITIALIZED => {
+                DriverError::NotInitialized
+            }
+            cudaError_enum::CUDA_ERROR_DEINITIALIZED => {
+                DriverError::Deinitialized
+            }
+            cudaError_enum::CUDA_ERROR_PROFILER_DISABLED => {
+                DriverError::ProfilerDisabled
+            }
+            cudaError_enum::CUDA_ERROR_PROFILER_NOT_INITIALIZED => {
+                DriverError::ProfilerNotInitialized
+            }
+            cudaError_enum::CUDA_ERROR_PROFILER_ALREADY_STARTED => {
+                DriverError::ProfilerAlreadyStarted
+            }
+            cudaError_enum::CUDA_ERROR_PROFILER_ALREADY_STOPPED => {
+                DriverError::ProfilerAlreadyStopped
+            }
+            cudaError_enum::CUDA_ERROR_NO_DEVICE => DriverError::NoDevice,
+            cudaError_enum::CUDA_ERROR_INVALID_DEVICE => {
+                DriverError::InvalidDevice
+            }
+            cudaError_enum::CUDA_ERROR_INVALID_IMAGE => {
+                DriverError::InvalidImage
+            }
+            cudaError_enum::CUDA_ERROR_INVALID_CONTEXT => {
+                DriverError::InvalidContext
+            }
+            cudaError_enum::CUDA_ERROR_CONTEXT_ALREADY_CURRENT => {
+                DriverError::ContextAlreadyCurrent
+            }
+            cudaError_enum::CUDA_ERROR_MAP_FAILED => DriverError::MapFailed,
+            cudaError_enum::CUDA_ERROR_UNMAP_FAILED => DriverError::UnmapFailed,
+            cudaError_enum::CUDA_ERROR_ARRAY_IS_MAPPED => {
+                DriverError::ArrayIsMapped
+            }
+            cudaError_enum::CUDA_ERROR_ALREADY_MAPPED => {
+                DriverError::AlreadyMapped
+            }
+            cudaError_enum::CUDA_ERROR_NO_BINARY_FOR_GPU => {
+                DriverError::NoBinaryForGpu
+            }
+            cudaError_enum::CUDA_ERROR_ALREADY_ACQUIRED => {
+                DriverError::AlreadyAcquired
+            }
+            cudaError_enum::CUDA_ERROR_NOT_MAPPED => DriverError::NotMapped,
+            cudaError_enum::CUDA_ERROR_NOT_MAPPED_AS_ARRAY => {
+                DriverError::NotMappedAsArray
+            }
+            cudaError_enum::CUDA_ERROR_NOT_MAPPED_AS_POINTER => {
+                DriverError::NotMappedAsPointer
+            }
+            cudaError_enum::CUDA_ERROR_ECC_UNCORRECTABLE => {
+                DriverError::ECCUncorrectable
+            }
+            cudaError_enum::CUDA_ERROR_UNSUPPORTED_LIMIT => {
+                DriverError::UnsupportedLimit
+            }
+            cudaError_enum::CUDA_ERROR_CONTEXT_ALREADY_IN_USE => {
+                DriverError::ContextAlreadyInUse
+            }
+            cudaError_enum::CUDA_ERROR_PEER_ACCESS_UNSUPPORTED => {
+                DriverError::PeerAccessUnsupported
+            }
+            cudaError_enum::CUDA_ERROR_INVALID_PTX => DriverError::InvalidPtx,
+            cudaError_enum::CUDA_ERROR_INVALID_GRAPHICS_CONTEXT => {
+                DriverError::InvalidGraphicsContext
+            }
+            cudaError_enum::CUDA_ERROR_NVLINK_UNCORRECTABLE => {
+                DriverError::NvlinkUncorrectable
+            }
+            cudaError_enum::CUDA_ERROR_JIT_COMPILER_NOT_FOUND => {
+                DriverError::JitCompilerNotFound
+            }
+            cudaError_enum::CUDA_ERROR_INVALID_SOURCE => {
+                DriverError::InvalidSource
+            }
+            cudaError_enum::CUDA_ERROR_FILE_NOT_FOUND => {
+                DriverError::FileNotFound
+            }
+            cudaError_enum::CUDA_ERROR_SHARED_OBJECT_SYMBOL_NOT_FOUND => {
+                DriverError::SharedObjectSymbolNotFound
+            }
+            cudaError_enum::CUDA_ERROR_SHARED_OBJECT_INIT_FAILED => {
+                DriverError::SharedObjectInitFailed
+            }
+            cudaError_enum::CUDA_ERROR_OPERATING_SYSTEM => {
+                DriverError::OperatingSystem
+            }
+            cudaError_enum::CUDA_ERROR_INVALID_HANDLE => {
+                DriverError::InvalidHandle
+            }
+            cudaError_enum::CUDA_ERROR_ILLEGAL_STATE => {
+                DriverError::IllegalState
+            }
+            cudaError_enum::CUDA_ERROR_NOT_FOUND => DriverError::NotFound,
+            cudaError_enum::CUDA_ERROR_NOT_READY => DriverError::NotReady,
+            cudaError_enum::CUDA_ERROR_ILLEGAL_ADDRESS => {
+                DriverError::IllegalAddress
+            }
+            cudaError_enum::CUDA_ERROR_LAUNCH_OUT_OF_RESOURCES => {
+                DriverError::LaunchOutOfResources
+            }
+            cudaError_enum::CUDA_ERROR_LAUNCH_TIMEOUT => {
+                DriverError::LaunchTimeout
+            }
+            cudaError_enum::CUDA_ERROR_LAUNCH_INCOMPATIBLE_TEXTURING => {
+                DriverError::LaunchIncompatibleTexturing
+            }
+            cudaError_enum::CUDA_ERROR_PEER_ACCESS_ALREADY_ENABLED => {
+                DriverError::PeerAccessAlreadyEnabled
+            }
+            cudaError_enum::CUDA_ERROR_PEER_ACCESS_NOT_ENABLED => {
+                DriverError::PeerAccessNotEnabled
+            }
+            cudaError_enum::CUDA_ERROR_PRIMARY_CONTEXT_ACTIVE => {
+                DriverError::PrimaryContextActive
+            }
+            cudaError_enum::CUDA_ERROR_CONTEXT_IS_DESTROYED => {
+                DriverError::ContextIsDestroyed
+            }
+            cudaError_enum::CUDA_ERROR_ASSERT => DriverError::Assert,
+            cudaError_enum::CUDA_ERROR_TOO_MANY_PEERS => {
+                DriverError::TooManyPeers
+            }
+            cudaError_enum::CUDA_ERROR_HOST_MEMORY_ALREADY_REGISTERED => {
+                DriverError::HostMemoryAlreadyRegistered
+            }
+            cudaError_enum::CUDA_ERROR_HOST_MEMORY_NOT_REGISTERED => {
+                DriverError::HostMemoryNotRegistered
+            }
+            cudaError_enum::CUDA_ERROR_HARDWARE_STACK_ERROR => {
+                DriverError::HardwareStackError
+            }
+            cudaError_enum::CUDA_ERROR_ILLEGAL_INSTRUCTION => {
+                DriverError::IllegalInstruction
+            }
+            cudaError_enum::CUDA_ERROR_MISALIGNED_ADDRESS => {
+                DriverError::MisalignedAddress
+            }
+            cudaError_enum::CUDA_ERROR_INVALID_ADDRESS_SPACE => {
+                DriverError::InvalidAddressSpace
+            }
+            cudaError_enum::CUDA_ERROR_INVALID_PC => DriverError::InvalidPc,
+            cudaError_enum::CUDA_ERROR_LAUNCH_FAILED => {
+                DriverError::LaunchFailed
+            }
+            cudaError_enum::CUDA_ERROR_COOPERATIVE_LAUNCH_TOO_LARGE => {
+                DriverError::CooperativeLaunchTooLarge
+            }
+            cudaError_enum::CUDA_ERROR_NOT_PERMITTED => {
+                DriverError::NotPermitted
+            }
+            cudaError_enum::CUDA_ERROR_NOT_SUPPORTED => {
+                DriverError::NotSupported
+            }
+            cudaError_enum::CUDA_ERROR_SYSTEM_NOT_READY => {
+                DriverError::SystemNotReady
+            }
+            cudaError_enum::CUDA_ERROR_SYSTEM_DRIVER_MISMATCH => {
+                DriverError::SystemDriverMismatch
+            }
+            cudaError_enum::CUDA_ERROR_COMPAT_NOT_SUPPORTED_ON_DEVICE => {
+                DriverError::CompatNotSupportedOnDevice
+            }
+            cudaError_enum::CUDA_ERROR_STREAM_CAPTURE_UNSUPPORTED => {
+                DriverError::StreamCaptureUnsupported
+            }
+            cudaError_enum::CUDA_ERROR_STREAM_CAPTURE_INVALIDATED => {
+                DriverError::StreamCaptureInvalidated
+            }
+            cudaError_enum::CUDA_ERROR_STREAM_CAPTURE_MERGE => {
+                DriverError::StreamCaptureMerge
+            }
+            cudaError_enum::CUDA_ERROR_STREAM_CAPTURE_UNMATCHED => {
+                DriverError::StreamCaptureUnmatched
+            }
+            cudaError_enum::CUDA_ERROR_STREAM_CAPTURE_UNJOINED => {
+                DriverError::StreamCaptureUnjoined
+            }
+            cudaError_enum::CUDA_ERROR_STREAM_CAPTURE_ISOLATION => {
+                DriverError::StreamCaptureIsolation
+            }
+            cudaError_enum::CUDA_ERROR_STREAM_CAPTURE_IMPLICIT => {
+                DriverError::StreamCaptureImplicit
+            }
+            cudaError_enum::CUDA_ERROR_CAPTURED_EVENT => {
+                DriverError::CapturedEvent
+            }
+            cudaError_enum::CUDA_ERROR_STREAM_CAPTURE_WRONG_THREAD => {
+                DriverError::StreamCaptureWrongThread
+            }
+            cudaError_enum::CUDA_ERROR_UNKNOWN => DriverError::Unknown,
+            code => DriverError::Unrecognized(code),
+        }
+    }
+}
+
+/// An error returned by a CUDA driver API call, as a `CUresult`.
+#[derive(Debug, Copy, Clone, thiserror::Error)]
+pub enum DriverError {
+    #[error("One or more of the parameters passed to the API call is not within an acceptable range of values.")]
+    InvalidValue,
+    #[error("The API call failed because it was unable to allocate enough memory to perform the requested operation.")]
+    OutOfMemory,
+    #[error("The CUDA driver has not been initialized with cuInit() or initialization has failed.")]
+    NotInitialized,
+    #[error("The CUDA driver is in the process of shutting down.")]
+    Deinitialized,
+    #[error("The profiler is not initialized for this run, which can happen when the application is running with external profiling tools.")]
+    ProfilerDisabled,
+    #[error("Deprecated as of CUDA 5.0. It is no longer an error to enable or disable profiling without initialization.")]
+    ProfilerNotInitialized,
+    #[error("Deprecated as of CUDA 5.0. It is no longer an error to call cuProfilerStart() when profiling is already enabled.")]
+    ProfilerAlreadyStarted,
+    #[error("Deprecated as of CUDA 5.0. It is no longer an error to call cuProfilerStop() when profiling is already disabled.")]
+    ProfilerAlreadyStopped,
+    #[error(
+        "No CUDA-capable devices were detected by the installed CUDA driver."
+    )]
+    NoDevice,
+    #[error("The device ordinal supplied by the user does not correspond to a valid CUDA device.")]
+    InvalidDevice,
+    #[error("The device kernel image is invalid, or the module is not a valid CUDA module.")]
+    InvalidImage,
+    #[error("There is no context bound to the current thread, or the context passed to the API call is not a valid handle.")]
+    InvalidContext,
+    #[error("Deprecated as of CUDA 3.2. It is no longer an error to make a context current that is already current.")]
+    ContextAlreadyCurrent,
+    #[error("A map or register operation has failed.")]
+    MapFailed,
+    #[error("An unmap or unregister operation has failed.")]
+    UnmapFailed,
+    #[error(
+        "The specified array is currently mapped and thus cannot be destroyed."
+    )]
+    ArrayIsMapped,
+    #[error("The resource is already mapped.")]
+    AlreadyMapped,
+    #[error(
+        "There is no kernel image available that is suitable for the device."
+    )]
+    NoBinaryForGpu,
+    #[error("The resource has already been acquired.")]
+    AlreadyAcquired,
+    #[error("The resource is not mapped.")]
+    NotMapped,
+    #[error("A mapped resource is not available for access as an array.")]
+    NotMappedAsArray,
+    #[error("A mapped resource is not available for access as a pointer.")]
+    NotMappedAsPointer,
+    #[error("An uncorrectable ECC error was detected during execution.")]
+    ECCUncorrectable,
+    #[error("The limit passed to the API call is not supported by the active device.")]
+    UnsupportedLimit,
+    #[error("The context passed to the API call can only be bound to a single CPU thread at a time but is already bound to a CPU thread.")]
+    ContextAlreadyInUse,
+    #[error("Peer access is not supported across the given devices.")]
+    PeerAccessUnsupported,
+    #[error("A PTX JIT compilation failed.")]
+    InvalidPtx,
+    #[error("An error with the OpenGL or DirectX context.")]
+    InvalidGraphicsContext,
+    #[error("An uncorrectable NVLink error was detected during execution.")]
+    NvlinkUncorrectable,
+    #[error("The PTX JIT compiler library was not found.")]
+    JitCompilerNotFound,
+    #[error("The device kernel source is invalid.")]
+    InvalidSource,
+    #[error("The file specified was not found.")]
+    FileNotFound,
+    #[error("A link to a shared object failed to resolve.")]
+    SharedObjectSymbolNotFound,
+    #[error("Initialization of a shared object failed.")]
+    SharedObjectInitFailed,
+    #[error("An OS call failed.")]
+    OperatingSystem,
+    #[error("A resource handle passed to the API call was not valid.")]
+    InvalidHandle,
+    #[error("A resource required by the API call is not in a valid state to perform the requested operation.")]
+    IllegalState,
+    #[error("A named symbol was not found.")]
+    NotFound,
+    #[error(
+        "Asynchronous operations issued previously have not completed yet."
+    )]
+    NotReady,
+    #[error("While executing a kernel, the device encountered a load or store instruction on an invalid memory address.")]
+    IllegalAddress,
+    #[error(
+        "A launch did not occur because it did not have appropriate resources."
+    )]
+    LaunchOutOfResources,
+    #[error("The device kernel took too long to execute.")]
+    LaunchTimeout,
+    #[error("A kernel launch that uses an incompatible texturing mode.")]
+    LaunchIncompatibleTexturing,
+    #[error("Peer access to a context was already enabled.")]
+    PeerAccessAlreadyEnabled,
+    #[error("Peer access to a context has not been enabled.")]
+    PeerAccessNotEnabled,
+    #[error("The primary context for the specified device has already been initialized.")]
+    PrimaryContextActive,
+    #[error("The context current to the calling thread has been destroyed, or is a primary context which has not yet been initialized.")]
+    ContextIsDestroyed,
+    #[error("A device-side assert triggered during kernel execution.")]
+    Assert,
+    #[error("The hardware resources required to enable peer access have been exhausted for one or more of the devices.")]
+    TooManyPeers,
+    #[error("The memory range passed to cuMemHostRegister() has already been registered.")]
+    HostMemoryAlreadyRegistered,
+    #[error("The pointer passed to cuMemHostUnregister() does not correspond to any currently registered memory region.")]
+    HostMemoryNotRegistered,
+    #[error("While executing a kernel, the device encountered a stack error.")]
+    HardwareStackError,
+    #[error("While executing a kernel, the device encountered an illegal instruction.")]
+    IllegalInstruction,
+    #[error("While executing a kernel, the device encountered a load or store instruction on a memory address which is not aligned.")]
+    MisalignedAddress,
+    #[error("While executing a kernel, the device encountered an instruction which can only operate on memory locations in certain address spaces.")]
+    InvalidAddressSpace,
+    #[error("While executing a kernel, the device program counter wrapped its address space.")]
+    InvalidPc,
+    #[error("An exception occurred on the device while executing a kernel.")]
+    LaunchFailed,
+    #[error("The number of blocks launched per grid for a cooperative kernel exceeds the maximum number allowed.")]
+    CooperativeLaunchTooLarge,
+    #[error("The attempted operation is not permitted.")]
+    NotPermitted,
+    #[error("The attempted operation is not supported on the current system or device.")]
+    NotSupported,
+    #[error("The system is not yet ready to start any CUDA work.")]
+    SystemNotReady,
+    #[error("There is a mismatch between the versions of the display driver and the CUDA driver.")]
+    SystemDriverMismatch,
+    #[error("The system was upgraded to run with forward compatibility but the visible hardware does not support this configuration.")]
+    CompatNotSupportedOnDevice,
+    #[error("The operation is not permitted when the stream is capturing.")]
+    StreamCaptureUnsupported,
+    #[error("The current capture sequence on the stream has been invalidated due to a previous error.")]
+    StreamCaptureInvalidated,
+    #[error("The operation would have resulted in a merge of two independent capture sequences.")]
+    StreamCaptureMerge,
+    #[error("The capture was not initiated in this stream.")]
+    StreamCaptureUnmatched,
+    #[error("The capture sequence contains a fork that was not joined to the primary stream.")]
+    StreamCaptureUnjoined,
+    #[error("A dependency would have been created which crosses the capture sequence boundary.")]
+    StreamCaptureIsolation,
+    #[error("The operation would have resulted in a disallowed implicit dependency on a current capture sequence from cudaStreamLegacy.")]
+    StreamCaptureImplicit,
+    #[error("The operation is not permitted on an event which was last recorded in a capturing stream.")]
+    CapturedEvent,
+    #[error("A stream capture sequence not initiated with the cudaStreamCaptureModeRelaxed argument to cuStreamBeginCapture was passed to cuStreamEndCapture in a different thread.")]
+    StreamCaptureWrongThread,
+    #[error("An unknown internal error has occurred.")]
+    Unknown,
+    /// A code that is not in the headers these bindings were generated from,
+    /// e.g. one added by a newer driver.
+    #[error("Unrecognized CUDA driver error code {0}")]
+    Unrecognized(CUresult),
+}
+
+impl DriverError {
+    /// The `CUresult` value this error was created from.
+    pub fn code(&self) -> CUresult {
+        match self {
+            DriverError::InvalidValue => {
+                cudaError_enum::CUDA_ERROR_INVALID_VALUE
+            }
+            DriverError::OutOfMemory => {
+                cudaError_enum::CUDA_ERROR_OUT_OF_MEMORY
+            }
+            DriverError::NotInitialized => {
+                cudaError_enum::CUDA_ERROR_NOT_INITIALIZED
+            }
+            DriverError::Deinitialized => {
+                cudaError_enum::CUDA_ERROR_DEINITIALIZED
+            }
+            DriverError::ProfilerDisabled => {
+                cudaError_enum::CUDA_ERROR_PROFILER_DISABLED
+            }
+            DriverError::ProfilerNotInitialized => {
+                cudaError_enum::CUDA_ERROR_PROFILER_NOT_INITIALIZED
+            }
+            DriverError::ProfilerAlreadyStarted => {
+                cudaError_enum::CUDA_ERROR_PROFILER_ALREADY_STARTED
+            }
+            DriverError::ProfilerAlreadyStopped => {
+                cudaError_enum::CUDA_ERROR_PROFILER_ALREADY_STOPPED
+            }
+            DriverError::NoDevice => cudaError_enum::CUDA_ERROR_NO_DEVICE,
+            DriverError::InvalidDevice => {
+                cudaError_enum::CUDA_ERROR_INVALID_DEVICE
+            }
+            DriverError::InvalidImage => {
+                cudaError_enum::CUDA_ERROR_INVALID_IMAGE
+            }
+            DriverError::InvalidContext => {
+                cudaError_enum::CUDA_ERROR_INVALID_CONTEXT
+            }
+            DriverError::ContextAlreadyCurrent => {
+                cudaError_enum::CUDA_ERROR_CONTEXT_ALREADY_CURRENT
+            }
+            DriverError::MapFailed => cudaError_enum::CUDA_ERROR_MAP_FAILED,
+            DriverError::UnmapFailed => cudaError_enum::CUDA_ERROR_UNMAP_FAILED,
+            DriverError::ArrayIsMapped => {
+                cudaError_enum::CUDA_ERROR_ARRAY_IS_MAPPED
+            }
+            DriverError::AlreadyMapped => {
+                cudaError_enum::CUDA_ERROR_ALREADY_MAPPED
+            }
+            DriverError::NoBinaryForGpu => {
+                cudaError_enum::CUDA_ERROR_NO_BINARY_FOR_GPU
+            }
+            DriverError::AlreadyAcquired => {
+                cudaError_enum::CUDA_ERROR_ALREADY_ACQUIRED
+            }
+            DriverError::NotMapped => cudaError_enum::CUDA_ERROR_NOT_MAPPED,
+            DriverError::NotMappedAsArray => {
+                cudaError_enum::CUDA_ERROR_NOT_MAPPED_AS_ARRAY
+            }
+            DriverError::NotMappedAsPointer => {
+                cudaError_enum::CUDA_ERROR_NOT_MAPPED_AS_POINTER
+            }
+            DriverError::ECCUncorrectable => {
+                cudaError_enum::CUDA_ERROR_ECC_UNCORRECTABLE
+            }
+            DriverError::UnsupportedLimit => {
+                cudaError_enum::CUDA_ERROR_UNSUPPORTED_LIMIT
+            }
+            DriverError::ContextAlreadyInUse => {
+                cudaError_enum::CUDA_ERROR_CONTEXT_ALREADY_IN_USE
+            }
+            DriverError::PeerAccessUnsupported => {
+                cudaError_enum::CUDA_ERROR_PEER_ACCESS_UNSUPPORTED
+            }
+            DriverError::InvalidPtx => cudaError_enum::CUDA_ERROR_INVALID_PTX,
+            DriverError::InvalidGraphicsContext => {
+                cudaError_enum::CUDA_ERROR_INVALID_GRAPHICS_CONTEXT
+            }
+            DriverError::NvlinkUncorrectable => {
+                cudaError_enum::CUDA_ERROR_NVLINK_UNCORRECTABLE
+            }
+            DriverError::JitCompilerNotFound => {
+                cudaError_enum::CUDA_ERROR_JIT_COMPILER_NOT_FOUND
+            }
+            DriverError::InvalidSource => {
+                cudaError_enum::CUDA_ERROR_INVALID_SOURCE
+            }
+            DriverError::FileNotFound => {
+                cudaError_enum::CUDA_ERROR_FILE_NOT_FOUND
+            }
+            DriverError::SharedObjectSymbolNotFound => {
+                cudaError_enum::CUDA_ERROR_SHARED_OBJECT_SYMBOL_NOT_FOUND
+            }
+            DriverError::SharedObjectInitFailed => {
+                cudaError_enum::CUDA_ERROR_SHARED_OBJECT_INIT_FAILED
+            }
+            DriverError::OperatingSystem => {
+                cudaError_enum::CUDA_ERROR_OPERATING_SYSTEM
+            }
+            DriverError::InvalidHandle => {
+                cudaError_enum::CUDA_ERROR_INVALID_HANDLE
+            }
+            DriverError::IllegalState => {
+                cudaError_enum::CUDA_ERROR_ILLEGAL_STATE
+            }
+            DriverError::NotFound => cudaError_enum::CUDA_ERROR_NOT_FOUND,
+            DriverError::NotReady => cudaError_enum::CUDA_ERROR_NOT_READY,
+            DriverError::IllegalAddress => {
+                cudaError_enum::CUDA_ERROR_ILLEGAL_ADDRESS
+            }
+            DriverError::LaunchOutOfResources => {
+                cudaError_enum::CUDA_ERROR_LAUNCH_OUT_OF_RESOURCES
+            }
+            DriverError::LaunchTimeout => {
+                cudaError_enum::CUDA_ERROR_LAUNCH_TIMEOUT
+            }
+            DriverError::LaunchIncompatibleTexturing => {
+                cudaError_enum::CUDA_ERROR_LAUNCH_INCOMPATIBLE_TEXTURING
+            }
+            DriverError::PeerAccessAlreadyEnabled => {
+                cudaError_enum::CUDA_ERROR_PEER_ACCESS_ALREADY_ENABLED
+            }
+            DriverError::PeerAccessNotEnabled => {
+                cudaError_enum::CUDA_ERROR_PEER_ACCESS_NOT_ENABLED
+            }
+            DriverError::PrimaryContextActive => {
+                cudaError_enum::CUDA_ERROR_PRIMARY_CONTEXT_ACTIVE
+            }
+            DriverError::ContextIsDestroyed => {
+                cudaError_enum::CUDA_ERROR_CONTEXT_IS_DESTROYED
+            }
+            DriverError::Assert => cudaError_enum::CUDA_ERROR_ASSERT,
+            DriverError::TooManyPeers => {
+                cudaError_enum::CUDA_ERROR_TOO_MANY_PEERS
+            }
+            DriverError::HostMemoryAlreadyRegistered => {
+                cudaError_enum::CUDA_ERROR_HOST_MEMORY_ALREADY_REGISTERED
+            }
+            DriverError::HostMemoryNotRegistered => {
+                cudaError_enum::CUDA_ERROR_HOST_MEMORY_NOT_REGISTERED
+            }
+            DriverError::HardwareStackError => {
+                cudaError_enum::CUDA_ERROR_HARDWARE_STACK_ERROR
+            }
+            DriverError::IllegalInstruction => {
+                cudaError_enum::CUDA_ERROR_ILLEGAL_INSTRUCTION
+            }
+            DriverError::MisalignedAddress => {
+                cudaError_enum::CUDA_ERROR_MISALIGNED_ADDRESS
+            }
+            DriverError::InvalidAddressSpace => {
+                cudaError_enum::CUDA_ERROR_INVALID_ADDRESS_SPACE
+            }
+            DriverError::InvalidPc => cudaError_enum::CUDA_ERROR_INVALID_PC,
+            DriverError::LaunchFailed => {
+                cudaError_enum::CUDA_ERROR_LAUNCH_FAILED
+            }
+            DriverError::CooperativeLaunchTooLarge => {
+                cudaError_enum::CUDA_ERROR_COOPERATIVE_LAUNCH_TOO_LARGE
+            }
+            DriverError::NotPermitted => {
+                cudaError_enum::CUDA_ERROR_NOT_PERMITTED
+            }
+            DriverError::NotSupported => {
+                cudaError_enum::CUDA_ERROR_NOT_SUPPORTED
+            }
+            DriverError::SystemNotReady => {
+                cudaError_enum::CUDA_ERROR_SYSTEM_NOT_READY
+            }
+            DriverError::SystemDriverMismatch => {
+                cudaError_enum::CUDA_ERROR_SYSTEM_DRIVER_MISMATCH
+            }
+            DriverError::CompatNotSupportedOnDevice => {
+                cudaError_enum::CUDA_ERROR_COMPAT_NOT_SUPPORTED_ON_DEVICE
+            }
+            DriverError::StreamCaptureUnsupported => {
+                cudaError_enum::CUDA_ERROR_STREAM_CAPTURE_UNSUPPORTED
+            }
+            DriverError::StreamCaptureInvalidated => {
+                cudaError_enum::CUDA_ERROR_STREAM_CAPTURE_INVALIDATED
+            }
+            DriverError::StreamCaptureMerge => {
+                cudaError_enum::CUDA_ERROR_STREAM_CAPTURE_MERGE
+            }
+            DriverError::StreamCaptureUnmatched => {
+                cudaError_enum::CUDA_ERROR_STREAM_CAPTURE_UNMATCHED
+            }
+            DriverError::StreamCaptureUnjoined => {
+                cudaError_enum::CUDA_ERROR_STREAM_CAPTURE_UNJOINED
+            }
+            DriverError::StreamCaptureIsolation => {
+                cudaError_enum::CUDA_ERROR_STREAM_CAPTURE_ISOLATION
+            }
+            DriverError::StreamCaptureImplicit => {
+                cudaError_enum::CUDA_ERROR_STREAM_CAPTURE_IMPLICIT
+            }
+            DriverError::CapturedEvent => {
+                cudaError_enum::CUDA_ERROR_CAPTURED_EVENT
+            }
+            DriverError::StreamCaptureWrongThread => {
+                cudaError_enum::CUDA_ERROR_STREAM_CAPTURE_WRONG_THREAD
+            }
+            DriverError::Unknown => cudaError_enum::CUDA_ERROR_UNKNOWN,
+            DriverError::Unrecognized(code) => *code,
+        }
+    }
+
+    /// The name of the `CUresult` value, e.g. "CUDA_ERROR_INVALID_VALUE".
+    pub fn name(&self) -> &'static str {
+        match self {
+            DriverError::InvalidValue => "CUDA_ERROR_INVALID_VALUE",
+            DriverError::OutOfMemory => "CUDA_ERROR_OUT_OF_MEMORY",
+            DriverError::NotInitialized => "CUDA_ERROR_NOT_INITIALIZED",
+            DriverError::Deinitialized => "CUDA_ERROR_DEINITIALIZED",
+            DriverError::ProfilerDisabled => "CUDA_ERROR_PROFILER_DISABLED",
+            DriverError::ProfilerNotInitialized => {
+                "CUDA_ERROR_PROFILER_NOT_INITIALIZED"
+            }
+            DriverError::ProfilerAlreadyStarted => {
+                "CUDA_ERROR_PROFILER_ALREADY_STARTED"
+            }
+            DriverError::ProfilerAlreadyStopped => {
+                "CUDA_ERROR_PROFILER_ALREADY_STOPPED"
+            }
+            DriverError::NoDevice => "CUDA_ERROR_NO_DEVICE",
+            DriverError::InvalidDevice => "CUDA_ERROR_INVALID_DEVICE",
+            DriverError::InvalidImage => "CUDA_ERROR_INVALID_IMAGE",
+            DriverError::InvalidContext => "CUDA_ERROR_INVALID_CONTEXT",
+            DriverError::ContextAlreadyCurrent => {
+                "CUDA_ERROR_CONTEXT_ALREADY_CURRENT"
+            }
+            DriverError::MapFailed => "CUDA_ERROR_MAP_FAILED",
+            DriverError::UnmapFailed => "CUDA_ERROR_UNMAP_FAILED",
+            DriverError::ArrayIsMapped => "CUDA_ERROR_ARRAY_IS_MAPPED",
+            DriverError::AlreadyMapped => "CUDA_ERROR_ALREADY_MAPPED",
+            DriverError::NoBinaryForGpu => "CUDA_ERROR_NO_BINARY_FOR_GPU",
+            DriverError::AlreadyAcquired => "CUDA_ERROR_ALREADY_ACQUIRED",
+            DriverError::NotMapped => "CUDA_ERROR_NOT_MAPPED",
+            DriverError::NotMappedAsArray => "CUDA_ERROR_NOT_MAPPED_AS_ARRAY",
+            DriverError::NotMappedAsPointer => {
+                "CUDA_ERROR_NOT_MAPPED_AS_POINTER"
+            }
+            DriverError::ECCUncorrectable => "CUDA_ERROR_ECC_UNCORRECTABLE",
+            DriverError::UnsupportedLimit => "CUDA_ERROR_UNSUPPORTED_LIMIT",
+            DriverError::ContextAlreadyInUse => {
+                "CUDA_ERROR_CONTEXT_ALREADY_IN_USE"
+            }
+            DriverError::PeerAccessUnsupported => {
+                "CUDA_ERROR_PEER_ACCESS_UNSUPPORTED"
+            }
+            DriverError::InvalidPtx => "CUDA_ERROR_INVALID_PTX",
+            DriverError::InvalidGraphicsContext => {
+                "CUDA_ERROR_INVALID_GRAPHICS_CONTEXT"
+            }
+            DriverError::NvlinkUncorrectable => {
+                "CUDA_ERROR_NVLINK_UNCORRECTABLE"
+            }
+            DriverError::JitCompilerNotFound => {
+                "CUDA_ERROR_JIT_COMPILER_NOT_FOUND"
+            }
+            DriverError::InvalidSource => "CUDA_ERROR_INVALID_SOURCE",
+            DriverError::FileNotFound => "CUDA_ERROR_FILE_NOT_FOUND",
+            DriverError::SharedObjectSymbolNotFound => {
+                "CUDA_ERROR_SHARED_OBJECT_SYMBOL_NOT_FOUND"
+            }
+            DriverError::SharedObjectInitFailed => {
+                "CUDA_ERROR_SHARED_OBJECT_INIT_FAILED"
+            }
+            DriverError::OperatingSystem => "CUDA_ERROR_OPERATING_SYSTEM",
+            DriverError::InvalidHandle => "CUDA_ERROR_INVALID_HANDLE",
+            DriverError::IllegalState => "CUDA_ERROR_ILLEGAL_STATE",
+            DriverError::NotFound => "CUDA_ERROR_NOT_FOUND",
+            DriverError::NotReady => "CUDA_ERROR_NOT_READY",
+            DriverError::IllegalAddress => "CUDA_ERROR_ILLEGAL_ADDRESS",
+            DriverError::LaunchOutOfResources => {
+                "CUDA_ERROR_LAUNCH_OUT_OF_RESOURCES"
+            }
+            DriverError::LaunchTimeout => "CUDA_ERROR_LAUNCH_TIMEOUT",
+            DriverError::LaunchIncompatibleTexturing => {
+                "CUDA_ERROR_LAUNCH_INCOMPATIBLE_TEXTURING"
+            }
+            DriverError::PeerAccessAlreadyEnabled => {
+                "CUDA_ERROR_PEER_ACCESS_ALREADY_ENABLED"
+            }
+            DriverError::PeerAccessNotEnabled => {
+                "CUDA_ERROR_PEER_ACCESS_NOT_ENABLED"
+            }
+            DriverError::PrimaryContextActive => {
+                "CUDA_ERROR_PRIMARY_CONTEXT_ACTIVE"
+            }
+            DriverError::ContextIsDestroyed => {
+                "CUDA_ERROR_CONTEXT_IS_DESTROYED"
+            }
+            DriverError::Assert => "CUDA_ERROR_ASSERT",
+            DriverError::TooManyPeers => "CUDA_ERROR_TOO_MANY_PEERS",
+            DriverError::HostMemoryAlreadyRegistered => {
+                "CUDA_ERROR_HOST_MEMORY_ALREADY_REGISTERED"
+            }
+            DriverError::HostMemoryNotRegistered => {
+                "CUDA_ERROR_HOST_MEMORY_NOT_REGISTERED"
+            }
+            DriverError::HardwareStackError => {
+                "CUDA_ERROR_HARDWARE_STACK_ERROR"
+            }
+            DriverError::IllegalInstruction => "CUDA_ERROR_ILLEGAL_INSTRUCTION",
+            DriverError::MisalignedAddress => "CUDA_ERROR_MISALIGNED_ADDRESS",
+            DriverError::InvalidAddressSpace => {
+                "CUDA_ERROR_INVALID_ADDRESS_SPACE"
+            }
+            DriverError::InvalidPc => "CUDA_ERROR_INVALID_PC",
+            DriverError::LaunchFailed => "CUDA_ERROR_LAUNCH_FAILED",
+            DriverError::CooperativeLaunchTooLarge => {
+                "CUDA_ERROR_COOPERATIVE_LAUNCH_TOO_LARGE"
+            }
+            DriverError::NotPermitted => "CUDA_ERROR_NOT_PERMITTED",
+            DriverError::NotSupported => "CUDA_ERROR_NOT_SUPPORTED",
+            DriverError::SystemNotReady => "CUDA_ERROR_SYSTEM_NOT_READY",
+            DriverError::SystemDriverMismatch => {
+                "CUDA_ERROR_SYSTEM_DRIVER_MISMATCH"
+            }
+            DriverError::CompatNotSupportedOnDevice => {
+                "CUDA_ERROR_COMPAT_NOT_SUPPORTED_ON_DEVICE"
+            }
+            DriverError::StreamCaptureUnsupported => {
+                "CUDA_ERROR_STREAM_CAPTURE_UNSUPPORTED"
+            }
+            DriverError::StreamCaptureInvalidated => {
+                "CUDA_ERROR_STREAM_CAPTURE_INVALIDATED"
+            }
+            DriverError::StreamCaptureMerge => {
+                "CUDA_ERROR_STREAM_CAPTURE_MERGE"
+            }
+            DriverError::StreamCaptureUnmatched => {
+                "CUDA_ERROR_STREAM_CAPTURE_UNMATCHED"
+            }
+            DriverError::StreamCaptureUnjoined => {
+                "CUDA_ERROR_STREAM_CAPTURE_UNJOINED"
+            }
+            DriverError::StreamCaptureIsolation => {
+                "CUDA_ERROR_STREAM_CAPTURE_ISOLATION"
+            }
+            DriverError::StreamCaptureImplicit => {
+                "CUDA_ERROR_STREAM_CAPTURE_IMPLICIT"
+            }
+            DriverError::CapturedEvent => "CUDA_ERROR_CAPTURED_EVENT",
+            DriverError::StreamCaptureWrongThread => {
+                "CUDA_ERROR_STREAM_CAPTURE_WRONG_THREAD"
+            }
+            DriverError::Unknown => "CUDA_ERROR_UNKNOWN",
+            DriverError::Unrecognized(_) => "CUDA_ERROR_UNRECOGNIZED",
+        }
+    }
 }
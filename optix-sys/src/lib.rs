@@ -1,16 +1,24 @@
 #![allow(non_upper_case_globals)]
 #![allow(non_camel_case_types)]
 #![allow(non_snake_case)]
-#![feature(untagged_unions)]
+// lints that fire on the bindgen output
+#![allow(
+    clippy::missing_safety_doc,
+    clippy::non_canonical_clone_impl,
+    clippy::type_complexity
+)]
 
+mod cuda_loader;
 pub mod cuda_sys;
 use cuda_sys::{CUcontext, CUdeviceptr, CUstream};
 
 include!(concat!(env!("OUT_DIR"), "/optix_wrapper.rs"));
 
-extern "C" {
-    pub fn optixInit() -> OptixResult;
-}
+pub mod loader;
+pub use loader::LoadError;
+
+mod stubs;
+pub use stubs::*;
 
 #[repr(C)]
 pub struct SbtRecordHeader {
@@ -31,9 +39,15 @@ impl Default for SbtRecordHeader {
     }
 }
 
+/// The build input union. The SDK pads this to 1024 bytes in every version so
+/// that newer input types can be added without changing its size.
 #[repr(C)]
 pub union OptixBuildInputUnion {
     pub triangle_array: OptixBuildInputTriangleArray,
+    #[cfg(optix_7_3)]
+    pub curve_array: OptixBuildInputCurveArray,
+    #[cfg(optix_7_5)]
+    pub sphere_array: OptixBuildInputSphereArray,
     pub aabb_array: OptixBuildInputCustomPrimitiveArray,
     pub instance_array: OptixBuildInputInstanceArray,
     pad: [std::os::raw::c_char; 1024],
@@ -62,8 +76,12 @@ fn _size_check() {
     }
 }
 
+/// An `OptixResult` other than `OPTIX_SUCCESS`.
+///
+/// Displays as the result's name followed by its description, e.g.
+/// "OPTIX_ERROR_INVALID_VALUE: Invalid value".
 #[repr(u32)]
-#[derive(Debug)]
+#[derive(Debug, Copy, Clone, PartialEq, Eq, Hash)]
 pub enum Error {
     InvalidValue = OptixResult::OPTIX_ERROR_INVALID_VALUE as u32,
     HostOutOfMemory = OptixResult::OPTIX_ERROR_HOST_OUT_OF_MEMORY as u32,
@@ -82,6 +100,8 @@ pub enum Error {
     InvalidDeviceContext =
         OptixResult::OPTIX_ERROR_INVALID_DEVICE_CONTEXT as u32,
     CudaNotInitialized = OptixResult::OPTIX_ERROR_CUDA_NOT_INITIALIZED as u32,
+    #[cfg(optix_7_3)]
+    ValidationFailure = OptixResult::OPTIX_ERROR_VALIDATION_FAILURE as u32,
     InvalidPtx = OptixResult::OPTIX_ERROR_INVALID_PTX as u32,
     InvalidLaunchParameter =
         OptixResult::OPTIX_ERROR_INVALID_LAUNCH_PARAMETER as u32,
@@ -94,7 +114,18 @@ pub enum Error {
         OptixResult::OPTIX_ERROR_INVALID_FUNCTION_ARGUMENTS as u32,
     PipelineOutOfConstantMemory =
         OptixResult::OPTIX_ERROR_PIPELINE_OUT_OF_CONSTANT_MEMORY as u32,
+    #[cfg(optix_7_4)]
+    PayloadTypeMismatch = OptixResult::OPTIX_ERROR_PAYLOAD_TYPE_MISMATCH as u32,
+    #[cfg(optix_7_4)]
+    PayloadTypeResolutionFailed =
+        OptixResult::OPTIX_ERROR_PAYLOAD_TYPE_RESOLUTION_FAILED as u32,
+    #[cfg(optix_7_4)]
+    PayloadTypeIdInvalid =
+        OptixResult::OPTIX_ERROR_PAYLOAD_TYPE_ID_INVALID as u32,
     PipelineLinkError = OptixResult::OPTIX_ERROR_PIPELINE_LINK_ERROR as u32,
+    #[cfg(optix_7_4)]
+    IllegalDuringTaskExecute =
+        OptixResult::OPTIX_ERROR_ILLEGAL_DURING_TASK_EXECUTE as u32,
     InternalCompilerError =
         OptixResult::OPTIX_ERROR_INTERNAL_COMPILER_ERROR as u32,
     DenoiserModelNotSet =
@@ -112,15 +143,72 @@ pub enum Error {
     LibraryNotFound = OptixResult::OPTIX_ERROR_LIBRARY_NOT_FOUND as u32,
     EntrySymbolNotFound =
         OptixResult::OPTIX_ERROR_ENTRY_SYMBOL_NOT_FOUND as u32,
+    #[cfg(optix_7_3)]
+    LibraryUnloadFailure =
+        OptixResult::OPTIX_ERROR_LIBRARY_UNLOAD_FAILURE as u32,
+    #[cfg(optix_7_5)]
+    DeviceOutOfMemory = OptixResult::OPTIX_ERROR_DEVICE_OUT_OF_MEMORY as u32,
     CudaError = OptixResult::OPTIX_ERROR_CUDA_ERROR as u32,
     InternalError = OptixResult::OPTIX_ERROR_INTERNAL_ERROR as u32,
     Unknown = OptixResult::OPTIX_ERROR_UNKNOWN as u32,
 }
 
+impl Error {
+    /// The `OptixResult` this error was created from.
+    pub fn result(&self) -> OptixResult {
+        // Safe because every discriminant of Error is the value of an
+        // OptixResult variant
+        unsafe { std::mem::transmute::<u32, OptixResult>(*self as u32) }
+    }
+
+    /// The name of the result code, e.g. "OPTIX_ERROR_INVALID_VALUE".
+    pub fn name(&self) -> &'static str {
+        result_name(self.result())
+    }
+
+    /// A short description of the result code, e.g. "Invalid value".
+    pub fn description(&self) -> &'static str {
+        result_description(self.result())
+    }
+}
+
+/// The name of `result`, from `optixGetErrorName()` if the function table is
+/// loaded or a static table if not.
+pub fn result_name(result: OptixResult) -> &'static str {
+    // the strings returned by the driver live as long as the library, which
+    // is never unloaded once the function table is installed
+    unsafe { static_str(optixGetErrorName(result)) }
+        .unwrap_or_else(|| {
+            static_str_bytes(stubs::static_error_strings(result).0)
+        })
+}
+
+/// The description of `result`, from `optixGetErrorString()` if the
+/// function table is loaded or a static table if not.
+pub fn result_description(result: OptixResult) -> &'static str {
+    unsafe { static_str(optixGetErrorString(result)) }
+        .unwrap_or_else(|| {
+            static_str_bytes(stubs::static_error_strings(result).1)
+        })
+}
+
+unsafe fn static_str(ptr: *const std::os::raw::c_char) -> Option<&'static str> {
+    if ptr.is_null() {
+        None
+    } else {
+        std::ffi::CStr::from_ptr(ptr).to_str().ok()
+    }
+}
+
+fn static_str_bytes(bytes: &'static [u8]) -> &'static str {
+    // the static table is all NUL-terminated ASCII
+    std::str::from_utf8(&bytes[..bytes.len() - 1]).unwrap()
+}
+
 use std::fmt;
 impl fmt::Display for Error {
     fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
-        write!(f, "{:?}", self)
+        write!(f, "{}: {}", self.name(), self.description())
     }
 }
 
@@ -157,6 +245,10 @@ impl From<OptixResult> for Error {
             OptixResult::OPTIX_ERROR_CUDA_NOT_INITIALIZED => {
                 Error::CudaNotInitialized
             }
+            #[cfg(optix_7_3)]
+            OptixResult::OPTIX_ERROR_VALIDATION_FAILURE => {
+                Error::ValidationFailure
+            }
             OptixResult::OPTIX_ERROR_INVALID_PTX => Error::InvalidPtx,
             OptixResult::OPTIX_ERROR_INVALID_LAUNCH_PARAMETER => {
                 Error::InvalidLaunchParameter
@@ -176,9 +268,25 @@ impl From<OptixResult> for Error {
             OptixResult::OPTIX_ERROR_PIPELINE_OUT_OF_CONSTANT_MEMORY => {
                 Error::PipelineOutOfConstantMemory
             }
+            #[cfg(optix_7_4)]
+            OptixResult::OPTIX_ERROR_PAYLOAD_TYPE_MISMATCH => {
+                Error::PayloadTypeMismatch
+            }
+            #[cfg(optix_7_4)]
+            OptixResult::OPTIX_ERROR_PAYLOAD_TYPE_RESOLUTION_FAILED => {
+                Error::PayloadTypeResolutionFailed
+            }
+            #[cfg(optix_7_4)]
+            OptixResult::OPTIX_ERROR_PAYLOAD_TYPE_ID_INVALID => {
+                Error::PayloadTypeIdInvalid
+            }
             OptixResult::OPTIX_ERROR_PIPELINE_LINK_ERROR => {
                 Error::PipelineLinkError
             }
+            #[cfg(optix_7_4)]
+            OptixResult::OPTIX_ERROR_ILLEGAL_DURING_TASK_EXECUTE => {
+                Error::IllegalDuringTaskExecute
+            }
             OptixResult::OPTIX_ERROR_INTERNAL_COMPILER_ERROR => {
                 Error::InternalCompilerError
             }
@@ -207,6 +315,14 @@ impl From<OptixResult> for Error {
             OptixResult::OPTIX_ERROR_ENTRY_SYMBOL_NOT_FOUND => {
                 Error::EntrySymbolNotFound
             }
+            #[cfg(optix_7_3)]
+            OptixResult::OPTIX_ERROR_LIBRARY_UNLOAD_FAILURE => {
+                Error::LibraryUnloadFailure
+            }
+            #[cfg(optix_7_5)]
+            OptixResult::OPTIX_ERROR_DEVICE_OUT_OF_MEMORY => {
+                Error::DeviceOutOfMemory
+            }
             OptixResult::OPTIX_ERROR_CUDA_ERROR => Error::CudaError,
             OptixResult::OPTIX_ERROR_INTERNAL_ERROR => Error::InternalError,
             OptixResult::OPTIX_ERROR_UNKNOWN => Error::Unknown,
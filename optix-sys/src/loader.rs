@@ -0,0 +1,255 @@
+//! Runtime loader for the OptiX function table.
+//!
+//! OptiX is shipped as part of the NVIDIA driver rather than as a library we
+//! can link against. The SDK expects the application to open the driver's
+//! OptiX library, look up `optixQueryFunctionTable` and use it to fill in a
+//! table of function pointers that all the `optix*` entry points forward to.
+//! This module does that in pure Rust so that binaries can start on machines
+//! without a driver and report exactly why OptiX is unavailable.
+
+use super::{OptixFunctionTable, OptixQueryFunctionTableOptions, OptixResult};
+
+use std::ffi::{OsStr, OsString};
+use std::os::raw::{c_int, c_uint, c_void};
+use std::sync::OnceLock;
+
+/// The ABI version of the headers these bindings were generated from.
+pub const ABI_VERSION: c_int = super::OptixAbiVersion;
+
+/// Name of the OptiX library installed by the driver on this platform.
+#[cfg(target_os = "windows")]
+pub const DEFAULT_LIBRARY: &str = "nvoptix.dll";
+#[cfg(not(target_os = "windows"))]
+pub const DEFAULT_LIBRARY: &str = "libnvoptix.so.1";
+
+#[cfg(target_os = "windows")]
+const CUDA_DRIVER_LIBRARY: &str = "nvcuda.dll";
+#[cfg(not(target_os = "windows"))]
+const CUDA_DRIVER_LIBRARY: &str = "libcuda.so.1";
+
+const QUERY_FUNCTION_TABLE_SYMBOL: &str = "optixQueryFunctionTable";
+
+type QueryFunctionTable = unsafe extern "C" fn(
+    c_int,
+    c_uint,
+    *mut OptixQueryFunctionTableOptions,
+    *mut *const c_void,
+    *mut c_void,
+    usize,
+) -> OptixResult;
+
+type DriverGetVersion = unsafe extern "C" fn(*mut c_int) -> c_int;
+
+#[derive(Debug, thiserror::Error)]
+pub enum LoadError {
+    #[error(
+        "Could not load OptiX library '{}': {reason:}",
+        .library.to_string_lossy()
+    )]
+    LibraryNotFound { library: OsString, reason: String },
+    #[error(
+        "OptiX library '{}' does not export '{symbol:}'",
+        .library.to_string_lossy()
+    )]
+    EntrySymbolNotFound {
+        library: OsString,
+        symbol: &'static str,
+    },
+    #[error(
+        "OptiX ABI version {abi_version:} is not supported by {}",
+        display_driver_version(.driver_version)
+    )]
+    UnsupportedAbiVersion {
+        abi_version: c_int,
+        driver_version: Option<i32>,
+    },
+    #[error(
+        "OptiX function table size mismatch for ABI version {abi_version:} (table is {table_size:} bytes) with {}",
+        display_driver_version(.driver_version)
+    )]
+    FunctionTableSizeMismatch {
+        abi_version: c_int,
+        table_size: usize,
+        driver_version: Option<i32>,
+    },
+    #[error("optixQueryFunctionTable failed with {result:?}")]
+    QueryFunctionTableFailed { result: OptixResult },
+}
+
+fn display_driver_version(driver_version: &Option<i32>) -> String {
+    match driver_version {
+        Some(v) => {
+            format!("CUDA driver version {}.{}", v / 1000, (v % 1000) / 10)
+        }
+        None => "the installed driver (version unknown)".into(),
+    }
+}
+
+/// An opened OptiX library together with the function table it provided.
+///
+/// The library is kept open for as long as the table is alive since the
+/// function pointers point into it.
+pub struct FunctionTable {
+    table: OptixFunctionTable,
+    _library: libloading::Library,
+}
+
+impl FunctionTable {
+    /// Open the OptiX library at `library` and query its function table for
+    /// the ABI version these bindings were generated against.
+    ///
+    /// This does not install the table for use by the `optix*` functions,
+    /// use `load_from()` for that.
+    pub fn open<P: AsRef<OsStr>>(
+        library: P,
+    ) -> Result<FunctionTable, LoadError> {
+        let library = library.as_ref();
+        let lib =
+            unsafe { libloading::Library::new(library) }.map_err(|e| {
+                LoadError::LibraryNotFound {
+                    library: library.to_os_string(),
+                    reason: e.to_string(),
+                }
+            })?;
+
+        let query = unsafe {
+            lib.get::<QueryFunctionTable>(
+                QUERY_FUNCTION_TABLE_SYMBOL.as_bytes(),
+            )
+        }
+        .map_err(|_| LoadError::EntrySymbolNotFound {
+            library: library.to_os_string(),
+            symbol: QUERY_FUNCTION_TABLE_SYMBOL,
+        })?;
+
+        // a zeroed table is all `None`s, which is what we want if the query
+        // only fills in part of it
+        let mut table: OptixFunctionTable = unsafe { std::mem::zeroed() };
+        let table_size = std::mem::size_of::<OptixFunctionTable>();
+        let res = unsafe {
+            query(
+                ABI_VERSION,
+                0,
+                std::ptr::null_mut(),
+                std::ptr::null_mut(),
+                &mut table as *mut OptixFunctionTable as *mut c_void,
+                table_size,
+            )
+        };
+
+        match res {
+            OptixResult::OPTIX_SUCCESS => Ok(FunctionTable {
+                table,
+                _library: lib,
+            }),
+            OptixResult::OPTIX_ERROR_UNSUPPORTED_ABI_VERSION => {
+                Err(LoadError::UnsupportedAbiVersion {
+                    abi_version: ABI_VERSION,
+                    driver_version: driver_version(),
+                })
+            }
+            OptixResult::OPTIX_ERROR_FUNCTION_TABLE_SIZE_MISMATCH => {
+                Err(LoadError::FunctionTableSizeMismatch {
+                    abi_version: ABI_VERSION,
+                    table_size,
+                    driver_version: driver_version(),
+                })
+            }
+            result => Err(LoadError::QueryFunctionTableFailed { result }),
+        }
+    }
+
+    pub fn table(&self) -> &OptixFunctionTable {
+        &self.table
+    }
+}
+
+static FUNCTION_TABLE: OnceLock<FunctionTable> = OnceLock::new();
+
+/// Load the driver's OptiX library and install its function table.
+///
+/// Calling this again once a table has been installed is a no-op.
+pub fn load() -> Result<(), LoadError> {
+    load_from(DEFAULT_LIBRARY)
+}
+
+/// Load the OptiX library at `library` and install its function table.
+///
+/// Calling this again once a table has been installed is a no-op.
+pub fn load_from<P: AsRef<OsStr>>(library: P) -> Result<(), LoadError> {
+    if is_loaded() {
+        return Ok(());
+    }
+
+    let table = FunctionTable::open(library)?;
+    // if another thread beat us to it then just drop ours
+    let _ = FUNCTION_TABLE.set(table);
+    Ok(())
+}
+
+/// Returns true if a function table has been installed.
+pub fn is_loaded() -> bool {
+    FUNCTION_TABLE.get().is_some()
+}
+
+/// Get the installed function table, or `None` if `load()` has not yet
+/// succeeded.
+pub fn function_table() -> Option<&'static OptixFunctionTable> {
+    FUNCTION_TABLE.get().map(|t| t.table())
+}
+
+/// Query the version of the installed CUDA driver, e.g. 11040 for 11.4.
+///
+/// The driver library is opened dynamically so this can be called on machines
+/// without a driver, in which case it returns `None`.
+pub fn driver_version() -> Option<i32> {
+    unsafe {
+        let lib = libloading::Library::new(CUDA_DRIVER_LIBRARY).ok()?;
+        let get_version =
+            lib.get::<DriverGetVersion>(b"cuDriverGetVersion").ok()?;
+        let mut version = 0;
+        if get_version(&mut version) == 0 {
+            Some(version)
+        } else {
+            None
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{FunctionTable, LoadError};
+
+    #[test]
+    fn missing_library() {
+        match FunctionTable::open("/nonexistent/libnvoptix.so.1") {
+            Err(LoadError::LibraryNotFound { library, .. }) => {
+                assert_eq!(library, "/nonexistent/libnvoptix.so.1")
+            }
+            Err(e) => panic!("unexpected error: {}", e),
+            Ok(_) => panic!("loaded a library that does not exist"),
+        }
+    }
+
+    #[cfg(target_os = "linux")]
+    #[test]
+    fn bogus_library() {
+        // libm is always present but certainly doesn't contain OptiX
+        match FunctionTable::open("libm.so.6") {
+            Err(LoadError::EntrySymbolNotFound { library, symbol }) => {
+                assert_eq!(library, "libm.so.6");
+                assert_eq!(symbol, "optixQueryFunctionTable");
+            }
+            Err(e) => panic!("unexpected error: {}", e),
+            Ok(_) => panic!("found an OptiX function table in libm"),
+        }
+    }
+
+    #[test]
+    fn driver_version_display() {
+        assert_eq!(
+            super::display_driver_version(&Some(11040)),
+            "CUDA driver version 11.4"
+        );
+    }
+}
@@ -0,0 +1,595 @@
+//! Entry points that forward to the function table installed by the
+//! `loader`. These replace the stubs the SDK provides in
+//! `optix_stubs.h`.
+//!
+//! If no function table has been loaded every entry point returns
+//! `OPTIX_ERROR_INVALID_OPERATION` rather than calling through a null
+//! pointer.
+#![allow(clippy::missing_safety_doc, clippy::too_many_arguments)]
+
+use super::cuda_sys::{CUcontext, CUdeviceptr, CUstream};
+use super::loader;
+use super::*;
+
+use std::os::raw::{c_char, c_int, c_uint, c_void};
+
+macro_rules! optix_stubs {
+    ($(pub fn $name:ident($($arg:ident: $ty:ty),* $(,)?);)*) => {
+        $(
+            pub unsafe fn $name($($arg: $ty),*) -> OptixResult {
+                match loader::function_table().and_then(|t| t.$name) {
+                    Some(f) => f($($arg),*),
+                    None => OptixResult::OPTIX_ERROR_INVALID_OPERATION,
+                }
+            }
+        )*
+    };
+}
+
+optix_stubs! {
+    pub fn optixDeviceContextCreate(
+        fromContext: CUcontext,
+        options: *const OptixDeviceContextOptions,
+        context: *mut OptixDeviceContext,
+    );
+    pub fn optixDeviceContextDestroy(context: OptixDeviceContext);
+    pub fn optixDeviceContextGetProperty(
+        context: OptixDeviceContext,
+        property: OptixDeviceProperty,
+        value: *mut c_void,
+        sizeInBytes: usize,
+    );
+    pub fn optixDeviceContextSetLogCallback(
+        context: OptixDeviceContext,
+        callbackFunction: OptixLogCallback,
+        callbackData: *mut c_void,
+        callbackLevel: c_uint,
+    );
+    pub fn optixDeviceContextSetCacheEnabled(
+        context: OptixDeviceContext,
+        enabled: c_int,
+    );
+    pub fn optixDeviceContextSetCacheLocation(
+        context: OptixDeviceContext,
+        location: *const c_char,
+    );
+    pub fn optixDeviceContextSetCacheDatabaseSizes(
+        context: OptixDeviceContext,
+        lowWaterMark: usize,
+        highWaterMark: usize,
+    );
+    pub fn optixDeviceContextGetCacheEnabled(
+        context: OptixDeviceContext,
+        enabled: *mut c_int,
+    );
+    pub fn optixDeviceContextGetCacheLocation(
+        context: OptixDeviceContext,
+        location: *mut c_char,
+        locationSize: usize,
+    );
+    pub fn optixDeviceContextGetCacheDatabaseSizes(
+        context: OptixDeviceContext,
+        lowWaterMark: *mut usize,
+        highWaterMark: *mut usize,
+    );
+    pub fn optixModuleCreateFromPTX(
+        context: OptixDeviceContext,
+        moduleCompileOptions: *const OptixModuleCompileOptions,
+        pipelineCompileOptions: *const OptixPipelineCompileOptions,
+        PTX: *const c_char,
+        PTXsize: usize,
+        logString: *mut c_char,
+        logStringSize: *mut usize,
+        module: *mut OptixModule,
+    );
+    pub fn optixModuleDestroy(module: OptixModule);
+    pub fn optixProgramGroupCreate(
+        context: OptixDeviceContext,
+        programDescriptions: *const OptixProgramGroupDesc,
+        numProgramGroups: c_uint,
+        options: *const OptixProgramGroupOptions,
+        logString: *mut c_char,
+        logStringSize: *mut usize,
+        programGroups: *mut OptixProgramGroup,
+    );
+    pub fn optixProgramGroupDestroy(programGroup: OptixProgramGroup);
+    pub fn optixProgramGroupGetStackSize(
+        programGroup: OptixProgramGroup,
+        stackSizes: *mut OptixStackSizes,
+    );
+    pub fn optixPipelineCreate(
+        context: OptixDeviceContext,
+        pipelineCompileOptions: *const OptixPipelineCompileOptions,
+        pipelineLinkOptions: *const OptixPipelineLinkOptions,
+        programGroups: *const OptixProgramGroup,
+        numProgramGroups: c_uint,
+        logString: *mut c_char,
+        logStringSize: *mut usize,
+        pipeline: *mut OptixPipeline,
+    );
+    pub fn optixPipelineDestroy(pipeline: OptixPipeline);
+    pub fn optixPipelineSetStackSize(
+        pipeline: OptixPipeline,
+        directCallableStackSizeFromTraversal: c_uint,
+        directCallableStackSizeFromState: c_uint,
+        continuationStackSize: c_uint,
+        maxTraversableGraphDepth: c_uint,
+    );
+    pub fn optixAccelComputeMemoryUsage(
+        context: OptixDeviceContext,
+        accelOptions: *const OptixAccelBuildOptions,
+        buildInputs: *const OptixBuildInput,
+        numBuildInputs: c_uint,
+        bufferSizes: *mut OptixAccelBufferSizes,
+    );
+    pub fn optixAccelBuild(
+        context: OptixDeviceContext,
+        stream: CUstream,
+        accelOptions: *const OptixAccelBuildOptions,
+        buildInputs: *const OptixBuildInput,
+        numBuildInputs: c_uint,
+        tempBuffer: CUdeviceptr,
+        tempBufferSizeInBytes: usize,
+        outputBuffer: CUdeviceptr,
+        outputBufferSizeInBytes: usize,
+        outputHandle: *mut OptixTraversableHandle,
+        emittedProperties: *const OptixAccelEmitDesc,
+        numEmittedProperties: c_uint,
+    );
+    pub fn optixAccelGetRelocationInfo(
+        context: OptixDeviceContext,
+        handle: OptixTraversableHandle,
+        info: *mut OptixAccelRelocationInfo,
+    );
+    pub fn optixAccelCheckRelocationCompatibility(
+        context: OptixDeviceContext,
+        info: *const OptixAccelRelocationInfo,
+        compatible: *mut c_int,
+    );
+    pub fn optixAccelRelocate(
+        context: OptixDeviceContext,
+        stream: CUstream,
+        info: *const OptixAccelRelocationInfo,
+        instanceTraversableHandles: CUdeviceptr,
+        numInstanceTraversableHandles: usize,
+        targetAccel: CUdeviceptr,
+        targetAccelSizeInBytes: usize,
+        targetHandle: *mut OptixTraversableHandle,
+    );
+    pub fn optixAccelCompact(
+        context: OptixDeviceContext,
+        stream: CUstream,
+        inputHandle: OptixTraversableHandle,
+        outputBuffer: CUdeviceptr,
+        outputBufferSizeInBytes: usize,
+        outputHandle: *mut OptixTraversableHandle,
+    );
+    pub fn optixConvertPointerToTraversableHandle(
+        onDevice: OptixDeviceContext,
+        pointer: CUdeviceptr,
+        traversableType: OptixTraversableType,
+        traversableHandle: *mut OptixTraversableHandle,
+    );
+    pub fn optixSbtRecordPackHeader(
+        programGroup: OptixProgramGroup,
+        sbtRecordHeaderHostPointer: *mut c_void,
+    );
+    pub fn optixLaunch(
+        pipeline: OptixPipeline,
+        stream: CUstream,
+        pipelineParams: CUdeviceptr,
+        pipelineParamsSize: usize,
+        sbt: *const OptixShaderBindingTable,
+        width: c_uint,
+        height: c_uint,
+        depth: c_uint,
+    );
+}
+
+// The denoiser API was reworked in 7.3 to take guide layers and a model kind
+// at creation time.
+#[cfg(not(optix_7_3))]
+optix_stubs! {
+    pub fn optixDenoiserCreate(
+        context: OptixDeviceContext,
+        options: *const OptixDenoiserOptions,
+        returnHandle: *mut OptixDenoiser,
+    );
+    pub fn optixDenoiserDestroy(handle: OptixDenoiser);
+    pub fn optixDenoiserComputeMemoryResources(
+        handle: OptixDenoiser,
+        maximumOutputWidth: c_uint,
+        maximumOutputHeight: c_uint,
+        returnSizes: *mut OptixDenoiserSizes,
+    );
+    pub fn optixDenoiserSetup(
+        denoiser: OptixDenoiser,
+        stream: CUstream,
+        outputWidth: c_uint,
+        outputHeight: c_uint,
+        state: CUdeviceptr,
+        stateSizeInBytes: usize,
+        scratch: CUdeviceptr,
+        scratchSizeInBytes: usize,
+    );
+    pub fn optixDenoiserInvoke(
+        denoiser: OptixDenoiser,
+        stream: CUstream,
+        params: *const OptixDenoiserParams,
+        denoiserState: CUdeviceptr,
+        denoiserStateSizeInBytes: usize,
+        inputLayers: *const OptixImage2D,
+        numInputLayers: c_uint,
+        inputOffsetX: c_uint,
+        inputOffsetY: c_uint,
+        outputLayer: *const OptixImage2D,
+        scratch: CUdeviceptr,
+        scratchSizeInBytes: usize,
+    );
+    pub fn optixDenoiserSetModel(
+        handle: OptixDenoiser,
+        kind: OptixDenoiserModelKind,
+        data: *mut c_void,
+        sizeInBytes: usize,
+    );
+    pub fn optixDenoiserComputeIntensity(
+        handle: OptixDenoiser,
+        stream: CUstream,
+        inputImage: *const OptixImage2D,
+        outputIntensity: CUdeviceptr,
+        scratch: CUdeviceptr,
+        scratchSizeInBytes: usize,
+    );
+}
+
+#[cfg(optix_7_3)]
+optix_stubs! {
+    pub fn optixBuiltinISModuleGet(
+        context: OptixDeviceContext,
+        moduleCompileOptions: *const OptixModuleCompileOptions,
+        pipelineCompileOptions: *const OptixPipelineCompileOptions,
+        builtinISOptions: *const OptixBuiltinISOptions,
+        builtinModule: *mut OptixModule,
+    );
+    pub fn optixDenoiserCreate(
+        context: OptixDeviceContext,
+        modelKind: OptixDenoiserModelKind,
+        options: *const OptixDenoiserOptions,
+        returnHandle: *mut OptixDenoiser,
+    );
+    pub fn optixDenoiserCreateWithUserModel(
+        context: OptixDeviceContext,
+        userData: *const c_void,
+        userDataSizeInBytes: usize,
+        returnHandle: *mut OptixDenoiser,
+    );
+    pub fn optixDenoiserDestroy(handle: OptixDenoiser);
+    pub fn optixDenoiserComputeMemoryResources(
+        handle: OptixDenoiser,
+        maximumOutputWidth: c_uint,
+        maximumOutputHeight: c_uint,
+        returnSizes: *mut OptixDenoiserSizes,
+    );
+    pub fn optixDenoiserSetup(
+        denoiser: OptixDenoiser,
+        stream: CUstream,
+        inputWidth: c_uint,
+        inputHeight: c_uint,
+        denoiserState: CUdeviceptr,
+        denoiserStateSizeInBytes: usize,
+        scratch: CUdeviceptr,
+        scratchSizeInBytes: usize,
+    );
+    pub fn optixDenoiserInvoke(
+        denoiser: OptixDenoiser,
+        stream: CUstream,
+        params: *const OptixDenoiserParams,
+        denoiserState: CUdeviceptr,
+        denoiserStateSizeInBytes: usize,
+        guideLayer: *const OptixDenoiserGuideLayer,
+        layers: *const OptixDenoiserLayer,
+        numLayers: c_uint,
+        inputOffsetX: c_uint,
+        inputOffsetY: c_uint,
+        scratch: CUdeviceptr,
+        scratchSizeInBytes: usize,
+    );
+    pub fn optixDenoiserComputeIntensity(
+        handle: OptixDenoiser,
+        stream: CUstream,
+        inputImage: *const OptixImage2D,
+        outputIntensity: CUdeviceptr,
+        scratch: CUdeviceptr,
+        scratchSizeInBytes: usize,
+    );
+    pub fn optixDenoiserComputeAverageColor(
+        handle: OptixDenoiser,
+        stream: CUstream,
+        inputImage: *const OptixImage2D,
+        outputAverageColor: CUdeviceptr,
+        scratch: CUdeviceptr,
+        scratchSizeInBytes: usize,
+    );
+}
+
+#[cfg(optix_7_4)]
+optix_stubs! {
+    pub fn optixModuleCreateFromPTXWithTasks(
+        context: OptixDeviceContext,
+        moduleCompileOptions: *const OptixModuleCompileOptions,
+        pipelineCompileOptions: *const OptixPipelineCompileOptions,
+        PTX: *const c_char,
+        PTXsize: usize,
+        logString: *mut c_char,
+        logStringSize: *mut usize,
+        module: *mut OptixModule,
+        firstTask: *mut OptixTask,
+    );
+    pub fn optixModuleGetCompilationState(
+        module: OptixModule,
+        state: *mut OptixModuleCompileState,
+    );
+    pub fn optixTaskExecute(
+        task: OptixTask,
+        additionalTasks: *mut OptixTask,
+        maxNumAdditionalTasks: c_uint,
+        numAdditionalTasksCreated: *mut c_uint,
+    );
+}
+
+/// The name and description of every result code, as NUL-terminated strings.
+///
+/// These are what `optixGetErrorName` and `optixGetErrorString` return when
+/// the function table has not been loaded, so errors are still readable when
+/// OptiX is missing or failed to load.
+pub(crate) fn static_error_strings(
+    result: OptixResult,
+) -> (&'static [u8], &'static [u8]) {
+    match result {
+        OptixResult::OPTIX_SUCCESS => (b"OPTIX_SUCCESS\0", b"Success\0"),
+        OptixResult::OPTIX_ERROR_INVALID_VALUE => {
+            (b"OPTIX_ERROR_INVALID_VALUE\0", b"Invalid value\0")
+        }
+        OptixResult::OPTIX_ERROR_HOST_OUT_OF_MEMORY => (
+            b"OPTIX_ERROR_HOST_OUT_OF_MEMORY\0",
+            b"Host is out of memory\0",
+        ),
+        OptixResult::OPTIX_ERROR_INVALID_OPERATION => {
+            (b"OPTIX_ERROR_INVALID_OPERATION\0", b"Invalid operation\0")
+        }
+        OptixResult::OPTIX_ERROR_FILE_IO_ERROR => {
+            (b"OPTIX_ERROR_FILE_IO_ERROR\0", b"File I/O error\0")
+        }
+        OptixResult::OPTIX_ERROR_INVALID_FILE_FORMAT => (
+            b"OPTIX_ERROR_INVALID_FILE_FORMAT\0",
+            b"Invalid file format\0",
+        ),
+        OptixResult::OPTIX_ERROR_DISK_CACHE_INVALID_PATH => (
+            b"OPTIX_ERROR_DISK_CACHE_INVALID_PATH\0",
+            b"Invalid path to disk cache file\0",
+        ),
+        OptixResult::OPTIX_ERROR_DISK_CACHE_PERMISSION_ERROR => (
+            b"OPTIX_ERROR_DISK_CACHE_PERMISSION_ERROR\0",
+            b"Disk cache file is not writable\0",
+        ),
+        OptixResult::OPTIX_ERROR_DISK_CACHE_DATABASE_ERROR => (
+            b"OPTIX_ERROR_DISK_CACHE_DATABASE_ERROR\0",
+            b"Disk cache database error\0",
+        ),
+        OptixResult::OPTIX_ERROR_DISK_CACHE_INVALID_DATA => (
+            b"OPTIX_ERROR_DISK_CACHE_INVALID_DATA\0",
+            b"Invalid data in disk cache\0",
+        ),
+        OptixResult::OPTIX_ERROR_LAUNCH_FAILURE => {
+            (b"OPTIX_ERROR_LAUNCH_FAILURE\0", b"Launch failure\0")
+        }
+        OptixResult::OPTIX_ERROR_INVALID_DEVICE_CONTEXT => (
+            b"OPTIX_ERROR_INVALID_DEVICE_CONTEXT\0",
+            b"Invalid device context\0",
+        ),
+        OptixResult::OPTIX_ERROR_CUDA_NOT_INITIALIZED => (
+            b"OPTIX_ERROR_CUDA_NOT_INITIALIZED\0",
+            b"CUDA is not initialized\0",
+        ),
+        #[cfg(optix_7_3)]
+        OptixResult::OPTIX_ERROR_VALIDATION_FAILURE => {
+            (b"OPTIX_ERROR_VALIDATION_FAILURE\0", b"Validation failure\0")
+        }
+        OptixResult::OPTIX_ERROR_INVALID_PTX => {
+            (b"OPTIX_ERROR_INVALID_PTX\0", b"Invalid PTX input\0")
+        }
+        OptixResult::OPTIX_ERROR_INVALID_LAUNCH_PARAMETER => (
+            b"OPTIX_ERROR_INVALID_LAUNCH_PARAMETER\0",
+            b"Invalid launch parameter\0",
+        ),
+        OptixResult::OPTIX_ERROR_INVALID_PAYLOAD_ACCESS => (
+            b"OPTIX_ERROR_INVALID_PAYLOAD_ACCESS\0",
+            b"Invalid payload access\0",
+        ),
+        OptixResult::OPTIX_ERROR_INVALID_ATTRIBUTE_ACCESS => (
+            b"OPTIX_ERROR_INVALID_ATTRIBUTE_ACCESS\0",
+            b"Invalid attribute access\0",
+        ),
+        OptixResult::OPTIX_ERROR_INVALID_FUNCTION_USE => (
+            b"OPTIX_ERROR_INVALID_FUNCTION_USE\0",
+            b"Invalid use of optix device function\0",
+        ),
+        OptixResult::OPTIX_ERROR_INVALID_FUNCTION_ARGUMENTS => (
+            b"OPTIX_ERROR_INVALID_FUNCTION_ARGUMENTS\0",
+            b"Invalid arguments for optix device function\0",
+        ),
+        OptixResult::OPTIX_ERROR_PIPELINE_OUT_OF_CONSTANT_MEMORY => (
+            b"OPTIX_ERROR_PIPELINE_OUT_OF_CONSTANT_MEMORY\0",
+            b"Pipeline parameter size exceeds the limit for constant memory\0",
+        ),
+        OptixResult::OPTIX_ERROR_PIPELINE_LINK_ERROR => (
+            b"OPTIX_ERROR_PIPELINE_LINK_ERROR\0",
+            b"Error during linking of the pipeline\0",
+        ),
+        #[cfg(optix_7_4)]
+        OptixResult::OPTIX_ERROR_ILLEGAL_DURING_TASK_EXECUTE => (
+            b"OPTIX_ERROR_ILLEGAL_DURING_TASK_EXECUTE\0",
+            b"Function is illegal while a task is executing\0",
+        ),
+        OptixResult::OPTIX_ERROR_INTERNAL_COMPILER_ERROR => (
+            b"OPTIX_ERROR_INTERNAL_COMPILER_ERROR\0",
+            b"Internal compiler error\0",
+        ),
+        OptixResult::OPTIX_ERROR_DENOISER_MODEL_NOT_SET => (
+            b"OPTIX_ERROR_DENOISER_MODEL_NOT_SET\0",
+            b"Denoiser model not set\0",
+        ),
+        OptixResult::OPTIX_ERROR_DENOISER_NOT_INITIALIZED => (
+            b"OPTIX_ERROR_DENOISER_NOT_INITIALIZED\0",
+            b"Denoiser not initialized\0",
+        ),
+        OptixResult::OPTIX_ERROR_ACCEL_NOT_COMPATIBLE => (
+            b"OPTIX_ERROR_ACCEL_NOT_COMPATIBLE\0",
+            b"Acceleration structure is not compatible with this \
+              version of OptiX\0",
+        ),
+        #[cfg(optix_7_4)]
+        OptixResult::OPTIX_ERROR_PAYLOAD_TYPE_MISMATCH => (
+            b"OPTIX_ERROR_PAYLOAD_TYPE_MISMATCH\0",
+            b"Payload type mismatch between program and pipeline\0",
+        ),
+        #[cfg(optix_7_4)]
+        OptixResult::OPTIX_ERROR_PAYLOAD_TYPE_RESOLUTION_FAILED => (
+            b"OPTIX_ERROR_PAYLOAD_TYPE_RESOLUTION_FAILED\0",
+            b"Payload type of the program could not be resolved\0",
+        ),
+        #[cfg(optix_7_4)]
+        OptixResult::OPTIX_ERROR_PAYLOAD_TYPE_ID_INVALID => (
+            b"OPTIX_ERROR_PAYLOAD_TYPE_ID_INVALID\0",
+            b"Invalid payload type ID\0",
+        ),
+        OptixResult::OPTIX_ERROR_NOT_SUPPORTED => {
+            (b"OPTIX_ERROR_NOT_SUPPORTED\0", b"Feature not supported\0")
+        }
+        OptixResult::OPTIX_ERROR_UNSUPPORTED_ABI_VERSION => (
+            b"OPTIX_ERROR_UNSUPPORTED_ABI_VERSION\0",
+            b"Unsupported ABI version\0",
+        ),
+        OptixResult::OPTIX_ERROR_FUNCTION_TABLE_SIZE_MISMATCH => (
+            b"OPTIX_ERROR_FUNCTION_TABLE_SIZE_MISMATCH\0",
+            b"Function table size mismatch\0",
+        ),
+        OptixResult::OPTIX_ERROR_INVALID_ENTRY_FUNCTION_OPTIONS => (
+            b"OPTIX_ERROR_INVALID_ENTRY_FUNCTION_OPTIONS\0",
+            b"Invalid options to entry function\0",
+        ),
+        OptixResult::OPTIX_ERROR_LIBRARY_NOT_FOUND => {
+            (b"OPTIX_ERROR_LIBRARY_NOT_FOUND\0", b"Library not found\0")
+        }
+        OptixResult::OPTIX_ERROR_ENTRY_SYMBOL_NOT_FOUND => (
+            b"OPTIX_ERROR_ENTRY_SYMBOL_NOT_FOUND\0",
+            b"Entry symbol not found\0",
+        ),
+        #[cfg(optix_7_3)]
+        OptixResult::OPTIX_ERROR_LIBRARY_UNLOAD_FAILURE => (
+            b"OPTIX_ERROR_LIBRARY_UNLOAD_FAILURE\0",
+            b"Library could not be unloaded\0",
+        ),
+        #[cfg(optix_7_5)]
+        OptixResult::OPTIX_ERROR_DEVICE_OUT_OF_MEMORY => (
+            b"OPTIX_ERROR_DEVICE_OUT_OF_MEMORY\0",
+            b"Device out of memory\0",
+        ),
+        OptixResult::OPTIX_ERROR_CUDA_ERROR => {
+            (b"OPTIX_ERROR_CUDA_ERROR\0", b"CUDA error\0")
+        }
+        OptixResult::OPTIX_ERROR_INTERNAL_ERROR => {
+            (b"OPTIX_ERROR_INTERNAL_ERROR\0", b"Internal error\0")
+        }
+        OptixResult::OPTIX_ERROR_UNKNOWN => {
+            (b"OPTIX_ERROR_UNKNOWN\0", b"Unknown error\0")
+        }
+    }
+}
+
+/// Returns the name of `result`, e.g. "OPTIX_ERROR_INVALID_VALUE".
+///
+/// Uses the driver's implementation if the function table has been loaded,
+/// otherwise a static table.
+pub unsafe fn optixGetErrorName(result: OptixResult) -> *const c_char {
+    if let Some(f) = loader::function_table().and_then(|t| t.optixGetErrorName)
+    {
+        return f(result);
+    }
+
+    static_error_strings(result).0.as_ptr() as *const c_char
+}
+
+/// Returns a description of `result`.
+///
+/// Uses the driver's implementation if the function table has been loaded,
+/// otherwise a static table.
+pub unsafe fn optixGetErrorString(result: OptixResult) -> *const c_char {
+    if let Some(f) =
+        loader::function_table().and_then(|t| t.optixGetErrorString)
+    {
+        return f(result);
+    }
+
+    static_error_strings(result).1.as_ptr() as *const c_char
+}
+
+/// Loads the OptiX library and initializes the function table used by the
+/// stubs above.
+///
+/// Prefer `loader::load()`, which reports why loading failed.
+pub unsafe fn optixInit() -> OptixResult {
+    match loader::load() {
+        Ok(()) => OptixResult::OPTIX_SUCCESS,
+        Err(loader::LoadError::LibraryNotFound { .. }) => {
+            OptixResult::OPTIX_ERROR_LIBRARY_NOT_FOUND
+        }
+        Err(loader::LoadError::EntrySymbolNotFound { .. }) => {
+            OptixResult::OPTIX_ERROR_ENTRY_SYMBOL_NOT_FOUND
+        }
+        Err(loader::LoadError::UnsupportedAbiVersion { .. }) => {
+            OptixResult::OPTIX_ERROR_UNSUPPORTED_ABI_VERSION
+        }
+        Err(loader::LoadError::FunctionTableSizeMismatch { .. }) => {
+            OptixResult::OPTIX_ERROR_FUNCTION_TABLE_SIZE_MISMATCH
+        }
+        Err(loader::LoadError::QueryFunctionTableFailed { result }) => result,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::cuda_sys::{self, cudaError};
+    use crate::{Error, OptixResult};
+
+    // the function table is never loaded in tests so these exercise the
+    // static table
+
+    #[test]
+    fn optix_error_strings() {
+        let e = Error::from(OptixResult::OPTIX_ERROR_PIPELINE_LINK_ERROR);
+        assert_eq!(e.result(), OptixResult::OPTIX_ERROR_PIPELINE_LINK_ERROR);
+        assert_eq!(e.name(), "OPTIX_ERROR_PIPELINE_LINK_ERROR");
+        assert_eq!(
+            e.to_string(),
+            "OPTIX_ERROR_PIPELINE_LINK_ERROR: Error during linking of the \
+             pipeline"
+        );
+        assert_eq!(
+            crate::result_name(OptixResult::OPTIX_SUCCESS),
+            "OPTIX_SUCCESS"
+        );
+    }
+
+    #[test]
+    fn cuda_error_strings() {
+        let e = cuda_sys::Error::from(cudaError::cudaErrorNoDevice);
+        assert_eq!(e.code(), cudaError::cudaErrorNoDevice);
+        assert_eq!(e.name(), "cudaErrorNoDevice");
+
+        // codes newer than our headers are kept rather than panicking
+        let e = cuda_sys::Error::from(12345);
+        assert_eq!(e.code(), 12345);
+        assert_eq!(e.to_string(), "Unrecognized CUDA error code 12345");
+    }
+}
@@ -0,0 +1,18 @@
+/// The OptiX releases after 7.0 that add API, matching optix-sys.
+const OPTIX_VERSIONS: &[&str] = &["7_3", "7_4", "7_5"];
+
+/// Set the same `optix_7_x` cfgs as optix-sys from the version it selected,
+/// which it passes on through its `links` metadata.
+fn main() {
+    println!("cargo:rerun-if-env-changed=DEP_OPTIX_VERSION");
+
+    let selected = std::env::var("DEP_OPTIX_VERSION")
+        .expect("optix-sys did not report the OptiX version it was built for");
+    let minor = |v: &str| v.trim_start_matches("7_").parse::<u32>().unwrap();
+    for version in OPTIX_VERSIONS {
+        println!("cargo:rustc-check-cfg=cfg(optix_{})", version);
+        if minor(version) <= minor(&selected) {
+            println!("cargo:rustc-cfg=optix_{}", version);
+        }
+    }
+}
@@ -75,11 +75,12 @@ where
         let cuda_context = cuda::Context::get_current()?;
 
         // Create the device context and enable logging
-        let mut ctx = optix::DeviceContext::create(cuda_context, None)?;
-        ctx.set_log_callback(
+        let options = optix::device_context::Options::new().log_callback(
             |level, tag, msg| println!("[{}]: {}", tag, msg),
             4,
         );
+        let mut ctx =
+            optix::DeviceContext::create(cuda_context.clone(), Some(options))?;
 
         // Create our first module
         let module_compile_options = optix::ModuleCompileOptions {
@@ -168,7 +169,7 @@ where
             2 * 1024,
             // maximum depth of a traversable graph passed to trace
             3,
-        );
+        )?;
 
         // Build Shader Binding Table
         let rg_rec =
@@ -184,9 +185,9 @@ where
             rg_rec,
             MemTags::SBT as u64,
             alloc,
-        )
-        .miss_records(vec![miss_rec], MemTags::MissRecords as u64, alloc)
-        .hitgroup_records(vec![hg_rec], MemTags::HgRecords as u64, alloc)
+        )?
+        .miss_records(vec![miss_rec], MemTags::MissRecords as u64, alloc)?
+        .hitgroup_records(vec![hg_rec], MemTags::HgRecords as u64, alloc)?
         .build();
 
         let mut color_buffer = cuda::Buffer::new(
@@ -1,6 +1,6 @@
 type Result<T, E = Error> = std::result::Result<T, E>;
 
-use std::rc::Rc;
+use std::sync::Arc;
 
 use optix::cuda::{self, Allocator};
 use optix::math::*;
@@ -123,11 +123,12 @@ where
         let cuda_context = cuda::Context::get_current()?;
 
         // Create the device context and enable logging
-        let mut ctx = optix::DeviceContext::create(cuda_context, None)?;
-        ctx.set_log_callback(
+        let options = optix::device_context::Options::new().log_callback(
             |level, tag, msg| println!("[{}]: {}", tag, msg),
             4,
         );
+        let mut ctx =
+            optix::DeviceContext::create(cuda_context.clone(), Some(options))?;
 
         // Create our first module
         let module_compile_options = optix::ModuleCompileOptions {
@@ -227,7 +228,7 @@ where
             2 * 1024,
             // maximum depth of a traversable graph passed to trace
             3,
-        );
+        )?;
 
         // Build Shader Binding Table
         let rg_rec = SbtRecord::new(
@@ -264,9 +265,9 @@ where
             rg_rec,
             MemTags::SBT as u64,
             alloc,
-        )
-        .miss_records(vec![miss_rec], MemTags::SBT as u64, alloc)
-        .hitgroup_records(vec![hg_rec], MemTags::SBT as u64, alloc)
+        )?
+        .miss_records(vec![miss_rec], MemTags::SBT as u64, alloc)?
+        .hitgroup_records(vec![hg_rec], MemTags::SBT as u64, alloc)?
         .build();
 
         let color_buffer = optix::Buffer::<'a, AllocT, V4f32>::uninitialized(
@@ -1,6 +1,6 @@
 type Result<T, E = Error> = std::result::Result<T, E>;
 
-use std::rc::Rc;
+use std::sync::Arc;
 
 use optix::cuda::{self, Allocator};
 use optix::math::*;
@@ -43,8 +43,8 @@ where
     last_set_camera: Camera,
 
     mesh: TriangleMesh,
-    vertex_buffer: Rc<optix::Buffer<'a, AllocT, V3f32>>,
-    index_buffer: Rc<optix::Buffer<'a, AllocT, V3i32>>,
+    vertex_buffer: Arc<optix::Buffer<'a, AllocT, V3f32>>,
+    index_buffer: Arc<optix::Buffer<'a, AllocT, V3i32>>,
 
     ctx: optix::DeviceContext,
 }
@@ -87,11 +87,12 @@ where
         let cuda_context = cuda::Context::get_current()?;
 
         // Create the device context and enable logging
-        let mut ctx = optix::DeviceContext::create(cuda_context, None)?;
-        ctx.set_log_callback(
+        let options = optix::device_context::Options::new().log_callback(
             |level, tag, msg| println!("[{}]: {}", tag, msg),
             4,
         );
+        let mut ctx =
+            optix::DeviceContext::create(cuda_context.clone(), Some(options))?;
 
         // Create our first module
         let module_compile_options = optix::ModuleCompileOptions {
@@ -169,7 +170,7 @@ where
 
         // build accel
         // upload the model data and create the triangle array build input
-        let vertex_buffer = Rc::new(
+        let vertex_buffer = Arc::new(
             optix::Buffer::new(
                 &mesh.vertex,
                 MemTags::VertexBuffer as u64,
@@ -177,15 +178,15 @@ where
             )
             .unwrap(),
         );
-        let index_buffer = Rc::new(
+        let index_buffer = Arc::new(
             optix::Buffer::new(&mesh.index, MemTags::IndexBuffer as u64, alloc)
                 .unwrap(),
         );
 
         let build_input = optix::BuildInput::Triangle(
             optix::TriangleArray::new(
-                vec![Rc::clone(&vertex_buffer)],
-                Rc::clone(&index_buffer),
+                vec![Arc::clone(&vertex_buffer)],
+                Arc::clone(&index_buffer),
                 optix::GeometryFlags::NONE,
             )
             .unwrap(),
@@ -300,7 +301,7 @@ where
             2 * 1024,
             // maximum depth of a traversable graph passed to trace
             3,
-        );
+        )?;
 
         // Build Shader Binding Table
         let rg_rec =
@@ -316,9 +317,9 @@ where
             rg_rec,
             MemTags::SBT as u64,
             alloc,
-        )
-        .miss_records(vec![miss_rec], MemTags::SBT as u64, alloc)
-        .hitgroup_records(vec![hg_rec], MemTags::SBT as u64, alloc)
+        )?
+        .miss_records(vec![miss_rec], MemTags::SBT as u64, alloc)?
+        .hitgroup_records(vec![hg_rec], MemTags::SBT as u64, alloc)?
         .build();
 
         let color_buffer = optix::Buffer::<'a, AllocT, V4f32>::uninitialized(
@@ -5,7 +5,7 @@ use optix::math::*;
 use optix::{DeviceShareable, SbtRecord, SharedVariable};
 use optix_derive::device_shared;
 
-use std::rc::Rc;
+use std::sync::Arc;
 
 #[device_shared]
 struct TriangleMeshSBTData<'a, AllocT>
@@ -13,8 +13,8 @@ where
     AllocT: 'a + Allocator,
 {
     color: V3f32,
-    vertex: Rc<optix::Buffer<'a, AllocT, V3f32>>,
-    index: Rc<optix::Buffer<'a, AllocT, V3i32>>,
+    vertex: Arc<optix::Buffer<'a, AllocT, V3f32>>,
+    index: Arc<optix::Buffer<'a, AllocT, V3i32>>,
 }
 
 enum_from_primitive! {
@@ -95,11 +95,12 @@ where
         let cuda_context = cuda::Context::get_current()?;
 
         // Create the device context and enable logging
-        let mut ctx = optix::DeviceContext::create(cuda_context, None)?;
-        ctx.set_log_callback(
+        let options = optix::device_context::Options::new().log_callback(
             |level, tag, msg| println!("[{}]: {}", tag, msg),
             4,
         );
+        let mut ctx =
+            optix::DeviceContext::create(cuda_context.clone(), Some(options))?;
 
         // Create our first module
         let module_compile_options = optix::ModuleCompileOptions {
@@ -200,7 +201,7 @@ where
             2 * 1024,
             // maximum depth of a traversable graph passed to trace
             3,
-        );
+        )?;
 
         // build accel
         // upload the model data and create the triangle array build input
@@ -214,7 +215,7 @@ where
         let mut hg_recs = Vec::with_capacity(meshes.len());
 
         for mesh in &meshes {
-            let vertex_buffer = Rc::new(
+            let vertex_buffer = Arc::new(
                 optix::Buffer::new(
                     &mesh.vertex,
                     MemTags::VertexBuffer as u64,
@@ -222,7 +223,7 @@ where
                 )
                 .unwrap(),
             );
-            let index_buffer = Rc::new(
+            let index_buffer = Arc::new(
                 optix::Buffer::new(
                     &mesh.index,
                     MemTags::IndexBuffer as u64,
@@ -233,8 +234,8 @@ where
 
             let mesh_sbt_data = TriangleMeshSBTData {
                 color: mesh.color.into(),
-                vertex: Rc::clone(&vertex_buffer),
-                index: Rc::clone(&index_buffer),
+                vertex: Arc::clone(&vertex_buffer),
+                index: Arc::clone(&index_buffer),
             };
 
             let hg_rec = SbtRecord::new(
@@ -260,9 +261,9 @@ where
             rg_rec,
             MemTags::SBT as u64,
             alloc,
-        )
-        .miss_records(vec![miss_rec], MemTags::SBT as u64, alloc)
-        .hitgroup_records(hg_recs, MemTags::SBT as u64, alloc)
+        )?
+        .miss_records(vec![miss_rec], MemTags::SBT as u64, alloc)?
+        .hitgroup_records(hg_recs, MemTags::SBT as u64, alloc)?
         .build();
 
         // BLAS setup
@@ -5,7 +5,7 @@ use optix::math::*;
 use optix::{DeviceShareable, SbtRecord, SharedVariable};
 use optix_derive::device_shared;
 
-use std::rc::Rc;
+use std::sync::Arc;
 
 enum_from_primitive! {
 #[repr(u64)]
@@ -30,12 +30,12 @@ where
     AllocT: 'a + Allocator,
 {
     color: V3f32,
-    vertex: Rc<optix::Buffer<'a, AllocT, V3f32>>,
-    normal: Rc<optix::Buffer<'a, AllocT, V3f32>>,
-    texcoord: Rc<optix::Buffer<'a, AllocT, V2f32>>,
-    index: Rc<optix::Buffer<'a, AllocT, V3i32>>,
+    vertex: Arc<optix::Buffer<'a, AllocT, V3f32>>,
+    normal: Arc<optix::Buffer<'a, AllocT, V3f32>>,
+    texcoord: Arc<optix::Buffer<'a, AllocT, V2f32>>,
+    index: Arc<optix::Buffer<'a, AllocT, V3i32>>,
     has_texture: bool,
-    texture: Option<Rc<optix::Texture>>,
+    texture: Option<Arc<optix::Texture>>,
 }
 
 pub struct Mesh {
@@ -54,7 +54,7 @@ pub struct Texture {
 
 pub struct Model {
     pub meshes: Vec<Mesh>,
-    pub textures: Vec<Rc<Texture>>,
+    pub textures: Vec<Arc<Texture>>,
     pub bounds: Box3f32,
 }
 
@@ -121,11 +121,12 @@ where
         let cuda_context = cuda::Context::get_current()?;
 
         // Create the device context and enable logging
-        let mut ctx = optix::DeviceContext::create(cuda_context, None)?;
-        ctx.set_log_callback(
+        let options = optix::device_context::Options::new().log_callback(
             |level, tag, msg| println!("[{}]: {}", tag, msg),
             4,
         );
+        let mut ctx =
+            optix::DeviceContext::create(cuda_context.clone(), Some(options))?;
 
         // Create our first module
         let module_compile_options = optix::ModuleCompileOptions {
@@ -226,7 +227,7 @@ where
             2 * 1024,
             // maximum depth of a traversable graph passed to trace
             3,
-        );
+        )?;
 
         // load textures
         let mut texture_objects = Vec::new();
@@ -264,7 +265,7 @@ where
             )
             .unwrap();
 
-            texture_objects.push(Rc::new(texture_object));
+            texture_objects.push(Arc::new(texture_object));
         }
         */
         for texture in &model.textures {
@@ -275,7 +276,7 @@ where
                 optix::WrapMode::Wrap,
             )?;
 
-            texture_objects.push(Rc::new(tex));
+            texture_objects.push(Arc::new(tex));
         }
 
         // Build Shader Binding Table
@@ -290,7 +291,7 @@ where
         let mut build_inputs = Vec::with_capacity(model.meshes.len());
 
         for mesh in &model.meshes {
-            let vertex_buffer = Rc::new(
+            let vertex_buffer = Arc::new(
                 optix::Buffer::new(
                     &mesh.vertex,
                     MemTags::VertexBuffer as u64,
@@ -298,7 +299,7 @@ where
                 )
                 .unwrap(),
             );
-            let index_buffer = Rc::new(
+            let index_buffer = Arc::new(
                 optix::Buffer::new(
                     &mesh.index,
                     MemTags::IndexBuffer as u64,
@@ -306,7 +307,7 @@ where
                 )
                 .unwrap(),
             );
-            let normal_buffer = Rc::new(
+            let normal_buffer = Arc::new(
                 optix::Buffer::new(
                     &mesh.normal,
                     MemTags::NormalBuffer as u64,
@@ -314,7 +315,7 @@ where
                 )
                 .unwrap(),
             );
-            let texcoord_buffer = Rc::new(
+            let texcoord_buffer = Arc::new(
                 optix::Buffer::new(
                     &mesh.texcoord,
                     MemTags::TexcoordBuffer as u64,
@@ -325,17 +326,17 @@ where
 
             let (has_texture, texture) =
                 if let Some(texture_id) = mesh.diffuse_texture_id {
-                    (true, Some(Rc::clone(&texture_objects[texture_id])))
+                    (true, Some(Arc::clone(&texture_objects[texture_id])))
                 } else {
                     (false, None)
                 };
 
             let mesh_sbt_data = TriangleMeshSBTData {
                 color: mesh.diffuse.into(),
-                vertex: Rc::clone(&vertex_buffer),
-                index: Rc::clone(&index_buffer),
-                normal: Rc::clone(&normal_buffer),
-                texcoord: Rc::clone(&texcoord_buffer),
+                vertex: Arc::clone(&vertex_buffer),
+                index: Arc::clone(&index_buffer),
+                normal: Arc::clone(&normal_buffer),
+                texcoord: Arc::clone(&texcoord_buffer),
                 has_texture,
                 texture,
             };
@@ -472,9 +473,9 @@ where
             rg_rec,
             MemTags::SBT as u64,
             alloc,
-        )
-        .miss_records(vec![miss_rec], MemTags::SBT as u64, alloc)
-        .hitgroup_records(hg_recs, MemTags::SBT as u64, alloc)
+        )?
+        .miss_records(vec![miss_rec], MemTags::SBT as u64, alloc)?
+        .hitgroup_records(hg_recs, MemTags::SBT as u64, alloc)?
         .build();
 
         let launch_params =
@@ -7,7 +7,6 @@ use optix::{
 };
 use optix_derive::device_shared;
 
-use std::rc::Rc;
 use std::sync::Arc;
 use optix::cuda::Allocator;
 
@@ -32,12 +31,12 @@ pub enum MemTags {
 struct TriangleMeshSBTData<'a, AllocT>
     where AllocT: 'a + Allocator {
     color: V3f32,
-    vertex: Rc<optix::Buffer<'a, AllocT, V3f32>>,
-    normal: Rc<optix::Buffer<'a, AllocT, V3f32>>,
-    texcoord: Rc<optix::Buffer<'a, AllocT, V2f32>>,
-    index: Rc<optix::Buffer<'a, AllocT, V3i32>>,
+    vertex: Arc<optix::Buffer<'a, AllocT, V3f32>>,
+    normal: Arc<optix::Buffer<'a, AllocT, V3f32>>,
+    texcoord: Arc<optix::Buffer<'a, AllocT, V2f32>>,
+    index: Arc<optix::Buffer<'a, AllocT, V3i32>>,
     has_texture: bool,
-    texture: Option<Rc<cuda::TextureObject>>,
+    texture: Option<Arc<cuda::TextureObject>>,
 }
 
 pub struct Mesh {
@@ -56,7 +55,7 @@ pub struct Texture {
 
 pub struct Model {
     pub meshes: Vec<Mesh>,
-    pub textures: Vec<Rc<Texture>>,
+    pub textures: Vec<Arc<Texture>>,
     pub bounds: Box3f32,
 }
 
@@ -120,11 +119,12 @@ impl<'a, AllocT> SampleRenderer<'a, AllocT>
         let cuda_context = cuda::Context::get_current()?;
 
         // Create the device context and enable logging
-        let mut ctx = optix::DeviceContext::create(cuda_context, None)?;
-        ctx.set_log_callback(
+        let options = optix::device_context::Options::new().log_callback(
             |level, tag, msg| println!("[{}]: {}", tag, msg),
             4,
         );
+        let mut ctx =
+            optix::DeviceContext::create(cuda_context.clone(), Some(options))?;
 
         // Create our first module
         let module_compile_options = optix::ModuleCompileOptions {
@@ -251,7 +251,7 @@ impl<'a, AllocT> SampleRenderer<'a, AllocT>
             2 * 1024,
             // maximum depth of a traversable graph passed to trace
             3,
-        );
+        )?;
 
         // load textures
         let mut texture_objects = Vec::new();
@@ -288,7 +288,7 @@ impl<'a, AllocT> SampleRenderer<'a, AllocT>
             )
             .unwrap();
 
-            texture_objects.push(Rc::new(texture_object));
+            texture_objects.push(Arc::new(texture_object));
         }
 
         // Build Shader Binding Table Records
@@ -306,32 +306,32 @@ impl<'a, AllocT> SampleRenderer<'a, AllocT>
 
         for mesh in &model.meshes {
             let vertex_buffer =
-                Rc::new(optix::Buffer::new(&mesh.vertex,MemTags::VertexBuffer as u64,
+                Arc::new(optix::Buffer::new(&mesh.vertex,MemTags::VertexBuffer as u64,
                                            alloc,).unwrap());
             let index_buffer =
-                Rc::new(optix::Buffer::new(&mesh.index, MemTags::IndexBuffer as u64,
+                Arc::new(optix::Buffer::new(&mesh.index, MemTags::IndexBuffer as u64,
                                            alloc,).unwrap());
             let normal_buffer =
-                Rc::new(optix::Buffer::new(&mesh.normal,MemTags::NormalBuffer as u64,
+                Arc::new(optix::Buffer::new(&mesh.normal,MemTags::NormalBuffer as u64,
                                            alloc,).unwrap());
             let texcoord_buffer =
-                Rc::new(optix::Buffer::new(&mesh.texcoord,MemTags::TexcoordBuffer as u64,
+                Arc::new(optix::Buffer::new(&mesh.texcoord,MemTags::TexcoordBuffer as u64,
                                            alloc,).unwrap());
 
             for pg in &hitgroup_pgs {
                 let (has_texture, texture) =
                     if let Some(texture_id) = mesh.diffuse_texture_id {
-                        (true, Some(Rc::clone(&texture_objects[texture_id])))
+                        (true, Some(Arc::clone(&texture_objects[texture_id])))
                     } else {
                         (false, None)
                     };
 
                 let mesh_sbt_data = TriangleMeshSBTData {
                     color: mesh.diffuse.into(),
-                    vertex: Rc::clone(&vertex_buffer),
-                    index: Rc::clone(&index_buffer),
-                    normal: Rc::clone(&normal_buffer),
-                    texcoord: Rc::clone(&texcoord_buffer),
+                    vertex: Arc::clone(&vertex_buffer),
+                    index: Arc::clone(&index_buffer),
+                    normal: Arc::clone(&normal_buffer),
+                    texcoord: Arc::clone(&texcoord_buffer),
                     has_texture,
                     texture,
                 };
@@ -463,9 +463,9 @@ impl<'a, AllocT> SampleRenderer<'a, AllocT>
 
         let sbt = optix::ShaderBindingTableBuilder::new(rg_rec,
                                                         MemTags::SBT as u64,
-                                                        alloc,)
-            .miss_records(miss_recs, MemTags::SBT as u64, alloc)
-            .hitgroup_records(hg_recs, MemTags::SBT as u64, alloc)
+                                                        alloc,)?
+            .miss_records(miss_recs, MemTags::SBT as u64, alloc)?
+            .hitgroup_records(hg_recs, MemTags::SBT as u64, alloc)?
             .build();
 
         let launch_params =
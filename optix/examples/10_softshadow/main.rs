@@ -7,7 +7,7 @@ use crate::gl_util::*;
 
 use optix::math::*;
 
-use std::rc::Rc;
+use std::sync::Arc;
 use optix::cuda::TaggedMallocator;
 
 fn main() {
@@ -121,7 +121,7 @@ fn handle_window_event(window: &mut glfw::Window, event: glfw::WindowEvent) {
     }
 }
 
-fn load_texture(path: &std::path::Path) -> Option<Rc<Texture>> {
+fn load_texture(path: &std::path::Path) -> Option<Arc<Texture>> {
     let im = match image::open(path) {
         Ok(im) => im.to_rgba8(),
         Err(e) => {
@@ -132,7 +132,7 @@ fn load_texture(path: &std::path::Path) -> Option<Rc<Texture>> {
 
     let dim = im.dimensions();
 
-    Some(Rc::new(Texture {
+    Some(Arc::new(Texture {
         resolution: v2i32(dim.0 as i32, dim.1 as i32),
         pixels: im.into_raw(),
     }))
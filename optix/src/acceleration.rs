@@ -2,11 +2,12 @@ use super::{
     cuda::{self, Allocator, Mallocator},
     math::{Box3f32, V3f32, V3i32},
 };
+use crate::backend;
 use optix_sys as sys;
 
 use super::{
     buffer::{Buffer, BufferElement, BufferFormat},
-    device_context::DeviceContext,
+    device_context::{DeviceContext, DeviceProperties},
     error::Error,
     instance::Instance,
 };
@@ -14,7 +15,7 @@ type Result<T, E = Error> = std::result::Result<T, E>;
 
 use std::convert::{TryFrom, TryInto};
 
-use std::rc::Rc;
+use std::sync::Arc;
 
 pub enum BuildInput<'a, AllocT, V = V3f32, I = V3i32>
 where
@@ -23,25 +24,51 @@ where
     I: BufferElement,
 {
     Triangle(TriangleArray<'a, AllocT, V, I>),
+    #[cfg(optix_7_3)]
+    Curve(CurveArray<'a, AllocT>),
+    #[cfg(optix_7_5)]
+    Sphere(SphereArray<'a, AllocT>),
     CustomPrimitive(CustomPrimitiveArray<'a, AllocT>),
     Instance(InstanceArray<'a, AllocT>),
 }
 
-impl<'a, AllocT, V, I> From<&BuildInput<'a, AllocT, V, I>>
+impl<'a, AllocT, V, I> TryFrom<&BuildInput<'a, AllocT, V, I>>
     for sys::OptixBuildInput
 where
     AllocT: Allocator,
     V: BufferElement,
     I: BufferElement,
 {
-    fn from(b: &BuildInput<'a, AllocT, V, I>) -> sys::OptixBuildInput {
+    type Error = Error;
+
+    fn try_from(
+        b: &BuildInput<'a, AllocT, V, I>,
+    ) -> Result<sys::OptixBuildInput> {
         let mut input = sys::OptixBuildInputUnion::default();
-        match b {
+        Ok(match b {
             BuildInput::Triangle(ta) => {
                 let type_ =
                     sys::OptixBuildInputType_OPTIX_BUILD_INPUT_TYPE_TRIANGLES;
                 unsafe {
-                    input.triangle_array = ta.try_into().unwrap();
+                    input.triangle_array = ta.try_into()?;
+                }
+                sys::OptixBuildInput { type_, input }
+            }
+            #[cfg(optix_7_3)]
+            BuildInput::Curve(ca) => {
+                let type_ =
+                    sys::OptixBuildInputType_OPTIX_BUILD_INPUT_TYPE_CURVES;
+                unsafe {
+                    input.curve_array = ca.into();
+                }
+                sys::OptixBuildInput { type_, input }
+            }
+            #[cfg(optix_7_5)]
+            BuildInput::Sphere(sa) => {
+                let type_ =
+                    sys::OptixBuildInputType_OPTIX_BUILD_INPUT_TYPE_SPHERES;
+                unsafe {
+                    input.sphere_array = sa.into();
                 }
                 sys::OptixBuildInput { type_, input }
             }
@@ -60,8 +87,105 @@ where
                 }
                 sys::OptixBuildInput { type_, input }
             }
+        })
+    }
+}
+
+/// Convert `build_inputs` for passing to OptiX, reporting the index of the
+/// first one that is invalid.
+fn build_inputs_to_sys<'a, AllocT, V, I>(
+    build_inputs: &[BuildInput<'a, AllocT, V, I>],
+) -> Result<Vec<sys::OptixBuildInput>>
+where
+    AllocT: Allocator,
+    V: BufferElement,
+    I: BufferElement,
+{
+    build_inputs
+        .iter()
+        .enumerate()
+        .map(|(index, b)| {
+            b.try_into().map_err(|e| Error::InvalidBuildInput {
+                index,
+                source: Box::new(e),
+            })
+        })
+        .collect()
+}
+
+/// Check the total size of `build_inputs` against the device limits.
+fn check_build_limits<'a, AllocT, V, I>(
+    build_inputs: &[BuildInput<'a, AllocT, V, I>],
+    props: &DeviceProperties,
+) -> Result<()>
+where
+    AllocT: Allocator,
+    V: BufferElement,
+    I: BufferElement,
+{
+    let mut num_primitives = 0u64;
+    let mut num_sbt_records = 0u64;
+    let mut num_instances = 0u64;
+    for b in build_inputs {
+        match b {
+            BuildInput::Triangle(ta) => {
+                num_primitives += ta.index_buffer.len() as u64;
+                num_sbt_records += 1;
+            }
+            #[cfg(optix_7_3)]
+            BuildInput::Curve(ca) => {
+                num_primitives += ca.num_primitives as u64;
+                num_sbt_records += 1;
+            }
+            #[cfg(optix_7_5)]
+            BuildInput::Sphere(sa) => {
+                num_primitives += sa.num_vertices as u64;
+                num_sbt_records += 1;
+            }
+            BuildInput::CustomPrimitive(cp) => {
+                num_primitives += cp.num_primitives as u64;
+                num_sbt_records += 1;
+            }
+            BuildInput::Instance(ia) => {
+                num_instances += ia.num_instances as u64;
+                DeviceProperties::check(
+                    "instance id",
+                    ia.max_instance_id as u64,
+                    props.max_instance_id,
+                )?;
+                DeviceProperties::check(
+                    "instance SBT offset",
+                    ia.max_sbt_offset as u64,
+                    props.max_sbt_offset,
+                )?;
+                let max_mask = (1u64
+                    << props.num_bits_instance_visibility_mask)
+                    .saturating_sub(1)
+                    .min(u32::MAX as u64);
+                DeviceProperties::check(
+                    "instance visibility mask",
+                    ia.visibility_mask as u64,
+                    max_mask as u32,
+                )?;
+            }
         }
     }
+
+    DeviceProperties::check(
+        "number of primitives",
+        num_primitives,
+        props.max_primitives_per_gas,
+    )?;
+    DeviceProperties::check(
+        "number of SBT records",
+        num_sbt_records,
+        props.max_sbt_records_per_gas,
+    )?;
+    DeviceProperties::check(
+        "number of instances",
+        num_instances,
+        props.max_instances_per_ias,
+    )
 }
 
 pub struct TriangleArray<'a, AllocT, V, I>
@@ -70,9 +194,9 @@ where
     V: BufferElement,
     I: BufferElement,
 {
-    vertex_buffers: Vec<Rc<Buffer<'a, AllocT, V>>>,
+    vertex_buffers: Vec<Arc<Buffer<'a, AllocT, V>>>,
     vertex_buffers_d: Vec<cuda::CUdeviceptr>,
-    index_buffer: Rc<Buffer<'a, AllocT, I>>,
+    index_buffer: Arc<Buffer<'a, AllocT, I>>,
     flags: GeometryFlags,
 }
 
@@ -83,8 +207,8 @@ where
     I: BufferElement,
 {
     pub fn new(
-        vertex_buffers: Vec<Rc<Buffer<'a, AllocT, V>>>,
-        index_buffer: Rc<Buffer<'a, AllocT, I>>,
+        vertex_buffers: Vec<Arc<Buffer<'a, AllocT, V>>>,
+        index_buffer: Arc<Buffer<'a, AllocT, I>>,
         flags: GeometryFlags,
     ) -> Result<TriangleArray<'a, AllocT, V, I>> {
         let vertex_buffers_d: Vec<cuda::CUdeviceptr> =
@@ -179,6 +303,9 @@ where
             sbtIndexOffsetSizeInBytes: 0,
             sbtIndexOffsetStrideInBytes: 0,
             primitiveIndexOffset: 0,
+            #[cfg(optix_7_3)]
+            transformFormat:
+                sys::OptixTransformFormat_OPTIX_TRANSFORM_FORMAT_NONE,
         })
     }
 }
@@ -256,7 +383,13 @@ where
 {
     instances: cuda::Buffer<'a, AllocT>,
     num_instances: u32,
+    // kept on the host to check against the device limits
+    max_instance_id: u32,
+    max_sbt_offset: u32,
+    visibility_mask: u32,
+    #[cfg(not(optix_7_3))]
     aabbs: Option<cuda::Buffer<'a, AllocT>>,
+    #[cfg(not(optix_7_3))]
     num_aabbs: u32,
 }
 
@@ -270,6 +403,12 @@ where
         allocator: &'a AllocT,
     ) -> Result<InstanceArray<'a, AllocT>> {
         let num_instances = instances.len() as u32;
+        let max_instance_id =
+            instances.iter().map(|i| i.instanceId).max().unwrap_or(0);
+        let max_sbt_offset =
+            instances.iter().map(|i| i.sbtOffset).max().unwrap_or(0);
+        let visibility_mask =
+            instances.iter().fold(0, |m, i| m | i.visibilityMask);
         let instances = cuda::Buffer::with_data(
             instances,
             sys::OptixInstanceByteAlignment,
@@ -279,7 +418,12 @@ where
         Ok(InstanceArray {
             instances,
             num_instances,
+            max_instance_id,
+            max_sbt_offset,
+            visibility_mask,
+            #[cfg(not(optix_7_3))]
             aabbs: None,
+            #[cfg(not(optix_7_3))]
             num_aabbs: 0,
         })
     }
@@ -296,16 +440,217 @@ where
         sys::OptixBuildInputInstanceArray {
             instances: ia.instances.as_device_ptr(),
             numInstances: ia.num_instances,
+            #[cfg(not(optix_7_3))]
             aabbs: if let Some(a) = &ia.aabbs {
                 a.as_device_ptr()
             } else {
                 0
             },
+            #[cfg(not(optix_7_3))]
             numAabbs: ia.num_aabbs,
         }
     }
 }
 
+/// The basis used to interpolate the control points of a `CurveArray`.
+#[cfg(optix_7_3)]
+#[repr(u32)]
+#[derive(Debug, Hash, PartialEq, Copy, Clone)]
+pub enum CurveType {
+    RoundQuadraticBSpline =
+        sys::OptixPrimitiveType_OPTIX_PRIMITIVE_TYPE_ROUND_QUADRATIC_BSPLINE,
+    RoundCubicBSpline =
+        sys::OptixPrimitiveType_OPTIX_PRIMITIVE_TYPE_ROUND_CUBIC_BSPLINE,
+    RoundLinear = sys::OptixPrimitiveType_OPTIX_PRIMITIVE_TYPE_ROUND_LINEAR,
+    #[cfg(optix_7_4)]
+    RoundCatmullRom =
+        sys::OptixPrimitiveType_OPTIX_PRIMITIVE_TYPE_ROUND_CATMULLROM,
+}
+
+/// A set of curves sharing one basis. Each entry in `indices` is the index
+/// of the first control point of a curve segment.
+#[cfg(optix_7_3)]
+pub struct CurveArray<'a, AllocT = Mallocator>
+where
+    AllocT: Allocator,
+{
+    curve_type: CurveType,
+    _vertex_buffers: Vec<cuda::Buffer<'a, AllocT>>,
+    vertex_buffers_d: Vec<cuda::CUdeviceptr>,
+    _width_buffers: Vec<cuda::Buffer<'a, AllocT>>,
+    width_buffers_d: Vec<cuda::CUdeviceptr>,
+    index_buffer: cuda::Buffer<'a, AllocT>,
+    num_vertices: u32,
+    num_primitives: u32,
+    flags: GeometryFlags,
+}
+
+#[cfg(optix_7_3)]
+impl<'a, AllocT> CurveArray<'a, AllocT>
+where
+    AllocT: Allocator,
+{
+    pub fn new(
+        curve_type: CurveType,
+        vertices: &[V3f32],
+        widths: &[f32],
+        indices: &[u32],
+        flags: GeometryFlags,
+        tag: u64,
+        allocator: &'a AllocT,
+    ) -> Result<CurveArray<'a, AllocT>> {
+        if widths.len() != vertices.len() {
+            return Err(Error::CurveWidthCountMismatch {
+                num_vertices: vertices.len(),
+                num_widths: widths.len(),
+            });
+        }
+
+        let vertex_buffers = vec![cuda::Buffer::with_data(
+            vertices,
+            std::mem::align_of::<V3f32>(),
+            tag,
+            allocator,
+        )?];
+        let width_buffers = vec![cuda::Buffer::with_data(
+            widths,
+            std::mem::align_of::<f32>(),
+            tag,
+            allocator,
+        )?];
+        let index_buffer = cuda::Buffer::with_data(
+            indices,
+            std::mem::align_of::<u32>(),
+            tag,
+            allocator,
+        )?;
+
+        let vertex_buffers_d =
+            vertex_buffers.iter().map(|b| b.as_device_ptr()).collect();
+        let width_buffers_d =
+            width_buffers.iter().map(|b| b.as_device_ptr()).collect();
+
+        Ok(CurveArray {
+            curve_type,
+            _vertex_buffers: vertex_buffers,
+            vertex_buffers_d,
+            _width_buffers: width_buffers,
+            width_buffers_d,
+            index_buffer,
+            num_vertices: vertices.len() as u32,
+            num_primitives: indices.len() as u32,
+            flags,
+        })
+    }
+}
+
+#[cfg(optix_7_3)]
+impl<'a, AllocT> From<&CurveArray<'a, AllocT>>
+    for sys::OptixBuildInputCurveArray
+where
+    AllocT: Allocator,
+{
+    fn from(arr: &CurveArray<'a, AllocT>) -> sys::OptixBuildInputCurveArray {
+        // zero-initialize so that fields added in later versions (such as
+        // the end cap flags) get their defaults. Zero strides mean the
+        // buffers are tightly packed.
+        let mut ca: sys::OptixBuildInputCurveArray =
+            unsafe { std::mem::zeroed() };
+        ca.curveType = arr.curve_type as u32;
+        ca.numPrimitives = arr.num_primitives;
+        ca.vertexBuffers = arr.vertex_buffers_d.as_ptr();
+        ca.numVertices = arr.num_vertices;
+        ca.widthBuffers = arr.width_buffers_d.as_ptr();
+        ca.indexBuffer = arr.index_buffer.as_device_ptr();
+        ca.flag = arr.flags.bits();
+        ca
+    }
+}
+
+/// A set of spheres. `radii` either has one entry per sphere or a single
+/// entry that is used for all of them.
+#[cfg(optix_7_5)]
+pub struct SphereArray<'a, AllocT = Mallocator>
+where
+    AllocT: Allocator,
+{
+    _center_buffers: Vec<cuda::Buffer<'a, AllocT>>,
+    center_buffers_d: Vec<cuda::CUdeviceptr>,
+    _radius_buffers: Vec<cuda::Buffer<'a, AllocT>>,
+    radius_buffers_d: Vec<cuda::CUdeviceptr>,
+    num_vertices: u32,
+    single_radius: bool,
+    flags: Box<u32>,
+}
+
+#[cfg(optix_7_5)]
+impl<'a, AllocT> SphereArray<'a, AllocT>
+where
+    AllocT: Allocator,
+{
+    pub fn new(
+        centers: &[V3f32],
+        radii: &[f32],
+        flags: GeometryFlags,
+        tag: u64,
+        allocator: &'a AllocT,
+    ) -> Result<SphereArray<'a, AllocT>> {
+        if radii.len() != 1 && radii.len() != centers.len() {
+            return Err(Error::SphereRadiusCountMismatch {
+                num_centers: centers.len(),
+                num_radii: radii.len(),
+            });
+        }
+
+        let center_buffers = vec![cuda::Buffer::with_data(
+            centers,
+            std::mem::align_of::<V3f32>(),
+            tag,
+            allocator,
+        )?];
+        let radius_buffers = vec![cuda::Buffer::with_data(
+            radii,
+            std::mem::align_of::<f32>(),
+            tag,
+            allocator,
+        )?];
+
+        let center_buffers_d =
+            center_buffers.iter().map(|b| b.as_device_ptr()).collect();
+        let radius_buffers_d =
+            radius_buffers.iter().map(|b| b.as_device_ptr()).collect();
+
+        Ok(SphereArray {
+            _center_buffers: center_buffers,
+            center_buffers_d,
+            _radius_buffers: radius_buffers,
+            radius_buffers_d,
+            num_vertices: centers.len() as u32,
+            single_radius: radii.len() == 1,
+            flags: Box::new(flags.bits()),
+        })
+    }
+}
+
+#[cfg(optix_7_5)]
+impl<'a, AllocT> From<&SphereArray<'a, AllocT>>
+    for sys::OptixBuildInputSphereArray
+where
+    AllocT: Allocator,
+{
+    fn from(arr: &SphereArray<'a, AllocT>) -> sys::OptixBuildInputSphereArray {
+        let mut sa: sys::OptixBuildInputSphereArray =
+            unsafe { std::mem::zeroed() };
+        sa.vertexBuffers = arr.center_buffers_d.as_ptr();
+        sa.numVertices = arr.num_vertices;
+        sa.radiusBuffers = arr.radius_buffers_d.as_ptr();
+        sa.singleRadius = arr.single_radius as i32;
+        sa.flags = arr.flags.as_ref() as *const u32;
+        sa.numSbtRecords = 1;
+        sa
+    }
+}
+
 bitflags! {
     pub struct GeometryFlags: u32 {
         const NONE = sys::OptixGeometryFlags::None as u32;
@@ -322,6 +667,8 @@ bitflags! {
         const PREFER_FAST_TRACE = sys::OptixBuildFlags_OPTIX_BUILD_FLAG_PREFER_FAST_TRACE;
         const FAST_BUILD = sys::OptixBuildFlags_OPTIX_BUILD_FLAG_PREFER_FAST_BUILD;
         const ALLOW_RANDOM_ACCESS_VERTEX = sys::OptixBuildFlags_OPTIX_BUILD_FLAG_ALLOW_RANDOM_VERTEX_ACCESS;
+        #[cfg(optix_7_4)]
+        const ALLOW_RANDOM_INSTANCE_ACCESS = sys::OptixBuildFlags_OPTIX_BUILD_FLAG_ALLOW_RANDOM_INSTANCE_ACCESS;
     }
 }
 
@@ -398,11 +745,12 @@ impl DeviceContext {
         let mut buffer_sizes =
             vec![AccelBufferSizes::default(); build_inputs.len()];
 
-        let build_inputs: Vec<sys::OptixBuildInput> =
-            build_inputs.into_iter().map(|b| b.into()).collect();
+        let num_build_inputs = build_inputs.len();
+        check_build_limits(build_inputs, &self.shared.properties)?;
+        let build_inputs = build_inputs_to_sys(build_inputs)?;
 
         let res = unsafe {
-            sys::optixAccelComputeMemoryUsage(
+            backend::optix_accel_compute_memory_usage(
                 self.ctx,
                 accel_options as *const AccelBuildOptions
                     as *const sys::OptixAccelBuildOptions,
@@ -415,6 +763,7 @@ impl DeviceContext {
         if res != sys::OptixResult::OPTIX_SUCCESS {
             return Err(Error::AccelComputeMemoryUsageFailed {
                 source: res.into(),
+                num_build_inputs,
             });
         }
 
@@ -435,8 +784,9 @@ impl DeviceContext {
         V: BufferElement,
         I: BufferElement,
     {
-        let build_inputs: Vec<sys::OptixBuildInput> =
-            build_inputs.into_iter().map(|b| b.into()).collect();
+        let num_build_inputs = build_inputs.len();
+        check_build_limits(build_inputs, &self.shared.properties)?;
+        let build_inputs = build_inputs_to_sys(build_inputs)?;
 
         let ep: Vec<sys::OptixAccelEmitDesc> = emitted_properties
             .iter()
@@ -448,7 +798,7 @@ impl DeviceContext {
         unsafe {
             let mut hnd = 0;
 
-            let res = sys::optixAccelBuild(
+            let res = backend::optix_accel_build(
                 self.ctx,
                 stream.as_sys_ptr(),
                 accel_options as *const AccelBuildOptions
@@ -465,7 +815,10 @@ impl DeviceContext {
             );
 
             if res != sys::OptixResult::OPTIX_SUCCESS {
-                return Err(Error::AccelBuildFailed { source: res.into() });
+                return Err(Error::AccelBuildFailed {
+                    source: res.into(),
+                    num_build_inputs,
+                });
             }
 
             Ok(TraversableHandle {
@@ -486,7 +839,7 @@ impl DeviceContext {
     {
         unsafe {
             let mut hnd = 0;
-            let res = sys::optixAccelCompact(
+            let res = backend::optix_accel_compact(
                 self.ctx,
                 stream.as_sys_ptr(),
                 input_handle.hnd,
@@ -496,7 +849,10 @@ impl DeviceContext {
             );
 
             if res != sys::OptixResult::OPTIX_SUCCESS {
-                return Err(Error::AccelCompactFailed { source: res.into() });
+                return Err(Error::AccelCompactFailed {
+                    source: res.into(),
+                    output_size: output_buffer.byte_size(),
+                });
             }
 
             Ok(TraversableHandle {
@@ -558,3 +914,222 @@ where
         0
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::backend::mock::{
+        fixtures, MockBackend, RecordedBuildInput, ACCEL_BYTES_PER_PRIMITIVE,
+    };
+
+    #[test]
+    fn build_and_compact_triangles() {
+        let mock = MockBackend::install();
+        let ctx = fixtures::device_context();
+        let stream = cuda::Stream::new().unwrap();
+        let alloc = Mallocator::new();
+
+        let vertices = [
+            V3f32::new(0.0, 0.0, 0.0),
+            V3f32::new(1.0, 0.0, 0.0),
+            V3f32::new(0.0, 1.0, 0.0),
+            V3f32::new(1.0, 1.0, 0.0),
+        ];
+        let indices = [V3i32::new(0, 1, 2), V3i32::new(1, 3, 2)];
+        let vertex_buffer =
+            Arc::new(Buffer::new(&vertices, 0, &alloc).unwrap());
+        let index_buffer = Arc::new(Buffer::new(&indices, 0, &alloc).unwrap());
+        let build_inputs = vec![BuildInput::Triangle(
+            TriangleArray::new(
+                vec![vertex_buffer.clone()],
+                index_buffer.clone(),
+                GeometryFlags::DISABLE_ANYHIT,
+            )
+            .unwrap(),
+        )];
+
+        let accel_options = AccelBuildOptions {
+            build_flags: BuildFlags::ALLOW_COMPACTION,
+            operation: BuildOperation::Build,
+            motion_options: MotionOptions {
+                num_keys: 1,
+                flags: MotionFlags::NONE,
+                time_begin: 0.0,
+                time_end: 1.0,
+            },
+        };
+
+        let sizes = ctx
+            .accel_compute_memory_usage(&accel_options, &build_inputs)
+            .unwrap();
+        assert_eq!(
+            sizes[0].output_size_in_bytes,
+            2 * ACCEL_BYTES_PER_PRIMITIVE
+        );
+
+        let temp_buffer =
+            cuda::Buffer::new(sizes[0].temp_size_in_bytes, 8, 0, &alloc)
+                .unwrap();
+        let output_buffer =
+            cuda::Buffer::new(sizes[0].output_size_in_bytes, 8, 0, &alloc)
+                .unwrap();
+        let compacted_size_buffer =
+            cuda::Buffer::new(std::mem::size_of::<u64>(), 8, 0, &alloc)
+                .unwrap();
+
+        let gas = ctx
+            .accel_build(
+                &stream,
+                &accel_options,
+                &build_inputs,
+                &temp_buffer,
+                output_buffer,
+                &[AccelEmitDesc::new(
+                    &compacted_size_buffer,
+                    AccelPropertyType::CompactedSize,
+                )],
+            )
+            .unwrap();
+
+        let builds = mock.accel_builds();
+        assert_eq!(builds.len(), 1);
+        assert_eq!(builds[0].handle, gas.hnd);
+        assert_eq!(builds[0].build_flags, BuildFlags::ALLOW_COMPACTION.bits());
+        match &builds[0].build_inputs[..] {
+            [RecordedBuildInput::Triangles {
+                vertex_buffers,
+                num_vertices,
+                vertex_stride_in_bytes,
+                index_buffer: ib,
+                num_index_triplets,
+                flags,
+                ..
+            }] => {
+                assert_eq!(vertex_buffers, &[vertex_buffer.as_device_ptr()]);
+                assert_eq!(*num_vertices, 4);
+                assert_eq!(*vertex_stride_in_bytes, 12);
+                assert_eq!(*ib, index_buffer.as_device_ptr());
+                assert_eq!(*num_index_triplets, 2);
+                assert_eq!(flags, &[GeometryFlags::DISABLE_ANYHIT.bits()]);
+            }
+            inputs => panic!("unexpected build inputs {:?}", inputs),
+        }
+
+        let compacted_size: u64 =
+            compacted_size_buffer.download_primitive().unwrap();
+        assert_eq!(compacted_size as usize, 2 * ACCEL_BYTES_PER_PRIMITIVE);
+
+        let compacted_buffer =
+            cuda::Buffer::new(compacted_size as usize, 8, 0, &alloc).unwrap();
+        let input_handle = gas.hnd;
+        let compacted =
+            ctx.accel_compact(&stream, gas, compacted_buffer).unwrap();
+        let compacts = mock.accel_compacts();
+        assert_eq!(compacts.len(), 1);
+        assert_eq!(compacts[0].input_handle, input_handle);
+        assert_eq!(compacts[0].handle, compacted.hnd);
+    }
+
+    #[test]
+    fn build_failure() {
+        let mock = MockBackend::install();
+        let ctx = fixtures::device_context();
+        mock.fail_optix(
+            "optix_accel_compute_memory_usage",
+            sys::OptixResult::OPTIX_ERROR_INVALID_VALUE,
+        );
+        let build_inputs: Vec<BuildInput<Mallocator>> = Vec::new();
+        let accel_options = AccelBuildOptions {
+            build_flags: BuildFlags::NONE,
+            operation: BuildOperation::Build,
+            motion_options: MotionOptions {
+                num_keys: 1,
+                flags: MotionFlags::NONE,
+                time_begin: 0.0,
+                time_end: 1.0,
+            },
+        };
+        match ctx.accel_compute_memory_usage(&accel_options, &build_inputs) {
+            Err(Error::AccelComputeMemoryUsageFailed {
+                num_build_inputs: 0,
+                ..
+            }) => (),
+            _ => panic!("expected AccelComputeMemoryUsageFailed"),
+        }
+        mock.clear_failures();
+
+        // floats can't be used as indices
+        let alloc = Mallocator::new();
+        let vertices = [V3f32::new(0.0, 0.0, 0.0); 3];
+        let vertex_buffer =
+            Arc::new(Buffer::new(&vertices, 0, &alloc).unwrap());
+        let build_inputs = vec![BuildInput::Triangle(
+            TriangleArray::new(
+                vec![vertex_buffer.clone()],
+                vertex_buffer.clone(),
+                GeometryFlags::NONE,
+            )
+            .unwrap(),
+        )];
+        match ctx.accel_compute_memory_usage(&accel_options, &build_inputs) {
+            Err(Error::InvalidBuildInput { index: 0, source }) => match *source
+            {
+                Error::IncorrectIndexBufferFormat { .. } => (),
+                e => panic!("unexpected error {}", e),
+            },
+            _ => panic!("expected InvalidBuildInput"),
+        }
+    }
+
+    #[test]
+    fn build_limits() {
+        let mock = MockBackend::install();
+        // the limits are queried when the context is created
+        mock.set_property(
+            sys::OptixDeviceProperty_OPTIX_DEVICE_PROPERTY_LIMIT_MAX_PRIMITIVES_PER_GAS,
+            1,
+        );
+        let ctx = fixtures::device_context();
+        let alloc = Mallocator::new();
+        let accel_options = AccelBuildOptions {
+            build_flags: BuildFlags::NONE,
+            operation: BuildOperation::Build,
+            motion_options: MotionOptions {
+                num_keys: 1,
+                flags: MotionFlags::NONE,
+                time_begin: 0.0,
+                time_end: 1.0,
+            },
+        };
+
+        let aabbs = [Box3f32::make_empty(), Box3f32::make_empty()];
+        let build_inputs = vec![BuildInput::<Mallocator>::CustomPrimitive(
+            CustomPrimitiveArray::new(&aabbs, GeometryFlags::NONE, 0, &alloc)
+                .unwrap(),
+        )];
+        match ctx.accel_compute_memory_usage(&accel_options, &build_inputs) {
+            Err(Error::DeviceLimitExceeded {
+                limit: "number of primitives",
+                value: 2,
+                max: 1,
+            }) => (),
+            r => panic!("expected DeviceLimitExceeded, got {:?}", r.err()),
+        }
+
+        // the mock reports 8 visibility mask bits
+        let mut instance: Instance = unsafe { std::mem::zeroed() };
+        instance.visibilityMask = 0x100;
+        let build_inputs = vec![BuildInput::<Mallocator>::Instance(
+            InstanceArray::new(&[instance], 0, &alloc).unwrap(),
+        )];
+        match ctx.accel_compute_memory_usage(&accel_options, &build_inputs) {
+            Err(Error::DeviceLimitExceeded {
+                limit: "instance visibility mask",
+                value: 0x100,
+                max: 0xff,
+            }) => (),
+            r => panic!("expected DeviceLimitExceeded, got {:?}", r.err()),
+        }
+        assert!(mock.accel_builds().is_empty());
+    }
+}
@@ -0,0 +1,1645 @@
+//! A host-memory `Backend` for testing.
+//!
+//! Device allocations are `Vec<u8>`s addressed by fake device pointers, OptiX
+//! objects are fake handles, and the calls that would do work on the GPU
+//! (acceleration structure builds, compaction and launches) are recorded so
+//! tests can check what the crate passed to OptiX.
+//!
+//! ```ignore
+//! let mock = MockBackend::install();
+//! let alloc = cuda::TaggedMallocator::new();
+//! let buffer = cuda::Buffer::with_data(&[1u32, 2, 3], 4, 0, &alloc)?;
+//! assert_eq!(mock.read(buffer.as_device_ptr(), 12).unwrap().len(), 12);
+//! ```
+use super::Backend;
+
+use optix_sys as sys;
+use optix_sys::cuda_sys::{
+    cudaError, cudaError_enum, cudaError_t, cudaMemcpyKind, cudaStream_t,
+    CUcontext, CUdevice, CUdeviceptr, CUresult, CUstream,
+};
+
+use std::cell::RefCell;
+use std::collections::{BTreeMap, HashMap};
+use std::ffi::{CStr, CString};
+use std::os::raw::{c_char, c_int, c_uint, c_void};
+use std::rc::Rc;
+
+/// The fake CUDA context returned by `cuCtxGetCurrent` when no context has
+/// been pushed.
+pub const MOCK_CUDA_CONTEXT: CUcontext = 0xC0DE0 as CUcontext;
+
+/// Start of the fake device address space.
+const DEVICE_BASE: u64 = 0xD000_0000_0000;
+/// Every allocation is aligned to this, like `cudaMalloc`.
+const DEVICE_ALIGNMENT: u64 = 512;
+
+/// Bytes of output and temp buffer reported per primitive by
+/// `optixAccelComputeMemoryUsage`.
+pub const ACCEL_BYTES_PER_PRIMITIVE: usize = 64;
+
+#[derive(Debug, Copy, Clone, PartialEq, Eq, Hash)]
+pub enum HandleKind {
+    CudaContext,
+    DeviceContext,
+    Module,
+    ProgramGroup,
+    Pipeline,
+    Stream,
+}
+
+/// A copy of the parts of an `OptixBuildInput` the mock understands, taken
+/// while the pointers in it were still valid.
+#[derive(Debug, Clone, PartialEq)]
+pub enum RecordedBuildInput {
+    Triangles {
+        vertex_buffers: Vec<CUdeviceptr>,
+        num_vertices: u32,
+        vertex_format: sys::OptixVertexFormat,
+        vertex_stride_in_bytes: u32,
+        index_buffer: CUdeviceptr,
+        num_index_triplets: u32,
+        index_format: sys::OptixIndicesFormat,
+        index_stride_in_bytes: u32,
+        flags: Vec<u32>,
+    },
+    CustomPrimitives {
+        aabb_buffers: Vec<CUdeviceptr>,
+        num_primitives: u32,
+        stride_in_bytes: u32,
+        flags: Vec<u32>,
+    },
+    Instances {
+        instances: CUdeviceptr,
+        num_instances: u32,
+    },
+    Other {
+        type_: sys::OptixBuildInputType,
+    },
+}
+
+impl RecordedBuildInput {
+    /// The number of primitives (triangles, AABBs or instances) in the input.
+    pub fn num_primitives(&self) -> usize {
+        match self {
+            RecordedBuildInput::Triangles {
+                num_vertices,
+                num_index_triplets,
+                index_buffer,
+                ..
+            } => {
+                if *index_buffer == 0 {
+                    *num_vertices as usize / 3
+                } else {
+                    *num_index_triplets as usize
+                }
+            }
+            RecordedBuildInput::CustomPrimitives { num_primitives, .. } => {
+                *num_primitives as usize
+            }
+            RecordedBuildInput::Instances { num_instances, .. } => {
+                *num_instances as usize
+            }
+            RecordedBuildInput::Other { .. } => 0,
+        }
+    }
+}
+
+#[derive(Debug, Clone)]
+pub struct AccelBuildRecord {
+    pub build_flags: u32,
+    pub operation: sys::OptixBuildOperation,
+    pub build_inputs: Vec<RecordedBuildInput>,
+    pub temp_buffer: CUdeviceptr,
+    pub temp_buffer_size: usize,
+    pub output_buffer: CUdeviceptr,
+    pub output_buffer_size: usize,
+    pub emitted_properties: Vec<(sys::OptixAccelPropertyType, CUdeviceptr)>,
+    pub handle: sys::OptixTraversableHandle,
+}
+
+#[derive(Debug, Clone)]
+pub struct AccelCompactRecord {
+    pub input_handle: sys::OptixTraversableHandle,
+    pub output_buffer: CUdeviceptr,
+    pub output_buffer_size: usize,
+    pub handle: sys::OptixTraversableHandle,
+}
+
+/// The fields of the `OptixShaderBindingTable` passed to a launch.
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct RecordedSbt {
+    pub raygen_record: CUdeviceptr,
+    pub exception_record: CUdeviceptr,
+    pub miss_record_base: CUdeviceptr,
+    pub miss_record_stride_in_bytes: u32,
+    pub miss_record_count: u32,
+    pub hitgroup_record_base: CUdeviceptr,
+    pub hitgroup_record_stride_in_bytes: u32,
+    pub hitgroup_record_count: u32,
+    pub callables_record_base: CUdeviceptr,
+    pub callables_record_stride_in_bytes: u32,
+    pub callables_record_count: u32,
+}
+
+#[derive(Debug, Clone)]
+pub struct LaunchRecord {
+    /// The CUDA device that was current when the launch was made.
+    pub device: i32,
+    pub pipeline: sys::OptixPipeline,
+    pub stream: CUstream,
+    /// The contents of the launch params buffer at the time of the launch.
+    pub params: Vec<u8>,
+    pub sbt: RecordedSbt,
+    pub width: u32,
+    pub height: u32,
+    pub depth: u32,
+}
+
+struct LogCallback {
+    function: sys::OptixLogCallback,
+    data: *mut c_void,
+    level: u32,
+}
+
+struct ContextState {
+    cache_enabled: bool,
+    cache_location: CString,
+    low_water_mark: usize,
+    high_water_mark: usize,
+    log_callback: Option<LogCallback>,
+    #[cfg(optix_7_3)]
+    validation_mode: sys::OptixDeviceContextValidationMode,
+}
+
+impl Default for ContextState {
+    fn default() -> ContextState {
+        ContextState {
+            cache_enabled: true,
+            cache_location: CString::new("/var/tmp/OptixCache").unwrap(),
+            low_water_mark: 1 << 30,
+            high_water_mark: 1 << 31,
+            log_callback: None,
+            #[cfg(optix_7_3)]
+            validation_mode: sys::OptixDeviceContextValidationMode_OPTIX_DEVICE_CONTEXT_VALIDATION_MODE_OFF,
+        }
+    }
+}
+
+struct State {
+    device_count: c_int,
+    current_device: c_int,
+    context_stack: Vec<CUcontext>,
+    primary_contexts: HashMap<CUdevice, (CUcontext, u32)>,
+    compile_log: String,
+    program_group_stack_sizes: HashMap<u64, sys::OptixStackSizes>,
+    next_handle: u64,
+    next_ptr: u64,
+    memory: BTreeMap<CUdeviceptr, Vec<u8>>,
+    handles: HashMap<u64, HandleKind>,
+    contexts: HashMap<u64, ContextState>,
+    properties: HashMap<sys::OptixDeviceProperty, u32>,
+    stack_sizes: HashMap<u64, [u32; 4]>,
+    traversables: Vec<sys::OptixTraversableHandle>,
+    accel_builds: Vec<AccelBuildRecord>,
+    accel_compacts: Vec<AccelCompactRecord>,
+    launches: Vec<LaunchRecord>,
+    optix_failures: HashMap<&'static str, sys::OptixResult>,
+    cuda_failures: HashMap<&'static str, cudaError_t>,
+}
+
+impl State {
+    fn new_handle(&mut self, kind: HandleKind) -> u64 {
+        self.next_handle += 0x10;
+        self.handles.insert(self.next_handle, kind);
+        self.next_handle
+    }
+
+    fn is_live(&self, handle: u64, kind: HandleKind) -> bool {
+        self.handles.get(&handle) == Some(&kind)
+    }
+
+    fn destroy_handle(&mut self, handle: u64, kind: HandleKind) -> bool {
+        if self.is_live(handle, kind) {
+            self.handles.remove(&handle);
+            true
+        } else {
+            false
+        }
+    }
+
+    fn new_traversable(&mut self) -> sys::OptixTraversableHandle {
+        self.next_handle += 0x10;
+        self.traversables.push(self.next_handle);
+        self.next_handle
+    }
+
+    /// Find the allocation containing `[ptr, ptr + len)`.
+    fn resolve(&mut self, ptr: CUdeviceptr, len: usize) -> Option<&mut [u8]> {
+        let (base, bytes) = self.memory.range_mut(..=ptr).next_back()?;
+        let offset = (ptr - *base) as usize;
+        if offset + len <= bytes.len() {
+            Some(&mut bytes[offset..offset + len])
+        } else {
+            None
+        }
+    }
+
+    fn is_device_ptr(&mut self, ptr: CUdeviceptr) -> bool {
+        self.resolve(ptr, 0).is_some()
+    }
+}
+
+/// See the module documentation.
+pub struct MockBackend {
+    state: RefCell<State>,
+}
+
+impl Default for MockBackend {
+    fn default() -> MockBackend {
+        MockBackend::new()
+    }
+}
+
+impl MockBackend {
+    pub fn new() -> MockBackend {
+        let properties = [
+            (sys::OptixDeviceProperty_OPTIX_DEVICE_PROPERTY_LIMIT_MAX_TRACE_DEPTH, 31),
+            (sys::OptixDeviceProperty_OPTIX_DEVICE_PROPERTY_LIMIT_MAX_TRAVERSABLE_GRAPH_DEPTH, 31),
+            (sys::OptixDeviceProperty_OPTIX_DEVICE_PROPERTY_LIMIT_MAX_PRIMITIVES_PER_GAS, 1 << 29),
+            (sys::OptixDeviceProperty_OPTIX_DEVICE_PROPERTY_LIMIT_MAX_INSTANCES_PER_IAS, 1 << 24),
+            (sys::OptixDeviceProperty_OPTIX_DEVICE_PROPERTY_RTCORE_VERSION, 0),
+            (sys::OptixDeviceProperty_OPTIX_DEVICE_PROPERTY_LIMIT_MAX_INSTANCE_ID, (1 << 24) - 1),
+            (sys::OptixDeviceProperty_OPTIX_DEVICE_PROPERTY_LIMIT_NUM_BITS_INSTANCE_VISIBILITY_MASK, 8),
+            (sys::OptixDeviceProperty_OPTIX_DEVICE_PROPERTY_LIMIT_MAX_SBT_RECORDS_PER_GAS, 1 << 24),
+            (sys::OptixDeviceProperty_OPTIX_DEVICE_PROPERTY_LIMIT_MAX_SBT_OFFSET, (1 << 24) - 1),
+        ]
+        .iter()
+        .cloned()
+        .collect();
+
+        MockBackend {
+            state: RefCell::new(State {
+                device_count: 1,
+                current_device: 0,
+                context_stack: Vec::new(),
+                primary_contexts: HashMap::new(),
+                compile_log: String::new(),
+                program_group_stack_sizes: HashMap::new(),
+                next_handle: 0x1000,
+                next_ptr: DEVICE_BASE,
+                memory: BTreeMap::new(),
+                handles: HashMap::new(),
+                contexts: HashMap::new(),
+                properties,
+                stack_sizes: HashMap::new(),
+                traversables: Vec::new(),
+                accel_builds: Vec::new(),
+                accel_compacts: Vec::new(),
+                launches: Vec::new(),
+                optix_failures: HashMap::new(),
+                cuda_failures: HashMap::new(),
+            }),
+        }
+    }
+
+    /// Create a mock and route the current thread's driver calls to it until
+    /// the returned guard is dropped.
+    ///
+    /// Declare the guard before any objects created through it so that they
+    /// are dropped while it is still installed.
+    pub fn install() -> MockGuard {
+        let mock = Rc::new(MockBackend::new());
+        let previous = super::set_thread_backend(Some(mock.clone()));
+        MockGuard { mock, previous }
+    }
+
+    /// Read `len` bytes of fake device memory starting at `ptr`, or `None` if
+    /// that range is not inside a live allocation.
+    pub fn read(&self, ptr: CUdeviceptr, len: usize) -> Option<Vec<u8>> {
+        self.state
+            .borrow_mut()
+            .resolve(ptr, len)
+            .map(|b| b.to_vec())
+    }
+
+    /// The number of live device allocations.
+    pub fn allocation_count(&self) -> usize {
+        self.state.borrow().memory.len()
+    }
+
+    /// The total size of the live device allocations.
+    pub fn allocated_bytes(&self) -> usize {
+        self.state.borrow().memory.values().map(|v| v.len()).sum()
+    }
+
+    /// The number of live objects of `kind`.
+    pub fn live_handles(&self, kind: HandleKind) -> usize {
+        self.state
+            .borrow()
+            .handles
+            .values()
+            .filter(|k| **k == kind)
+            .count()
+    }
+
+    pub fn accel_builds(&self) -> Vec<AccelBuildRecord> {
+        self.state.borrow().accel_builds.clone()
+    }
+
+    pub fn accel_compacts(&self) -> Vec<AccelCompactRecord> {
+        self.state.borrow().accel_compacts.clone()
+    }
+
+    pub fn launches(&self) -> Vec<LaunchRecord> {
+        self.state.borrow().launches.clone()
+    }
+
+    /// The arguments of the last `optixPipelineSetStackSize` call for
+    /// `pipeline`, in order.
+    pub fn stack_size(&self, pipeline: sys::OptixPipeline) -> Option<[u32; 4]> {
+        self.state
+            .borrow()
+            .stack_sizes
+            .get(&(pipeline as u64))
+            .cloned()
+    }
+
+    /// The validation mode `context` was created with.
+    #[cfg(optix_7_3)]
+    pub fn validation_mode(
+        &self,
+        context: sys::OptixDeviceContext,
+    ) -> Option<sys::OptixDeviceContextValidationMode> {
+        self.state
+            .borrow()
+            .contexts
+            .get(&(context as u64))
+            .map(|c| c.validation_mode)
+    }
+
+    /// Set the number of CUDA devices reported by `cudaGetDeviceCount`. The
+    /// mock starts with a single device.
+    pub fn set_device_count(&self, count: i32) {
+        self.state.borrow_mut().device_count = count;
+    }
+
+    /// The device most recently made current with `cudaSetDevice`.
+    pub fn current_device(&self) -> i32 {
+        self.state.borrow().current_device
+    }
+
+    /// The calling thread's CUDA context stack, with the current context
+    /// last.
+    pub fn context_stack(&self) -> Vec<CUcontext> {
+        self.state.borrow().context_stack.clone()
+    }
+
+    /// How many times the primary context of `device` is currently retained.
+    pub fn primary_context_retains(&self, device: i32) -> u32 {
+        self.state
+            .borrow()
+            .primary_contexts
+            .get(&device)
+            .map_or(0, |(_, count)| *count)
+    }
+
+    /// Set the log written by module, program group and pipeline creation,
+    /// whether or not they succeed.
+    pub fn set_compile_log(&self, log: &str) {
+        self.state.borrow_mut().compile_log = log.to_string();
+    }
+
+    /// Set the stack sizes returned for `program_group` by
+    /// `optixProgramGroupGetStackSize`, which are all zero otherwise.
+    pub fn set_program_group_stack_sizes(
+        &self,
+        program_group: sys::OptixProgramGroup,
+        stack_sizes: sys::OptixStackSizes,
+    ) {
+        self.state
+            .borrow_mut()
+            .program_group_stack_sizes
+            .insert(program_group as u64, stack_sizes);
+    }
+
+    /// Set the value returned for `property` by
+    /// `optixDeviceContextGetProperty`.
+    pub fn set_property(&self, property: sys::OptixDeviceProperty, value: u32) {
+        self.state.borrow_mut().properties.insert(property, value);
+    }
+
+    /// Make every call to the OptiX entry point `name` (the name of the
+    /// `Backend` method, e.g. "optix_accel_build") return `result`.
+    pub fn fail_optix(&self, name: &'static str, result: sys::OptixResult) {
+        self.state.borrow_mut().optix_failures.insert(name, result);
+    }
+
+    /// Make every call to the CUDA entry point `name` (the name of the
+    /// `Backend` method, e.g. "cuda_malloc") return `error`. Driver API
+    /// entry points, e.g. "cu_ctx_create", take a `CUresult`.
+    pub fn fail_cuda(&self, name: &'static str, error: cudaError_t) {
+        self.state.borrow_mut().cuda_failures.insert(name, error);
+    }
+
+    pub fn clear_failures(&self) {
+        let mut state = self.state.borrow_mut();
+        state.optix_failures.clear();
+        state.cuda_failures.clear();
+    }
+
+    /// Send a message to the log callbacks of all live device contexts whose
+    /// level is at least `level`, as OptiX would.
+    pub fn log(&self, level: u32, tag: &str, message: &str) {
+        let tag = CString::new(tag).unwrap();
+        let message = CString::new(message).unwrap();
+        // collect first so a callback can call back into the mock
+        let callbacks: Vec<_> = self
+            .state
+            .borrow()
+            .contexts
+            .values()
+            .filter_map(|c| c.log_callback.as_ref())
+            .filter(|cb| level <= cb.level)
+            .map(|cb| (cb.function, cb.data))
+            .collect();
+        for (function, data) in callbacks {
+            if let Some(function) = function {
+                unsafe {
+                    function(level, tag.as_ptr(), message.as_ptr(), data)
+                };
+            }
+        }
+    }
+
+    fn optix_failure(&self, name: &'static str) -> Option<sys::OptixResult> {
+        self.state.borrow().optix_failures.get(name).cloned()
+    }
+
+    fn cuda_failure(&self, name: &'static str) -> Option<cudaError_t> {
+        self.state.borrow().cuda_failures.get(name).cloned()
+    }
+}
+
+/// Uninstalls the `MockBackend` it derefs to when dropped, restoring the
+/// backend that was installed before it.
+pub struct MockGuard {
+    mock: Rc<MockBackend>,
+    previous: Option<Rc<dyn Backend>>,
+}
+
+impl MockGuard {
+    pub fn backend(&self) -> Rc<MockBackend> {
+        self.mock.clone()
+    }
+}
+
+impl std::ops::Deref for MockGuard {
+    type Target = MockBackend;
+    fn deref(&self) -> &MockBackend {
+        &self.mock
+    }
+}
+
+impl Drop for MockGuard {
+    fn drop(&mut self) {
+        super::set_thread_backend(self.previous.take());
+    }
+}
+
+macro_rules! check_optix {
+    ($self:ident, $name:literal) => {
+        if let Some(result) = $self.optix_failure($name) {
+            return result;
+        }
+    };
+}
+
+macro_rules! check_cuda {
+    ($self:ident, $name:literal) => {
+        if let Some(error) = $self.cuda_failure($name) {
+            return error;
+        }
+    };
+}
+
+/// Write `log` into an OptiX log string argument, truncating it to fit and
+/// reporting the size needed to hold all of it, as OptiX does.
+unsafe fn write_log(
+    log_string: *mut c_char,
+    log_string_size: *mut usize,
+    log: &str,
+) {
+    if log_string.is_null() || log_string_size.is_null() {
+        return;
+    }
+    let capacity = *log_string_size;
+    if capacity == 0 {
+        return;
+    }
+    let len = log.len().min(capacity - 1);
+    std::ptr::copy_nonoverlapping(
+        log.as_ptr() as *const c_char,
+        log_string,
+        len,
+    );
+    *log_string.add(len) = 0;
+    *log_string_size = log.len() + 1;
+}
+
+unsafe fn read_array<T: Copy>(ptr: *const T, len: usize) -> Vec<T> {
+    if ptr.is_null() {
+        Vec::new()
+    } else {
+        std::slice::from_raw_parts(ptr, len).to_vec()
+    }
+}
+
+unsafe fn record_build_input(
+    input: &sys::OptixBuildInput,
+    num_keys: usize,
+) -> RecordedBuildInput {
+    match input.type_ {
+        sys::OptixBuildInputType_OPTIX_BUILD_INPUT_TYPE_TRIANGLES => {
+            let ta = &input.input.triangle_array;
+            RecordedBuildInput::Triangles {
+                vertex_buffers: read_array(ta.vertexBuffers, num_keys),
+                num_vertices: ta.numVertices,
+                vertex_format: ta.vertexFormat,
+                vertex_stride_in_bytes: ta.vertexStrideInBytes,
+                index_buffer: ta.indexBuffer,
+                num_index_triplets: ta.numIndexTriplets,
+                index_format: ta.indexFormat,
+                index_stride_in_bytes: ta.indexStrideInBytes,
+                flags: read_array(ta.flags, ta.numSbtRecords as usize),
+            }
+        }
+        sys::OptixBuildInputType_OPTIX_BUILD_INPUT_TYPE_CUSTOM_PRIMITIVES => {
+            let aa = &input.input.aabb_array;
+            RecordedBuildInput::CustomPrimitives {
+                aabb_buffers: read_array(aa.aabbBuffers, num_keys),
+                num_primitives: aa.numPrimitives,
+                stride_in_bytes: aa.strideInBytes,
+                flags: read_array(aa.flags, aa.numSbtRecords as usize),
+            }
+        }
+        sys::OptixBuildInputType_OPTIX_BUILD_INPUT_TYPE_INSTANCES => {
+            let ia = &input.input.instance_array;
+            RecordedBuildInput::Instances {
+                instances: ia.instances,
+                num_instances: ia.numInstances,
+            }
+        }
+        type_ => RecordedBuildInput::Other { type_ },
+    }
+}
+
+unsafe fn record_build_inputs(
+    accel_options: *const sys::OptixAccelBuildOptions,
+    build_inputs: *const sys::OptixBuildInput,
+    num_build_inputs: c_uint,
+) -> Vec<RecordedBuildInput> {
+    let num_keys = (*accel_options).motionOptions.numKeys.max(1) as usize;
+    std::slice::from_raw_parts(build_inputs, num_build_inputs as usize)
+        .iter()
+        .map(|b| record_build_input(b, num_keys))
+        .collect()
+}
+
+impl Backend for MockBackend {
+    unsafe fn cu_init(&self, _flags: c_uint) -> CUresult {
+        check_cuda!(self, "cu_init");
+        cudaError_enum::CUDA_SUCCESS
+    }
+
+    unsafe fn cu_device_get(
+        &self,
+        device: *mut CUdevice,
+        ordinal: c_int,
+    ) -> CUresult {
+        check_cuda!(self, "cu_device_get");
+        if ordinal < 0 || ordinal >= self.state.borrow().device_count {
+            return cudaError_enum::CUDA_ERROR_INVALID_DEVICE;
+        }
+        *device = ordinal;
+        cudaError_enum::CUDA_SUCCESS
+    }
+
+    unsafe fn cu_ctx_get_current(&self, pctx: *mut CUcontext) -> CUresult {
+        check_cuda!(self, "cu_ctx_get_current");
+        *pctx = self
+            .state
+            .borrow()
+            .context_stack
+            .last()
+            .cloned()
+            .unwrap_or(MOCK_CUDA_CONTEXT);
+        cudaError_enum::CUDA_SUCCESS
+    }
+
+    unsafe fn cu_ctx_create(
+        &self,
+        pctx: *mut CUcontext,
+        _flags: c_uint,
+        dev: CUdevice,
+    ) -> CUresult {
+        check_cuda!(self, "cu_ctx_create");
+        let mut state = self.state.borrow_mut();
+        if dev < 0 || dev >= state.device_count {
+            return cudaError_enum::CUDA_ERROR_INVALID_DEVICE;
+        }
+        let ctx = state.new_handle(HandleKind::CudaContext) as CUcontext;
+        state.context_stack.push(ctx);
+        *pctx = ctx;
+        cudaError_enum::CUDA_SUCCESS
+    }
+
+    unsafe fn cu_ctx_destroy(&self, ctx: CUcontext) -> CUresult {
+        check_cuda!(self, "cu_ctx_destroy");
+        let mut state = self.state.borrow_mut();
+        if !state.destroy_handle(ctx as u64, HandleKind::CudaContext) {
+            return cudaError_enum::CUDA_ERROR_INVALID_VALUE;
+        }
+        state.context_stack.retain(|c| *c != ctx);
+        cudaError_enum::CUDA_SUCCESS
+    }
+
+    unsafe fn cu_ctx_push_current(&self, ctx: CUcontext) -> CUresult {
+        check_cuda!(self, "cu_ctx_push_current");
+        let mut state = self.state.borrow_mut();
+        let is_primary =
+            state.primary_contexts.values().any(|(c, _)| *c == ctx);
+        if !is_primary && !state.is_live(ctx as u64, HandleKind::CudaContext) {
+            return cudaError_enum::CUDA_ERROR_INVALID_VALUE;
+        }
+        state.context_stack.push(ctx);
+        cudaError_enum::CUDA_SUCCESS
+    }
+
+    unsafe fn cu_ctx_pop_current(&self, pctx: *mut CUcontext) -> CUresult {
+        check_cuda!(self, "cu_ctx_pop_current");
+        match self.state.borrow_mut().context_stack.pop() {
+            Some(ctx) => {
+                if !pctx.is_null() {
+                    *pctx = ctx;
+                }
+                cudaError_enum::CUDA_SUCCESS
+            }
+            None => cudaError_enum::CUDA_ERROR_INVALID_VALUE,
+        }
+    }
+
+    unsafe fn cu_device_primary_ctx_retain(
+        &self,
+        pctx: *mut CUcontext,
+        dev: CUdevice,
+    ) -> CUresult {
+        check_cuda!(self, "cu_device_primary_ctx_retain");
+        let mut state = self.state.borrow_mut();
+        if dev < 0 || dev >= state.device_count {
+            return cudaError_enum::CUDA_ERROR_INVALID_DEVICE;
+        }
+        let ctx = match state.primary_contexts.get(&dev) {
+            Some((ctx, _)) => *ctx,
+            None => {
+                state.next_handle += 0x10;
+                state.next_handle as CUcontext
+            }
+        };
+        state.primary_contexts.entry(dev).or_insert((ctx, 0)).1 += 1;
+        *pctx = ctx;
+        cudaError_enum::CUDA_SUCCESS
+    }
+
+    unsafe fn cu_device_primary_ctx_release(&self, dev: CUdevice) -> CUresult {
+        check_cuda!(self, "cu_device_primary_ctx_release");
+        match self.state.borrow_mut().primary_contexts.get_mut(&dev) {
+            Some((_, count)) if *count > 0 => {
+                *count -= 1;
+                cudaError_enum::CUDA_SUCCESS
+            }
+            _ => cudaError_enum::CUDA_ERROR_INVALID_VALUE,
+        }
+    }
+
+    unsafe fn cuda_get_device_count(&self, count: *mut c_int) -> cudaError_t {
+        check_cuda!(self, "cuda_get_device_count");
+        *count = self.state.borrow().device_count;
+        cudaError::cudaSuccess
+    }
+
+    unsafe fn cuda_set_device(&self, device: c_int) -> cudaError_t {
+        check_cuda!(self, "cuda_set_device");
+        let mut state = self.state.borrow_mut();
+        if device < 0 || device >= state.device_count {
+            return cudaError::cudaErrorInvalidDevice;
+        }
+        state.current_device = device;
+        cudaError::cudaSuccess
+    }
+
+    unsafe fn cuda_malloc(
+        &self,
+        dev_ptr: *mut *mut c_void,
+        size: usize,
+    ) -> cudaError_t {
+        check_cuda!(self, "cuda_malloc");
+        let mut state = self.state.borrow_mut();
+        let ptr = state.next_ptr;
+        state.next_ptr +=
+            (size.max(1) as u64).next_multiple_of(DEVICE_ALIGNMENT);
+        state.memory.insert(ptr, vec![0u8; size]);
+        *dev_ptr = ptr as *mut c_void;
+        cudaError::cudaSuccess
+    }
+
+    unsafe fn cuda_free(&self, dev_ptr: *mut c_void) -> cudaError_t {
+        check_cuda!(self, "cuda_free");
+        if dev_ptr.is_null() {
+            return cudaError::cudaSuccess;
+        }
+        match self.state.borrow_mut().memory.remove(&(dev_ptr as u64)) {
+            Some(_) => cudaError::cudaSuccess,
+            None => cudaError::cudaErrorInvalidDevicePointer,
+        }
+    }
+
+    unsafe fn cuda_memcpy(
+        &self,
+        dst: *mut c_void,
+        src: *const c_void,
+        count: usize,
+        kind: cudaMemcpyKind::Type,
+    ) -> cudaError_t {
+        check_cuda!(self, "cuda_memcpy");
+        let mut state = self.state.borrow_mut();
+        let kind = if kind == cudaMemcpyKind::cudaMemcpyDefault {
+            match (
+                state.is_device_ptr(dst as u64),
+                state.is_device_ptr(src as u64),
+            ) {
+                (false, false) => cudaMemcpyKind::cudaMemcpyHostToHost,
+                (true, false) => cudaMemcpyKind::cudaMemcpyHostToDevice,
+                (false, true) => cudaMemcpyKind::cudaMemcpyDeviceToHost,
+                (true, true) => cudaMemcpyKind::cudaMemcpyDeviceToDevice,
+            }
+        } else {
+            kind
+        };
+
+        let src_bytes: Vec<u8> = match kind {
+            cudaMemcpyKind::cudaMemcpyHostToHost
+            | cudaMemcpyKind::cudaMemcpyHostToDevice => {
+                read_array(src as *const u8, count)
+            }
+            _ => match state.resolve(src as u64, count) {
+                Some(bytes) => bytes.to_vec(),
+                None => return cudaError::cudaErrorInvalidValue,
+            },
+        };
+
+        match kind {
+            cudaMemcpyKind::cudaMemcpyHostToHost
+            | cudaMemcpyKind::cudaMemcpyDeviceToHost => {
+                std::ptr::copy_nonoverlapping(
+                    src_bytes.as_ptr(),
+                    dst as *mut u8,
+                    count,
+                );
+            }
+            _ => match state.resolve(dst as u64, count) {
+                Some(bytes) => bytes.copy_from_slice(&src_bytes),
+                None => return cudaError::cudaErrorInvalidValue,
+            },
+        }
+
+        cudaError::cudaSuccess
+    }
+
+    unsafe fn cuda_stream_create(
+        &self,
+        stream: *mut cudaStream_t,
+    ) -> cudaError_t {
+        check_cuda!(self, "cuda_stream_create");
+        *stream = self.state.borrow_mut().new_handle(HandleKind::Stream)
+            as cudaStream_t;
+        cudaError::cudaSuccess
+    }
+
+    unsafe fn cuda_stream_destroy(&self, stream: cudaStream_t) -> cudaError_t {
+        check_cuda!(self, "cuda_stream_destroy");
+        if self
+            .state
+            .borrow_mut()
+            .destroy_handle(stream as u64, HandleKind::Stream)
+        {
+            cudaError::cudaSuccess
+        } else {
+            cudaError::cudaErrorInvalidResourceHandle
+        }
+    }
+
+    unsafe fn optix_device_context_create(
+        &self,
+        _from_context: CUcontext,
+        options: *const sys::OptixDeviceContextOptions,
+        context: *mut sys::OptixDeviceContext,
+    ) -> sys::OptixResult {
+        check_optix!(self, "optix_device_context_create");
+        let mut ctx_state = ContextState::default();
+        #[cfg(optix_7_3)]
+        if !options.is_null() {
+            match (*options).validationMode {
+                sys::OptixDeviceContextValidationMode_OPTIX_DEVICE_CONTEXT_VALIDATION_MODE_OFF
+                | sys::OptixDeviceContextValidationMode_OPTIX_DEVICE_CONTEXT_VALIDATION_MODE_ALL => {
+                    ctx_state.validation_mode = (*options).validationMode
+                }
+                _ => return sys::OptixResult::OPTIX_ERROR_INVALID_VALUE,
+            }
+        }
+        let mut state = self.state.borrow_mut();
+        let handle = state.new_handle(HandleKind::DeviceContext);
+        if !options.is_null() && (*options).logCallbackFunction.is_some() {
+            ctx_state.log_callback = Some(LogCallback {
+                function: (*options).logCallbackFunction,
+                data: (*options).logCallbackData,
+                level: (*options).logCallbackLevel as u32,
+            });
+        }
+        state.contexts.insert(handle, ctx_state);
+        *context = handle as sys::OptixDeviceContext;
+        sys::OptixResult::OPTIX_SUCCESS
+    }
+
+    unsafe fn optix_device_context_destroy(
+        &self,
+        context: sys::OptixDeviceContext,
+    ) -> sys::OptixResult {
+        check_optix!(self, "optix_device_context_destroy");
+        let mut state = self.state.borrow_mut();
+        if state.destroy_handle(context as u64, HandleKind::DeviceContext) {
+            state.contexts.remove(&(context as u64));
+            sys::OptixResult::OPTIX_SUCCESS
+        } else {
+            sys::OptixResult::OPTIX_ERROR_INVALID_DEVICE_CONTEXT
+        }
+    }
+
+    unsafe fn optix_device_context_get_property(
+        &self,
+        context: sys::OptixDeviceContext,
+        property: sys::OptixDeviceProperty,
+        value: *mut c_void,
+        size_in_bytes: usize,
+    ) -> sys::OptixResult {
+        check_optix!(self, "optix_device_context_get_property");
+        let state = self.state.borrow();
+        if !state.is_live(context as u64, HandleKind::DeviceContext) {
+            return sys::OptixResult::OPTIX_ERROR_INVALID_DEVICE_CONTEXT;
+        }
+        match state.properties.get(&property) {
+            Some(v) if size_in_bytes == std::mem::size_of::<u32>() => {
+                *(value as *mut u32) = *v;
+                sys::OptixResult::OPTIX_SUCCESS
+            }
+            _ => sys::OptixResult::OPTIX_ERROR_INVALID_VALUE,
+        }
+    }
+
+    unsafe fn optix_device_context_set_log_callback(
+        &self,
+        context: sys::OptixDeviceContext,
+        callback_function: sys::OptixLogCallback,
+        callback_data: *mut c_void,
+        callback_level: c_uint,
+    ) -> sys::OptixResult {
+        check_optix!(self, "optix_device_context_set_log_callback");
+        if callback_level > 4 {
+            return sys::OptixResult::OPTIX_ERROR_INVALID_VALUE;
+        }
+        match self.state.borrow_mut().contexts.get_mut(&(context as u64)) {
+            Some(c) => {
+                c.log_callback = Some(LogCallback {
+                    function: callback_function,
+                    data: callback_data,
+                    level: callback_level,
+                });
+                sys::OptixResult::OPTIX_SUCCESS
+            }
+            None => sys::OptixResult::OPTIX_ERROR_INVALID_DEVICE_CONTEXT,
+        }
+    }
+
+    unsafe fn optix_device_context_set_cache_enabled(
+        &self,
+        context: sys::OptixDeviceContext,
+        enabled: c_int,
+    ) -> sys::OptixResult {
+        check_optix!(self, "optix_device_context_set_cache_enabled");
+        match self.state.borrow_mut().contexts.get_mut(&(context as u64)) {
+            Some(c) => {
+                c.cache_enabled = enabled != 0;
+                sys::OptixResult::OPTIX_SUCCESS
+            }
+            None => sys::OptixResult::OPTIX_ERROR_INVALID_DEVICE_CONTEXT,
+        }
+    }
+
+    unsafe fn optix_device_context_set_cache_location(
+        &self,
+        context: sys::OptixDeviceContext,
+        location: *const c_char,
+    ) -> sys::OptixResult {
+        check_optix!(self, "optix_device_context_set_cache_location");
+        if location.is_null() {
+            return sys::OptixResult::OPTIX_ERROR_INVALID_VALUE;
+        }
+        match self.state.borrow_mut().contexts.get_mut(&(context as u64)) {
+            Some(c) => {
+                c.cache_location = CStr::from_ptr(location).to_owned();
+                sys::OptixResult::OPTIX_SUCCESS
+            }
+            None => sys::OptixResult::OPTIX_ERROR_INVALID_DEVICE_CONTEXT,
+        }
+    }
+
+    unsafe fn optix_device_context_set_cache_database_sizes(
+        &self,
+        context: sys::OptixDeviceContext,
+        low_water_mark: usize,
+        high_water_mark: usize,
+    ) -> sys::OptixResult {
+        check_optix!(self, "optix_device_context_set_cache_database_sizes");
+        if low_water_mark != 0
+            && high_water_mark != 0
+            && high_water_mark < low_water_mark
+        {
+            return sys::OptixResult::OPTIX_ERROR_INVALID_VALUE;
+        }
+        match self.state.borrow_mut().contexts.get_mut(&(context as u64)) {
+            Some(c) => {
+                c.low_water_mark = low_water_mark;
+                c.high_water_mark = high_water_mark;
+                sys::OptixResult::OPTIX_SUCCESS
+            }
+            None => sys::OptixResult::OPTIX_ERROR_INVALID_DEVICE_CONTEXT,
+        }
+    }
+
+    unsafe fn optix_device_context_get_cache_enabled(
+        &self,
+        context: sys::OptixDeviceContext,
+        enabled: *mut c_int,
+    ) -> sys::OptixResult {
+        check_optix!(self, "optix_device_context_get_cache_enabled");
+        match self.state.borrow().contexts.get(&(context as u64)) {
+            Some(c) => {
+                *enabled = c.cache_enabled as c_int;
+                sys::OptixResult::OPTIX_SUCCESS
+            }
+            None => sys::OptixResult::OPTIX_ERROR_INVALID_DEVICE_CONTEXT,
+        }
+    }
+
+    unsafe fn optix_device_context_get_cache_location(
+        &self,
+        context: sys::OptixDeviceContext,
+        location: *mut c_char,
+        location_size: usize,
+    ) -> sys::OptixResult {
+        check_optix!(self, "optix_device_context_get_cache_location");
+        match self.state.borrow().contexts.get(&(context as u64)) {
+            Some(c) => {
+                let bytes = c.cache_location.as_bytes_with_nul();
+                if bytes.len() > location_size {
+                    return sys::OptixResult::OPTIX_ERROR_INVALID_VALUE;
+                }
+                std::ptr::copy_nonoverlapping(
+                    bytes.as_ptr() as *const c_char,
+                    location,
+                    bytes.len(),
+                );
+                sys::OptixResult::OPTIX_SUCCESS
+            }
+            None => sys::OptixResult::OPTIX_ERROR_INVALID_DEVICE_CONTEXT,
+        }
+    }
+
+    unsafe fn optix_device_context_get_cache_database_sizes(
+        &self,
+        context: sys::OptixDeviceContext,
+        low_water_mark: *mut usize,
+        high_water_mark: *mut usize,
+    ) -> sys::OptixResult {
+        check_optix!(self, "optix_device_context_get_cache_database_sizes");
+        match self.state.borrow().contexts.get(&(context as u64)) {
+            Some(c) => {
+                *low_water_mark = c.low_water_mark;
+                *high_water_mark = c.high_water_mark;
+                sys::OptixResult::OPTIX_SUCCESS
+            }
+            None => sys::OptixResult::OPTIX_ERROR_INVALID_DEVICE_CONTEXT,
+        }
+    }
+
+    unsafe fn optix_module_create_from_ptx(
+        &self,
+        context: sys::OptixDeviceContext,
+        _module_compile_options: *const sys::OptixModuleCompileOptions,
+        _pipeline_compile_options: *const sys::OptixPipelineCompileOptions,
+        _ptx: *const c_char,
+        _ptx_size: usize,
+        log_string: *mut c_char,
+        log_string_size: *mut usize,
+        module: *mut sys::OptixModule,
+    ) -> sys::OptixResult {
+        write_log(
+            log_string,
+            log_string_size,
+            &self.state.borrow().compile_log,
+        );
+        check_optix!(self, "optix_module_create_from_ptx");
+        let mut state = self.state.borrow_mut();
+        if !state.is_live(context as u64, HandleKind::DeviceContext) {
+            return sys::OptixResult::OPTIX_ERROR_INVALID_DEVICE_CONTEXT;
+        }
+        *module = state.new_handle(HandleKind::Module) as sys::OptixModule;
+        sys::OptixResult::OPTIX_SUCCESS
+    }
+
+    unsafe fn optix_module_destroy(
+        &self,
+        module: sys::OptixModule,
+    ) -> sys::OptixResult {
+        check_optix!(self, "optix_module_destroy");
+        if self
+            .state
+            .borrow_mut()
+            .destroy_handle(module as u64, HandleKind::Module)
+        {
+            sys::OptixResult::OPTIX_SUCCESS
+        } else {
+            sys::OptixResult::OPTIX_ERROR_INVALID_VALUE
+        }
+    }
+
+    #[cfg(optix_7_3)]
+    unsafe fn optix_builtin_is_module_get(
+        &self,
+        context: sys::OptixDeviceContext,
+        _module_compile_options: *const sys::OptixModuleCompileOptions,
+        _pipeline_compile_options: *const sys::OptixPipelineCompileOptions,
+        _builtin_is_options: *const sys::OptixBuiltinISOptions,
+        builtin_module: *mut sys::OptixModule,
+    ) -> sys::OptixResult {
+        check_optix!(self, "optix_builtin_is_module_get");
+        let mut state = self.state.borrow_mut();
+        if !state.is_live(context as u64, HandleKind::DeviceContext) {
+            return sys::OptixResult::OPTIX_ERROR_INVALID_DEVICE_CONTEXT;
+        }
+        *builtin_module =
+            state.new_handle(HandleKind::Module) as sys::OptixModule;
+        sys::OptixResult::OPTIX_SUCCESS
+    }
+
+    unsafe fn optix_program_group_create(
+        &self,
+        context: sys::OptixDeviceContext,
+        _program_descriptions: *const sys::OptixProgramGroupDesc,
+        num_program_groups: c_uint,
+        _options: *const sys::OptixProgramGroupOptions,
+        log_string: *mut c_char,
+        log_string_size: *mut usize,
+        program_groups: *mut sys::OptixProgramGroup,
+    ) -> sys::OptixResult {
+        write_log(
+            log_string,
+            log_string_size,
+            &self.state.borrow().compile_log,
+        );
+        check_optix!(self, "optix_program_group_create");
+        let mut state = self.state.borrow_mut();
+        if !state.is_live(context as u64, HandleKind::DeviceContext) {
+            return sys::OptixResult::OPTIX_ERROR_INVALID_DEVICE_CONTEXT;
+        }
+        for i in 0..num_program_groups as usize {
+            *program_groups.add(i) = state.new_handle(HandleKind::ProgramGroup)
+                as sys::OptixProgramGroup;
+        }
+        sys::OptixResult::OPTIX_SUCCESS
+    }
+
+    unsafe fn optix_program_group_destroy(
+        &self,
+        program_group: sys::OptixProgramGroup,
+    ) -> sys::OptixResult {
+        check_optix!(self, "optix_program_group_destroy");
+        if self
+            .state
+            .borrow_mut()
+            .destroy_handle(program_group as u64, HandleKind::ProgramGroup)
+        {
+            sys::OptixResult::OPTIX_SUCCESS
+        } else {
+            sys::OptixResult::OPTIX_ERROR_INVALID_VALUE
+        }
+    }
+
+    unsafe fn optix_program_group_get_stack_size(
+        &self,
+        program_group: sys::OptixProgramGroup,
+        stack_sizes: *mut sys::OptixStackSizes,
+    ) -> sys::OptixResult {
+        check_optix!(self, "optix_program_group_get_stack_size");
+        let state = self.state.borrow();
+        if !state.is_live(program_group as u64, HandleKind::ProgramGroup) {
+            return sys::OptixResult::OPTIX_ERROR_INVALID_VALUE;
+        }
+        *stack_sizes = match state
+            .program_group_stack_sizes
+            .get(&(program_group as u64))
+        {
+            Some(s) => *s,
+            None => std::mem::zeroed(),
+        };
+        sys::OptixResult::OPTIX_SUCCESS
+    }
+
+    unsafe fn optix_pipeline_create(
+        &self,
+        context: sys::OptixDeviceContext,
+        _pipeline_compile_options: *const sys::OptixPipelineCompileOptions,
+        _pipeline_link_options: *const sys::OptixPipelineLinkOptions,
+        program_groups: *const sys::OptixProgramGroup,
+        num_program_groups: c_uint,
+        log_string: *mut c_char,
+        log_string_size: *mut usize,
+        pipeline: *mut sys::OptixPipeline,
+    ) -> sys::OptixResult {
+        write_log(
+            log_string,
+            log_string_size,
+            &self.state.borrow().compile_log,
+        );
+        check_optix!(self, "optix_pipeline_create");
+        let mut state = self.state.borrow_mut();
+        if !state.is_live(context as u64, HandleKind::DeviceContext) {
+            return sys::OptixResult::OPTIX_ERROR_INVALID_DEVICE_CONTEXT;
+        }
+        let pgs = read_array(program_groups, num_program_groups as usize);
+        if !pgs
+            .iter()
+            .all(|pg| state.is_live(*pg as u64, HandleKind::ProgramGroup))
+        {
+            return sys::OptixResult::OPTIX_ERROR_INVALID_VALUE;
+        }
+        *pipeline =
+            state.new_handle(HandleKind::Pipeline) as sys::OptixPipeline;
+        sys::OptixResult::OPTIX_SUCCESS
+    }
+
+    unsafe fn optix_pipeline_destroy(
+        &self,
+        pipeline: sys::OptixPipeline,
+    ) -> sys::OptixResult {
+        check_optix!(self, "optix_pipeline_destroy");
+        if self
+            .state
+            .borrow_mut()
+            .destroy_handle(pipeline as u64, HandleKind::Pipeline)
+        {
+            sys::OptixResult::OPTIX_SUCCESS
+        } else {
+            sys::OptixResult::OPTIX_ERROR_INVALID_VALUE
+        }
+    }
+
+    unsafe fn optix_pipeline_set_stack_size(
+        &self,
+        pipeline: sys::OptixPipeline,
+        direct_callable_stack_size_from_traversal: c_uint,
+        direct_callable_stack_size_from_state: c_uint,
+        continuation_stack_size: c_uint,
+        max_traversable_graph_depth: c_uint,
+    ) -> sys::OptixResult {
+        check_optix!(self, "optix_pipeline_set_stack_size");
+        let mut state = self.state.borrow_mut();
+        if !state.is_live(pipeline as u64, HandleKind::Pipeline) {
+            return sys::OptixResult::OPTIX_ERROR_INVALID_VALUE;
+        }
+        state.stack_sizes.insert(
+            pipeline as u64,
+            [
+                direct_callable_stack_size_from_traversal,
+                direct_callable_stack_size_from_state,
+                continuation_stack_size,
+                max_traversable_graph_depth,
+            ],
+        );
+        sys::OptixResult::OPTIX_SUCCESS
+    }
+
+    unsafe fn optix_accel_compute_memory_usage(
+        &self,
+        context: sys::OptixDeviceContext,
+        accel_options: *const sys::OptixAccelBuildOptions,
+        build_inputs: *const sys::OptixBuildInput,
+        num_build_inputs: c_uint,
+        buffer_sizes: *mut sys::OptixAccelBufferSizes,
+    ) -> sys::OptixResult {
+        check_optix!(self, "optix_accel_compute_memory_usage");
+        if !self
+            .state
+            .borrow()
+            .is_live(context as u64, HandleKind::DeviceContext)
+        {
+            return sys::OptixResult::OPTIX_ERROR_INVALID_DEVICE_CONTEXT;
+        }
+        let num_primitives: usize =
+            record_build_inputs(accel_options, build_inputs, num_build_inputs)
+                .iter()
+                .map(|b| b.num_primitives())
+                .sum();
+        let size = num_primitives.max(1) * ACCEL_BYTES_PER_PRIMITIVE;
+        *buffer_sizes = sys::OptixAccelBufferSizes {
+            outputSizeInBytes: size,
+            tempSizeInBytes: size,
+            tempUpdateSizeInBytes: size,
+        };
+        sys::OptixResult::OPTIX_SUCCESS
+    }
+
+    unsafe fn optix_accel_build(
+        &self,
+        context: sys::OptixDeviceContext,
+        _stream: CUstream,
+        accel_options: *const sys::OptixAccelBuildOptions,
+        build_inputs: *const sys::OptixBuildInput,
+        num_build_inputs: c_uint,
+        temp_buffer: CUdeviceptr,
+        temp_buffer_size_in_bytes: usize,
+        output_buffer: CUdeviceptr,
+        output_buffer_size_in_bytes: usize,
+        output_handle: *mut sys::OptixTraversableHandle,
+        emitted_properties: *const sys::OptixAccelEmitDesc,
+        num_emitted_properties: c_uint,
+    ) -> sys::OptixResult {
+        check_optix!(self, "optix_accel_build");
+        let build_inputs =
+            record_build_inputs(accel_options, build_inputs, num_build_inputs);
+        let emitted_properties: Vec<_> = if emitted_properties.is_null() {
+            Vec::new()
+        } else {
+            std::slice::from_raw_parts(
+                emitted_properties,
+                num_emitted_properties as usize,
+            )
+            .iter()
+            .map(|e| (e.type_, e.result))
+            .collect()
+        };
+
+        let mut state = self.state.borrow_mut();
+        if !state.is_live(context as u64, HandleKind::DeviceContext) {
+            return sys::OptixResult::OPTIX_ERROR_INVALID_DEVICE_CONTEXT;
+        }
+        if state
+            .resolve(output_buffer, output_buffer_size_in_bytes)
+            .is_none()
+            || state
+                .resolve(temp_buffer, temp_buffer_size_in_bytes)
+                .is_none()
+        {
+            return sys::OptixResult::OPTIX_ERROR_INVALID_VALUE;
+        }
+
+        // the mock never compacts anything, so the compacted size is the
+        // full output size
+        for (type_, result) in &emitted_properties {
+            if *type_
+                == sys::OptixAccelPropertyType_OPTIX_PROPERTY_TYPE_COMPACTED_SIZE
+            {
+                match state.resolve(*result, 8) {
+                    Some(bytes) => bytes.copy_from_slice(
+                        &(output_buffer_size_in_bytes as u64).to_le_bytes(),
+                    ),
+                    None => return sys::OptixResult::OPTIX_ERROR_INVALID_VALUE,
+                }
+            }
+        }
+
+        let handle = state.new_traversable();
+        *output_handle = handle;
+        state.accel_builds.push(AccelBuildRecord {
+            build_flags: (*accel_options).buildFlags,
+            operation: (*accel_options).operation,
+            build_inputs,
+            temp_buffer,
+            temp_buffer_size: temp_buffer_size_in_bytes,
+            output_buffer,
+            output_buffer_size: output_buffer_size_in_bytes,
+            emitted_properties,
+            handle,
+        });
+        sys::OptixResult::OPTIX_SUCCESS
+    }
+
+    unsafe fn optix_accel_compact(
+        &self,
+        context: sys::OptixDeviceContext,
+        _stream: CUstream,
+        input_handle: sys::OptixTraversableHandle,
+        output_buffer: CUdeviceptr,
+        output_buffer_size_in_bytes: usize,
+        output_handle: *mut sys::OptixTraversableHandle,
+    ) -> sys::OptixResult {
+        check_optix!(self, "optix_accel_compact");
+        let mut state = self.state.borrow_mut();
+        if !state.is_live(context as u64, HandleKind::DeviceContext) {
+            return sys::OptixResult::OPTIX_ERROR_INVALID_DEVICE_CONTEXT;
+        }
+        if !state.traversables.contains(&input_handle)
+            || state
+                .resolve(output_buffer, output_buffer_size_in_bytes)
+                .is_none()
+        {
+            return sys::OptixResult::OPTIX_ERROR_INVALID_VALUE;
+        }
+        let handle = state.new_traversable();
+        *output_handle = handle;
+        state.accel_compacts.push(AccelCompactRecord {
+            input_handle,
+            output_buffer,
+            output_buffer_size: output_buffer_size_in_bytes,
+            handle,
+        });
+        sys::OptixResult::OPTIX_SUCCESS
+    }
+
+    /// Packs the program group handle into the first 8 bytes of the header
+    /// so tests can check which group a record was packed for.
+    unsafe fn optix_sbt_record_pack_header(
+        &self,
+        program_group: sys::OptixProgramGroup,
+        sbt_record_header_host_pointer: *mut c_void,
+    ) -> sys::OptixResult {
+        check_optix!(self, "optix_sbt_record_pack_header");
+        if !self
+            .state
+            .borrow()
+            .is_live(program_group as u64, HandleKind::ProgramGroup)
+        {
+            return sys::OptixResult::OPTIX_ERROR_INVALID_VALUE;
+        }
+        let header = std::slice::from_raw_parts_mut(
+            sbt_record_header_host_pointer as *mut u8,
+            sys::OptixSbtRecordHeaderSize,
+        );
+        header.iter_mut().for_each(|b| *b = 0);
+        header[..8].copy_from_slice(&(program_group as u64).to_le_bytes());
+        sys::OptixResult::OPTIX_SUCCESS
+    }
+
+    unsafe fn optix_launch(
+        &self,
+        pipeline: sys::OptixPipeline,
+        stream: CUstream,
+        pipeline_params: CUdeviceptr,
+        pipeline_params_size: usize,
+        sbt: *const sys::OptixShaderBindingTable,
+        width: c_uint,
+        height: c_uint,
+        depth: c_uint,
+    ) -> sys::OptixResult {
+        check_optix!(self, "optix_launch");
+        let mut state = self.state.borrow_mut();
+        if !state.is_live(pipeline as u64, HandleKind::Pipeline) {
+            return sys::OptixResult::OPTIX_ERROR_INVALID_VALUE;
+        }
+        let params = match state.resolve(pipeline_params, pipeline_params_size)
+        {
+            Some(bytes) => bytes.to_vec(),
+            None => return sys::OptixResult::OPTIX_ERROR_INVALID_VALUE,
+        };
+        let sbt = &*sbt;
+        let device = state.current_device;
+        state.launches.push(LaunchRecord {
+            device,
+            pipeline,
+            stream,
+            params,
+            sbt: RecordedSbt {
+                raygen_record: sbt.raygenRecord,
+                exception_record: sbt.exceptionRecord,
+                miss_record_base: sbt.missRecordBase,
+                miss_record_stride_in_bytes: sbt.missRecordStrideInBytes,
+                miss_record_count: sbt.missRecordCount,
+                hitgroup_record_base: sbt.hitgroupRecordBase,
+                hitgroup_record_stride_in_bytes: sbt
+                    .hitgroupRecordStrideInBytes,
+                hitgroup_record_count: sbt.hitgroupRecordCount,
+                callables_record_base: sbt.callablesRecordBase,
+                callables_record_stride_in_bytes: sbt
+                    .callablesRecordStrideInBytes,
+                callables_record_count: sbt.callablesRecordCount,
+            },
+            width,
+            height,
+            depth,
+        });
+        sys::OptixResult::OPTIX_SUCCESS
+    }
+}
+
+/// Objects that many tests need.
+#[cfg(test)]
+pub(crate) mod fixtures {
+    use crate::cuda;
+    use crate::module::{ExceptionFlags, TraversableGraphFlags};
+    use crate::{
+        CompileDebugLevel, CompileOptimizationLevel, DeviceContext,
+        ModuleCompileOptions, ModuleRef, PipelineCompileOptions,
+    };
+
+    pub fn device_context() -> DeviceContext {
+        DeviceContext::create(cuda::Context::get_current().unwrap(), None)
+            .unwrap()
+    }
+
+    pub fn pipeline_compile_options() -> PipelineCompileOptions {
+        PipelineCompileOptions {
+            uses_motion_blur: false,
+            traversable_graph_flags: TraversableGraphFlags::ALLOW_ANY,
+            num_payload_values: 2,
+            num_attribute_values: 2,
+            exception_flags: ExceptionFlags::NONE,
+            pipeline_launch_params_variable_name: ustr::ustr("params"),
+            #[cfg(optix_7_3)]
+            uses_primitive_type_flags: crate::module::PrimitiveTypeFlags::empty(
+            ),
+        }
+    }
+
+    pub fn module_compile_options() -> ModuleCompileOptions {
+        ModuleCompileOptions {
+            max_register_count: 0,
+            opt_level: CompileOptimizationLevel::Level0,
+            debug_level: CompileDebugLevel::None,
+            #[cfg(optix_7_4)]
+            payload_types: Vec::new(),
+        }
+    }
+
+    pub fn module(ctx: &mut DeviceContext) -> ModuleRef {
+        ctx.module_create_from_ptx(
+            module_compile_options(),
+            &pipeline_compile_options(),
+            "",
+        )
+        .unwrap()
+        .0
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{HandleKind, MockBackend};
+    use crate::backend::{self, Backend};
+    use optix_sys as sys;
+    use optix_sys::cuda_sys::{cudaError, cudaMemcpyKind};
+
+    use std::os::raw::{c_char, c_uint, c_void};
+
+    #[test]
+    fn memory() {
+        let mock = MockBackend::new();
+        unsafe {
+            let mut a = std::ptr::null_mut();
+            let mut b = std::ptr::null_mut();
+            assert_eq!(mock.cuda_malloc(&mut a, 100), cudaError::cudaSuccess);
+            assert_eq!(mock.cuda_malloc(&mut b, 8), cudaError::cudaSuccess);
+            assert_eq!(a as u64 % 512, 0);
+            assert_eq!(b as u64 % 512, 0);
+            assert_eq!(mock.allocated_bytes(), 108);
+
+            // copy into the middle of a, then device to device into b
+            let data = [1u8, 2, 3, 4, 5, 6, 7, 8];
+            let res = mock.cuda_memcpy(
+                (a as u64 + 16) as *mut c_void,
+                data.as_ptr() as *const c_void,
+                8,
+                cudaMemcpyKind::cudaMemcpyHostToDevice,
+            );
+            assert_eq!(res, cudaError::cudaSuccess);
+            let res = mock.cuda_memcpy(
+                b,
+                (a as u64 + 16) as *const c_void,
+                8,
+                cudaMemcpyKind::cudaMemcpyDefault,
+            );
+            assert_eq!(res, cudaError::cudaSuccess);
+            assert_eq!(mock.read(b as u64, 8).unwrap(), data);
+
+            // reading off the end of an allocation is an error
+            let mut out = [0u8; 16];
+            let res = mock.cuda_memcpy(
+                out.as_mut_ptr() as *mut c_void,
+                b,
+                16,
+                cudaMemcpyKind::cudaMemcpyDeviceToHost,
+            );
+            assert_eq!(res, cudaError::cudaErrorInvalidValue);
+
+            assert_eq!(mock.cuda_free(a), cudaError::cudaSuccess);
+            assert_eq!(
+                mock.cuda_free(a),
+                cudaError::cudaErrorInvalidDevicePointer
+            );
+            assert_eq!(
+                mock.cuda_free(std::ptr::null_mut()),
+                cudaError::cudaSuccess
+            );
+            assert_eq!(mock.allocation_count(), 1);
+        }
+    }
+
+    #[test]
+    fn handles_and_failures() {
+        let mock = MockBackend::install();
+        unsafe {
+            let mut ctx = std::ptr::null_mut();
+            let res = backend::optix_device_context_create(
+                std::ptr::null_mut(),
+                std::ptr::null(),
+                &mut ctx,
+            );
+            assert_eq!(res, sys::OptixResult::OPTIX_SUCCESS);
+            assert_eq!(mock.live_handles(HandleKind::DeviceContext), 1);
+
+            let mut depth = 0u32;
+            mock.set_property(
+                sys::OptixDeviceProperty_OPTIX_DEVICE_PROPERTY_LIMIT_MAX_TRACE_DEPTH,
+                7,
+            );
+            let res = backend::optix_device_context_get_property(
+                ctx,
+                sys::OptixDeviceProperty_OPTIX_DEVICE_PROPERTY_LIMIT_MAX_TRACE_DEPTH,
+                &mut depth as *mut u32 as *mut c_void,
+                4,
+            );
+            assert_eq!(res, sys::OptixResult::OPTIX_SUCCESS);
+            assert_eq!(depth, 7);
+
+            mock.fail_optix(
+                "optix_device_context_destroy",
+                sys::OptixResult::OPTIX_ERROR_INTERNAL_ERROR,
+            );
+            assert_eq!(
+                backend::optix_device_context_destroy(ctx),
+                sys::OptixResult::OPTIX_ERROR_INTERNAL_ERROR
+            );
+            mock.clear_failures();
+            assert_eq!(
+                backend::optix_device_context_destroy(ctx),
+                sys::OptixResult::OPTIX_SUCCESS
+            );
+            assert_eq!(
+                backend::optix_device_context_destroy(ctx),
+                sys::OptixResult::OPTIX_ERROR_INVALID_DEVICE_CONTEXT
+            );
+            assert_eq!(mock.live_handles(HandleKind::DeviceContext), 0);
+        }
+    }
+
+    #[test]
+    fn log_callback() {
+        unsafe extern "C" fn callback(
+            level: c_uint,
+            _tag: *const c_char,
+            _message: *const c_char,
+            data: *mut c_void,
+        ) {
+            *(data as *mut u32) += level;
+        }
+
+        let mock = MockBackend::new();
+        let mut received = 0u32;
+        unsafe {
+            let mut ctx = std::ptr::null_mut();
+            mock.optix_device_context_create(
+                std::ptr::null_mut(),
+                std::ptr::null(),
+                &mut ctx,
+            );
+            mock.optix_device_context_set_log_callback(
+                ctx,
+                Some(callback),
+                &mut received as *mut u32 as *mut c_void,
+                2,
+            );
+        }
+        mock.log(2, "TAG", "printed");
+        mock.log(4, "TAG", "filtered");
+        assert_eq!(received, 2);
+    }
+}
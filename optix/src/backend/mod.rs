@@ -0,0 +1,288 @@
+//! The layer between the safe wrappers and the CUDA and OptiX C APIs.
+//!
+//! Every call the crate makes into the driver goes through the functions in
+//! this module rather than calling `optix_sys` directly. They forward to
+//! `NativeBackend`, unless a different `Backend` has been installed for the
+//! current thread with `set_thread_backend()` (available in tests and with
+//! the `mock` feature), which lets the host-side logic of the crate be
+//! exercised against `mock::MockBackend` without a GPU.
+//!
+//! `NativeBackend` calls CUDA through the functions `optix_sys::cuda_sys`
+//! loads from the installed libraries at runtime, so tests that only use the
+//! mock build and run without a CUDA toolkit either. Without one, calls that
+//! do reach `NativeBackend` return an error code.
+//!
+//! The methods mirror the C API exactly, including returning the raw result
+//! codes, so the error handling at each call site is the same whichever
+//! backend is in use.
+#![allow(clippy::missing_safety_doc, clippy::too_many_arguments)]
+
+use optix_sys as sys;
+use optix_sys::cuda_sys::{
+    self, cudaError_t, cudaMemcpyKind, cudaStream_t, CUcontext, CUdevice,
+    CUdeviceptr, CUresult, CUstream,
+};
+
+use std::os::raw::{c_char, c_int, c_uint, c_void};
+#[cfg(any(test, feature = "mock"))]
+use std::{cell::RefCell, rc::Rc};
+
+#[cfg(any(test, feature = "mock"))]
+pub mod mock;
+
+macro_rules! backend_api {
+    ($(
+        $(#[$meta:meta])*
+        fn $name:ident($($arg:ident: $ty:ty),* $(,)?) -> $ret:ty = $sys:path;
+    )*) => {
+        /// The set of driver entry points used by the crate.
+        ///
+        /// Each method has the same semantics as the C function named in
+        /// `NativeBackend`'s implementation of it.
+        pub trait Backend {
+            $(
+                $(#[$meta])*
+                unsafe fn $name(&self, $($arg: $ty),*) -> $ret;
+            )*
+        }
+
+        /// The backend that calls the real CUDA runtime and OptiX.
+        pub struct NativeBackend;
+
+        impl Backend for NativeBackend {
+            $(
+                $(#[$meta])*
+                unsafe fn $name(&self, $($arg: $ty),*) -> $ret {
+                    $sys($($arg),*)
+                }
+            )*
+        }
+
+        $(
+            $(#[$meta])*
+            pub(crate) unsafe fn $name($($arg: $ty),*) -> $ret {
+                #[cfg(any(test, feature = "mock"))]
+                if let Some(backend) = thread_backend() {
+                    return backend.$name($($arg),*);
+                }
+                NativeBackend.$name($($arg),*)
+            }
+        )*
+    };
+}
+
+backend_api! {
+    fn cu_init(flags: c_uint) -> CUresult = cuda_sys::cuInit;
+    fn cu_device_get(device: *mut CUdevice, ordinal: c_int) -> CUresult =
+        cuda_sys::cuDeviceGet;
+    fn cu_ctx_get_current(pctx: *mut CUcontext) -> CUresult =
+        cuda_sys::cuCtxGetCurrent;
+    fn cu_ctx_create(
+        pctx: *mut CUcontext,
+        flags: c_uint,
+        dev: CUdevice,
+    ) -> CUresult = cuda_sys::cuCtxCreate_v2;
+    fn cu_ctx_destroy(ctx: CUcontext) -> CUresult = cuda_sys::cuCtxDestroy_v2;
+    fn cu_ctx_push_current(ctx: CUcontext) -> CUresult =
+        cuda_sys::cuCtxPushCurrent_v2;
+    fn cu_ctx_pop_current(pctx: *mut CUcontext) -> CUresult =
+        cuda_sys::cuCtxPopCurrent_v2;
+    fn cu_device_primary_ctx_retain(
+        pctx: *mut CUcontext,
+        dev: CUdevice,
+    ) -> CUresult = cuda_sys::cuDevicePrimaryCtxRetain;
+    fn cu_device_primary_ctx_release(dev: CUdevice) -> CUresult =
+        cuda_sys::cuDevicePrimaryCtxRelease;
+    fn cuda_get_device_count(count: *mut c_int) -> cudaError_t =
+        cuda_sys::cudaGetDeviceCount;
+    fn cuda_set_device(device: c_int) -> cudaError_t = cuda_sys::cudaSetDevice;
+    fn cuda_malloc(dev_ptr: *mut *mut c_void, size: usize) -> cudaError_t =
+        cuda_sys::cudaMalloc;
+    fn cuda_free(dev_ptr: *mut c_void) -> cudaError_t = cuda_sys::cudaFree;
+    fn cuda_memcpy(
+        dst: *mut c_void,
+        src: *const c_void,
+        count: usize,
+        kind: cudaMemcpyKind::Type,
+    ) -> cudaError_t = cuda_sys::cudaMemcpy;
+    fn cuda_stream_create(stream: *mut cudaStream_t) -> cudaError_t =
+        cuda_sys::cudaStreamCreate;
+    fn cuda_stream_destroy(stream: cudaStream_t) -> cudaError_t =
+        cuda_sys::cudaStreamDestroy;
+
+    fn optix_device_context_create(
+        from_context: CUcontext,
+        options: *const sys::OptixDeviceContextOptions,
+        context: *mut sys::OptixDeviceContext,
+    ) -> sys::OptixResult = sys::optixDeviceContextCreate;
+    fn optix_device_context_destroy(
+        context: sys::OptixDeviceContext,
+    ) -> sys::OptixResult = sys::optixDeviceContextDestroy;
+    fn optix_device_context_get_property(
+        context: sys::OptixDeviceContext,
+        property: sys::OptixDeviceProperty,
+        value: *mut c_void,
+        size_in_bytes: usize,
+    ) -> sys::OptixResult = sys::optixDeviceContextGetProperty;
+    fn optix_device_context_set_log_callback(
+        context: sys::OptixDeviceContext,
+        callback_function: sys::OptixLogCallback,
+        callback_data: *mut c_void,
+        callback_level: c_uint,
+    ) -> sys::OptixResult = sys::optixDeviceContextSetLogCallback;
+    fn optix_device_context_set_cache_enabled(
+        context: sys::OptixDeviceContext,
+        enabled: c_int,
+    ) -> sys::OptixResult = sys::optixDeviceContextSetCacheEnabled;
+    fn optix_device_context_set_cache_location(
+        context: sys::OptixDeviceContext,
+        location: *const c_char,
+    ) -> sys::OptixResult = sys::optixDeviceContextSetCacheLocation;
+    fn optix_device_context_set_cache_database_sizes(
+        context: sys::OptixDeviceContext,
+        low_water_mark: usize,
+        high_water_mark: usize,
+    ) -> sys::OptixResult = sys::optixDeviceContextSetCacheDatabaseSizes;
+    fn optix_device_context_get_cache_enabled(
+        context: sys::OptixDeviceContext,
+        enabled: *mut c_int,
+    ) -> sys::OptixResult = sys::optixDeviceContextGetCacheEnabled;
+    fn optix_device_context_get_cache_location(
+        context: sys::OptixDeviceContext,
+        location: *mut c_char,
+        location_size: usize,
+    ) -> sys::OptixResult = sys::optixDeviceContextGetCacheLocation;
+    fn optix_device_context_get_cache_database_sizes(
+        context: sys::OptixDeviceContext,
+        low_water_mark: *mut usize,
+        high_water_mark: *mut usize,
+    ) -> sys::OptixResult = sys::optixDeviceContextGetCacheDatabaseSizes;
+
+    fn optix_module_create_from_ptx(
+        context: sys::OptixDeviceContext,
+        module_compile_options: *const sys::OptixModuleCompileOptions,
+        pipeline_compile_options: *const sys::OptixPipelineCompileOptions,
+        ptx: *const c_char,
+        ptx_size: usize,
+        log_string: *mut c_char,
+        log_string_size: *mut usize,
+        module: *mut sys::OptixModule,
+    ) -> sys::OptixResult = sys::optixModuleCreateFromPTX;
+    fn optix_module_destroy(module: sys::OptixModule) -> sys::OptixResult =
+        sys::optixModuleDestroy;
+    #[cfg(optix_7_3)]
+    fn optix_builtin_is_module_get(
+        context: sys::OptixDeviceContext,
+        module_compile_options: *const sys::OptixModuleCompileOptions,
+        pipeline_compile_options: *const sys::OptixPipelineCompileOptions,
+        builtin_is_options: *const sys::OptixBuiltinISOptions,
+        builtin_module: *mut sys::OptixModule,
+    ) -> sys::OptixResult = sys::optixBuiltinISModuleGet;
+
+    fn optix_program_group_create(
+        context: sys::OptixDeviceContext,
+        program_descriptions: *const sys::OptixProgramGroupDesc,
+        num_program_groups: c_uint,
+        options: *const sys::OptixProgramGroupOptions,
+        log_string: *mut c_char,
+        log_string_size: *mut usize,
+        program_groups: *mut sys::OptixProgramGroup,
+    ) -> sys::OptixResult = sys::optixProgramGroupCreate;
+    fn optix_program_group_destroy(
+        program_group: sys::OptixProgramGroup,
+    ) -> sys::OptixResult = sys::optixProgramGroupDestroy;
+    fn optix_program_group_get_stack_size(
+        program_group: sys::OptixProgramGroup,
+        stack_sizes: *mut sys::OptixStackSizes,
+    ) -> sys::OptixResult = sys::optixProgramGroupGetStackSize;
+
+    fn optix_pipeline_create(
+        context: sys::OptixDeviceContext,
+        pipeline_compile_options: *const sys::OptixPipelineCompileOptions,
+        pipeline_link_options: *const sys::OptixPipelineLinkOptions,
+        program_groups: *const sys::OptixProgramGroup,
+        num_program_groups: c_uint,
+        log_string: *mut c_char,
+        log_string_size: *mut usize,
+        pipeline: *mut sys::OptixPipeline,
+    ) -> sys::OptixResult = sys::optixPipelineCreate;
+    fn optix_pipeline_destroy(
+        pipeline: sys::OptixPipeline,
+    ) -> sys::OptixResult = sys::optixPipelineDestroy;
+    fn optix_pipeline_set_stack_size(
+        pipeline: sys::OptixPipeline,
+        direct_callable_stack_size_from_traversal: c_uint,
+        direct_callable_stack_size_from_state: c_uint,
+        continuation_stack_size: c_uint,
+        max_traversable_graph_depth: c_uint,
+    ) -> sys::OptixResult = sys::optixPipelineSetStackSize;
+
+    fn optix_accel_compute_memory_usage(
+        context: sys::OptixDeviceContext,
+        accel_options: *const sys::OptixAccelBuildOptions,
+        build_inputs: *const sys::OptixBuildInput,
+        num_build_inputs: c_uint,
+        buffer_sizes: *mut sys::OptixAccelBufferSizes,
+    ) -> sys::OptixResult = sys::optixAccelComputeMemoryUsage;
+    fn optix_accel_build(
+        context: sys::OptixDeviceContext,
+        stream: CUstream,
+        accel_options: *const sys::OptixAccelBuildOptions,
+        build_inputs: *const sys::OptixBuildInput,
+        num_build_inputs: c_uint,
+        temp_buffer: CUdeviceptr,
+        temp_buffer_size_in_bytes: usize,
+        output_buffer: CUdeviceptr,
+        output_buffer_size_in_bytes: usize,
+        output_handle: *mut sys::OptixTraversableHandle,
+        emitted_properties: *const sys::OptixAccelEmitDesc,
+        num_emitted_properties: c_uint,
+    ) -> sys::OptixResult = sys::optixAccelBuild;
+    fn optix_accel_compact(
+        context: sys::OptixDeviceContext,
+        stream: CUstream,
+        input_handle: sys::OptixTraversableHandle,
+        output_buffer: CUdeviceptr,
+        output_buffer_size_in_bytes: usize,
+        output_handle: *mut sys::OptixTraversableHandle,
+    ) -> sys::OptixResult = sys::optixAccelCompact;
+
+    fn optix_sbt_record_pack_header(
+        program_group: sys::OptixProgramGroup,
+        sbt_record_header_host_pointer: *mut c_void,
+    ) -> sys::OptixResult = sys::optixSbtRecordPackHeader;
+    fn optix_launch(
+        pipeline: sys::OptixPipeline,
+        stream: CUstream,
+        pipeline_params: CUdeviceptr,
+        pipeline_params_size: usize,
+        sbt: *const sys::OptixShaderBindingTable,
+        width: c_uint,
+        height: c_uint,
+        depth: c_uint,
+    ) -> sys::OptixResult = sys::optixLaunch;
+}
+
+#[cfg(any(test, feature = "mock"))]
+thread_local! {
+    static THREAD_BACKEND: RefCell<Option<Rc<dyn Backend>>> =
+        RefCell::new(None);
+}
+
+#[cfg(any(test, feature = "mock"))]
+fn thread_backend() -> Option<Rc<dyn Backend>> {
+    THREAD_BACKEND.with(|b| b.borrow().clone())
+}
+
+/// Route all driver calls made on the current thread to `backend`, or back
+/// to `NativeBackend` if `backend` is `None`. Returns the backend that was
+/// previously installed.
+///
+/// Objects created with one backend must not be used or dropped while
+/// another is installed.
+#[cfg(any(test, feature = "mock"))]
+pub fn set_thread_backend(
+    backend: Option<Rc<dyn Backend>>,
+) -> Option<Rc<dyn Backend>> {
+    THREAD_BACKEND.with(|b| std::mem::replace(&mut *b.borrow_mut(), backend))
+}
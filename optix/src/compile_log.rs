@@ -0,0 +1,326 @@
+//! The logs OptiX writes when creating modules, program groups and
+//! pipelines, and the diagnostics in them.
+//!
+//! Each line of a log starts with its severity, e.g.
+//!
+//! ```text
+//! Info: Properties for entry function "__raygen__renderFrame"
+//! Info:     semantic type: RAYGEN
+//! Info:     trace call(s): 1
+//! Warning: Entry function "__closesthit__radiance" with semantic type ...
+//! ```
+//!
+//! `CompileLog::diagnostics()` turns this into one `Diagnostic` per message,
+//! folding indented lines and lines without a severity into the message
+//! before them.
+use optix_sys as sys;
+
+use std::ffi::CStr;
+use std::os::raw::c_char;
+
+/// Size of the log buffer used for the first attempt at creating an object.
+const INITIAL_LOG_SIZE: usize = 4096;
+
+/// The log from creating a module, program group or pipeline.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct CompileLog {
+    text: String,
+    truncated: bool,
+}
+
+impl CompileLog {
+    /// Wrap log text captured elsewhere, e.g. to parse it.
+    pub fn new<S: Into<String>>(text: S) -> CompileLog {
+        CompileLog {
+            text: text.into(),
+            truncated: false,
+        }
+    }
+
+    pub fn as_str(&self) -> &str {
+        &self.text
+    }
+
+    /// Whether OptiX produced more log than could be captured, in which
+    /// case the end of the log is missing.
+    pub fn is_truncated(&self) -> bool {
+        self.truncated
+    }
+
+    /// The messages in the log, in order.
+    pub fn diagnostics(&self) -> Vec<Diagnostic> {
+        parse_diagnostics(&self.text)
+    }
+
+    /// The messages in the log with severity `Warning` or `Error`.
+    pub fn warnings(&self) -> Vec<Diagnostic> {
+        self.diagnostics()
+            .into_iter()
+            .filter(|d| d.severity >= Severity::Warning)
+            .collect()
+    }
+}
+
+impl std::ops::Deref for CompileLog {
+    type Target = str;
+    fn deref(&self) -> &str {
+        &self.text
+    }
+}
+
+impl std::fmt::Display for CompileLog {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.write_str(&self.text)?;
+        if self.truncated {
+            f.write_str("\n[log truncated]")?;
+        }
+        Ok(())
+    }
+}
+
+#[derive(Debug, Copy, Clone, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub enum Severity {
+    Info,
+    Warning,
+    Error,
+}
+
+/// One message from a `CompileLog`.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Diagnostic {
+    pub severity: Severity,
+    /// The entry function the message is about, if it names one
+    pub program: Option<String>,
+    pub message: String,
+}
+
+/// Split `text` into the messages it contains.
+pub fn parse_diagnostics(text: &str) -> Vec<Diagnostic> {
+    let mut diagnostics: Vec<Diagnostic> = Vec::new();
+    for line in text.lines() {
+        if line.trim().is_empty() {
+            continue;
+        }
+        match split_severity(line) {
+            Some((severity, message))
+                if !(message.starts_with("  ")
+                    && diagnostics.last().map(|d| d.severity)
+                        == Some(severity)) =>
+            {
+                let message = message.trim();
+                diagnostics.push(Diagnostic {
+                    severity,
+                    program: program_name(message),
+                    message: message.to_string(),
+                });
+            }
+            // an indented line with the same severity, or a line with no
+            // severity at all, continues the previous message
+            continued => {
+                let message =
+                    continued.map_or(line, |(_, message)| message).trim();
+                match diagnostics.last_mut() {
+                    Some(last) => {
+                        last.message.push('\n');
+                        last.message.push_str(message);
+                    }
+                    None => diagnostics.push(Diagnostic {
+                        severity: Severity::Info,
+                        program: program_name(message),
+                        message: message.to_string(),
+                    }),
+                }
+            }
+        }
+    }
+    diagnostics
+}
+
+/// Split the severity prefix off `line`, leaving the rest of the line
+/// (including any indentation after the prefix).
+fn split_severity(line: &str) -> Option<(Severity, &str)> {
+    const PREFIXES: &[(&str, Severity)] = &[
+        ("COMPILE ERROR:", Severity::Error),
+        ("Error:", Severity::Error),
+        ("Warning:", Severity::Warning),
+        ("Info:", Severity::Info),
+    ];
+    let line = line.trim_start();
+    PREFIXES.iter().find_map(|(prefix, severity)| {
+        line.strip_prefix(prefix)
+            .map(|rest| (*severity, rest.strip_prefix(' ').unwrap_or(rest)))
+    })
+}
+
+/// The quoted name following "entry function" (in any case) in `message`,
+/// if any.
+fn program_name(message: &str) -> Option<String> {
+    const PHRASE: &str = "entry function \"";
+    let start = message.to_ascii_lowercase().find(PHRASE)? + PHRASE.len();
+    let len = message[start..].find('"')?;
+    Some(message[start..start + len].to_string())
+}
+
+/// Call `create` with a log buffer to create an object, and capture the log.
+///
+/// If the log did not fit, the object is destroyed with `destroy` (if it was
+/// created) and `create` is called again with a buffer of the size OptiX
+/// asked for. Should the log still not fit, it is flagged as truncated.
+pub(crate) fn create_with_log<T, C, D>(
+    mut create: C,
+    destroy: D,
+) -> (sys::OptixResult, T, CompileLog)
+where
+    C: FnMut(*mut c_char, *mut usize) -> (sys::OptixResult, T),
+    D: FnOnce(T),
+{
+    let mut log = vec![0u8; INITIAL_LOG_SIZE];
+    let mut log_len = log.len();
+    let (mut res, mut object) =
+        create(log.as_mut_ptr() as *mut c_char, &mut log_len);
+
+    if log_len > log.len() {
+        if res == sys::OptixResult::OPTIX_SUCCESS {
+            destroy(object);
+        }
+        log = vec![0u8; log_len];
+        log_len = log.len();
+        let (r, o) = create(log.as_mut_ptr() as *mut c_char, &mut log_len);
+        res = r;
+        object = o;
+    }
+
+    let truncated = log_len > log.len();
+    let text = CStr::from_bytes_until_nul(&log[..log_len.min(log.len())])
+        .map(|s| s.to_string_lossy().into_owned())
+        .unwrap_or_default();
+
+    (res, object, CompileLog { text, truncated })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{parse_diagnostics, CompileLog, Diagnostic, Severity};
+    use crate::backend::mock::{fixtures, HandleKind, MockBackend};
+    use crate::error::Error;
+    use crate::{CompileDebugLevel, PipelineLinkOptions};
+    use optix_sys as sys;
+
+    // in the format OptiX 7 writes pipeline logs
+    const PIPELINE_LOG: &str = r#"Info: Pipeline parameter "params" size is 40 bytes
+Info: Pipeline statistics
+	module(s)                            :     1
+	entry function(s)                    :     4
+	trace call(s)                        :     1
+Info: Properties for entry function "__raygen__renderFrame"
+Info:     semantic type: RAYGEN
+Info:     trace call(s): 1
+Warning: Entry function "__closesthit__radiance" with semantic type CLOSESTHIT has 16 bytes of local memory (spill stores: 4 bytes, spill loads: 4 bytes)
+Info: Compiled programs with 64 registers
+"#;
+
+    #[test]
+    fn parse_pipeline_log() {
+        let diagnostics = parse_diagnostics(PIPELINE_LOG);
+        assert_eq!(diagnostics.len(), 5);
+
+        let statistics: Vec<&str> = diagnostics[1].message.lines().collect();
+        assert_eq!(statistics.len(), 4);
+        assert_eq!(statistics[0], "Pipeline statistics");
+        assert!(statistics[3].starts_with("trace call(s) "));
+        assert!(statistics[3].ends_with(":     1"));
+        assert_eq!(diagnostics[1].program, None);
+
+        assert_eq!(
+            diagnostics[2],
+            Diagnostic {
+                severity: Severity::Info,
+                program: Some("__raygen__renderFrame".into()),
+                message: "Properties for entry function \
+                          \"__raygen__renderFrame\"\nsemantic type: \
+                          RAYGEN\ntrace call(s): 1"
+                    .into(),
+            }
+        );
+
+        let warnings = CompileLog::new(PIPELINE_LOG).warnings();
+        assert_eq!(warnings.len(), 1);
+        assert_eq!(warnings[0].severity, Severity::Warning);
+        assert_eq!(
+            warnings[0].program.as_deref(),
+            Some("__closesthit__radiance")
+        );
+        assert!(warnings[0].message.contains("spill stores: 4 bytes"));
+    }
+
+    #[test]
+    fn parse_compile_error() {
+        let diagnostics = parse_diagnostics(
+            "COMPILE ERROR: Malformed input. See compile details for more \
+             information.\nError: Invalid PTX input: ptx2llvm-module-001: \
+             error: Failed to parse input PTX string\nptx2llvm-module-001, \
+             line 12; fatal   : Parsing error near 'foo': syntax error\n",
+        );
+        assert_eq!(diagnostics.len(), 2);
+        assert!(diagnostics.iter().all(|d| d.severity == Severity::Error));
+        assert!(diagnostics[1].message.ends_with("syntax error"));
+        assert!(parse_diagnostics("").is_empty());
+    }
+
+    #[test]
+    fn long_log_is_not_truncated() {
+        let mock = MockBackend::install();
+        let mut ctx = fixtures::device_context();
+        let log: String = (0..200)
+            .map(|i| format!("Info: Compiled entry function \"f{}\"\n", i))
+            .collect();
+        assert!(log.len() > super::INITIAL_LOG_SIZE);
+        mock.set_compile_log(&log);
+
+        let (_module, module_log) = ctx
+            .module_create_from_ptx(
+                fixtures::module_compile_options(),
+                &fixtures::pipeline_compile_options(),
+                "",
+            )
+            .unwrap();
+        assert_eq!(module_log.as_str(), log);
+        assert!(!module_log.is_truncated());
+        assert_eq!(module_log.diagnostics().len(), 200);
+        assert_eq!(
+            module_log.diagnostics()[199].program.as_deref(),
+            Some("f199")
+        );
+        // the module created with the short log buffer was destroyed
+        assert_eq!(mock.live_handles(HandleKind::Module), 1);
+    }
+
+    #[test]
+    fn failure_carries_log() {
+        let mock = MockBackend::install();
+        let mut ctx = fixtures::device_context();
+        mock.set_compile_log("Error: Unresolved external function \"foo\"\n");
+        mock.fail_optix(
+            "optix_pipeline_create",
+            sys::OptixResult::OPTIX_ERROR_PIPELINE_LINK_ERROR,
+        );
+        let link_options = PipelineLinkOptions {
+            max_trace_depth: 1,
+            debug_level: CompileDebugLevel::None,
+            #[cfg(not(optix_7_3))]
+            override_uses_motion_blur: false,
+        };
+        match ctx.pipeline_create(
+            &fixtures::pipeline_compile_options(),
+            link_options,
+            &[],
+        ) {
+            Err(Error::PipelineCreationFailed { log, .. }) => {
+                let diagnostics = log.diagnostics();
+                assert_eq!(diagnostics.len(), 1);
+                assert_eq!(diagnostics[0].severity, Severity::Error);
+            }
+            r => panic!("unexpected result {:?}", r.err()),
+        }
+    }
+}
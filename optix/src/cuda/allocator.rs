@@ -1,8 +1,11 @@
 use super::{CUdeviceptr, Error};
+use crate::backend;
 use bitfield::*;
 use optix_sys::cuda_sys as sys;
 use std::cell::{Cell, Ref, RefCell};
 use std::collections::HashMap;
+use std::sync::atomic::{AtomicUsize, Ordering};
+use std::sync::{Mutex, MutexGuard};
 
 type Result<T, E = Error> = std::result::Result<T, E>;
 
@@ -55,6 +58,38 @@ impl TaggedMallocator {
     }
 }
 
+/// A `TaggedMallocator` that can be shared between threads, for example to
+/// build acceleration structures on worker threads.
+pub struct SyncTaggedAllocator {
+    total_allocated: AtomicUsize,
+    allocs_by_tag: Mutex<HashMap<u64, usize>>,
+}
+
+impl SyncTaggedAllocator {
+    pub fn new() -> SyncTaggedAllocator {
+        SyncTaggedAllocator {
+            total_allocated: AtomicUsize::new(0),
+            allocs_by_tag: Mutex::new(HashMap::new()),
+        }
+    }
+
+    pub fn tag_allocations(&self) -> MutexGuard<'_, HashMap<u64, usize>> {
+        // the map is only ever updated with simple arithmetic so it is still
+        // consistent if another thread panicked while holding the lock
+        self.allocs_by_tag.lock().unwrap_or_else(|e| e.into_inner())
+    }
+
+    pub fn total_allocated(&self) -> usize {
+        self.total_allocated.load(Ordering::Relaxed)
+    }
+}
+
+impl Default for SyncTaggedAllocator {
+    fn default() -> SyncTaggedAllocator {
+        SyncTaggedAllocator::new()
+    }
+}
+
 pub struct Allocation {
     ptr: CUdeviceptr,
     tagged_size: TaggedSize,
@@ -105,7 +140,7 @@ impl Allocator for Mallocator {
         }
 
         let mut ptr = std::ptr::null_mut();
-        let res = sys::cudaMalloc(&mut ptr, size);
+        let res = backend::cuda_malloc(&mut ptr, size);
         if res != sys::cudaError::cudaSuccess || ptr.is_null() {
             Err(Error::AllocationFailed {
                 source: res.into(),
@@ -117,7 +152,7 @@ impl Allocator for Mallocator {
     }
 
     unsafe fn dealloc(&self, allocation: Allocation) -> Result<()> {
-        sys::cudaFree(allocation.ptr as *mut std::os::raw::c_void);
+        backend::cuda_free(allocation.ptr as *mut std::os::raw::c_void);
         Ok(())
     }
 }
@@ -140,7 +175,7 @@ impl Allocator for TaggedMallocator {
         }
 
         let mut ptr = std::ptr::null_mut();
-        let res = sys::cudaMalloc(&mut ptr, size);
+        let res = backend::cuda_malloc(&mut ptr, size);
         if res != sys::cudaError::cudaSuccess || ptr.is_null() {
             Err(Error::AllocationFailed {
                 source: res.into(),
@@ -157,22 +192,71 @@ impl Allocator for TaggedMallocator {
     }
 
     unsafe fn dealloc(&self, allocation: Allocation) -> Result<()> {
-        sys::cudaFree(allocation.ptr as *mut std::os::raw::c_void);
+        backend::cuda_free(allocation.ptr as *mut std::os::raw::c_void);
+        // must match what alloc() added
+        let aligned_size = allocation.size().max(512);
         self.total_allocated
-            .set(self.total_allocated.get() - allocation.size());
+            .set(self.total_allocated.get() - aligned_size);
         // *self
         //     .allocs_by_tag
         //     .borrow_mut()
         //     .get_mut(&allocation.tag())
         //     .unwrap() -= allocation.size();
         match self.allocs_by_tag.borrow_mut().get_mut(&allocation.tag()) {
-            Some(total) => *total -= allocation.size(),
+            Some(total) => *total -= aligned_size,
             None => (),
         }
         Ok(())
     }
 }
 
+impl Allocator for SyncTaggedAllocator {
+    unsafe fn alloc(
+        &self,
+        size: usize,
+        alignment: usize,
+        tag: u64,
+    ) -> Result<Allocation> {
+        let allocation = Mallocator::new().alloc(size, alignment, tag)?;
+        // cuda mallocs are always at least 512 bytes
+        let aligned_size = size.max(512);
+        self.total_allocated
+            .fetch_add(aligned_size, Ordering::Relaxed);
+        *self.tag_allocations().entry(tag).or_insert(0) += aligned_size;
+        Ok(allocation)
+    }
+
+    unsafe fn dealloc(&self, allocation: Allocation) -> Result<()> {
+        // must match what alloc() added
+        let aligned_size = allocation.size().max(512);
+        let tag = allocation.tag();
+        Mallocator::new().dealloc(allocation)?;
+        self.total_allocated
+            .fetch_sub(aligned_size, Ordering::Relaxed);
+        if let Some(total) = self.tag_allocations().get_mut(&tag) {
+            *total -= aligned_size;
+        }
+        Ok(())
+    }
+}
+
+impl TaggedAllocator for SyncTaggedAllocator {
+    fn total_allocated(&self) -> usize {
+        self.total_allocated.load(Ordering::Relaxed)
+    }
+
+    fn visit<F>(&self, mut closure: F)
+    where
+        F: FnMut(&HashMap<u64, usize>),
+    {
+        closure(&*self.tag_allocations());
+    }
+
+    fn clone_map(&self) -> HashMap<u64, usize> {
+        self.tag_allocations().clone()
+    }
+}
+
 impl TaggedAllocator for TaggedMallocator {
     fn total_allocated(&self) -> usize {
         self.total_allocated.get()
@@ -203,4 +287,47 @@ mod test {
         assert_eq!(sz.get_size(), 578);
         assert_eq!(sz.get_tag(), 1017);
     }
+
+    #[test]
+    fn test_tagged_accounting() {
+        use super::{Allocator, TaggedAllocator, TaggedMallocator};
+        use crate::backend::mock::MockBackend;
+
+        let mock = MockBackend::install();
+        let alloc = TaggedMallocator::new();
+        unsafe {
+            let a = alloc.alloc(16, 16, 1).unwrap();
+            let b = alloc.alloc(1024, 16, 2).unwrap();
+            assert_eq!(alloc.total_allocated(), 512 + 1024);
+            assert_eq!(mock.allocation_count(), 2);
+
+            alloc.dealloc(a).unwrap();
+            assert_eq!(alloc.total_allocated(), 1024);
+            assert_eq!(alloc.clone_map()[&1], 0);
+            alloc.dealloc(b).unwrap();
+            assert_eq!(alloc.total_allocated(), 0);
+        }
+        assert_eq!(mock.allocation_count(), 0);
+    }
+
+    #[test]
+    fn test_sync_tagged_accounting() {
+        use super::{Allocator, SyncTaggedAllocator, TaggedAllocator};
+        use crate::backend::mock::MockBackend;
+
+        let mock = MockBackend::install();
+        let alloc = SyncTaggedAllocator::new();
+        unsafe {
+            let a = alloc.alloc(16, 16, 1).unwrap();
+            let b = alloc.alloc(1024, 16, 2).unwrap();
+            assert_eq!(alloc.total_allocated(), 512 + 1024);
+            assert_eq!(alloc.clone_map()[&2], 1024);
+
+            alloc.dealloc(a).unwrap();
+            alloc.dealloc(b).unwrap();
+            assert_eq!(alloc.total_allocated(), 0);
+            assert_eq!(alloc.clone_map()[&1], 0);
+        }
+        assert_eq!(mock.allocation_count(), 0);
+    }
 }
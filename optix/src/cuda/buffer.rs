@@ -1,5 +1,6 @@
 use super::allocator::{Allocation, Allocator, Mallocator};
-use optix_sys::cuda_sys::{cudaError, cudaMemcpy, cudaMemcpyKind, CUdeviceptr};
+use crate::backend::cuda_memcpy;
+use optix_sys::cuda_sys::{cudaError, cudaMemcpyKind, CUdeviceptr};
 
 use std::os::raw::c_void;
 
@@ -92,7 +93,7 @@ where
                 unsafe { allocator.alloc(size_in_bytes, alignment, tag)? };
 
             let res = unsafe {
-                cudaMemcpy(
+                cuda_memcpy(
                     allocation.ptr() as *mut c_void,
                     data.as_ptr() as *const c_void,
                     size_in_bytes,
@@ -129,7 +130,7 @@ where
             });
         }
         unsafe {
-            let res = cudaMemcpy(
+            let res = cuda_memcpy(
                 self.allocation.ptr() as *mut c_void,
                 data.as_ptr() as *const c_void,
                 self.allocation.size(),
@@ -148,7 +149,7 @@ where
         data: *const c_void,
         size: usize,
     ) -> Result<()> {
-        let res = cudaMemcpy(
+        let res = cuda_memcpy(
             self.allocation.ptr() as *mut c_void,
             data as *const c_void,
             size,
@@ -170,7 +171,7 @@ where
             });
         }
         unsafe {
-            let res = cudaMemcpy(
+            let res = cuda_memcpy(
                 data.as_mut_ptr() as *mut c_void,
                 self.allocation.ptr() as *mut c_void,
                 self.allocation.size(),
@@ -198,7 +199,7 @@ where
 
         let mut data = T::default();
         unsafe {
-            let res = cudaMemcpy(
+            let res = cuda_memcpy(
                 &mut data as *mut T as *mut c_void,
                 self.allocation.ptr() as *mut c_void,
                 self.allocation.size(),
@@ -241,3 +242,48 @@ where
         }
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::{Buffer, Error, Mallocator};
+    use crate::backend::mock::MockBackend;
+    use optix_sys::cuda_sys::cudaError;
+
+    #[test]
+    fn upload_download() {
+        let mock = MockBackend::install();
+        let alloc = Mallocator::new();
+
+        let mut buffer =
+            Buffer::with_data(&[1u32, 2, 3, 4], 16, 0, &alloc).unwrap();
+        let bytes = mock.read(buffer.as_device_ptr(), 16).unwrap();
+        assert_eq!(&bytes[..4], &1u32.to_ne_bytes());
+
+        buffer.upload(&[5u32, 6, 7, 8]).unwrap();
+        let mut data = [0u32; 4];
+        buffer.download(&mut data).unwrap();
+        assert_eq!(data, [5, 6, 7, 8]);
+
+        match buffer.upload(&[1u32]) {
+            Err(Error::BufferUploadWrongSize {
+                upload_size: 4,
+                buffer_size: 16,
+            }) => (),
+            _ => panic!("expected BufferUploadWrongSize"),
+        }
+
+        drop(buffer);
+        assert_eq!(mock.allocation_count(), 0);
+    }
+
+    #[test]
+    fn upload_failure() {
+        let mock = MockBackend::install();
+        let alloc = Mallocator::new();
+        mock.fail_cuda("cuda_memcpy", cudaError::cudaErrorInvalidValue);
+        match Buffer::with_data(&[1u32], 4, 0, &alloc) {
+            Err(Error::BufferUploadFailed { .. }) => (),
+            _ => panic!("expected BufferUploadFailed"),
+        };
+    }
+}
@@ -1,39 +1,316 @@
+use crate::backend;
 use optix_sys::cuda_sys as sys;
 
 use super::error::Error;
 type Result<T, E = Error> = std::result::Result<T, E>;
 
-#[derive(Copy, Clone)]
-pub struct ContextRef {
+use std::marker::PhantomData;
+use std::sync::Arc;
+
+bitflags! {
+    /// Flags for `Context::create`, as for `cuCtxCreate`.
+    pub struct ContextFlags: u32 {
+        const SCHED_AUTO = 0x00;
+        const SCHED_SPIN = 0x01;
+        const SCHED_YIELD = 0x02;
+        const SCHED_BLOCKING_SYNC = 0x04;
+        const MAP_HOST = 0x08;
+        const LMEM_RESIZE_TO_MAX = 0x10;
+    }
+}
+
+/// Who is responsible for a CUDA context, and so what happens to it when the
+/// last `ContextRef` to it is dropped.
+enum Ownership {
+    /// Created by us with cuCtxCreate, destroyed with cuCtxDestroy
+    Created,
+    /// The device's primary context, retained by us and released with
+    /// cuDevicePrimaryCtxRelease
+    Primary { device: sys::CUdevice },
+    /// Made by someone else (e.g. the runtime API), left alone
+    Borrowed,
+}
+
+/// A CUDA context. Only ever handled through a `ContextRef`.
+pub struct Context {
     ctx: sys::CUcontext,
+    ownership: Ownership,
+}
+
+// A CUDA context can be made current on, and used from, any thread.
+unsafe impl Send for Context {}
+unsafe impl Sync for Context {}
+
+impl Drop for Context {
+    fn drop(&mut self) {
+        // errors can't be reported from here, and the context is unusable
+        // afterwards either way
+        unsafe {
+            match self.ownership {
+                Ownership::Created => {
+                    backend::cu_ctx_destroy(self.ctx);
+                }
+                Ownership::Primary { device } => {
+                    backend::cu_device_primary_ctx_release(device);
+                }
+                Ownership::Borrowed => (),
+            }
+        }
+    }
+}
+
+/// A shared reference to a CUDA context.
+///
+/// A context that was created or retained through this type is destroyed or
+/// released when the last reference to it is dropped. Anything that is
+/// created from a context, such as a `DeviceContext`, holds a reference to
+/// it so that it cannot outlive it.
+#[derive(Clone)]
+pub struct ContextRef {
+    inner: Arc<Context>,
 }
 
 impl ContextRef {
+    fn new(ctx: sys::CUcontext, ownership: Ownership) -> ContextRef {
+        ContextRef {
+            inner: Arc::new(Context { ctx, ownership }),
+        }
+    }
+
     pub fn ctx(&self) -> sys::CUcontext {
-        self.ctx
+        self.inner.ctx
+    }
+
+    /// Push the context onto the calling thread's context stack, making it
+    /// current until the returned guard is dropped.
+    pub fn push(&self) -> Result<ContextGuard<'_>> {
+        let res = unsafe { backend::cu_ctx_push_current(self.inner.ctx) };
+        if res != sys::cudaError_enum::CUDA_SUCCESS {
+            return Err(Error::ContextPushFailed { source: res.into() });
+        }
+        Ok(ContextGuard {
+            ctx: self,
+            _not_send: PhantomData,
+        })
     }
 }
 
 impl std::ops::Deref for ContextRef {
     type Target = sys::CUcontext;
     fn deref(&self) -> &sys::CUcontext {
-        &self.ctx
+        &self.inner.ctx
     }
 }
 
-pub struct Context {}
+/// Pops the context it was created from off the thread's context stack when
+/// dropped, restoring the context that was current before it was pushed.
+pub struct ContextGuard<'a> {
+    ctx: &'a ContextRef,
+    // the context stack belongs to the thread that pushed onto it
+    _not_send: PhantomData<*const ()>,
+}
+
+impl<'a> Drop for ContextGuard<'a> {
+    fn drop(&mut self) {
+        // errors can't be returned from here, but failing to pop, or popping
+        // anything other than our context, means the thread's context stack
+        // was changed underneath the guard
+        let mut popped = std::ptr::null_mut();
+        let res = unsafe { backend::cu_ctx_pop_current(&mut popped) };
+        if res != sys::cudaError_enum::CUDA_SUCCESS {
+            log::error!(
+                "Failed to pop context {:?}: {}",
+                self.ctx.ctx(),
+                sys::DriverError::from(res)
+            );
+        } else if popped != self.ctx.ctx() {
+            log::error!(
+                "Popped context {:?} instead of the guarded context {:?}",
+                popped,
+                self.ctx.ctx()
+            );
+        }
+        debug_assert!(
+            std::thread::panicking()
+                || (res == sys::cudaError_enum::CUDA_SUCCESS
+                    && popped == self.ctx.ctx()),
+            "ContextGuard did not pop its own context"
+        );
+    }
+}
 
 impl Context {
+    /// Get the context that is current on the calling thread.
+    ///
+    /// The context is not owned by the returned reference, so whoever made
+    /// it (usually the CUDA runtime, after `cuda::init()`) must keep it alive
+    /// for as long as anything created from it is in use.
     pub fn get_current() -> Result<ContextRef> {
         unsafe {
             let mut ctx = std::ptr::null_mut();
-            let res = sys::cuCtxGetCurrent(&mut ctx);
-            if res != sys::cudaError::cudaSuccess {
+            let res = backend::cu_ctx_get_current(&mut ctx);
+            if res != sys::cudaError_enum::CUDA_SUCCESS {
                 return Err(Error::CouldNotGetCurrentContext {
                     source: res.into(),
                 });
             }
-            Ok(ContextRef { ctx })
+            Ok(ContextRef::new(ctx, Ownership::Borrowed))
         }
     }
+
+    /// Create a new context on device `ordinal` that is destroyed when the
+    /// last reference to it is dropped.
+    ///
+    /// Unlike `cuCtxCreate`, this leaves the calling thread's current
+    /// context unchanged. Use `ContextRef::push()` to make it current.
+    pub fn create(ordinal: i32, flags: ContextFlags) -> Result<ContextRef> {
+        let device = get_device(ordinal)?;
+        unsafe {
+            let mut ctx = std::ptr::null_mut();
+            let res = backend::cu_ctx_create(&mut ctx, flags.bits(), device);
+            if res != sys::cudaError_enum::CUDA_SUCCESS {
+                return Err(Error::ContextCreationFailed {
+                    source: res.into(),
+                    device: ordinal,
+                });
+            }
+            // own the context before anything else can fail so that it is
+            // destroyed if it does
+            let ctx = ContextRef::new(ctx, Ownership::Created);
+            let mut popped = std::ptr::null_mut();
+            let res = backend::cu_ctx_pop_current(&mut popped);
+            if res != sys::cudaError_enum::CUDA_SUCCESS {
+                return Err(Error::ContextPopFailed { source: res.into() });
+            }
+            Ok(ctx)
+        }
+    }
+
+    /// Retain the primary context of device `ordinal`, the context the
+    /// runtime API uses for it, which is released when the last reference
+    /// to it is dropped.
+    pub fn retain_primary(ordinal: i32) -> Result<ContextRef> {
+        let device = get_device(ordinal)?;
+        unsafe {
+            let mut ctx = std::ptr::null_mut();
+            let res = backend::cu_device_primary_ctx_retain(&mut ctx, device);
+            if res != sys::cudaError_enum::CUDA_SUCCESS {
+                return Err(Error::PrimaryContextRetainFailed {
+                    source: res.into(),
+                    device: ordinal,
+                });
+            }
+            Ok(ContextRef::new(ctx, Ownership::Primary { device }))
+        }
+    }
+}
+
+/// Initialize the driver API and get the handle of device `ordinal`.
+fn get_device(ordinal: i32) -> Result<sys::CUdevice> {
+    unsafe {
+        let res = backend::cu_init(0);
+        if res != sys::cudaError_enum::CUDA_SUCCESS {
+            return Err(Error::DriverInitFailed { source: res.into() });
+        }
+        let mut device = 0;
+        let res = backend::cu_device_get(&mut device, ordinal);
+        if res != sys::cudaError_enum::CUDA_SUCCESS {
+            return Err(Error::CouldNotGetDevice {
+                source: res.into(),
+                device: ordinal,
+            });
+        }
+        Ok(device)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{Context, ContextFlags};
+    use crate::backend::mock::{HandleKind, MockBackend, MOCK_CUDA_CONTEXT};
+    use crate::cuda::Error;
+    use crate::DeviceContext;
+    use optix_sys::cuda_sys::{cudaError_enum, DriverError};
+
+    #[test]
+    fn create_and_push() {
+        let mock = MockBackend::install();
+        mock.set_device_count(2);
+        let ctx = Context::create(1, ContextFlags::SCHED_AUTO).unwrap();
+        assert_eq!(mock.live_handles(HandleKind::CudaContext), 1);
+        // creating a context doesn't change the current one
+        assert!(mock.context_stack().is_empty());
+        assert_eq!(Context::get_current().unwrap().ctx(), MOCK_CUDA_CONTEXT);
+
+        {
+            let _guard = ctx.push().unwrap();
+            assert_eq!(mock.context_stack(), vec![ctx.ctx()]);
+            assert_eq!(Context::get_current().unwrap().ctx(), ctx.ctx());
+        }
+        assert!(mock.context_stack().is_empty());
+
+        let other = ctx.clone();
+        drop(ctx);
+        assert_eq!(mock.live_handles(HandleKind::CudaContext), 1);
+        drop(other);
+        assert_eq!(mock.live_handles(HandleKind::CudaContext), 0);
+
+        match Context::create(2, ContextFlags::SCHED_AUTO) {
+            Err(Error::CouldNotGetDevice { device: 2, .. }) => (),
+            r => panic!("unexpected result {:?}", r.err()),
+        }
+    }
+
+    #[test]
+    fn driver_errors() {
+        let mock = MockBackend::install();
+        mock.fail_cuda(
+            "cu_ctx_create",
+            cudaError_enum::CUDA_ERROR_OUT_OF_MEMORY,
+        );
+        match Context::create(0, ContextFlags::SCHED_AUTO) {
+            Err(Error::ContextCreationFailed { source, device: 0 }) => {
+                assert!(matches!(source, DriverError::OutOfMemory));
+                assert_eq!(source.name(), "CUDA_ERROR_OUT_OF_MEMORY");
+            }
+            r => panic!("unexpected result {:?}", r.err()),
+        }
+    }
+
+    #[test]
+    #[should_panic(expected = "ContextGuard did not pop its own context")]
+    fn guards_dropped_out_of_order() {
+        let _mock = MockBackend::install();
+        let a = Context::create(0, ContextFlags::SCHED_AUTO).unwrap();
+        let b = Context::create(0, ContextFlags::SCHED_AUTO).unwrap();
+        let guard_a = a.push().unwrap();
+        let _guard_b = b.push().unwrap();
+        // pops b
+        drop(guard_a);
+    }
+
+    #[test]
+    fn retain_primary() {
+        let mock = MockBackend::install();
+        let a = Context::retain_primary(0).unwrap();
+        let b = Context::retain_primary(0).unwrap();
+        assert_eq!(a.ctx(), b.ctx());
+        assert_eq!(mock.primary_context_retains(0), 2);
+        drop(a);
+        assert_eq!(mock.primary_context_retains(0), 1);
+        drop(b);
+        assert_eq!(mock.primary_context_retains(0), 0);
+    }
+
+    #[test]
+    fn device_context_keeps_cuda_context_alive() {
+        let mock = MockBackend::install();
+        let ctx = Context::create(0, ContextFlags::SCHED_AUTO).unwrap();
+        let device_context = DeviceContext::create(ctx.clone(), None).unwrap();
+        assert_eq!(device_context.cuda_context().ctx(), ctx.ctx());
+        drop(ctx);
+        assert_eq!(mock.live_handles(HandleKind::CudaContext), 1);
+        drop(device_context);
+        assert_eq!(mock.live_handles(HandleKind::CudaContext), 0);
+    }
 }
@@ -0,0 +1,399 @@
+//! The messages in the logs of NVRTC and nvcc, with the location they refer
+//! to.
+//!
+//! The CUDA front end writes messages as
+//!
+//! ```text
+//! devicePrograms.cu(57): warning #177-D: variable "u" was declared but never referenced
+//!       const float u = 0.0f;
+//!                   ^
+//! ```
+//!
+//! where newer versions follow the message with the source line and a caret
+//! under the column. nvcc logs can also contain messages from the host
+//! compiler (`kernel.cu:23:5: error: ...`), ptxas and nvcc itself, which are
+//! parsed as well. `Diagnostic` renders in the style of rustc.
+use crate::compile_log::Severity;
+
+use std::fmt;
+
+/// How far the front end indents the source line it quotes after a message.
+const SNIPPET_INDENT: usize = 2;
+
+/// One message from a compiler log.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Diagnostic {
+    /// The file as named in the log, which for NVRTC is the program name or
+    /// the name of a header
+    pub file: Option<String>,
+    /// 1-based line number
+    pub line: Option<u32>,
+    /// 1-based column number
+    pub column: Option<u32>,
+    pub severity: Severity,
+    /// The number of the message, e.g. `177-D`, if the log gives one
+    pub code: Option<String>,
+    pub message: String,
+    /// The source line the message refers to
+    pub snippet: Option<String>,
+}
+
+impl Diagnostic {
+    fn new(severity: Severity, message: &str) -> Diagnostic {
+        Diagnostic {
+            file: None,
+            line: None,
+            column: None,
+            severity,
+            code: None,
+            message: message.trim().to_string(),
+            snippet: None,
+        }
+    }
+
+    /// Take the snippet from `source`, which is the contents of `file`, if
+    /// this diagnostic refers to a line in it.
+    pub fn add_snippet(&mut self, file: &str, source: &str) {
+        if self.file.as_deref() != Some(file) {
+            return;
+        }
+        let index = self.line.and_then(|l| (l as usize).checked_sub(1));
+        if let Some(text) = index.and_then(|i| source.lines().nth(i)) {
+            self.snippet = Some(text.to_string());
+        }
+    }
+}
+
+/// Parse the messages from an NVRTC or nvcc log. Lines that aren't part of a
+/// message, such as the summary at the end, are skipped.
+pub fn parse_log(log: &str) -> Vec<Diagnostic> {
+    let mut diagnostics: Vec<Diagnostic> = Vec::new();
+    // whether the lines following a message still belong to it
+    let mut in_message = false;
+    // the last line following a message, which is the snippet if the next
+    // line is a caret
+    let mut pending: Option<&str> = None;
+
+    for line in log.lines() {
+        if let Some(diagnostic) = parse_line(line) {
+            flush(&mut diagnostics, &mut pending);
+            diagnostics.push(diagnostic);
+            in_message = true;
+        } else if line.trim().is_empty() {
+            flush(&mut diagnostics, &mut pending);
+            in_message = false;
+        } else if !in_message {
+            continue;
+        } else if let (Some(caret), Some(snippet)) =
+            (caret_column(line), pending.take())
+        {
+            let last = diagnostics.last_mut().unwrap();
+            let indent = snippet.len() - snippet.trim_start().len();
+            let indent = indent.min(SNIPPET_INDENT);
+            last.snippet = Some(snippet[indent..].to_string());
+            last.column = Some((caret.saturating_sub(indent) + 1) as u32);
+        } else {
+            flush(&mut diagnostics, &mut pending);
+            pending = Some(line);
+        }
+    }
+    flush(&mut diagnostics, &mut pending);
+    diagnostics
+}
+
+/// Add a line that turned out not to be a snippet to the message before it.
+fn flush(diagnostics: &mut [Diagnostic], pending: &mut Option<&str>) {
+    if let (Some(line), Some(last)) = (pending.take(), diagnostics.last_mut()) {
+        last.message.push('\n');
+        last.message.push_str(line.trim());
+    }
+}
+
+/// The 0-based position of the caret in a line containing only a caret.
+fn caret_column(line: &str) -> Option<usize> {
+    let trimmed = line.trim_start();
+    if trimmed.trim_end() == "^" {
+        Some(line.len() - trimmed.len())
+    } else {
+        None
+    }
+}
+
+fn parse_severity(s: &str) -> Option<Severity> {
+    match s.trim() {
+        "error" | "catastrophic error" | "internal error" | "fatal"
+        | "fatal error" => Some(Severity::Error),
+        "warning" => Some(Severity::Warning),
+        "remark" | "note" | "info" => Some(Severity::Info),
+        _ => None,
+    }
+}
+
+fn parse_line(line: &str) -> Option<Diagnostic> {
+    parse_ptxas(line)
+        .or_else(|| parse_front_end(line))
+        .or_else(|| parse_host_compiler(line))
+        .or_else(|| parse_tool(line))
+}
+
+/// `file(line): severity[ #code]: message`
+fn parse_front_end(line: &str) -> Option<Diagnostic> {
+    let (location, rest) = line.split_once("): ")?;
+    let (file, line_number) = location.rsplit_once('(')?;
+    let line_number = line_number.parse().ok()?;
+    let (severity, message) = rest.split_once(": ")?;
+    let (severity, code) = match severity.split_once(" #") {
+        Some((severity, code)) => (severity, Some(code.to_string())),
+        None => (severity, None),
+    };
+    Some(Diagnostic {
+        file: Some(file.to_string()),
+        line: Some(line_number),
+        code,
+        ..Diagnostic::new(parse_severity(severity)?, message)
+    })
+}
+
+/// `file:line:column: severity: message`, as written by gcc and clang
+fn parse_host_compiler(line: &str) -> Option<Diagnostic> {
+    let mut fields = line.splitn(2, ": ");
+    let location = fields.next()?;
+    let (severity, message) = fields.next()?.split_once(": ")?;
+    let severity = parse_severity(severity)?;
+    // the file name may itself contain a colon, as in C:\
+    let mut location = location.rsplitn(3, ':');
+    let column = location.next()?.parse().ok()?;
+    let line_number = location.next()?.parse().ok()?;
+    let file = location.next()?;
+    Some(Diagnostic {
+        file: Some(file.to_string()),
+        line: Some(line_number),
+        column: Some(column),
+        ..Diagnostic::new(severity, message)
+    })
+}
+
+/// `ptxas file, line N; severity : message`
+fn parse_ptxas(line: &str) -> Option<Diagnostic> {
+    let rest = line.strip_prefix("ptxas ")?;
+    let (location, rest) = rest.split_once("; ")?;
+    let (file, line_number) = location.rsplit_once(", line ")?;
+    let line_number = line_number.trim().parse().ok()?;
+    let (severity, message) = rest.split_once(':')?;
+    Some(Diagnostic {
+        file: Some(file.to_string()),
+        line: Some(line_number),
+        ..Diagnostic::new(parse_severity(severity)?, message)
+    })
+}
+
+/// `tool severity : message`, as written by nvcc and ptxas about things
+/// other than a location
+fn parse_tool(line: &str) -> Option<Diagnostic> {
+    let (tool, rest) = line.split_once(' ')?;
+    if !(tool == "nvcc" || tool == "ptxas" || tool == "nvlink") {
+        return None;
+    }
+    let (severity, message) = rest.split_once(':')?;
+    Some(Diagnostic::new(parse_severity(severity)?, message))
+}
+
+impl fmt::Display for Diagnostic {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        let severity = match self.severity {
+            Severity::Error => "error",
+            Severity::Warning => "warning",
+            Severity::Info => "note",
+        };
+        let mut lines = self.message.lines();
+        write!(f, "{}", severity)?;
+        if let Some(code) = &self.code {
+            write!(f, "[{}]", code)?;
+        }
+        writeln!(f, ": {}", lines.next().unwrap_or_default())?;
+
+        let line_number = self.line.map(|l| l.to_string()).unwrap_or_default();
+        let gutter = " ".repeat(line_number.len());
+        if let Some(file) = &self.file {
+            write!(f, "{}--> {}", gutter, file)?;
+            if let Some(line) = self.line {
+                write!(f, ":{}", line)?;
+                if let Some(column) = self.column {
+                    write!(f, ":{}", column)?;
+                }
+            }
+            writeln!(f)?;
+        }
+        if let (Some(snippet), Some(_)) = (&self.snippet, self.line) {
+            writeln!(f, "{} |", gutter)?;
+            writeln!(f, "{} | {}", line_number, snippet)?;
+            if let Some(column) = self.column {
+                let indent = " ".repeat(column.max(1) as usize - 1);
+                writeln!(f, "{} | {}^", gutter, indent)?;
+            }
+        }
+        for line in lines {
+            writeln!(f, "{} = {}", gutter, line)?;
+        }
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{parse_log, Diagnostic};
+    use crate::compile_log::Severity;
+
+    // as written by NVRTC in CUDA 11
+    const NVRTC_LOG: &str = r#"devicePrograms.cu(42): error: identifier "prd" is undefined
+
+launch_params.h(12): error: expected a ";"
+
+devicePrograms.cu(57): warning: variable "u" was declared but never referenced
+
+2 errors detected in the compilation of "devicePrograms.cu".
+"#;
+
+    // as written by NVRTC in CUDA 12, which quotes the source
+    const NVRTC_12_LOG: &str = r#"devicePrograms.cu(42): error: identifier "prd" is undefined
+      prd.color = make_float3(1.0f);
+      ^
+
+devicePrograms.cu(57): warning #177-D: variable "u" was declared but never referenced
+      const float u = 0.0f;
+                  ^
+
+Remark: The warnings can be suppressed with "-diag-suppress <warning-number>"
+
+1 error detected in the compilation of "devicePrograms.cu".
+"#;
+
+    // as written by nvcc, with messages from the host compiler and ptxas
+    const NVCC_LOG: &str = r#"kernel.cu(10): error: no instance of overloaded function "atomicAdd" matches the argument list
+            argument types are: (double *, double)
+
+kernel.cu:23:5: error: 'foo' was not declared in this scope
+ptxas /tmp/tmpxft_00001234_00000000-6_kernel.ptx, line 55; error   : Call has wrong number of parameters
+ptxas fatal   : Ptx assembly aborted due to errors
+nvcc fatal   : Unsupported gpu architecture 'compute_10'
+"#;
+
+    fn location(d: &Diagnostic) -> (Option<&str>, Option<u32>, Option<u32>) {
+        (d.file.as_deref(), d.line, d.column)
+    }
+
+    #[test]
+    fn parse_nvrtc() {
+        let diagnostics = parse_log(NVRTC_LOG);
+        assert_eq!(diagnostics.len(), 3);
+        assert_eq!(
+            diagnostics[0],
+            Diagnostic {
+                file: Some("devicePrograms.cu".into()),
+                line: Some(42),
+                column: None,
+                severity: Severity::Error,
+                code: None,
+                message: "identifier \"prd\" is undefined".into(),
+                snippet: None,
+            }
+        );
+        assert_eq!(
+            location(&diagnostics[1]),
+            (Some("launch_params.h"), Some(12), None)
+        );
+        assert_eq!(diagnostics[2].severity, Severity::Warning);
+    }
+
+    #[test]
+    fn parse_nvrtc_with_snippets() {
+        let diagnostics = parse_log(NVRTC_12_LOG);
+        assert_eq!(diagnostics.len(), 2);
+        assert_eq!(
+            location(&diagnostics[0]),
+            (Some("devicePrograms.cu"), Some(42), Some(5))
+        );
+        assert_eq!(
+            diagnostics[0].snippet.as_deref(),
+            Some("    prd.color = make_float3(1.0f);")
+        );
+        assert_eq!(diagnostics[0].message, "identifier \"prd\" is undefined");
+
+        assert_eq!(diagnostics[1].code.as_deref(), Some("177-D"));
+        assert_eq!(diagnostics[1].column, Some(17));
+        assert_eq!(
+            diagnostics[1].message,
+            "variable \"u\" was declared but never referenced"
+        );
+    }
+
+    #[test]
+    fn parse_nvcc() {
+        let diagnostics = parse_log(NVCC_LOG);
+        let locations: Vec<_> = diagnostics.iter().map(location).collect();
+        assert_eq!(
+            locations,
+            [
+                (Some("kernel.cu"), Some(10), None),
+                (Some("kernel.cu"), Some(23), Some(5)),
+                (
+                    Some("/tmp/tmpxft_00001234_00000000-6_kernel.ptx"),
+                    Some(55),
+                    None
+                ),
+                (None, None, None),
+                (None, None, None),
+            ]
+        );
+        assert!(diagnostics.iter().all(|d| d.severity == Severity::Error));
+        assert_eq!(
+            diagnostics[0].message.lines().nth(1),
+            Some("argument types are: (double *, double)")
+        );
+        assert_eq!(
+            diagnostics[2].message,
+            "Call has wrong number of parameters"
+        );
+        assert_eq!(
+            diagnostics[4].message,
+            "Unsupported gpu architecture 'compute_10'"
+        );
+        assert!(parse_log("").is_empty());
+    }
+
+    #[test]
+    fn render() {
+        let diagnostics = parse_log(NVRTC_12_LOG);
+        assert_eq!(
+            diagnostics[1].to_string(),
+            r#"warning[177-D]: variable "u" was declared but never referenced
+  --> devicePrograms.cu:57:17
+   |
+57 |     const float u = 0.0f;
+   |                 ^
+"#
+        );
+
+        // the snippet is taken from the source if the log doesn't have it
+        let mut diagnostic = parse_log(NVCC_LOG).remove(0);
+        let source = "\n".repeat(9) + "    atomicAdd(out, x);\n";
+        diagnostic.add_snippet("other.cu", &source);
+        assert_eq!(diagnostic.snippet, None);
+        diagnostic.add_snippet("kernel.cu", &source);
+        assert_eq!(
+            diagnostic.to_string(),
+            r#"error: no instance of overloaded function "atomicAdd" matches the argument list
+  --> kernel.cu:10
+   |
+10 |     atomicAdd(out, x);
+   = argument types are: (double *, double)
+"#
+        );
+
+        let diagnostic = parse_log(NVCC_LOG).pop().unwrap();
+        assert_eq!(
+            diagnostic.to_string(),
+            "error: Unsupported gpu architecture 'compute_10'\n"
+        );
+    }
+}
@@ -1,3 +1,4 @@
+use optix_sys::cuda_sys::DriverError;
 use optix_sys::cuda_sys::Error as CudaError;
 
 #[derive(Debug, thiserror::Error)]
@@ -48,7 +49,19 @@ pub enum Error {
     #[error("Could not get device {device:} properties")]
     CouldNotGetDeviceProperties { source: CudaError, device: i32 },
     #[error("Could not get current context")]
-    CouldNotGetCurrentContext { source: CudaError },
+    CouldNotGetCurrentContext { source: DriverError },
+    #[error("Failed to initialize the CUDA driver API")]
+    DriverInitFailed { source: DriverError },
+    #[error("Could not get device {device:}")]
+    CouldNotGetDevice { source: DriverError, device: i32 },
+    #[error("Failed to create a context on device {device:}")]
+    ContextCreationFailed { source: DriverError, device: i32 },
+    #[error("Failed to retain the primary context of device {device:}")]
+    PrimaryContextRetainFailed { source: DriverError, device: i32 },
+    #[error("Failed to push context onto the current thread")]
+    ContextPushFailed { source: DriverError },
+    #[error("Failed to pop context from the current thread")]
+    ContextPopFailed { source: DriverError },
     #[error("Device sync failed")]
     DeviceSyncFailed { source: CudaError },
     #[error("Texture object creation failed")]
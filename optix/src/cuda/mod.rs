@@ -1,8 +1,9 @@
 pub mod context;
-pub use context::{Context, ContextRef};
+pub use context::{Context, ContextFlags, ContextGuard, ContextRef};
 pub mod buffer;
 pub use buffer::{Buffer, MemcpyKind};
 pub mod error;
+pub mod diagnostics;
 pub mod nvrtc;
 pub mod stream;
 pub use stream::Stream;
@@ -14,11 +15,15 @@ pub use texture_object::{
 pub mod array;
 pub use array::{Array, ArrayFlags, ChannelFormatDesc, ChannelFormatKind};
 pub mod allocator;
-pub use allocator::{Allocator, Mallocator, TaggedAllocator, TaggedMallocator};
+pub use allocator::{
+    Allocator, Mallocator, SyncTaggedAllocator, TaggedAllocator,
+    TaggedMallocator,
+};
 
 pub use error::Error;
 type Result<T, E = Error> = std::result::Result<T, E>;
 
+use crate::backend;
 use optix_sys::cuda_sys as sys;
 
 use std::ffi::CStr;
@@ -27,14 +32,14 @@ pub use sys::{cudaTextureObject_t, CUdeviceptr};
 
 pub fn init() {
     unsafe {
-        sys::cudaFree(std::ptr::null_mut());
+        backend::cuda_free(std::ptr::null_mut());
     }
 }
 
 pub fn get_device_count() -> i32 {
     let mut count = 0i32;
     unsafe {
-        sys::cudaGetDeviceCount(&mut count as *mut i32);
+        backend::cuda_get_device_count(&mut count as *mut i32);
     }
 
     count
@@ -42,7 +47,7 @@ pub fn get_device_count() -> i32 {
 
 pub fn set_device(device: i32) -> Result<()> {
     unsafe {
-        let res = sys::cudaSetDevice(device);
+        let res = backend::cuda_set_device(device);
         if res != sys::cudaError_enum::CUDA_SUCCESS as u32 {
             return Err(Error::CouldNotSetDevice {
                 source: res.into(),
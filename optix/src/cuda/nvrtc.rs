@@ -1,21 +1,54 @@
 use optix_sys::cuda_sys::{
-    nvrtcCompileProgram, nvrtcCreateProgram, nvrtcDestroyProgram,
-    nvrtcGetErrorString, nvrtcGetPTX, nvrtcGetPTXSize, nvrtcGetProgramLog,
-    nvrtcGetProgramLogSize, nvrtcProgram, nvrtcResult,
+    nvrtcAddNameExpression, nvrtcCompileProgram, nvrtcCreateProgram,
+    nvrtcDestroyProgram, nvrtcGetErrorString, nvrtcGetLoweredName, nvrtcGetPTX,
+    nvrtcGetPTXSize, nvrtcGetProgramLog, nvrtcGetProgramLogSize, nvrtcProgram,
+    nvrtcResult, nvrtcVersion,
 };
-use std::ffi::CString;
+use std::collections::HashMap;
+use std::ffi::{CStr, CString};
 use std::os::raw::c_char;
 
 use std::fmt;
 
+pub use super::diagnostics::{parse_log, Diagnostic};
+pub use optix_build::{Arch, CompileOptions, CppStandard};
+
 #[derive(Debug)]
 pub struct Error {
     error_string: String,
+    diagnostics: Vec<Diagnostic>,
+}
+
+impl Error {
+    /// A failed compilation with the log `log`, e.g. from a compiler other
+    /// than NVRTC. The log is kept as it is if it can't be parsed into
+    /// diagnostics.
+    pub fn from_log(log: &str) -> Error {
+        let diagnostics = parse_log(log);
+        let error_string = if diagnostics.is_empty() {
+            log.to_string()
+        } else {
+            "compilation failed".to_string()
+        };
+        Error {
+            error_string,
+            diagnostics,
+        }
+    }
+
+    /// The messages in the program log, if this is a failed compilation
+    pub fn diagnostics(&self) -> &[Diagnostic] {
+        &self.diagnostics
+    }
 }
 
 impl fmt::Display for Error {
     fn fmt(&self, output: &mut fmt::Formatter) -> fmt::Result {
-        write!(output, "nvrtc compilation error: {}", self.error_string)
+        write!(output, "nvrtc compilation error: {}", self.error_string)?;
+        for diagnostic in &self.diagnostics {
+            write!(output, "\n{}", diagnostic)?;
+        }
+        Ok(())
     }
 }
 
@@ -31,13 +64,62 @@ pub fn get_error_string(result: nvrtcResult::Type) -> Error {
             error_string: std::ffi::CStr::from_ptr(nvrtcGetErrorString(result))
                 .to_string_lossy()
                 .into_owned(),
+            diagnostics: Vec::new(),
         }
     }
 }
 
+/// The version of NVRTC as (major, minor)
+pub fn version() -> Result<(i32, i32)> {
+    let mut major = 0;
+    let mut minor = 0;
+    let result = unsafe { nvrtcVersion(&mut major, &mut minor) };
+    if result != nvrtcResult::NVRTC_SUCCESS {
+        Err(get_error_string(result))
+    } else {
+        Ok((major, minor))
+    }
+}
+
 /// A CUDA program object that can be compiled to generate PTX
 pub struct Program {
     pub prog: nvrtcProgram,
+    // the source and headers, to quote in diagnostics
+    sources: Vec<Header>,
+    name_expressions: Vec<String>,
+}
+
+/// The lowered (mangled) names of C++ name expressions, such as
+/// `__closesthit__radiance<float>`, as returned by `Program::lowered_names()`.
+/// These can be kept after the program is gone, e.g. alongside cached PTX.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct LoweredNames {
+    names: HashMap<String, String>,
+}
+
+impl LoweredNames {
+    pub fn new() -> LoweredNames {
+        LoweredNames::default()
+    }
+
+    /// The lowered name of `expression`, which must be written exactly as
+    /// it was added to the program.
+    pub fn get(&self, expression: &str) -> Option<&str> {
+        self.names.get(expression).map(String::as_str)
+    }
+
+    pub fn insert<E: Into<String>, L: Into<String>>(
+        &mut self,
+        expression: E,
+        lowered_name: L,
+    ) {
+        self.names.insert(expression.into(), lowered_name.into());
+    }
+
+    /// The name expressions and their lowered names, in no particular order.
+    pub fn iter(&self) -> impl Iterator<Item = (&str, &str)> {
+        self.names.iter().map(|(e, l)| (e.as_str(), l.as_str()))
+    }
 }
 
 /// Represents a header file that can be included by a program. The `name` is
@@ -63,6 +145,12 @@ impl Program {
     /// `name` and with a list of `headers` to include. If there are no headers
     /// to include, just pass an empty `Vec`.
     pub fn new(src: &str, name: &str, headers: &[Header]) -> Result<Program> {
+        let mut sources = vec![Header {
+            name: name.to_string(),
+            contents: src.to_string(),
+        }];
+        sources.extend(headers.iter().cloned());
+
         let src = CString::new(src).unwrap();
         let name = CString::new(name).unwrap();
         let mut header_names = Vec::new();
@@ -95,7 +183,11 @@ impl Program {
         if result != nvrtcResult::NVRTC_SUCCESS {
             Err(get_error_string(result))
         } else {
-            Ok(Program { prog })
+            Ok(Program {
+                prog,
+                sources,
+                name_expressions: Vec::new(),
+            })
         }
     }
 
@@ -120,18 +212,80 @@ impl Program {
             )
         };
         if result != nvrtcResult::NVRTC_SUCCESS {
+            let error_string = get_error_string(result).error_string;
+            let log = self.get_program_log()?;
+            let mut diagnostics = parse_log(&log);
+            for diagnostic in &mut diagnostics {
+                for source in &self.sources {
+                    diagnostic.add_snippet(&source.name, &source.contents);
+                }
+            }
+            // keep the log as it is if it couldn't be parsed
+            let error_string = if diagnostics.is_empty() {
+                format!("{}\n{}", error_string, log)
+            } else {
+                error_string
+            };
             Err(Error {
-                error_string: format!(
-                    "{}\n{}",
-                    get_error_string(result).error_string,
-                    self.get_program_log().unwrap()
-                ),
+                error_string,
+                diagnostics,
             })
         } else {
             Ok(())
         }
     }
 
+    /// Compile this program with the given `options`
+    pub fn compile(&mut self, options: &CompileOptions) -> Result<()> {
+        self.compile_program(&options.to_strings())
+    }
+
+    /// Register `expression`, the name of a `__global__` function or
+    /// `__device__` variable such as an instantiation of a template, e.g.
+    /// `__closesthit__radiance<float>`. This makes sure the function is
+    /// compiled, and its mangled name can be looked up with `lowered_name()`
+    /// after compiling. Must be called before `compile()`.
+    pub fn add_name_expression(&mut self, expression: &str) -> Result<()> {
+        let cexpr = name_expression_cstring(expression)?;
+        let result =
+            unsafe { nvrtcAddNameExpression(self.prog, cexpr.as_ptr()) };
+        if result != nvrtcResult::NVRTC_SUCCESS {
+            Err(get_error_string(result))
+        } else {
+            self.name_expressions.push(expression.to_string());
+            Ok(())
+        }
+    }
+
+    /// Assuming a successful compilation, get the lowered (mangled) name of
+    /// `expression`, as it appears in the PTX. `expression` must have been
+    /// added with `add_name_expression()`.
+    pub fn lowered_name(&self, expression: &str) -> Result<String> {
+        let cexpr = name_expression_cstring(expression)?;
+        let mut lowered: *const c_char = std::ptr::null();
+        let result = unsafe {
+            nvrtcGetLoweredName(self.prog, cexpr.as_ptr(), &mut lowered)
+        };
+        if result != nvrtcResult::NVRTC_SUCCESS {
+            Err(get_error_string(result))
+        } else {
+            // owned by the program
+            Ok(unsafe { CStr::from_ptr(lowered) }
+                .to_string_lossy()
+                .into_owned())
+        }
+    }
+
+    /// Assuming a successful compilation, get the lowered names of all the
+    /// name expressions that were added.
+    pub fn lowered_names(&self) -> Result<LoweredNames> {
+        let mut names = LoweredNames::new();
+        for expression in &self.name_expressions {
+            names.insert(expression.as_str(), self.lowered_name(expression)?);
+        }
+        Ok(names)
+    }
+
     /// Get the program compilation log
     pub fn get_program_log(&self) -> Result<String> {
         let (log_size, result) = unsafe {
@@ -190,6 +344,19 @@ impl Drop for Program {
     }
 }
 
+/// `expression` as a C string, or an error if it contains a nul byte, which
+/// NVRTC would take as the end of the expression.
+fn name_expression_cstring(expression: &str) -> Result<CString> {
+    CString::new(expression).map_err(|e| Error {
+        error_string: format!(
+            "name expression {:?} contains a nul byte at {}",
+            expression,
+            e.nul_position()
+        ),
+        diagnostics: Vec::new(),
+    })
+}
+
 fn create_whitespace_cstring(len: usize) -> CString {
     let mut buffer: Vec<u8> = Vec::with_capacity(len as usize);
     buffer.extend([b' '].iter().cycle().take(len as usize - 1));
@@ -236,3 +403,11 @@ fn test_compile() {
         }
     }
 }
+
+#[test]
+fn name_expression_with_nul() {
+    let err = name_expression_cstring("__raygen__\0main").unwrap_err();
+    assert!(err.to_string().contains("contains a nul byte at 10"));
+    assert!(err.diagnostics().is_empty());
+    assert!(name_expression_cstring("__raygen__main").is_ok());
+}
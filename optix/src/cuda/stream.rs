@@ -1,3 +1,4 @@
+use crate::backend;
 use optix_sys::cuda_sys as sys;
 
 use super::error::Error;
@@ -7,10 +8,14 @@ pub struct Stream {
     s: sys::CUstream,
 }
 
+// CUDA streams may be used from any thread in the context they belong to.
+unsafe impl Send for Stream {}
+unsafe impl Sync for Stream {}
+
 impl Stream {
     pub fn new() -> Result<Stream> {
         let mut s: sys::CUstream = std::ptr::null_mut();
-        let res = unsafe { sys::cudaStreamCreate(&mut s) };
+        let res = unsafe { backend::cuda_stream_create(&mut s) };
 
         if res != sys::cudaError::cudaSuccess {
             return Err(Error::StreamCreationFailed { source: res.into() });
@@ -36,7 +41,7 @@ impl Drop for Stream {
     fn drop(&mut self) {
         unsafe {
             if !self.s.is_null() {
-                sys::cudaStreamDestroy(self.s);
+                backend::cuda_stream_destroy(self.s);
             }
         }
     }
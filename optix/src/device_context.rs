@@ -1,5 +1,6 @@
 use super::cuda::{self, Allocator};
 use cuda::ContextRef;
+use crate::backend;
 use optix_sys as sys;
 
 use super::error::Error;
@@ -7,7 +8,9 @@ type Result<T, E = Error> = std::result::Result<T, E>;
 
 use std::ffi::{CStr, CString};
 use std::os::raw::{c_char, c_void};
-use std::path::Path;
+use std::path::{Path, PathBuf};
+use std::sync::atomic::{AtomicUsize, Ordering};
+use std::sync::Mutex;
 
 use super::{
     pipeline::PipelineRef,
@@ -17,251 +20,423 @@ use super::{
 pub struct DeviceContext {
     pub(crate) ctx: sys::OptixDeviceContext,
 
-    // handles for all object types
-    // pub(crate) modules: Vec<ModuleRef>,
-    // pub(crate) program_groups: Vec<ProgramGroupRef>,
-    pub(crate) pipelines: Vec<PipelineRef>,
+    // owns `ctx`, and is shared with every module, program group and
+    // pipeline created from it
+    pub(crate) shared: super::Ref<SharedContext>,
 }
 
-pub struct Options {
-    // TODO: logging stuff goes in here
+// OptiX allows calls on a device context from any thread, and the methods
+// that change the context's state take `&mut self`. The log callback may be
+// invoked from whichever thread is calling into OptiX, so it must be `Send`
+// and is called with its slot's mutex held.
+unsafe impl Send for DeviceContext {}
+unsafe impl Sync for DeviceContext {}
+
+/// The OptiX device context handle and everything that has to stay alive
+/// with it.
+///
+/// Modules, program groups and pipelines each hold a reference to this, so
+/// the handle is only destroyed once the `DeviceContext` and all of the
+/// objects created from it have been dropped.
+pub(crate) struct SharedContext {
+    ctx: sys::OptixDeviceContext,
+
+    // every slot that has been passed to OptiX, the current one last. OptiX
+    // may still be calling into a replaced slot from another thread, so they
+    // are only dropped after optixDeviceContextDestroy has returned (see
+    // Drop). Replacing a callback only drops the closure inside its slot.
+    // The slots are boxed so their addresses don't change as the Vec grows.
+    #[allow(clippy::vec_box)]
+    log_callbacks: Mutex<Vec<Box<LogCallbackSlot>>>,
+
+    // the device limits don't change, so they're queried once at creation
+    // for the checks made before calls into OptiX
+    pub(crate) properties: DeviceProperties,
+
+    pub(crate) modules: ObjectCounter,
+    pub(crate) program_groups: ObjectCounter,
+    pub(crate) pipelines: ObjectCounter,
+
+    // keeps the CUDA context alive until after the device context has been
+    // destroyed, since fields are dropped after Drop::drop has run
+    cuda_context: ContextRef,
 }
 
-impl DeviceContext {
-    /// Create a device context associated with the `cuda::Context` referenced with `cuda_context`.
-    pub fn create(
-        cuda_context: ContextRef,
-        _options: Option<Options>,
-    ) -> Result<DeviceContext> {
-        unsafe {
-            let mut ctx = std::ptr::null_mut();
-            let res = sys::optixDeviceContextCreate(
-                *cuda_context,
-                std::ptr::null_mut(),
-                &mut ctx,
-            );
-            if res != sys::OptixResult::OPTIX_SUCCESS {
-                return Err(Error::DeviceContextCreateFailed {
-                    source: res.into(),
-                });
-            }
-            if ctx.is_null() {
-                panic!("optixDeviceContextCreate returned NULL");
-            }
+// See DeviceContext
+unsafe impl Send for SharedContext {}
+unsafe impl Sync for SharedContext {}
 
-            Ok(DeviceContext { ctx, 
-            // modules: Vec::new(), 
-            // program_groups: Vec::new(), 
-            pipelines: Vec::new() })
+impl Drop for SharedContext {
+    fn drop(&mut self) {
+        unsafe {
+            backend::optix_device_context_destroy(self.ctx);
         }
+        // the destroy may still have logged, so only free the callbacks now
+        self.log_callbacks
+            .get_mut()
+            .unwrap_or_else(|e| e.into_inner())
+            .clear();
     }
+}
 
-    /// Returns the low and high water marks for disk cache garbage collection.
-    pub fn get_cache_database_sizes(&self) -> (usize, usize) {
-        let mut lo = 0usize;
-        let mut hi = 0usize;
-        let res = unsafe {
-            sys::optixDeviceContextGetCacheDatabaseSizes(
-                self.ctx, &mut lo, &mut hi,
-            )
-        };
-        if res != sys::OptixResult::OPTIX_SUCCESS {
-            panic!("optixDeviceContextGetCacheDatabaseSizes failed");
-        }
+/// Counts the objects of one kind created from a device context.
+#[derive(Default)]
+pub(crate) struct ObjectCounter {
+    created: AtomicUsize,
+    destroyed: AtomicUsize,
+}
 
-        (lo, hi)
+impl ObjectCounter {
+    pub(crate) fn created(&self) {
+        self.created.fetch_add(1, Ordering::Relaxed);
     }
 
-    /// Indicates whether the disk cache is enabled or disabled.
-    pub fn get_cache_enabled(&self) -> bool {
-        let mut e = 0i32;
-        let res =
-            unsafe { sys::optixDeviceContextGetCacheEnabled(self.ctx, &mut e) };
-        if res != sys::OptixResult::OPTIX_SUCCESS {
-            panic!("optixDeviceContextGetCacheEnabled failed");
+    pub(crate) fn destroyed(&self) {
+        self.destroyed.fetch_add(1, Ordering::Relaxed);
+    }
+
+    fn get(&self) -> ObjectCount {
+        ObjectCount {
+            created: self.created.load(Ordering::Relaxed),
+            destroyed: self.destroyed.load(Ordering::Relaxed),
         }
+    }
+}
 
-        e != 0
+/// How many objects of one kind have been created from a device context, and
+/// how many of those have been destroyed again.
+#[derive(Debug, Default, Copy, Clone, PartialEq, Eq, Hash)]
+pub struct ObjectCount {
+    pub created: usize,
+    pub destroyed: usize,
+}
+
+impl ObjectCount {
+    /// The number of objects that are still alive.
+    pub fn live(&self) -> usize {
+        self.created - self.destroyed
     }
+}
 
-    /// Returns the location of the disk cache.
-    pub fn get_cache_location(&self) -> String {
-        let mut bytes = [0i8; 4096];
-        let res = unsafe {
-            sys::optixDeviceContextGetCacheLocation(
-                self.ctx,
-                bytes.as_mut_ptr(),
-                bytes.len(),
-            )
-        };
-        if res != sys::OptixResult::OPTIX_SUCCESS {
-            panic!("optixDeviceGetCacheLocation failed");
-        }
+/// The number of modules, program groups and pipelines created and destroyed
+/// on a device context, as returned by `DeviceContext::object_counts()`.
+///
+/// Modules are destroyed once no program group uses them, program groups
+/// once no pipeline uses them, so anything that is still live after all of
+/// the application's references have been dropped has leaked.
+#[derive(Debug, Default, Copy, Clone, PartialEq, Eq, Hash)]
+pub struct ObjectCounts {
+    pub modules: ObjectCount,
+    pub program_groups: ObjectCount,
+    pub pipelines: ObjectCount,
+}
 
-        unsafe {
-            CStr::from_ptr(bytes.as_ptr())
-                .to_string_lossy()
-                .into_owned()
-        }
+impl ObjectCounts {
+    /// The number of objects of all kinds that are still alive.
+    pub fn live(&self) -> usize {
+        self.modules.live() + self.program_groups.live() + self.pipelines.live()
     }
+}
 
+/// The limits of a device, as reported by optixDeviceContextGetProperty.
+#[derive(Debug, Default, Copy, Clone, PartialEq, Eq, Hash)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct DeviceProperties {
     /// Maximum value for OptixPipelineLinkOptions::maxTraceDepth
-    pub fn max_trace_depth(&self) -> u32 {
-        let mut value = 0u32;
-        let res = unsafe {
-            sys::optixDeviceContextGetProperty(self.ctx,
-                sys::OptixDeviceProperty_OPTIX_DEVICE_PROPERTY_LIMIT_MAX_TRACE_DEPTH,
-                &mut value as *mut u32 as *mut std::os::raw::c_void, 
-                std::mem::size_of::<u32>(),
-            )
-        };
-        if res != sys::OptixResult::OPTIX_SUCCESS {
-            panic!("optixDeviceGetProperty failed");
+    pub max_trace_depth: u32,
+    /// Maximum value to pass into optixPipelineSetStackSize for parameter
+    /// maxTraversableGraphDepth
+    pub max_traversable_graph_depth: u32,
+    /// The maximum number of primitives (over all build inputs) as input to a
+    /// single Geometry Acceleration Structure (GAS)
+    pub max_primitives_per_gas: u32,
+    /// The maximum number of instances that can be added to a single Instance
+    /// Acceleration Structure (IAS)
+    pub max_instances_per_ias: u32,
+    /// The RT core version supported by the device (0 for no support, 10 for
+    /// version 1.0)
+    pub rtcore_version: u32,
+    /// The maximum value for OptixInstance::instanceId
+    pub max_instance_id: u32,
+    /// The number of bits available for the OptixInstance::visibilityMask.
+    /// Higher bits must be set to zero
+    pub num_bits_instance_visibility_mask: u32,
+    /// The maximum number for the sum of the number of SBT records of all
+    /// build inputs to a single Geometry Acceleration Structure (GAS)
+    pub max_sbt_records_per_gas: u32,
+    /// The maximum value for OptixInstance::sbtOffset
+    pub max_sbt_offset: u32,
+}
+
+impl DeviceProperties {
+    /// Check `value` of the limit `limit` against `max`.
+    pub(crate) fn check(
+        limit: &'static str,
+        value: u64,
+        max: u32,
+    ) -> Result<()> {
+        if value > max as u64 {
+            Err(Error::DeviceLimitExceeded {
+                limit,
+                value,
+                max: max as u64,
+            })
+        } else {
+            Ok(())
         }
+    }
+}
+
+type LogCallbackFn = Box<dyn FnMut(u32, &str, &str) + Send>;
+type LogCallbackSlot = Mutex<LogCallbackFn>;
 
-        value
+/// Settings applied when a `DeviceContext` is created.
+///
+/// The log callback and validation mode are passed to
+/// optixDeviceContextCreate, the disk cache settings are applied straight
+/// after. If any of them fails the context is destroyed again and the error
+/// names the setting that failed. Validation mode can only be set at
+/// creation, so a failed create with it enabled is reported against it.
+#[derive(Default)]
+pub struct Options {
+    log_callback: Option<(LogCallbackFn, u32)>,
+    cache_location: Option<PathBuf>,
+    cache_enabled: Option<bool>,
+    cache_database_sizes: Option<(usize, usize)>,
+    #[cfg(optix_7_3)]
+    validation_mode: bool,
+}
+
+impl Options {
+    pub fn new() -> Options {
+        Options::default()
     }
 
-    /// Maximum value to pass into optixPipelineSetStackSize for parameter 
-    /// maxTraversableGraphDepth
-    pub fn max_traversable_graph_depth(&self) -> u32 {
-        let mut value = 0u32;
-        let res = unsafe {
-            sys::optixDeviceContextGetProperty(self.ctx,
-                sys::OptixDeviceProperty_OPTIX_DEVICE_PROPERTY_LIMIT_MAX_TRAVERSABLE_GRAPH_DEPTH,
-                &mut value as *mut u32 as *mut std::os::raw::c_void, 
-                std::mem::size_of::<u32>(),
-            )
-        };
-        if res != sys::OptixResult::OPTIX_SUCCESS {
-            panic!("optixDeviceGetProperty failed");
-        }
+    /// Install `cb` as the log callback for messages at `level` and below.
+    /// See `DeviceContext::set_log_callback` for the meaning of the levels.
+    pub fn log_callback<F>(mut self, cb: F, level: u32) -> Options
+    where
+        F: FnMut(u32, &str, &str) + Send + 'static,
+    {
+        self.log_callback = Some((Box::new(cb), level));
+        self
+    }
 
-        value
+    /// Directory of the disk cache. See `DeviceContext::set_cache_location`.
+    pub fn cache_location<P: AsRef<Path>>(mut self, path: P) -> Options {
+        self.cache_location = Some(path.as_ref().to_path_buf());
+        self
     }
 
-    /// The maximum number of primitives (over all build inputs) as input to a 
-    /// single Geometry Acceleration Structure (GAS)
-    pub fn max_primtives_per_gas(&self) -> u32 {
-        let mut value = 0u32;
-        let res = unsafe {
-            sys::optixDeviceContextGetProperty(self.ctx,
-                sys::OptixDeviceProperty_OPTIX_DEVICE_PROPERTY_LIMIT_MAX_PRIMITIVES_PER_GAS,
-                &mut value as *mut u32 as *mut std::os::raw::c_void, 
-                std::mem::size_of::<u32>(),
-            )
-        };
-        if res != sys::OptixResult::OPTIX_SUCCESS {
-            panic!("optixDeviceGetProperty failed");
-        }
+    /// Send log messages to the `log` crate with `forward_to_log`, at the
+    /// OptiX level matching `log::max_level()`.
+    pub fn log_to_log_crate(self) -> Options {
+        self.log_callback(forward_to_log, optix_log_level(log::max_level()))
+    }
 
-        value
+    /// Enable or disable the disk cache. See
+    /// `DeviceContext::set_cache_enabled`.
+    pub fn cache_enabled(mut self, enabled: bool) -> Options {
+        self.cache_enabled = Some(enabled);
+        self
     }
 
-    /// The maximum number of instances that can be added to a single Instance 
-    /// Acceleration Structure (IAS)
-    pub fn max_instances_per_ias(&self) -> u32 {
-        let mut value = 0u32;
-        let res = unsafe {
-            sys::optixDeviceContextGetProperty(self.ctx,
-                sys::OptixDeviceProperty_OPTIX_DEVICE_PROPERTY_LIMIT_MAX_INSTANCES_PER_IAS,
-                &mut value as *mut u32 as *mut std::os::raw::c_void, 
-                std::mem::size_of::<u32>(),
-            )
-        };
-        if res != sys::OptixResult::OPTIX_SUCCESS {
-            panic!("optixDeviceGetProperty failed");
-        }
+    /// Low and high water marks for disk cache garbage collection. See
+    /// `DeviceContext::set_database_cache_sizes`.
+    pub fn cache_database_sizes(
+        mut self,
+        low_water_mark: usize,
+        high_water_mark: usize,
+    ) -> Options {
+        self.cache_database_sizes = Some((low_water_mark, high_water_mark));
+        self
+    }
 
-        value
+    /// Enable OptiX validation mode, which checks API calls and launches for
+    /// errors at the cost of performance. Off by default.
+    #[cfg(optix_7_3)]
+    pub fn validation_mode(mut self, enabled: bool) -> Options {
+        self.validation_mode = enabled;
+        self
     }
+}
 
-    /// The RT core version supported by the device (0 for no support, 10 for 
-    /// version 1.0)
-    pub fn rtcore_version(&self) -> u32 {
-        let mut value = 0u32;
+impl DeviceContext {
+    /// Create a device context associated with the `cuda::Context` referenced
+    /// with `cuda_context`, configured with `options`.
+    ///
+    /// The device context holds on to `cuda_context`, so a CUDA context that
+    /// was created or retained through `cuda::Context` is not destroyed or
+    /// released until the device context has been dropped.
+    pub fn create(
+        cuda_context: ContextRef,
+        options: Option<Options>,
+    ) -> Result<DeviceContext> {
+        let options = options.unwrap_or_default();
+
+        let mut sys_options = sys::OptixDeviceContextOptions {
+            logCallbackFunction: None,
+            logCallbackData: std::ptr::null_mut(),
+            logCallbackLevel: 0,
+            #[cfg(optix_7_3)]
+            validationMode: if options.validation_mode {
+                sys::OptixDeviceContextValidationMode_OPTIX_DEVICE_CONTEXT_VALIDATION_MODE_ALL
+            } else {
+                sys::OptixDeviceContextValidationMode_OPTIX_DEVICE_CONTEXT_VALIDATION_MODE_OFF
+            },
+        };
+        let mut log_callbacks = Vec::new();
+        if let Some((cb, level)) = options.log_callback {
+            let cb = Box::new(Mutex::new(cb));
+            sys_options.logCallbackFunction = Some(log_trampoline);
+            sys_options.logCallbackData = log_callback_data(&cb);
+            sys_options.logCallbackLevel = level as i32;
+            log_callbacks.push(cb);
+        }
+
+        let mut ctx = std::ptr::null_mut();
         let res = unsafe {
-            sys::optixDeviceContextGetProperty(self.ctx,
-                sys::OptixDeviceProperty_OPTIX_DEVICE_PROPERTY_RTCORE_VERSION,
-                &mut value as *mut u32 as *mut std::os::raw::c_void, 
-                std::mem::size_of::<u32>(),
+            backend::optix_device_context_create(
+                *cuda_context,
+                &sys_options,
+                &mut ctx,
             )
         };
         if res != sys::OptixResult::OPTIX_SUCCESS {
-            panic!("optixDeviceGetProperty failed");
+            let e = Error::DeviceContextCreateFailed { source: res.into() };
+            #[cfg(optix_7_3)]
+            if options.validation_mode {
+                return Err(Error::DeviceContextOptionFailed {
+                    option: "validation_mode",
+                    source: Box::new(e),
+                });
+            }
+            return Err(e);
+        }
+        if ctx.is_null() {
+            return Err(Error::DeviceContextCreateReturnedNull);
         }
 
-        value
-    }
+        let properties = match query_properties(ctx) {
+            Ok(properties) => properties,
+            Err(e) => {
+                unsafe {
+                    backend::optix_device_context_destroy(ctx);
+                }
+                return Err(e);
+            }
+        };
 
-    /// The maximum value for OptixInstance::instanceId
-    pub fn max_instance_id(&self) -> u32 {
-        let mut value = 0u32;
-        let res = unsafe {
-            sys::optixDeviceContextGetProperty(self.ctx,
-                sys::OptixDeviceProperty_OPTIX_DEVICE_PROPERTY_LIMIT_MAX_INSTANCE_ID,
-                &mut value as *mut u32 as *mut std::os::raw::c_void, 
-                std::mem::size_of::<u32>(),
-            )
+        // from here on dropping `context` destroys it again if a setting
+        // can't be applied
+        let mut context = DeviceContext {
+            ctx,
+            shared: super::Ref::new(SharedContext {
+                ctx,
+                log_callbacks: Mutex::new(log_callbacks),
+                properties,
+                modules: ObjectCounter::default(),
+                program_groups: ObjectCounter::default(),
+                pipelines: ObjectCounter::default(),
+                cuda_context,
+            }),
         };
-        if res != sys::OptixResult::OPTIX_SUCCESS {
-            panic!("optixDeviceGetProperty failed");
+
+        let option_failed = |option: &'static str| {
+            move |source| Error::DeviceContextOptionFailed {
+                option,
+                source: Box::new(source),
+            }
+        };
+        if let Some(path) = &options.cache_location {
+            context
+                .set_cache_location(path)
+                .map_err(option_failed("cache_location"))?;
+        }
+        if let Some((lo, hi)) = options.cache_database_sizes {
+            context
+                .set_database_cache_sizes(lo, hi)
+                .map_err(option_failed("cache_database_sizes"))?;
+        }
+        if let Some(enabled) = options.cache_enabled {
+            context
+                .set_cache_enabled(enabled)
+                .map_err(option_failed("cache_enabled"))?;
         }
 
-        value
+        Ok(context)
     }
 
-    /// The number of bits available for the OptixInstance::visibilityMask. 
-    /// Higher bits must be set to zero
-    pub fn num_bits_instance_visibility_mask(&self) -> u32 {
-        let mut value = 0u32;
+    /// The CUDA context this device context was created from.
+    pub fn cuda_context(&self) -> &ContextRef {
+        &self.shared.cuda_context
+    }
+
+    /// The number of modules, program groups and pipelines created from this
+    /// context and destroyed so far, e.g. to check for leaks.
+    pub fn object_counts(&self) -> ObjectCounts {
+        ObjectCounts {
+            modules: self.shared.modules.get(),
+            program_groups: self.shared.program_groups.get(),
+            pipelines: self.shared.pipelines.get(),
+        }
+    }
+
+    /// Returns the low and high water marks for disk cache garbage collection.
+    pub fn get_cache_database_sizes(&self) -> Result<(usize, usize)> {
+        let mut lo = 0usize;
+        let mut hi = 0usize;
         let res = unsafe {
-            sys::optixDeviceContextGetProperty(self.ctx,
-                sys::OptixDeviceProperty_OPTIX_DEVICE_PROPERTY_LIMIT_NUM_BITS_INSTANCE_VISIBILITY_MASK,
-                &mut value as *mut u32 as *mut std::os::raw::c_void, 
-                std::mem::size_of::<u32>(),
+            backend::optix_device_context_get_cache_database_sizes(
+                self.ctx, &mut lo, &mut hi,
             )
         };
         if res != sys::OptixResult::OPTIX_SUCCESS {
-            panic!("optixDeviceGetProperty failed");
+            return Err(Error::GetCacheDatabaseSizesFailed {
+                source: res.into(),
+            });
         }
 
-        value
+        Ok((lo, hi))
     }
 
-    /// The maximum number for the sum of the number of SBT records of all build 
-    /// inputs to a single Geometry Acceleration Structure (GAS)
-    pub fn max_sbt_records_per_gas(&self) -> u32 {
-        let mut value = 0u32;
+    /// Indicates whether the disk cache is enabled or disabled.
+    pub fn get_cache_enabled(&self) -> Result<bool> {
+        let mut e = 0i32;
         let res = unsafe {
-            sys::optixDeviceContextGetProperty(self.ctx,
-                sys::OptixDeviceProperty_OPTIX_DEVICE_PROPERTY_LIMIT_MAX_SBT_RECORDS_PER_GAS,
-                &mut value as *mut u32 as *mut std::os::raw::c_void, 
-                std::mem::size_of::<u32>(),
-            )
+            backend::optix_device_context_get_cache_enabled(self.ctx, &mut e)
         };
         if res != sys::OptixResult::OPTIX_SUCCESS {
-            panic!("optixDeviceGetProperty failed");
+            return Err(Error::GetCacheEnabledFailed { source: res.into() });
         }
 
-        value
+        Ok(e != 0)
     }
 
-    /// The maximum value for OptixInstance::sbtOffset
-    pub fn max_sbt_offset(&self) -> u32 {
-        let mut value = 0u32;
+    /// Returns the location of the disk cache.
+    pub fn get_cache_location(&self) -> Result<String> {
+        let mut bytes = [0i8; 4096];
         let res = unsafe {
-            sys::optixDeviceContextGetProperty(self.ctx,
-                sys::OptixDeviceProperty_OPTIX_DEVICE_PROPERTY_LIMIT_MAX_SBT_OFFSET,
-                &mut value as *mut u32 as *mut std::os::raw::c_void, 
-                std::mem::size_of::<u32>(),
+            backend::optix_device_context_get_cache_location(
+                self.ctx,
+                bytes.as_mut_ptr(),
+                bytes.len(),
             )
         };
         if res != sys::OptixResult::OPTIX_SUCCESS {
-            panic!("optixDeviceGetProperty failed");
+            return Err(Error::GetCacheLocationFailed { source: res.into() });
         }
 
-        value
+        Ok(unsafe {
+            CStr::from_ptr(bytes.as_ptr())
+                .to_string_lossy()
+                .into_owned()
+        })
+    }
+
+    /// Query all of the device limits at once.
+    pub fn properties(&self) -> Result<DeviceProperties> {
+        query_properties(self.ctx)
     }
 
     /// Sets the low and high water marks for disk cache garbage collection.
@@ -281,13 +456,27 @@ impl DeviceContext {
     /// If the size of a compiled module exceeds the value configured for the 
     /// high water mark and garbage collection is enabled, the module will not 
     /// be added to the cache and a warning will be added to the log.
-    pub fn set_database_cache_sizes(&mut self, low_water_mark: usize, high_water_mark: usize) {
+    pub fn set_database_cache_sizes(
+        &mut self,
+        low_water_mark: usize,
+        high_water_mark: usize,
+    ) -> Result<()> {
         let res = unsafe {
-            sys::optixDeviceContextSetCacheDatabaseSizes(self.ctx, low_water_mark, high_water_mark)
+            backend::optix_device_context_set_cache_database_sizes(
+                self.ctx,
+                low_water_mark,
+                high_water_mark,
+            )
         };
         if res != sys::OptixResult::OPTIX_SUCCESS {
-            panic!("optixDeviceContextSetCacheDatabaseSizes failed");
+            return Err(Error::SetCacheDatabaseSizesFailed {
+                source: res.into(),
+                low_water_mark,
+                high_water_mark,
+            });
         }
+
+        Ok(())
     }
 
     /// Enables or disables the disk cache.
@@ -297,14 +486,19 @@ impl DeviceContext {
     /// location. An error will be returned if initialization fails.
     /// Note that no in-memory cache is used, so no caching behavior will be 
     /// observed if the disk cache is disabled.
-    pub fn set_cache_enabled(&mut self, enabled: bool) {
+    pub fn set_cache_enabled(&mut self, enabled: bool) -> Result<()> {
         let e = if enabled { 1 } else {0};
         let res = unsafe {
-            sys::optixDeviceContextSetCacheEnabled(self.ctx, e)
+            backend::optix_device_context_set_cache_enabled(self.ctx, e)
         };
         if res != sys::OptixResult::OPTIX_SUCCESS {
-            panic!("optixDeviceContextSetCacheEnabled failed");
+            return Err(Error::SetCacheEnabledFailed {
+                source: res.into(),
+                enabled,
+            });
         }
+
+        Ok(())
     }
 
     /// Sets the location of the disk cache.
@@ -327,9 +521,15 @@ impl DeviceContext {
     /// are omitted if the username cannot be obtained
     /// * MacOS X: /Library/Application Support/NVIDIA/OptixCache
     pub fn set_cache_location<P: AsRef<Path>>(&mut self, path: P) -> Result<()> {
-        let cs = CString::new(path.as_ref().to_str().unwrap()).unwrap();
+        let cs = path
+            .as_ref()
+            .to_str()
+            .and_then(|p| CString::new(p).ok())
+            .ok_or_else(|| Error::InvalidCacheLocation {
+                path: path.as_ref().to_path_buf(),
+            })?;
         let res = unsafe {
-            sys::optixDeviceContextSetCacheLocation(self.ctx, cs.as_ptr())
+            backend::optix_device_context_set_cache_location(self.ctx, cs.as_ptr())
         };
         if res != sys::OptixResult::OPTIX_SUCCESS {
             return Err(Error::SetCacheLocationFailed{source: res.into(), path: path.as_ref().to_path_buf()});
@@ -352,24 +552,51 @@ impl DeviceContext {
     /// * 4 - print: Status or progress messages.
     /// Higher levels might occur.
     /// 
-    /// # Safety
-    /// Note that the callback must live longer than the DeviceContext which it's
-    /// set for. This is because OptiX will fire messages when the underlying
-    /// OptixDeviceContext is destroyed. In order to do ensure this we leak the
-    /// closure `cb`. So don't go setting a new closure every frame.
-    pub fn set_log_callback<F>(&mut self, cb: F, level: u32) where F: FnMut(u32, &str, &str) + 'static {
-        let (closure, trampoline) = unsafe {unpack_closure(cb)};
+    /// The callback is owned by the `DeviceContext` and is dropped when it
+    /// is replaced or after the underlying OptixDeviceContext is destroyed.
+    /// Panics in the callback are caught and discarded as they cannot unwind
+    /// through OptiX. Use `forward_to_log` as the callback to send messages
+    /// to the `log` crate.
+    pub fn set_log_callback<F>(&mut self, cb: F, level: u32) -> Result<()>
+    where
+        F: FnMut(u32, &str, &str) + Send + 'static,
+    {
+        let cb: Box<LogCallbackSlot> = Box::new(Mutex::new(Box::new(cb)));
         let res = unsafe {
-            sys::optixDeviceContextSetLogCallback(self.ctx, Some(trampoline), closure, level)
+            backend::optix_device_context_set_log_callback(
+                self.ctx,
+                Some(log_trampoline),
+                log_callback_data(&cb),
+                level,
+            )
         };
         if res != sys::OptixResult::OPTIX_SUCCESS {
-            panic!("Failed to set log callback");
+            return Err(Error::SetLogCallbackFailed {
+                source: res.into(),
+                level,
+            });
         }
+
+        let mut log_callbacks = self
+            .shared
+            .log_callbacks
+            .lock()
+            .unwrap_or_else(|e| e.into_inner());
+        // a call that OptiX started before the switch may still be about to
+        // lock the previous slot, so only its closure is dropped here. The
+        // slot's mutex waits for a call in progress to finish first.
+        if let Some(previous) = log_callbacks.last() {
+            *previous.lock().unwrap_or_else(|e| e.into_inner()) =
+                Box::new(|_, _, _| ());
+        }
+        log_callbacks.push(cb);
+
+        Ok(())
     }
 
     pub fn launch<'a, 't, AllocT>(&self, pipeline: &PipelineRef, stream: &cuda::Stream, launch_params: &cuda::Buffer<'a, AllocT>, sbt: &ShaderBindingTable<'a, 't, AllocT>, width: u32, height: u32, depth: u32) -> Result<()> where AllocT: Allocator{
         let res = unsafe {
-            sys::optixLaunch(
+            backend::optix_launch(
                 pipeline.pipeline,
                 stream.as_sys_ptr(),
                 launch_params.as_device_ptr(),
@@ -379,7 +606,12 @@ impl DeviceContext {
             )
         };
         if res != sys::OptixResult::OPTIX_SUCCESS {
-            return Err(Error::LaunchFailed{source: res.into()});
+            return Err(Error::LaunchFailed {
+                source: res.into(),
+                width,
+                height,
+                depth,
+            });
         }
 
         Ok(())
@@ -388,46 +620,437 @@ impl DeviceContext {
 
 use std::os::raw::c_uint;
 
-type LogCallback = extern "C" fn(c_uint, *const c_char, *const c_char, *mut c_void);
+/// Get the pointer that is passed to `log_trampoline` as its `data`.
+fn log_callback_data(cb: &LogCallbackSlot) -> *mut c_void {
+    cb as *const LogCallbackSlot as *mut c_void
+}
 
-/// Unpack a Rust closure, extracting a `void*` pointer to the data and a
-/// trampoline function which can be used to invoke it.
-///
-/// # Safety
-///
-/// It is the user's responsibility to ensure the closure outlives the returned
-/// `void*` pointer.
-///
-/// Calling the trampoline function with anything except the `void*` pointer
-/// will result in *Undefined Behaviour*.
-///
-/// The closure should guarantee that it never panics, seeing as panicking 
-/// across the FFI barrier is *Undefined Behaviour*. You may find 
-/// `std::panic::catch_unwind()` useful.
-unsafe fn unpack_closure<F>(closure: F) -> (*mut c_void, LogCallback)
-where
-    F: FnMut(u32, &str, &str),
-{
-    extern "C" fn trampoline<F>(level: c_uint, tag: *const c_char, msg: *const c_char, data: *mut c_void)
-    where
-        F: FnMut(u32, &str, &str),
-    {
-        let tag = unsafe {CStr::from_ptr(tag).to_string_lossy().into_owned()};
-        let msg = unsafe {CStr::from_ptr(msg).to_string_lossy().into_owned()};
-        let closure: &mut F = unsafe { &mut *(data as *mut F) };
-        (*closure)(level, &tag, &msg);
+/// Invokes the callback in the `LogCallbackSlot` pointed to by `data`, which
+/// must have come from `log_callback_data` and still be alive.
+extern "C" fn log_trampoline(
+    level: c_uint,
+    tag: *const c_char,
+    msg: *const c_char,
+    data: *mut c_void,
+) {
+    let tag = unsafe { CStr::from_ptr(tag).to_string_lossy() };
+    let msg = unsafe { CStr::from_ptr(msg).to_string_lossy() };
+    let slot = unsafe { &*(data as *const LogCallbackSlot) };
+    // callback panics are caught below so they never poison the mutex
+    let mut cb = slot.lock().unwrap_or_else(|e| e.into_inner());
+    // unwinding into OptiX is undefined behaviour, so the panic is dropped
+    // here. The default hook has already printed it.
+    let _ = std::panic::catch_unwind(std::panic::AssertUnwindSafe(|| {
+        cb(level, &tag, &msg)
+    }));
+}
+
+/// The `log::Level` for an OptiX log level, or `None` for level 0, which
+/// disables logging.
+fn log_level(level: u32) -> Option<log::Level> {
+    match level {
+        0 => None,
+        1 | 2 => Some(log::Level::Error),
+        3 => Some(log::Level::Warn),
+        4 => Some(log::Level::Info),
+        _ => Some(log::Level::Debug),
+    }
+}
+
+/// The OptiX log level that lets through everything `filter` would log.
+fn optix_log_level(filter: log::LevelFilter) -> u32 {
+    match filter {
+        log::LevelFilter::Off => 0,
+        log::LevelFilter::Error => 2,
+        log::LevelFilter::Warn => 3,
+        _ => 4,
     }
+}
+
+fn query_properties(ctx: sys::OptixDeviceContext) -> Result<DeviceProperties> {
+    Ok(DeviceProperties {
+        max_trace_depth: get_property_u32(
+            ctx,
+            sys::OptixDeviceProperty_OPTIX_DEVICE_PROPERTY_LIMIT_MAX_TRACE_DEPTH,
+            "max_trace_depth",
+        )?,
+        max_traversable_graph_depth: get_property_u32(
+            ctx,
+            sys::OptixDeviceProperty_OPTIX_DEVICE_PROPERTY_LIMIT_MAX_TRAVERSABLE_GRAPH_DEPTH,
+            "max_traversable_graph_depth",
+        )?,
+        max_primitives_per_gas: get_property_u32(
+            ctx,
+            sys::OptixDeviceProperty_OPTIX_DEVICE_PROPERTY_LIMIT_MAX_PRIMITIVES_PER_GAS,
+            "max_primitives_per_gas",
+        )?,
+        max_instances_per_ias: get_property_u32(
+            ctx,
+            sys::OptixDeviceProperty_OPTIX_DEVICE_PROPERTY_LIMIT_MAX_INSTANCES_PER_IAS,
+            "max_instances_per_ias",
+        )?,
+        rtcore_version: get_property_u32(
+            ctx,
+            sys::OptixDeviceProperty_OPTIX_DEVICE_PROPERTY_RTCORE_VERSION,
+            "rtcore_version",
+        )?,
+        max_instance_id: get_property_u32(
+            ctx,
+            sys::OptixDeviceProperty_OPTIX_DEVICE_PROPERTY_LIMIT_MAX_INSTANCE_ID,
+            "max_instance_id",
+        )?,
+        num_bits_instance_visibility_mask: get_property_u32(
+            ctx,
+            sys::OptixDeviceProperty_OPTIX_DEVICE_PROPERTY_LIMIT_NUM_BITS_INSTANCE_VISIBILITY_MASK,
+            "num_bits_instance_visibility_mask",
+        )?,
+        max_sbt_records_per_gas: get_property_u32(
+            ctx,
+            sys::OptixDeviceProperty_OPTIX_DEVICE_PROPERTY_LIMIT_MAX_SBT_RECORDS_PER_GAS,
+            "max_sbt_records_per_gas",
+        )?,
+        max_sbt_offset: get_property_u32(
+            ctx,
+            sys::OptixDeviceProperty_OPTIX_DEVICE_PROPERTY_LIMIT_MAX_SBT_OFFSET,
+            "max_sbt_offset",
+        )?,
+    })
+}
 
-    let cb = Box::new(closure);
-    let cb = Box::leak(cb);
+fn get_property_u32(
+    ctx: sys::OptixDeviceContext,
+    property: sys::OptixDeviceProperty,
+    name: &'static str,
+) -> Result<u32> {
+    let mut value = 0u32;
+    let res = unsafe {
+        backend::optix_device_context_get_property(
+            ctx,
+            property,
+            &mut value as *mut u32 as *mut c_void,
+            std::mem::size_of::<u32>(),
+        )
+    };
+    if res != sys::OptixResult::OPTIX_SUCCESS {
+        return Err(Error::GetPropertyFailed {
+            source: res.into(),
+            property: name,
+        });
+    }
 
-    (cb as *mut F as  *mut c_void, trampoline::<F>)
+    Ok(value)
 }
 
-impl Drop for DeviceContext {
-    fn drop(&mut self) {
-        unsafe {
-            sys::optixDeviceContextDestroy(self.ctx);
+/// A log callback that forwards OptiX messages to the `log` crate.
+///
+/// Fatal and error messages are logged at `Error`, warnings at `Warn` and
+/// print messages at `Info`. The OptiX tag is used as the log target.
+pub fn forward_to_log(level: u32, tag: &str, msg: &str) {
+    if let Some(level) = log_level(level) {
+        log::log!(target: tag, level, "{}", msg);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{
+        log_callback_data, log_trampoline, DeviceContext, ObjectCount, Options,
+    };
+    use crate::backend::mock::{fixtures, HandleKind, MockBackend};
+    use crate::cuda;
+    use crate::error::Error;
+    use crate::{
+        CompileDebugLevel, PipelineLinkOptions, ProgramGroupDesc,
+        ProgramGroupModule,
+    };
+    use optix_sys as sys;
+    use std::sync::{
+        atomic::{AtomicU32, Ordering},
+        Arc,
+    };
+
+    #[test]
+    fn properties() {
+        let mock = MockBackend::install();
+        let ctx = fixtures::device_context();
+        mock.set_property(
+            sys::OptixDeviceProperty_OPTIX_DEVICE_PROPERTY_RTCORE_VERSION,
+            10,
+        );
+        let props = ctx.properties().unwrap();
+        assert_eq!(props.max_trace_depth, 31);
+        assert_eq!(props.num_bits_instance_visibility_mask, 8);
+        assert_eq!(props.rtcore_version, 10);
+
+        mock.fail_optix(
+            "optix_device_context_get_property",
+            sys::OptixResult::OPTIX_ERROR_INVALID_DEVICE_CONTEXT,
+        );
+        match ctx.properties() {
+            Err(Error::GetPropertyFailed { property, .. }) => {
+                assert_eq!(property, "max_trace_depth")
+            }
+            r => panic!("expected GetPropertyFailed, got {:?}", r),
+        }
+    }
+
+    #[test]
+    fn cache_and_log_failures() {
+        let mock = MockBackend::install();
+        let mut ctx = fixtures::device_context();
+
+        mock.fail_optix(
+            "optix_device_context_set_cache_enabled",
+            sys::OptixResult::OPTIX_ERROR_DISK_CACHE_DATABASE_ERROR,
+        );
+        assert!(matches!(
+            ctx.set_cache_enabled(true),
+            Err(Error::SetCacheEnabledFailed { enabled: true, .. })
+        ));
+
+        // the mock only accepts log levels 0 through 4
+        assert!(matches!(
+            ctx.set_log_callback(|_, _, _| {}, 5),
+            Err(Error::SetLogCallbackFailed { level: 5, .. })
+        ));
+    }
+
+    #[test]
+    fn create_with_options() {
+        let mock = MockBackend::install();
+        let messages = Arc::new(AtomicU32::new(0));
+        let options = Options::new()
+            .log_callback(
+                {
+                    let messages = Arc::clone(&messages);
+                    move |_, _, _| {
+                        messages.fetch_add(1, Ordering::Relaxed);
+                    }
+                },
+                3,
+            )
+            .cache_location("/tmp/optix-rs-cache")
+            .cache_database_sizes(1 << 20, 1 << 30)
+            .cache_enabled(false);
+        let ctx = DeviceContext::create(
+            cuda::Context::get_current().unwrap(),
+            Some(options),
+        )
+        .unwrap();
+
+        assert_eq!(ctx.get_cache_location().unwrap(), "/tmp/optix-rs-cache");
+        assert_eq!(ctx.get_cache_database_sizes().unwrap(), (1 << 20, 1 << 30));
+        assert!(!ctx.get_cache_enabled().unwrap());
+
+        mock.log(3, "TAG", "warning");
+        mock.log(4, "TAG", "filtered");
+        assert_eq!(messages.load(Ordering::Relaxed), 1);
+    }
+
+    #[test]
+    fn create_reports_failed_option() {
+        let mock = MockBackend::install();
+        // the high water mark must not be below the low water mark
+        let options = Options::new().cache_database_sizes(2, 1);
+        match DeviceContext::create(
+            cuda::Context::get_current().unwrap(),
+            Some(options),
+        ) {
+            Err(Error::DeviceContextOptionFailed { option, source }) => {
+                assert_eq!(option, "cache_database_sizes");
+                assert!(matches!(
+                    *source,
+                    Error::SetCacheDatabaseSizesFailed { .. }
+                ));
+            }
+            Err(e) => panic!("unexpected error {}", e),
+            Ok(_) => panic!("context creation should have failed"),
         }
+        assert_eq!(mock.live_handles(HandleKind::DeviceContext), 0);
+    }
+
+    #[cfg(optix_7_3)]
+    #[test]
+    fn create_with_validation_mode() {
+        let mock = MockBackend::install();
+        let ctx = DeviceContext::create(
+            cuda::Context::get_current().unwrap(),
+            Some(Options::new().validation_mode(true)),
+        )
+        .unwrap();
+        assert_eq!(
+            mock.validation_mode(ctx.ctx),
+            Some(sys::OptixDeviceContextValidationMode_OPTIX_DEVICE_CONTEXT_VALIDATION_MODE_ALL)
+        );
+
+        mock.fail_optix(
+            "optix_device_context_create",
+            sys::OptixResult::OPTIX_ERROR_VALIDATION_FAILURE,
+        );
+        match DeviceContext::create(
+            cuda::Context::get_current().unwrap(),
+            Some(Options::new().validation_mode(true)),
+        ) {
+            Err(Error::DeviceContextOptionFailed { option, source }) => {
+                assert_eq!(option, "validation_mode");
+                assert!(matches!(
+                    *source,
+                    Error::DeviceContextCreateFailed { .. }
+                ));
+            }
+            Err(e) => panic!("unexpected error {}", e),
+            Ok(_) => panic!("context creation should have failed"),
+        }
+    }
+
+    #[test]
+    fn create_queries_properties_once() {
+        let mock = MockBackend::install();
+        mock.fail_optix(
+            "optix_device_context_get_property",
+            sys::OptixResult::OPTIX_ERROR_INVALID_DEVICE_CONTEXT,
+        );
+        match DeviceContext::create(cuda::Context::get_current().unwrap(), None)
+        {
+            Err(Error::GetPropertyFailed { .. }) => (),
+            Err(e) => panic!("unexpected error {}", e),
+            Ok(_) => panic!("context creation should have failed"),
+        }
+        assert_eq!(mock.live_handles(HandleKind::DeviceContext), 0);
+
+        mock.clear_failures();
+        let mut ctx = fixtures::device_context();
+        assert_eq!(ctx.shared.properties, ctx.properties().unwrap());
+
+        // the limit checks use the properties queried at creation
+        mock.fail_optix(
+            "optix_device_context_get_property",
+            sys::OptixResult::OPTIX_ERROR_INVALID_DEVICE_CONTEXT,
+        );
+        let link_options = PipelineLinkOptions {
+            max_trace_depth: 1,
+            debug_level: CompileDebugLevel::None,
+            #[cfg(not(optix_7_3))]
+            override_uses_motion_blur: false,
+        };
+        let (mut pipeline, _) = ctx
+            .pipeline_create(
+                &fixtures::pipeline_compile_options(),
+                link_options,
+                &[],
+            )
+            .unwrap();
+        ctx.pipeline_set_stack_size(&mut pipeline, 0, 0, 0, 1)
+            .unwrap();
+    }
+
+    #[test]
+    fn log_callback_ownership() {
+        let mock = MockBackend::install();
+        let mut ctx = fixtures::device_context();
+        let first = Arc::new(AtomicU32::new(0));
+        let second = Arc::new(AtomicU32::new(0));
+
+        let counter = |count: &Arc<AtomicU32>| {
+            let count = Arc::clone(count);
+            move |_: u32, _: &str, _: &str| {
+                count.fetch_add(1, Ordering::Relaxed);
+            }
+        };
+        ctx.set_log_callback(counter(&first), 4).unwrap();
+        mock.log(4, "TAG", "first");
+        ctx.set_log_callback(counter(&second), 4).unwrap();
+        mock.log(4, "TAG", "second");
+
+        // replacing the callback drops the old closure
+        assert_eq!(first.load(Ordering::Relaxed), 1);
+        assert_eq!(Arc::strong_count(&first), 1);
+        assert_eq!(second.load(Ordering::Relaxed), 1);
+        assert_eq!(Arc::strong_count(&second), 2);
+
+        // but keeps its slot alive for calls OptiX started before the switch
+        let data = {
+            let log_callbacks = ctx.shared.log_callbacks.lock().unwrap();
+            assert_eq!(log_callbacks.len(), 2);
+            log_callback_data(&log_callbacks[0])
+        };
+        let text = std::ffi::CString::new("late").unwrap();
+        log_trampoline(4, text.as_ptr(), text.as_ptr(), data);
+        assert_eq!(first.load(Ordering::Relaxed), 1);
+        assert_eq!(second.load(Ordering::Relaxed), 1);
+
+        drop(ctx);
+        assert_eq!(Arc::strong_count(&second), 1);
+    }
+
+    #[test]
+    fn log_callback_panic_is_caught() {
+        let mock = MockBackend::install();
+        let mut ctx = fixtures::device_context();
+        ctx.set_log_callback(|_, _, msg| panic!("{}", msg), 4)
+            .unwrap();
+        mock.log(2, "TAG", "callback panicked");
+    }
+
+    #[test]
+    fn object_ownership() {
+        let mock = MockBackend::install();
+        let mut ctx = fixtures::device_context();
+        let module = fixtures::module(&mut ctx);
+        let (pg, _) = ctx
+            .program_group_create(ProgramGroupDesc::Raygen(
+                ProgramGroupModule {
+                    module: module.clone(),
+                    entry_function_name: ustr::ustr("__raygen__main"),
+                },
+            ))
+            .unwrap();
+        let link_options = PipelineLinkOptions {
+            max_trace_depth: 1,
+            debug_level: CompileDebugLevel::None,
+            #[cfg(not(optix_7_3))]
+            override_uses_motion_blur: false,
+        };
+        let (pipeline, _) = ctx
+            .pipeline_create(
+                &fixtures::pipeline_compile_options(),
+                link_options,
+                std::slice::from_ref(&pg),
+            )
+            .unwrap();
+
+        // the program group keeps the module alive, and the pipeline the
+        // program group
+        drop(module);
+        drop(pg);
+        assert_eq!(mock.live_handles(HandleKind::Module), 1);
+        assert_eq!(mock.live_handles(HandleKind::ProgramGroup), 1);
+
+        drop(fixtures::module(&mut ctx));
+        let counts = ctx.object_counts();
+        assert_eq!(counts.modules, ObjectCount { created: 2, destroyed: 1 });
+        assert_eq!(counts.pipelines.live(), 1);
+        assert_eq!(counts.live(), 3);
+
+        // and all of them the device context
+        drop(ctx);
+        assert_eq!(mock.live_handles(HandleKind::DeviceContext), 1);
+        drop(pipeline);
+        assert_eq!(mock.live_handles(HandleKind::Pipeline), 0);
+        assert_eq!(mock.live_handles(HandleKind::ProgramGroup), 0);
+        assert_eq!(mock.live_handles(HandleKind::Module), 0);
+        assert_eq!(mock.live_handles(HandleKind::DeviceContext), 0);
+    }
+
+    #[test]
+    fn log_levels() {
+        assert_eq!(super::log_level(0), None);
+        assert_eq!(super::log_level(1), Some(log::Level::Error));
+        assert_eq!(super::log_level(2), Some(log::Level::Error));
+        assert_eq!(super::log_level(3), Some(log::Level::Warn));
+        assert_eq!(super::log_level(4), Some(log::Level::Info));
+        assert_eq!(super::optix_log_level(log::LevelFilter::Off), 0);
+        assert_eq!(super::optix_log_level(log::LevelFilter::Warn), 3);
+        assert_eq!(super::optix_log_level(log::LevelFilter::Trace), 4);
     }
 }
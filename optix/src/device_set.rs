@@ -0,0 +1,570 @@
+//! Split-frame launches across several GPUs.
+//!
+//! A `DeviceSet` holds a `DeviceContext` and a stream for each CUDA device it
+//! was created with. Modules, pipelines and shader binding tables are created
+//! once per device with `replicate()` (or the wrappers built on it), and a
+//! launch is split into bands of rows with `tiles()`, one band per device.
+//! `launch()` starts each device on its band, and `gather()` copies the bands
+//! back into a single host frame.
+//!
+//! Each device's launch params are expected to tell its raygen program which
+//! part of the frame it is rendering, so the launch index `(x, y)` on a
+//! device corresponds to `(tile.x + x, tile.y + y)` in the frame and is
+//! written to a per-device output buffer of exactly `tile.len()` elements.
+use crate::compile_log::CompileLog;
+use crate::cuda::{self, Allocator};
+use crate::device_context::{DeviceContext, Options};
+use crate::{
+    ModuleCompileOptions, ModuleRef, PipelineCompileOptions,
+    PipelineLinkOptions, PipelineRef, ProgramGroupRef, ShaderBindingTable,
+};
+
+use super::error::Error;
+type Result<T, E = Error> = std::result::Result<T, E>;
+
+/// The part of a launch assigned to one device of a `DeviceSet`.
+#[derive(Debug, Copy, Clone, PartialEq, Eq, Hash)]
+pub struct Tile {
+    /// Index of the device in the set (not its CUDA ordinal)
+    pub device: usize,
+    pub x: u32,
+    pub y: u32,
+    pub width: u32,
+    pub height: u32,
+}
+
+impl Tile {
+    /// Number of launch indices in the tile.
+    pub fn len(&self) -> usize {
+        self.width as usize * self.height as usize
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.len() == 0
+    }
+}
+
+/// Split a `width` x `height` launch between `num_devices` devices.
+///
+/// Each device gets a band of whole rows, and the heights of the bands
+/// differ by at most one row. Devices that would get no rows because the
+/// launch is shorter than the number of devices get no tile.
+pub fn split_launch(width: u32, height: u32, num_devices: usize) -> Vec<Tile> {
+    if width == 0 || num_devices == 0 {
+        return Vec::new();
+    }
+
+    let row = |device: usize| {
+        (height as u64 * device as u64 / num_devices as u64) as u32
+    };
+    (0..num_devices)
+        .filter_map(|device| {
+            let (y0, y1) = (row(device), row(device + 1));
+            if y1 > y0 {
+                Some(Tile {
+                    device,
+                    x: 0,
+                    y: y0,
+                    width,
+                    height: y1 - y0,
+                })
+            } else {
+                None
+            }
+        })
+        .collect()
+}
+
+/// Copy `data`, the row-major output for `tile`, into its place in `frame`,
+/// a row-major image `frame_width` elements wide.
+pub fn gather_tile<T: Copy>(
+    frame: &mut [T],
+    frame_width: u32,
+    tile: &Tile,
+    data: &[T],
+) -> Result<()> {
+    if data.len() != tile.len() {
+        return Err(Error::TileSizeMismatch {
+            tile: *tile,
+            expected: tile.len(),
+            actual: data.len(),
+        });
+    }
+    if tile.is_empty() {
+        return Ok(());
+    }
+
+    let frame_width = frame_width as usize;
+    let (x, y) = (tile.x as usize, tile.y as usize);
+    let tile_width = tile.width as usize;
+    if x + tile_width > frame_width
+        || (y + tile.height as usize) * frame_width > frame.len()
+    {
+        return Err(Error::TileOutsideFrame {
+            tile: *tile,
+            frame_width: frame_width as u32,
+            frame_height: (frame.len() / frame_width.max(1)) as u32,
+        });
+    }
+
+    for (row, src) in data.chunks_exact(tile_width).enumerate() {
+        let start = (y + row) * frame_width + x;
+        frame[start..start + tile_width].copy_from_slice(src);
+    }
+
+    Ok(())
+}
+
+/// One device of a `DeviceSet`.
+pub struct Device {
+    ordinal: i32,
+    stream: cuda::Stream,
+    ctx: DeviceContext,
+}
+
+impl Device {
+    /// The CUDA ordinal of the device.
+    pub fn ordinal(&self) -> i32 {
+        self.ordinal
+    }
+
+    pub fn context(&self) -> &DeviceContext {
+        &self.ctx
+    }
+
+    pub fn context_mut(&mut self) -> &mut DeviceContext {
+        &mut self.ctx
+    }
+
+    /// The stream launches on this device are made on.
+    pub fn stream(&self) -> &cuda::Stream {
+        &self.stream
+    }
+
+    /// Make this the current CUDA device for the calling thread, so that
+    /// buffers and textures created afterwards are allocated on it.
+    pub fn make_current(&self) -> Result<()> {
+        cuda::set_device(self.ordinal)?;
+        Ok(())
+    }
+}
+
+/// A `DeviceContext` and stream for each of a set of CUDA devices.
+pub struct DeviceSet {
+    devices: Vec<Device>,
+}
+
+impl DeviceSet {
+    /// Create a set with every CUDA device in the system. `options` is
+    /// called with each device's ordinal to get the options for its context.
+    pub fn new<F>(options: F) -> Result<DeviceSet>
+    where
+        F: FnMut(i32) -> Option<Options>,
+    {
+        let ordinals: Vec<i32> = (0..cuda::get_device_count()).collect();
+        DeviceSet::with_devices(&ordinals, options)
+    }
+
+    /// Create a set with the CUDA devices `ordinals`, in that order.
+    pub fn with_devices<F>(
+        ordinals: &[i32],
+        mut options: F,
+    ) -> Result<DeviceSet>
+    where
+        F: FnMut(i32) -> Option<Options>,
+    {
+        if ordinals.is_empty() {
+            return Err(Error::NoDevices);
+        }
+
+        let devices = ordinals
+            .iter()
+            .map(|&ordinal| {
+                // the device context keeps the primary context retained for
+                // as long as the device is in the set
+                let ctx = DeviceContext::create(
+                    cuda::Context::retain_primary(ordinal)?,
+                    options(ordinal),
+                )?;
+                cuda::set_device(ordinal)?;
+                let stream = cuda::Stream::new()?;
+                Ok(Device {
+                    ordinal,
+                    stream,
+                    ctx,
+                })
+            })
+            .collect::<Result<Vec<_>>>()?;
+
+        Ok(DeviceSet { devices })
+    }
+
+    pub fn len(&self) -> usize {
+        self.devices.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.devices.is_empty()
+    }
+
+    pub fn devices(&self) -> &[Device] {
+        &self.devices
+    }
+
+    /// Split a `width` x `height` launch between the devices in the set.
+    /// See `split_launch()`.
+    pub fn tiles(&self, width: u32, height: u32) -> Vec<Tile> {
+        split_launch(width, height, self.devices.len())
+    }
+
+    /// Call `f` with the index of each device in the set and the device,
+    /// after making it current, and collect the results in device order.
+    ///
+    /// This is how objects that live on a device, such as program groups
+    /// and shader binding tables, are created on every device in the set.
+    pub fn replicate<T, F>(&mut self, mut f: F) -> Result<Vec<T>>
+    where
+        F: FnMut(usize, &mut Device) -> Result<T>,
+    {
+        self.devices
+            .iter_mut()
+            .enumerate()
+            .map(|(index, device)| {
+                device.make_current()?;
+                f(index, device)
+            })
+            .collect()
+    }
+
+    /// Create a module from `ptx` on every device in the set.
+    // ModuleCompileOptions is only Copy before 7.4
+    #[allow(clippy::clone_on_copy)]
+    pub fn module_create_from_ptx(
+        &mut self,
+        module_compile_options: ModuleCompileOptions,
+        pipeline_compile_options: &PipelineCompileOptions,
+        ptx: &str,
+    ) -> Result<Vec<(ModuleRef, CompileLog)>> {
+        self.replicate(|_, device| {
+            device.ctx.module_create_from_ptx(
+                module_compile_options.clone(),
+                pipeline_compile_options,
+                ptx,
+            )
+        })
+    }
+
+    /// Create a pipeline on every device in the set, from the program groups
+    /// in `program_groups` for that device.
+    pub fn pipeline_create(
+        &mut self,
+        pipeline_compile_options: &PipelineCompileOptions,
+        link_options: PipelineLinkOptions,
+        program_groups: &[Vec<ProgramGroupRef>],
+    ) -> Result<Vec<(PipelineRef, CompileLog)>> {
+        self.check_per_device("program group lists", program_groups.len())?;
+        self.replicate(|index, device| {
+            device.ctx.pipeline_create(
+                pipeline_compile_options,
+                link_options,
+                &program_groups[index],
+            )
+        })
+    }
+
+    /// Launch each of `tiles` on its device's stream, using that device's
+    /// entry in `pipelines`, `launch_params` and `sbts`.
+    ///
+    /// The launches are asynchronous, so the devices render their tiles
+    /// concurrently.
+    pub fn launch<'a, 't, AllocT>(
+        &self,
+        tiles: &[Tile],
+        pipelines: &[PipelineRef],
+        launch_params: &[cuda::Buffer<'a, AllocT>],
+        sbts: &[ShaderBindingTable<'a, 't, AllocT>],
+    ) -> Result<()>
+    where
+        AllocT: Allocator,
+    {
+        self.check_per_device("pipelines", pipelines.len())?;
+        self.check_per_device("launch params buffers", launch_params.len())?;
+        self.check_per_device("shader binding tables", sbts.len())?;
+
+        for tile in tiles {
+            let device = self.device_for(tile)?;
+            device.make_current()?;
+            device.ctx.launch(
+                &pipelines[tile.device],
+                &device.stream,
+                &launch_params[tile.device],
+                &sbts[tile.device],
+                tile.width,
+                tile.height,
+                1,
+            )?;
+        }
+
+        Ok(())
+    }
+
+    /// Download each device's entry in `outputs` and copy it into its
+    /// tile's place in `frame`, a row-major image `frame_width` elements
+    /// wide.
+    ///
+    /// The downloads wait for the launches made by `launch()` to finish.
+    pub fn gather<'a, AllocT, T>(
+        &self,
+        tiles: &[Tile],
+        outputs: &[cuda::Buffer<'a, AllocT>],
+        frame_width: u32,
+        frame: &mut [T],
+    ) -> Result<()>
+    where
+        AllocT: Allocator,
+        T: Copy + Default,
+    {
+        self.check_per_device("output buffers", outputs.len())?;
+
+        for tile in tiles {
+            let device = self.device_for(tile)?;
+            device.make_current()?;
+            let mut data = vec![T::default(); tile.len()];
+            outputs[tile.device].download(&mut data)?;
+            gather_tile(frame, frame_width, tile, &data)?;
+        }
+
+        Ok(())
+    }
+
+    fn check_per_device(
+        &self,
+        what: &'static str,
+        actual: usize,
+    ) -> Result<()> {
+        if actual != self.devices.len() {
+            Err(Error::DeviceSetMismatch {
+                what,
+                expected: self.devices.len(),
+                actual,
+            })
+        } else {
+            Ok(())
+        }
+    }
+
+    fn device_for(&self, tile: &Tile) -> Result<&Device> {
+        self.devices
+            .get(tile.device)
+            .ok_or(Error::TileDeviceOutOfRange {
+                tile: *tile,
+                num_devices: self.devices.len(),
+            })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{gather_tile, split_launch, DeviceSet, Tile};
+    use crate::backend::mock::{fixtures, HandleKind, MockBackend};
+    use crate::cuda;
+    use crate::error::Error;
+    use crate::{
+        CompileDebugLevel, PipelineLinkOptions, ProgramGroupDesc,
+        ProgramGroupModule, SbtRecord, ShaderBindingTable,
+    };
+
+    #[test]
+    fn split_launch_covers_frame() {
+        for num_devices in 1..=5 {
+            for &height in &[0u32, 1, 3, 7, 480] {
+                let tiles = split_launch(640, height, num_devices);
+                assert_eq!(tiles.len(), num_devices.min(height as usize));
+
+                let mut y = 0;
+                for (tile, next) in tiles.iter().zip(tiles.iter().skip(1)) {
+                    assert!(tile.device < next.device);
+                }
+                for tile in &tiles {
+                    assert_eq!((tile.x, tile.y, tile.width), (0, y, 640));
+                    let even = height / num_devices as u32;
+                    assert!(tile.height == even || tile.height == even + 1);
+                    y += tile.height;
+                }
+                assert_eq!(y, height);
+            }
+        }
+        assert!(split_launch(0, 480, 2).is_empty());
+        assert!(split_launch(640, 480, 0).is_empty());
+    }
+
+    #[test]
+    fn gather_reassembles_frame() {
+        let (width, height) = (5u32, 7u32);
+        let mut frame = vec![0u32; (width * height) as usize];
+        for tile in split_launch(width, height, 3) {
+            // what the raygen program on the tile's device would write
+            let data: Vec<u32> = (0..tile.height)
+                .flat_map(|y| {
+                    (0..tile.width).map(move |x| (tile.y + y) * width + x)
+                })
+                .collect();
+            gather_tile(&mut frame, width, &tile, &data).unwrap();
+        }
+        assert_eq!(frame, (0..width * height).collect::<Vec<_>>());
+    }
+
+    #[test]
+    fn gather_tile_rejects_bad_tiles() {
+        let mut frame = vec![0u8; 16];
+        let tile = Tile {
+            device: 0,
+            x: 2,
+            y: 2,
+            width: 2,
+            height: 2,
+        };
+        match gather_tile(&mut frame, 4, &tile, &[1, 2, 3]) {
+            Err(Error::TileSizeMismatch {
+                expected: 4,
+                actual: 3,
+                ..
+            }) => (),
+            r => panic!("unexpected result {:?}", r),
+        }
+        match gather_tile(&mut frame, 3, &tile, &[1, 2, 3, 4]) {
+            Err(Error::TileOutsideFrame {
+                frame_width: 3,
+                frame_height: 5,
+                ..
+            }) => (),
+            r => panic!("unexpected result {:?}", r),
+        }
+        gather_tile(&mut frame, 4, &tile, &[1, 2, 3, 4]).unwrap();
+        assert_eq!(&frame[8..], &[0, 0, 1, 2, 0, 0, 3, 4]);
+    }
+
+    #[test]
+    fn launch_and_gather_on_simulated_devices() {
+        let mock = MockBackend::install();
+        mock.set_device_count(3);
+        let mut devices = DeviceSet::new(|_| None).unwrap();
+        assert_eq!(devices.len(), 3);
+        assert_eq!(mock.live_handles(HandleKind::DeviceContext), 3);
+
+        let pco = fixtures::pipeline_compile_options();
+        let modules = devices
+            .module_create_from_ptx(
+                fixtures::module_compile_options(),
+                &pco,
+                "",
+            )
+            .unwrap();
+        let raygens = devices
+            .replicate(|index, device| {
+                let (pg, _) = device.context_mut().program_group_create(
+                    ProgramGroupDesc::Raygen(ProgramGroupModule {
+                        module: modules[index].0.clone(),
+                        entry_function_name: ustr::ustr("__raygen__"),
+                    }),
+                )?;
+                Ok(pg)
+            })
+            .unwrap();
+        let link_options = PipelineLinkOptions {
+            max_trace_depth: 1,
+            debug_level: CompileDebugLevel::None,
+            #[cfg(not(optix_7_3))]
+            override_uses_motion_blur: false,
+        };
+        let program_groups: Vec<_> =
+            raygens.iter().map(|pg| vec![pg.clone()]).collect();
+        let pipelines: Vec<_> = devices
+            .pipeline_create(&pco, link_options, &program_groups)
+            .unwrap()
+            .into_iter()
+            .map(|(pipeline, _)| pipeline)
+            .collect();
+        assert_eq!(mock.live_handles(HandleKind::Pipeline), 3);
+
+        let alloc = cuda::Mallocator::new();
+        let sbts = devices
+            .replicate(|index, _| {
+                Ok(ShaderBindingTable::new(
+                    SbtRecord::new(0i32, raygens[index].clone()),
+                    0,
+                    &alloc,
+                )?
+                .build())
+            })
+            .unwrap();
+
+        let (width, height) = (4u32, 8u32);
+        let tiles = devices.tiles(width, height);
+        let launch_params = devices
+            .replicate(|index, _| {
+                let tile = tiles[index];
+                Ok(cuda::Buffer::with_data(&[tile.y], 4, 0, &alloc)?)
+            })
+            .unwrap();
+        devices
+            .launch(&tiles, &pipelines, &launch_params, &sbts)
+            .unwrap();
+
+        let launches = mock.launches();
+        assert_eq!(launches.len(), 3);
+        for (launch, tile) in launches.iter().zip(&tiles) {
+            assert_eq!(launch.device, tile.device as i32);
+            assert_eq!(launch.pipeline, pipelines[tile.device].pipeline);
+            assert_eq!(launch.params, tile.y.to_ne_bytes());
+            assert_eq!(
+                (launch.width, launch.height, launch.depth),
+                (tile.width, tile.height, 1)
+            );
+        }
+
+        // fill each device's output as its raygen program would have
+        let outputs = devices
+            .replicate(|index, _| {
+                let tile = tiles[index];
+                let data: Vec<u32> =
+                    (tile.y * width..(tile.y + tile.height) * width).collect();
+                Ok(cuda::Buffer::with_data(&data, 4, 0, &alloc)?)
+            })
+            .unwrap();
+        let mut frame = vec![0u32; (width * height) as usize];
+        devices.gather(&tiles, &outputs, width, &mut frame).unwrap();
+        assert_eq!(frame, (0..width * height).collect::<Vec<_>>());
+
+        match devices.launch(&tiles, &pipelines[..2], &launch_params, &sbts) {
+            Err(Error::DeviceSetMismatch {
+                what: "pipelines",
+                expected: 3,
+                actual: 2,
+            }) => (),
+            r => panic!("unexpected result {:?}", r.err()),
+        }
+    }
+
+    #[test]
+    fn devices_retain_primary_contexts() {
+        let mock = MockBackend::install();
+        mock.set_device_count(2);
+        let devices = DeviceSet::with_devices(&[1, 0], |_| None).unwrap();
+        assert_eq!(mock.primary_context_retains(0), 1);
+        assert_eq!(mock.primary_context_retains(1), 1);
+        assert_eq!(devices.devices()[0].ordinal(), 1);
+        drop(devices);
+        assert_eq!(mock.primary_context_retains(0), 0);
+        assert_eq!(mock.primary_context_retains(1), 0);
+    }
+
+    #[test]
+    fn empty_device_set() {
+        let _mock = MockBackend::install();
+        match DeviceSet::with_devices(&[], |_| None) {
+            Err(Error::NoDevices) => (),
+            r => panic!("unexpected result {:?}", r.err()),
+        }
+    }
+}
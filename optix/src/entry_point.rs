@@ -0,0 +1,483 @@
+//! Finding the programs in a module from the entry functions in its PTX.
+//!
+//! OptiX identifies the kind of a program by the prefix of its entry
+//! function's name, e.g. `__closesthit__radiance` is a closest hit program.
+//! For C++ functions, such as instantiations of templates, this is the
+//! prefix of the unqualified name within the mangled name, e.g.
+//! `_Z22__closesthit__radianceIfEvv` for `__closesthit__radiance<float>`.
+//! `parse_entry_points()` finds the `.entry` directives in PTX text and
+//! classifies them this way, and `ProgramGroupDesc::from_entry_points()`
+//! turns the result into program group descriptions.
+use super::module::ModuleRef;
+use super::program_group::{ProgramGroupDesc, ProgramGroupModule};
+
+/// The kind of an OptiX program, as given by the prefix of its name.
+#[derive(Debug, Copy, Clone, PartialEq, Eq, Hash)]
+pub enum ProgramKind {
+    Raygen,
+    Miss,
+    Exception,
+    ClosestHit,
+    AnyHit,
+    Intersection,
+    DirectCallable,
+    ContinuationCallable,
+}
+
+impl ProgramKind {
+    const ALL: [ProgramKind; 8] = [
+        ProgramKind::Raygen,
+        ProgramKind::Miss,
+        ProgramKind::Exception,
+        ProgramKind::ClosestHit,
+        ProgramKind::AnyHit,
+        ProgramKind::Intersection,
+        ProgramKind::DirectCallable,
+        ProgramKind::ContinuationCallable,
+    ];
+
+    /// The prefix OptiX requires the names of programs of this kind to have.
+    pub fn prefix(self) -> &'static str {
+        match self {
+            ProgramKind::Raygen => "__raygen__",
+            ProgramKind::Miss => "__miss__",
+            ProgramKind::Exception => "__exception__",
+            ProgramKind::ClosestHit => "__closesthit__",
+            ProgramKind::AnyHit => "__anyhit__",
+            ProgramKind::Intersection => "__intersection__",
+            ProgramKind::DirectCallable => "__direct_callable__",
+            ProgramKind::ContinuationCallable => "__continuation_callable__",
+        }
+    }
+
+    /// The kind of the program called `name`, if it has one of the prefixes.
+    /// `name` may be mangled.
+    pub fn from_name(name: &str) -> Option<ProgramKind> {
+        let name = function_name(name);
+        ProgramKind::ALL
+            .iter()
+            .copied()
+            .find(|kind| name.starts_with(kind.prefix()))
+    }
+}
+
+/// An entry function in a module's PTX.
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+pub struct EntryPoint {
+    pub name: String,
+    /// The kind of program, or `None` if the name has no OptiX prefix
+    pub kind: Option<ProgramKind>,
+}
+
+impl EntryPoint {
+    /// The part of the name after the prefix, e.g. `radiance` for
+    /// `__closesthit__radiance`, or the whole name if there is no prefix.
+    /// For mangled names this is the rest of the unqualified name, without
+    /// any template arguments.
+    pub fn suffix(&self) -> &str {
+        match self.kind {
+            Some(kind) => &function_name(&self.name)[kind.prefix().len()..],
+            None => &self.name,
+        }
+    }
+}
+
+/// The unqualified name of the function with the (Itanium ABI) mangled name
+/// `name`, e.g. `__closesthit__radiance` for `_Z22__closesthit__radianceIfEvv`
+/// or `_ZN6shader22__closesthit__radianceEv`, or `name` if it isn't mangled.
+///
+/// Only the name itself is demangled: qualifiers, template arguments and
+/// substitutions in the enclosing scopes are skipped rather than resolved.
+pub(crate) fn function_name(name: &str) -> &str {
+    let rest = match name.strip_prefix("_Z") {
+        Some(rest) => rest,
+        None => return name,
+    };
+    let (nested, mut rest) = match rest.strip_prefix('N') {
+        Some(rest) => {
+            // the cv- and ref-qualifiers of a member function
+            let rest = rest.trim_start_matches(&['r', 'V', 'K'][..]);
+            (true, rest.strip_prefix(&['R', 'O'][..]).unwrap_or(rest))
+        }
+        None => (false, rest),
+    };
+    // a sequence of <length><identifier> for the namespaces and function,
+    // with template arguments or substitutions in between
+    let mut last = None;
+    loop {
+        if let Some(after) = skip_substitution(rest) {
+            rest = after;
+        } else if rest.starts_with('I') {
+            match skip_template_args(rest) {
+                Some(after) => rest = after,
+                None => break,
+            }
+        } else {
+            match source_name(rest) {
+                Some((identifier, after)) => {
+                    last = Some(identifier);
+                    rest = after;
+                }
+                None => break,
+            }
+            if !nested {
+                break;
+            }
+        }
+    }
+    last.unwrap_or(name)
+}
+
+/// Split `<length><identifier>` off the start of `s`.
+fn source_name(s: &str) -> Option<(&str, &str)> {
+    let digits = s.bytes().take_while(u8::is_ascii_digit).count();
+    let len: usize = s[..digits].parse().ok()?;
+    let end = digits.checked_add(len)?;
+    let identifier = s.get(digits..end)?;
+    Some((identifier, &s[end..]))
+}
+
+/// Skip a substitution at the start of `s`: one of the `std` abbreviations
+/// such as `St` or `Sa`, or a reference `S_`/`S<seq-id>_` to an earlier
+/// component.
+fn skip_substitution(s: &str) -> Option<&str> {
+    let rest = s.strip_prefix('S')?;
+    match rest.bytes().next()? {
+        b't' | b'a' | b'b' | b's' | b'i' | b'o' | b'd' => Some(&rest[1..]),
+        c if c == b'_' || c.is_ascii_digit() || c.is_ascii_uppercase() => {
+            let end = rest.find('_')?;
+            Some(&rest[end + 1..])
+        }
+        _ => None,
+    }
+}
+
+/// Skip the template arguments `I...E` at the start of `s`, which may
+/// themselves contain nested names, template arguments and literals.
+fn skip_template_args(s: &str) -> Option<&str> {
+    let mut depth = 0;
+    let mut rest = s;
+    loop {
+        if let Some((_, after)) = source_name(rest) {
+            // identifiers may contain any of the letters matched below
+            rest = after;
+            continue;
+        }
+        // symbols in PTX aren't necessarily ASCII
+        let c = rest.chars().next()?;
+        rest = &rest[c.len_utf8()..];
+        match c {
+            // nested names, expressions and function types also end in `E`
+            'I' | 'N' | 'X' | 'F' => depth += 1,
+            // a literal, e.g. `Li1E`, whose value is not a length
+            'L' => rest = &rest[rest.find('E')? + 1..],
+            'E' => {
+                depth -= 1;
+                if depth == 0 {
+                    return Some(rest);
+                }
+            }
+            _ => (),
+        }
+    }
+}
+
+/// Find the entry functions declared with `.entry` or `.visible .entry`
+/// directives in `ptx`, in the order they appear.
+pub fn parse_entry_points(ptx: &str) -> Vec<EntryPoint> {
+    let mut entry_points: Vec<EntryPoint> = Vec::new();
+    for line in ptx.lines() {
+        let line = match line.find("//") {
+            Some(comment) => &line[..comment],
+            None => line,
+        };
+        let mut tokens = line.split_whitespace();
+        // the directive may be preceded by linking directives such as
+        // .visible or .weak
+        if tokens.by_ref().any(|token| token == ".entry") {
+            let name = match tokens.next() {
+                Some(token) => token.split('(').next().unwrap_or(token),
+                None => continue,
+            };
+            // an entry may be declared before it is defined
+            if !name.is_empty() && entry_points.iter().all(|e| e.name != name) {
+                entry_points.push(EntryPoint {
+                    name: name.to_string(),
+                    kind: ProgramKind::from_name(name),
+                });
+            }
+        }
+    }
+    entry_points
+}
+
+impl ProgramGroupDesc {
+    /// Describe a program group for each of the programs in `modules`.
+    ///
+    /// Every raygen and miss program gets a group of its own. Closest hit,
+    /// any hit and intersection programs with the same suffix are put
+    /// together in a hit group, e.g. `__closesthit__shadow` and
+    /// `__anyhit__shadow`, as are direct and continuation callables. The
+    /// groups are returned with the raygen groups first, then the miss,
+    /// hit and callables groups, each in the order their first program
+    /// appears in the modules. Exception programs and entry functions
+    /// without a prefix are left out.
+    pub fn from_entry_points(modules: &[ModuleRef]) -> Vec<ProgramGroupDesc> {
+        let mut raygen = Vec::new();
+        let mut miss = Vec::new();
+        // the programs of each group by suffix, in the order of the fields
+        // of the group
+        let mut hitgroups: Vec<(String, [Option<ProgramGroupModule>; 3])> =
+            Vec::new();
+        let mut callables: Vec<(String, [Option<ProgramGroupModule>; 2])> =
+            Vec::new();
+
+        fn add<'a, T: Default>(
+            groups: &'a mut Vec<(String, T)>,
+            suffix: &str,
+        ) -> &'a mut T {
+            let index = match groups.iter().position(|(s, _)| s == suffix) {
+                Some(index) => index,
+                None => {
+                    groups.push((suffix.to_string(), T::default()));
+                    groups.len() - 1
+                }
+            };
+            &mut groups[index].1
+        }
+
+        for module in modules {
+            for entry_point in module.entry_points() {
+                let pgm = ProgramGroupModule {
+                    module: ModuleRef::clone(module),
+                    entry_function_name: ustr::ustr(&entry_point.name),
+                };
+                let suffix = entry_point.suffix();
+                let slot = match entry_point.kind {
+                    Some(ProgramKind::Raygen) => {
+                        raygen.push(ProgramGroupDesc::Raygen(pgm));
+                        continue;
+                    }
+                    Some(ProgramKind::Miss) => {
+                        miss.push(ProgramGroupDesc::Miss(pgm));
+                        continue;
+                    }
+                    Some(ProgramKind::ClosestHit) => {
+                        &mut add(&mut hitgroups, suffix)[0]
+                    }
+                    Some(ProgramKind::AnyHit) => {
+                        &mut add(&mut hitgroups, suffix)[1]
+                    }
+                    Some(ProgramKind::Intersection) => {
+                        &mut add(&mut hitgroups, suffix)[2]
+                    }
+                    Some(ProgramKind::DirectCallable) => {
+                        &mut add(&mut callables, suffix)[0]
+                    }
+                    Some(ProgramKind::ContinuationCallable) => {
+                        &mut add(&mut callables, suffix)[1]
+                    }
+                    Some(ProgramKind::Exception) | None => continue,
+                };
+                // the first module that has a program wins
+                slot.get_or_insert(pgm);
+            }
+        }
+
+        let hitgroups = hitgroups
+            .into_iter()
+            .map(|(_, [ch, ah, is])| ProgramGroupDesc::Hitgroup { ch, ah, is });
+        let callables = callables
+            .into_iter()
+            .map(|(_, [dc, cc])| ProgramGroupDesc::Callables { dc, cc });
+        raygen
+            .into_iter()
+            .chain(miss)
+            .chain(hitgroups)
+            .chain(callables)
+            .collect()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{function_name, parse_entry_points, EntryPoint, ProgramKind};
+    use crate::backend::mock::{fixtures, MockBackend};
+    use crate::ProgramGroupDesc;
+
+    // in the format nvcc writes PTX for OptiX programs
+    const PTX: &str = r#"
+//
+// Generated by NVIDIA NVVM Compiler
+//
+.version 6.4
+.target sm_52
+.address_size 64
+
+	// .globl	__closesthit__shadow
+.const .align 8 .b8 optixLaunchParams[40];
+
+.visible .entry __closesthit__shadow()
+{
+	ret;
+}
+	// .globl	__closesthit__radiance
+.visible .entry __closesthit__radiance(
+
+)
+{
+	ret;
+}
+.entry __anyhit__shadow()
+{
+	ret;
+}
+.visible .entry __miss__radiance()
+{
+	ret;
+}
+.visible .entry __miss__shadow()
+{
+	ret;
+}
+.visible .entry __raygen__renderFrame()
+{
+	ret;
+}
+.weak .entry helper()
+{
+	ret;
+}
+.visible .func  (.param .b32 func_retval0) __direct_callable__not_an_entry(
+	.param .b32 x
+)
+{
+	ret;
+}
+"#;
+
+    #[test]
+    fn parse() {
+        let entry_points = parse_entry_points(PTX);
+        let names: Vec<&str> =
+            entry_points.iter().map(|e| e.name.as_str()).collect();
+        assert_eq!(
+            names,
+            [
+                "__closesthit__shadow",
+                "__closesthit__radiance",
+                "__anyhit__shadow",
+                "__miss__radiance",
+                "__miss__shadow",
+                "__raygen__renderFrame",
+                "helper",
+            ]
+        );
+        assert_eq!(entry_points[2].kind, Some(ProgramKind::AnyHit));
+        assert_eq!(entry_points[2].suffix(), "shadow");
+        assert_eq!(
+            entry_points[6],
+            EntryPoint {
+                name: "helper".into(),
+                kind: None,
+            }
+        );
+        assert!(parse_entry_points("").is_empty());
+    }
+
+    #[test]
+    fn mangled_names() {
+        assert_eq!(
+            function_name("_Z22__closesthit__radianceIfEvv"),
+            "__closesthit__radiance"
+        );
+        assert_eq!(
+            function_name("_ZN6shader2v116__miss__radianceEv"),
+            "__miss__radiance"
+        );
+        assert_eq!(
+            function_name("_ZNK6shader6Shader19__closesthit__shadeEv"),
+            "__closesthit__shade"
+        );
+        assert_eq!(
+            function_name("_ZNVKR6shader16__anyhit__shadowEv"),
+            "__anyhit__shadow"
+        );
+        assert_eq!(
+            function_name("_ZNSt16__miss__radianceEv"),
+            "__miss__radiance"
+        );
+        assert_eq!(
+            function_name("_ZSt17__raygen__pinholev"),
+            "__raygen__pinhole"
+        );
+        assert_eq!(function_name("_ZN1A1BIiE3fooEv"), "foo");
+        // template arguments containing names, literals and substitutions
+        assert_eq!(
+            function_name("_ZN1AI6EntityLi1ENS_1BIS0_EEE4bar_Ev"),
+            "bar_"
+        );
+        assert_eq!(
+            function_name("_ZN6shader22__closesthit__radianceIfEEvv"),
+            "__closesthit__radiance"
+        );
+        assert_eq!(function_name("__raygen__main"), "__raygen__main");
+        assert_eq!(function_name("_Z"), "_Z");
+        assert_eq!(function_name("_Z99__short"), "_Z99__short");
+        // non-ASCII and malformed symbols don't panic
+        assert_eq!(function_name("_ZN1AIéE3fooEv"), "foo");
+        assert_eq!(function_name("_ZN1AIé"), "A");
+        assert_eq!(function_name("_Zé"), "_Zé");
+        assert_eq!(
+            function_name("_Z18446744073709551615x"),
+            "_Z18446744073709551615x"
+        );
+
+        let entry_point = EntryPoint {
+            name: "_Z22__closesthit__radianceIfEvv".into(),
+            kind: ProgramKind::from_name("_Z22__closesthit__radianceIfEvv"),
+        };
+        assert_eq!(entry_point.kind, Some(ProgramKind::ClosestHit));
+        assert_eq!(entry_point.suffix(), "radiance");
+    }
+
+    #[test]
+    fn program_groups_from_entry_points() {
+        let _mock = MockBackend::install();
+        let mut ctx = fixtures::device_context();
+        let (module, _) = ctx
+            .module_create_from_ptx(
+                fixtures::module_compile_options(),
+                &fixtures::pipeline_compile_options(),
+                PTX,
+            )
+            .unwrap();
+        assert_eq!(module.ptx(), PTX);
+
+        let descs = ProgramGroupDesc::from_entry_points(&[module]);
+        let groups: Vec<(&str, Vec<String>)> = descs
+            .iter()
+            .map(|d| (d.kind_name(), d.entry_function_names()))
+            .collect();
+        assert_eq!(
+            groups,
+            [
+                ("raygen", vec!["__raygen__renderFrame".to_string()]),
+                ("miss", vec!["__miss__radiance".to_string()]),
+                ("miss", vec!["__miss__shadow".to_string()]),
+                (
+                    "hitgroup",
+                    vec![
+                        "__closesthit__shadow".to_string(),
+                        "__anyhit__shadow".to_string(),
+                    ]
+                ),
+                ("hitgroup", vec!["__closesthit__radiance".to_string()]),
+            ]
+        );
+        assert!(descs.iter().all(|d| d.validate().is_ok()));
+
+        let (pgs, _) = ctx.program_groups_create(&descs).unwrap();
+        assert_eq!(pgs.len(), 5);
+    }
+}
@@ -1,30 +1,202 @@
 use super::cuda;
 use optix_sys as sys;
 
+use super::compile_log::CompileLog;
 use super::BufferFormat;
 
 #[derive(Debug, thiserror::Error)]
 pub enum Error {
-    #[error("OptiX initialization failed")]
-    InitializationFailed { source: sys::Error },
-    #[error("Failed to create OptiX device context")]
+    #[error("OptiX initialization failed: {source:}")]
+    InitializationFailed { source: sys::LoadError },
+    #[error("Failed to create OptiX device context: {source:}")]
     DeviceContextCreateFailed { source: sys::Error },
-    #[error("A device context method failed")]
-    DeviceContextMethodFailed { source: sys::Error },
-    #[error("Failed to set disk cache path '{}'", "path.display()")]
+    #[error("optixDeviceContextCreate returned a null device context")]
+    DeviceContextCreateReturnedNull,
+    #[error("Failed to apply device context option {option:}: {source:}")]
+    DeviceContextOptionFailed {
+        option: &'static str,
+        source: Box<Error>,
+    },
+    #[error("Failed to get disk cache database sizes: {source:}")]
+    GetCacheDatabaseSizesFailed { source: sys::Error },
+    #[error("Failed to get whether the disk cache is enabled: {source:}")]
+    GetCacheEnabledFailed { source: sys::Error },
+    #[error("Failed to get disk cache location: {source:}")]
+    GetCacheLocationFailed { source: sys::Error },
+    #[error("Failed to get device property {property:}: {source:}")]
+    GetPropertyFailed {
+        source: sys::Error,
+        property: &'static str,
+    },
+    #[error("{limit:} of {value:} exceeds the device limit of {max:}")]
+    DeviceLimitExceeded {
+        limit: &'static str,
+        value: u64,
+        max: u64,
+    },
+    #[error(
+        "Failed to set disk cache database sizes to \
+         [{low_water_mark:}, {high_water_mark:}]: {source:}"
+    )]
+    SetCacheDatabaseSizesFailed {
+        source: sys::Error,
+        low_water_mark: usize,
+        high_water_mark: usize,
+    },
+    #[error("Failed to set disk cache enabled to {enabled:}: {source:}")]
+    SetCacheEnabledFailed { source: sys::Error, enabled: bool },
+    #[error(
+        "Disk cache path '{}' is not valid UTF-8 or contains a NUL byte",
+        .path.display()
+    )]
+    InvalidCacheLocation { path: std::path::PathBuf },
+    #[error(
+        "Failed to set disk cache path '{}': {source:}",
+        .path.display()
+    )]
     SetCacheLocationFailed {
         source: sys::Error,
         path: std::path::PathBuf,
     },
-    #[error("Module creation failed:\n{log:}")]
-    ModuleCreationFailed { source: sys::Error, log: String },
-    #[error("ProgramGroup creation failed:\n{log:}")]
-    ProgramGroupCreationFailed { source: sys::Error, log: String },
-    #[error("Pipeline creation failed:\n{log:}")]
-    PipelineCreationFailed { source: sys::Error, log: String },
-    #[error("OptiX launch failed")]
-    LaunchFailed { source: sys::Error },
-    #[error("CUDA error")]
+    #[error("Failed to set log callback at level {level:}: {source:}")]
+    SetLogCallbackFailed { source: sys::Error, level: u32 },
+    #[error(
+        "Module creation from {ptx_size:} bytes of PTX failed: \
+         {source:}\n{log:}"
+    )]
+    ModuleCreationFailed {
+        source: sys::Error,
+        log: CompileLog,
+        ptx_size: usize,
+    },
+    #[cfg(optix_7_3)]
+    #[error(
+        "Failed to get built-in intersection module for {primitive_type:?}: \
+         {source:}"
+    )]
+    BuiltinISModuleGetFailed {
+        source: sys::Error,
+        primitive_type: super::PrimitiveType,
+    },
+    #[error(
+        "Creation of {kind:} program group with entry functions [{}] failed: \
+         {source:}\n{log:}",
+        .entry_functions.join(", ")
+    )]
+    ProgramGroupCreationFailed {
+        source: sys::Error,
+        log: CompileLog,
+        kind: &'static str,
+        entry_functions: Vec<String>,
+    },
+    #[error(
+        "Creation of {num_program_groups:} program groups failed: \
+         {source:}\n{log:}"
+    )]
+    ProgramGroupsCreationFailed {
+        source: sys::Error,
+        log: CompileLog,
+        num_program_groups: usize,
+    },
+    #[error("Entry function name {name:} does not start with {prefix:}")]
+    InvalidEntryFunctionName { name: String, prefix: &'static str },
+    #[error("No lowered name for the name expression {expression:}")]
+    UnknownNameExpression { expression: String },
+    #[error("Program group description {index:} is invalid: {source:}")]
+    InvalidProgramGroupDesc { index: usize, source: Box<Error> },
+    #[error(
+        "The module of {entry_function:} was compiled with pipeline compile \
+         options that differ from the pipeline's in {}",
+        .fields.join(", ")
+    )]
+    PipelineCompileOptionsMismatch {
+        entry_function: String,
+        fields: Vec<&'static str>,
+    },
+    #[error(
+        "Creation of pipeline from {num_program_groups:} program groups \
+         failed: {source:}\n{log:}"
+    )]
+    PipelineCreationFailed {
+        source: sys::Error,
+        log: CompileLog,
+        num_program_groups: usize,
+    },
+    #[error(
+        "Failed to set pipeline stack sizes (direct callable from \
+         traversable: {direct_callable_stack_size_from_traversable:}, from \
+         state: {direct_callable_stack_size_from_state:}, continuation: \
+         {continuation_stack_size:}, max traversable graph depth: \
+         {max_traversable_graph_depth:}): {source:}"
+    )]
+    PipelineSetStackSizeFailed {
+        source: sys::Error,
+        direct_callable_stack_size_from_traversable: u32,
+        direct_callable_stack_size_from_state: u32,
+        continuation_stack_size: u32,
+        max_traversable_graph_depth: u32,
+    },
+    #[error(
+        "Cannot set the stack size of a pipeline while other references to \
+         it exist"
+    )]
+    PipelineShared,
+    #[error("Failed to read '{}': {source:}", .path.display())]
+    ReadSourceFailed {
+        source: std::io::Error,
+        path: std::path::PathBuf,
+    },
+    #[error("Compiling '{}' to PTX failed: {source:}", .path.display())]
+    PtxCompilationFailed {
+        path: std::path::PathBuf,
+        source: cuda::nvrtc::Error,
+    },
+    #[error("Failed to get program group stack sizes: {source:}")]
+    ProgramGroupGetStackSizeFailed { source: sys::Error },
+    #[error("Failed to pack SBT record header: {source:}")]
+    SbtRecordPackHeaderFailed { source: sys::Error },
+    #[error("Failed to upload {count:} {kind:} SBT records: {source:}")]
+    SbtRecordUploadFailed {
+        source: cuda::Error,
+        kind: &'static str,
+        count: usize,
+    },
+    #[error("OptiX launch of {width:}x{height:}x{depth:} failed: {source:}")]
+    LaunchFailed {
+        source: sys::Error,
+        width: u32,
+        height: u32,
+        depth: u32,
+    },
+    #[error("The device set has no devices")]
+    NoDevices,
+    #[error(
+        "Expected one of the {what:} per device in the set ({expected:}) \
+         but got {actual:}"
+    )]
+    DeviceSetMismatch {
+        what: &'static str,
+        expected: usize,
+        actual: usize,
+    },
+    #[error("{tile:?} is for a device outside the set of {num_devices:}")]
+    TileDeviceOutOfRange {
+        tile: super::device_set::Tile,
+        num_devices: usize,
+    },
+    #[error("{tile:?} has {expected:} elements but got {actual:}")]
+    TileSizeMismatch {
+        tile: super::device_set::Tile,
+        expected: usize,
+        actual: usize,
+    },
+    #[error("{tile:?} lies outside the {frame_width:}x{frame_height:} frame")]
+    TileOutsideFrame {
+        tile: super::device_set::Tile,
+        frame_width: u32,
+        frame_height: u32,
+    },
+    #[error("CUDA error: {source:}")]
     CudaError {
         #[from]
         source: cuda::Error,
@@ -33,15 +205,44 @@ pub enum Error {
     IncorrectVertexBufferFormat { format: super::BufferFormat },
     #[error("Incorrect index buffer format: {format:?}")]
     IncorrectIndexBufferFormat { format: super::BufferFormat },
-    #[error("Failed to compute accel memory usage")]
-    AccelComputeMemoryUsageFailed { source: sys::Error },
-    #[error("Failed to build accel")]
-    AccelBuildFailed { source: sys::Error },
-    #[error("Failed to compact accel")]
-    AccelCompactFailed { source: sys::Error },
+    #[error("Build input {index:} is invalid: {source:}")]
+    InvalidBuildInput { index: usize, source: Box<Error> },
+    #[error(
+        "Failed to compute accel memory usage for {num_build_inputs:} build \
+         inputs: {source:}"
+    )]
+    AccelComputeMemoryUsageFailed {
+        source: sys::Error,
+        num_build_inputs: usize,
+    },
+    #[error(
+        "Failed to build accel from {num_build_inputs:} build inputs: \
+         {source:}"
+    )]
+    AccelBuildFailed {
+        source: sys::Error,
+        num_build_inputs: usize,
+    },
+    #[error("Failed to compact accel into {output_size:} bytes: {source:}")]
+    AccelCompactFailed {
+        source: sys::Error,
+        output_size: usize,
+    },
     #[error("Buffer shape mismatch. Expected {e_format:?}x{e_count:}")]
     BufferShapeMismatch {
         e_format: BufferFormat,
         e_count: usize,
     },
+    #[cfg(optix_7_3)]
+    #[error("Curve has {num_vertices:} vertices but {num_widths:} widths")]
+    CurveWidthCountMismatch {
+        num_vertices: usize,
+        num_widths: usize,
+    },
+    #[cfg(optix_7_5)]
+    #[error("Sphere array has {num_centers:} centers but {num_radii:} radii")]
+    SphereRadiusCountMismatch {
+        num_centers: usize,
+        num_radii: usize,
+    },
 }
@@ -0,0 +1,251 @@
+//! Finding the headers a CUDA source includes, so they can be passed to
+//! NVRTC as `Header`s instead of being found through `-I` options.
+//!
+//! NVRTC looks up an included name in the headers it was given before
+//! searching include directories, so each header is registered under the
+//! name it's included by. An included name is looked up in the virtual
+//! files first, then, for `#include "..."`, in the directory of the file that
+//! includes it, and then in the search paths in order.
+//!
+//! The scan doesn't evaluate the preprocessor, so headers that are only
+//! included under some condition are resolved (and depended on) anyway, and
+//! a quoted include that isn't found isn't an error, since it may never be
+//! compiled. Such names are listed in `ResolvedIncludes::unresolved`.
+use super::cuda::nvrtc::Header;
+use super::error::Error;
+type Result<T, E = Error> = std::result::Result<T, E>;
+
+use std::collections::BTreeSet;
+use std::path::{Path, PathBuf};
+
+/// Resolves the includes of CUDA sources against search paths and virtual
+/// files.
+#[derive(Debug, Clone, Default)]
+pub struct IncludeResolver {
+    search_paths: Vec<PathBuf>,
+    virtual_files: Vec<Header>,
+}
+
+/// The headers found by `IncludeResolver::resolve()`.
+#[derive(Debug, Clone, Default)]
+pub struct ResolvedIncludes {
+    /// Every header included directly or indirectly, in the order they were
+    /// found, named as they're included
+    pub headers: Vec<Header>,
+    /// The files on disk the headers were read from, which a program
+    /// compiled with them needs to be rebuilt for when they change
+    pub dependencies: BTreeSet<PathBuf>,
+    /// Names in `#include "..."` directives that weren't found
+    pub unresolved: Vec<String>,
+}
+
+/// Where an included name was found.
+enum Resolved<'a> {
+    Virtual(&'a Header),
+    File(PathBuf),
+}
+
+impl IncludeResolver {
+    pub fn new() -> IncludeResolver {
+        IncludeResolver::default()
+    }
+
+    /// Add a directory to look for included files in. Directories are
+    /// searched in the order they're added.
+    pub fn search_path<P: AsRef<Path>>(mut self, dir: P) -> Self {
+        self.search_paths.push(dir.as_ref().to_path_buf());
+        self
+    }
+
+    /// Add a file that isn't on disk, such as a header generated from Rust
+    /// types or embedded with `include_str!()`, which is found by its exact
+    /// `name` before anything else.
+    pub fn virtual_file<N: Into<String>, C: Into<String>>(
+        mut self,
+        name: N,
+        contents: C,
+    ) -> Self {
+        let name = name.into();
+        self.virtual_files.retain(|h| h.name != name);
+        self.virtual_files.push(Header {
+            name,
+            contents: contents.into(),
+        });
+        self
+    }
+
+    /// Find the headers included by `source`, which is in the directory
+    /// `dir`, or isn't a file if `dir` is `None`.
+    ///
+    /// # Errors
+    /// If an included file can't be read.
+    pub fn resolve(
+        &self,
+        source: &str,
+        dir: Option<&Path>,
+    ) -> Result<ResolvedIncludes> {
+        let mut resolved = ResolvedIncludes::default();
+        // the headers still to scan, as an index into resolved.headers and
+        // the directory the header is in
+        let mut pending: Vec<(Option<usize>, Option<PathBuf>)> =
+            vec![(None, dir.map(Path::to_path_buf))];
+
+        while let Some((index, dir)) = pending.pop() {
+            let contents = match index {
+                Some(index) => resolved.headers[index].contents.clone(),
+                None => source.to_string(),
+            };
+            for (name, quoted) in includes(&contents) {
+                if resolved.headers.iter().any(|h| h.name == name) {
+                    continue;
+                }
+                let dir = if quoted { dir.as_deref() } else { None };
+                let (header, dir) = match self.find(name, dir) {
+                    Some(Resolved::Virtual(header)) => (header.clone(), None),
+                    Some(Resolved::File(path)) => {
+                        let contents = std::fs::read_to_string(&path).map_err(
+                            |source| Error::ReadSourceFailed {
+                                path: path.clone(),
+                                source,
+                            },
+                        )?;
+                        let path = path.canonicalize().unwrap_or(path);
+                        let dir = path.parent().map(Path::to_path_buf);
+                        resolved.dependencies.insert(path);
+                        let header = Header {
+                            name: name.to_string(),
+                            contents,
+                        };
+                        (header, dir)
+                    }
+                    None => {
+                        // system headers are left to NVRTC
+                        if quoted
+                            && !resolved.unresolved.iter().any(|n| n == name)
+                        {
+                            resolved.unresolved.push(name.to_string());
+                        }
+                        continue;
+                    }
+                };
+                resolved.headers.push(header);
+                pending.push((Some(resolved.headers.len() - 1), dir));
+            }
+        }
+        Ok(resolved)
+    }
+
+    fn find(&self, name: &str, dir: Option<&Path>) -> Option<Resolved<'_>> {
+        if let Some(header) = self.virtual_files.iter().find(|h| h.name == name)
+        {
+            return Some(Resolved::Virtual(header));
+        }
+        dir.into_iter()
+            .chain(self.search_paths.iter().map(PathBuf::as_path))
+            .map(|dir| dir.join(name))
+            .find(|path| path.is_file())
+            .map(Resolved::File)
+    }
+}
+
+/// The names in the `#include` directives in `source`, and whether each is
+/// quoted (as opposed to in angle brackets).
+fn includes(source: &str) -> Vec<(&str, bool)> {
+    source
+        .lines()
+        .filter_map(|line| {
+            let rest = line.trim_start().strip_prefix('#')?;
+            let rest = rest.trim_start().strip_prefix("include")?.trim_start();
+            let (close, quoted) = match rest.chars().next()? {
+                '"' => ('"', true),
+                '<' => ('>', false),
+                _ => return None,
+            };
+            let rest = &rest[1..];
+            rest.find(close).map(|end| (&rest[..end], quoted))
+        })
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{includes, IncludeResolver};
+    use std::fs;
+    use std::path::PathBuf;
+
+    #[test]
+    fn parse_includes() {
+        let source = r#"
+#include <optix.h>
+#include "launch_params.h"
+  #  include   "common/vec.h" // trailing comment
+// #include "commented.h"
+#include MACRO_HEADER
+#pragma once
+"#;
+        assert_eq!(
+            includes(source),
+            [
+                ("optix.h", false),
+                ("launch_params.h", true),
+                ("common/vec.h", true),
+            ]
+        );
+    }
+
+    #[test]
+    fn resolve() {
+        let root = std::env::temp_dir()
+            .join(format!("optix-rs-includes-{}", std::process::id()));
+        let _ = fs::remove_dir_all(&root);
+        let src = root.join("src");
+        let common = root.join("common");
+        fs::create_dir_all(&src).unwrap();
+        fs::create_dir_all(&common).unwrap();
+        let write = |path: PathBuf, contents: &str| {
+            fs::write(&path, contents).unwrap();
+            path.canonicalize().unwrap()
+        };
+        // found next to the source before the search paths
+        let params = write(
+            src.join("launch_params.h"),
+            "#pragma once\n#include \"vec.h\"\n#include \"generated.h\"\n",
+        );
+        write(common.join("launch_params.h"), "// shadowed\n");
+        let vec = write(
+            common.join("vec.h"),
+            "#pragma once\n#include \"launch_params.h\"\n\
+             #include \"missing.h\"\n",
+        );
+
+        let resolver = IncludeResolver::new()
+            .search_path(&common)
+            .virtual_file("generated.h", "// old")
+            .virtual_file("generated.h", "struct Generated {};");
+        let resolved = resolver
+            .resolve(
+                "#include <optix.h>\n#include \"launch_params.h\"\n",
+                Some(&src),
+            )
+            .unwrap();
+
+        let names: Vec<&str> =
+            resolved.headers.iter().map(|h| h.name.as_str()).collect();
+        assert_eq!(names, ["launch_params.h", "vec.h", "generated.h"]);
+        assert!(resolved.headers[0].contents.contains("vec.h"));
+        assert_eq!(resolved.headers[2].contents, "struct Generated {};");
+        assert_eq!(
+            resolved.dependencies.iter().collect::<Vec<_>>(),
+            [&vec, &params]
+        );
+        assert_eq!(resolved.unresolved, ["missing.h"]);
+
+        // without a directory for the source, the search paths are used
+        let resolved = resolver
+            .resolve("#include \"launch_params.h\"\n", None)
+            .unwrap();
+        assert_eq!(resolved.headers[0].contents, "// shadowed\n");
+
+        fs::remove_dir_all(&root).unwrap();
+    }
+}
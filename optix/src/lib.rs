@@ -3,23 +3,41 @@ extern crate bitflags;
 
 use optix_sys as sys;
 
+pub mod backend;
+
 pub mod cuda;
 use cuda::Allocator;
 
 pub mod error;
 pub use error::Error;
-type Result<T, E = Error> = std::result::Result<T, E>;
+/// Result type returned by every fallible call in this crate.
+pub type Result<T, E = Error> = std::result::Result<T, E>;
 
 type Ref<T> = std::sync::Arc<T>;
 
 pub mod device_context;
-pub use device_context::DeviceContext;
+pub use device_context::{
+    DeviceContext, DeviceProperties, ObjectCount, ObjectCounts,
+};
+
+pub mod device_set;
+pub use device_set::{DeviceSet, Tile};
+
+pub mod compile_log;
+pub use compile_log::{CompileLog, Diagnostic, Severity};
 
 pub mod module;
 pub use module::{
     CompileDebugLevel, CompileOptimizationLevel, Module, ModuleCompileOptions,
     ModuleRef, PipelineCompileOptions,
 };
+#[cfg(optix_7_3)]
+pub use module::{BuiltinISOptions, PrimitiveType, PrimitiveTypeFlags};
+#[cfg(optix_7_4)]
+pub use module::{PayloadSemantics, PayloadType};
+
+pub mod entry_point;
+pub use entry_point::{EntryPoint, ProgramKind};
 
 pub mod program_group;
 pub use program_group::{
@@ -29,11 +47,22 @@ pub use program_group::{
 pub mod pipeline;
 pub use pipeline::{PipelineLinkOptions, PipelineRef};
 
+pub mod stack_size;
+pub use stack_size::{PipelineStackSize, StackSizes};
+
 pub mod shader_binding_table;
 pub use shader_binding_table::{
-    SbtData, SbtRecord, ShaderBindingTable, ShaderBindingTableBuilder,
+    SbtData, SbtRecord, SbtRecordHeader, ShaderBindingTable,
+    ShaderBindingTableBuilder,
 };
 
+pub mod reload;
+pub use reload::{PipelineReloader, ReloadLog, ReloadedPrograms};
+pub mod include_resolver;
+pub use include_resolver::{IncludeResolver, ResolvedIncludes};
+pub mod ptx_cache;
+pub use ptx_cache::{CacheStats, PtxCache};
+
 pub mod acceleration;
 pub use acceleration::*;
 
@@ -50,15 +79,20 @@ pub mod math;
 
 /// Initialize the OptiX library function table. This function *MUST* be called
 /// before any other optix functions.
+///
+/// If the driver's OptiX library cannot be loaded the returned error says
+/// which library was missing, or which driver version rejected our ABI
+/// version, so callers can fall back to running without OptiX.
 pub fn init() -> Result<()> {
-    unsafe {
-        let res = sys::optixInit();
-        if res != sys::OptixResult::OPTIX_SUCCESS {
-            return Err(Error::InitializationFailed { source: res.into() });
-        }
+    sys::loader::load()
+        .map_err(|source| Error::InitializationFailed { source })
+}
 
-        Ok(())
-    }
+/// Initialize the OptiX library function table from the library at `library`
+/// rather than the one installed by the driver.
+pub fn init_with_library<P: AsRef<std::ffi::OsStr>>(library: P) -> Result<()> {
+    sys::loader::load_from(library)
+        .map_err(|source| Error::InitializationFailed { source })
 }
 
 /// Trait to represent a type that can convert itself to a CUDA-compatible
@@ -321,6 +355,28 @@ where
     }
 }
 
+impl<T> DeviceShareable for std::sync::Arc<T>
+where
+    T: DeviceShareable,
+{
+    type Target = T::Target;
+    fn to_device(&self) -> T::Target {
+        (**self).to_device()
+    }
+
+    fn cuda_type() -> String {
+        T::cuda_type()
+    }
+
+    fn cuda_decl() -> String {
+        T::cuda_decl()
+    }
+
+    fn zero() -> Self::Target {
+        T::zero()
+    }
+}
+
 impl<T> DeviceShareable for std::rc::Rc<std::cell::RefCell<T>>
 where
     T: DeviceShareable,
@@ -598,6 +654,24 @@ where
     }
 }
 
+/// Embed the PTX compiled from `<name>.cu` by `optix_build::PtxBuilder` in
+/// the crate's build script, as a `&'static str`.
+///
+/// ```ignore
+/// let ptx = optix::include_ptx!("devicePrograms");
+/// let (module, log) = ctx.module_create_from_ptx(
+///     module_compile_options,
+///     &pipeline_compile_options,
+///     ptx,
+/// )?;
+/// ```
+#[macro_export]
+macro_rules! include_ptx {
+    ($name:literal) => {
+        include_str!(concat!(env!("OUT_DIR"), "/", $name, ".ptx"))
+    };
+}
+
 /// Macro to generate a newtype wrapper with DeviceShareable and Deref
 /// implemented
 #[macro_export]
@@ -695,4 +769,28 @@ mod tests {
         C,
         D,
     }
+
+    #[test]
+    fn thread_safety() {
+        fn assert_send_sync<T: Send + Sync>() {}
+
+        assert_send_sync::<super::DeviceContext>();
+        assert_send_sync::<super::ModuleRef>();
+        assert_send_sync::<super::ProgramGroupRef>();
+        assert_send_sync::<super::PipelineRef>();
+        assert_send_sync::<super::cuda::ContextRef>();
+        assert_send_sync::<super::cuda::Stream>();
+        assert_send_sync::<super::cuda::SyncTaggedAllocator>();
+        assert_send_sync::<
+            super::TriangleArray<
+                'static,
+                super::cuda::SyncTaggedAllocator,
+                super::math::V3f32,
+                super::math::V3i32,
+            >,
+        >();
+        assert_send_sync::<
+            super::TraversableHandle<'static, super::cuda::SyncTaggedAllocator>,
+        >();
+    }
 }
@@ -1,11 +1,15 @@
+use crate::backend;
 use optix_sys as sys;
 
 use super::error::Error;
 type Result<T, E = Error> = std::result::Result<T, E>;
 
+use super::compile_log::{create_with_log, CompileLog};
 pub use super::device_context::DeviceContext;
+use super::device_context::SharedContext;
+use super::entry_point::{parse_entry_points, EntryPoint};
 
-use std::ffi::{CStr, CString};
+use std::ffi::CString;
 use ustr::Ustr;
 
 #[repr(u32)]
@@ -24,28 +28,94 @@ pub enum CompileOptimizationLevel {
 #[repr(u32)]
 #[derive(Debug, Hash, PartialEq, Copy, Clone)]
 pub enum CompileDebugLevel {
+    #[cfg(optix_7_4)]
+    Default = sys::OptixCompileDebugLevel::OPTIX_COMPILE_DEBUG_LEVEL_DEFAULT,
     None = sys::OptixCompileDebugLevel::OPTIX_COMPILE_DEBUG_LEVEL_NONE,
+    #[cfg(not(optix_7_4))]
     LineInfo = sys::OptixCompileDebugLevel::OPTIX_COMPILE_DEBUG_LEVEL_LINEINFO,
+    #[cfg(optix_7_4)]
+    Minimal = sys::OptixCompileDebugLevel::OPTIX_COMPILE_DEBUG_LEVEL_MINIMAL,
+    #[cfg(optix_7_4)]
+    Moderate = sys::OptixCompileDebugLevel::OPTIX_COMPILE_DEBUG_LEVEL_MODERATE,
     FULL = sys::OptixCompileDebugLevel::OPTIX_COMPILE_DEBUG_LEVEL_FULL,
 }
 
-#[derive(Debug, Hash, PartialEq, Copy, Clone)]
+#[cfg(optix_7_4)]
+bitflags! {
+    /// How each stage accesses a payload value. See `PayloadType`.
+    pub struct PayloadSemantics: u32 {
+        const TRACE_CALLER_READ = sys::OptixPayloadSemantics_OPTIX_PAYLOAD_SEMANTICS_TRACE_CALLER_READ;
+        const TRACE_CALLER_WRITE = sys::OptixPayloadSemantics_OPTIX_PAYLOAD_SEMANTICS_TRACE_CALLER_WRITE;
+        const TRACE_CALLER_READ_WRITE = sys::OptixPayloadSemantics_OPTIX_PAYLOAD_SEMANTICS_TRACE_CALLER_READ_WRITE;
+        const CH_READ = sys::OptixPayloadSemantics_OPTIX_PAYLOAD_SEMANTICS_CH_READ;
+        const CH_WRITE = sys::OptixPayloadSemantics_OPTIX_PAYLOAD_SEMANTICS_CH_WRITE;
+        const CH_READ_WRITE = sys::OptixPayloadSemantics_OPTIX_PAYLOAD_SEMANTICS_CH_READ_WRITE;
+        const MS_READ = sys::OptixPayloadSemantics_OPTIX_PAYLOAD_SEMANTICS_MS_READ;
+        const MS_WRITE = sys::OptixPayloadSemantics_OPTIX_PAYLOAD_SEMANTICS_MS_WRITE;
+        const MS_READ_WRITE = sys::OptixPayloadSemantics_OPTIX_PAYLOAD_SEMANTICS_MS_READ_WRITE;
+        const AH_READ = sys::OptixPayloadSemantics_OPTIX_PAYLOAD_SEMANTICS_AH_READ;
+        const AH_WRITE = sys::OptixPayloadSemantics_OPTIX_PAYLOAD_SEMANTICS_AH_WRITE;
+        const AH_READ_WRITE = sys::OptixPayloadSemantics_OPTIX_PAYLOAD_SEMANTICS_AH_READ_WRITE;
+        const IS_READ = sys::OptixPayloadSemantics_OPTIX_PAYLOAD_SEMANTICS_IS_READ;
+        const IS_WRITE = sys::OptixPayloadSemantics_OPTIX_PAYLOAD_SEMANTICS_IS_WRITE;
+        const IS_READ_WRITE = sys::OptixPayloadSemantics_OPTIX_PAYLOAD_SEMANTICS_IS_READ_WRITE;
+    }
+}
+
+/// Describes the layout of a ray payload: one `PayloadSemantics` entry per
+/// payload value.
+///
+/// When a module declares payload types the `num_payload_values` of the
+/// `PipelineCompileOptions` must be 0.
+#[cfg(optix_7_4)]
+#[derive(Debug, Hash, PartialEq, Clone)]
+pub struct PayloadType {
+    pub semantics: Vec<PayloadSemantics>,
+}
+
+#[cfg(optix_7_4)]
+impl PayloadType {
+    pub(crate) fn semantics_bits(&self) -> Vec<u32> {
+        self.semantics.iter().map(|s| s.bits()).collect()
+    }
+}
+
+#[cfg_attr(not(optix_7_4), derive(Copy))]
+#[derive(Debug, Hash, PartialEq, Clone)]
 pub struct ModuleCompileOptions {
     pub max_register_count: i32,
     pub opt_level: CompileOptimizationLevel,
     pub debug_level: CompileDebugLevel,
+    #[cfg(optix_7_4)]
+    pub payload_types: Vec<PayloadType>,
 }
 
-impl From<ModuleCompileOptions> for sys::OptixModuleCompileOptions {
-    fn from(o: ModuleCompileOptions) -> sys::OptixModuleCompileOptions {
+/// Converts everything but the payload types, which need storage that
+/// outlives the returned struct and are filled in by the caller.
+impl From<&ModuleCompileOptions> for sys::OptixModuleCompileOptions {
+    fn from(o: &ModuleCompileOptions) -> sys::OptixModuleCompileOptions {
         sys::OptixModuleCompileOptions {
             maxRegisterCount: o.max_register_count,
             optLevel: o.opt_level as u32,
             debugLevel: o.debug_level as u32,
+            #[cfg(optix_7_3)]
+            boundValues: std::ptr::null(),
+            #[cfg(optix_7_3)]
+            numBoundValues: 0,
+            #[cfg(optix_7_4)]
+            numPayloadTypes: 0,
+            #[cfg(optix_7_4)]
+            payloadTypes: std::ptr::null_mut(),
         }
     }
 }
 
+impl From<ModuleCompileOptions> for sys::OptixModuleCompileOptions {
+    fn from(o: ModuleCompileOptions) -> sys::OptixModuleCompileOptions {
+        (&o).into()
+    }
+}
+
 bitflags! {
     pub struct TraversableGraphFlags: u32 {
         const ALLOW_ANY = sys::OptixTraversableGraphFlags::OPTIX_TRAVERSABLE_GRAPH_FLAG_ALLOW_ANY;
@@ -64,6 +134,43 @@ bitflags! {
     }
 }
 
+#[cfg(optix_7_3)]
+bitflags! {
+    /// The primitive types a pipeline will trace against. Leaving this empty
+    /// is the same as `CUSTOM | TRIANGLE`.
+    ///
+    /// `TRIANGLE` is `1 << 31` so the SDK's enum is signed, hence the casts.
+    pub struct PrimitiveTypeFlags: u32 {
+        const CUSTOM = sys::OptixPrimitiveTypeFlags_OPTIX_PRIMITIVE_TYPE_FLAGS_CUSTOM as u32;
+        const ROUND_QUADRATIC_BSPLINE = sys::OptixPrimitiveTypeFlags_OPTIX_PRIMITIVE_TYPE_FLAGS_ROUND_QUADRATIC_BSPLINE as u32;
+        const ROUND_CUBIC_BSPLINE = sys::OptixPrimitiveTypeFlags_OPTIX_PRIMITIVE_TYPE_FLAGS_ROUND_CUBIC_BSPLINE as u32;
+        const ROUND_LINEAR = sys::OptixPrimitiveTypeFlags_OPTIX_PRIMITIVE_TYPE_FLAGS_ROUND_LINEAR as u32;
+        #[cfg(optix_7_4)]
+        const ROUND_CATMULLROM = sys::OptixPrimitiveTypeFlags_OPTIX_PRIMITIVE_TYPE_FLAGS_ROUND_CATMULLROM as u32;
+        #[cfg(optix_7_5)]
+        const SPHERE = sys::OptixPrimitiveTypeFlags_OPTIX_PRIMITIVE_TYPE_FLAGS_SPHERE as u32;
+        const TRIANGLE = sys::OptixPrimitiveTypeFlags_OPTIX_PRIMITIVE_TYPE_FLAGS_TRIANGLE as u32;
+    }
+}
+
+/// The primitive types that are intersected by one of the built-in
+/// intersection modules. See `DeviceContext::builtin_is_module_get()`.
+#[cfg(optix_7_3)]
+#[repr(u32)]
+#[derive(Debug, Hash, PartialEq, Copy, Clone)]
+pub enum PrimitiveType {
+    RoundQuadraticBSpline =
+        sys::OptixPrimitiveType_OPTIX_PRIMITIVE_TYPE_ROUND_QUADRATIC_BSPLINE,
+    RoundCubicBSpline =
+        sys::OptixPrimitiveType_OPTIX_PRIMITIVE_TYPE_ROUND_CUBIC_BSPLINE,
+    RoundLinear = sys::OptixPrimitiveType_OPTIX_PRIMITIVE_TYPE_ROUND_LINEAR,
+    #[cfg(optix_7_4)]
+    RoundCatmullRom =
+        sys::OptixPrimitiveType_OPTIX_PRIMITIVE_TYPE_ROUND_CATMULLROM,
+    #[cfg(optix_7_5)]
+    Sphere = sys::OptixPrimitiveType_OPTIX_PRIMITIVE_TYPE_SPHERE,
+}
+
 #[derive(Debug, Hash, PartialEq, Clone)]
 pub struct PipelineCompileOptions {
     pub uses_motion_blur: bool,
@@ -72,10 +179,119 @@ pub struct PipelineCompileOptions {
     pub num_attribute_values: i32,
     pub exception_flags: ExceptionFlags,
     pub pipeline_launch_params_variable_name: Ustr,
+    #[cfg(optix_7_3)]
+    pub uses_primitive_type_flags: PrimitiveTypeFlags,
+}
+
+impl PipelineCompileOptions {
+    /// The names of the fields that differ between `self` and `other`.
+    pub fn differences(
+        &self,
+        other: &PipelineCompileOptions,
+    ) -> Vec<&'static str> {
+        let mut fields = Vec::new();
+        let mut check = |differs: bool, field| {
+            if differs {
+                fields.push(field);
+            }
+        };
+        check(
+            self.uses_motion_blur != other.uses_motion_blur,
+            "uses_motion_blur",
+        );
+        check(
+            self.traversable_graph_flags != other.traversable_graph_flags,
+            "traversable_graph_flags",
+        );
+        check(
+            self.num_payload_values != other.num_payload_values,
+            "num_payload_values",
+        );
+        check(
+            self.num_attribute_values != other.num_attribute_values,
+            "num_attribute_values",
+        );
+        check(
+            self.exception_flags != other.exception_flags,
+            "exception_flags",
+        );
+        check(
+            self.pipeline_launch_params_variable_name
+                != other.pipeline_launch_params_variable_name,
+            "pipeline_launch_params_variable_name",
+        );
+        #[cfg(optix_7_3)]
+        check(
+            self.uses_primitive_type_flags != other.uses_primitive_type_flags,
+            "uses_primitive_type_flags",
+        );
+        fields
+    }
+}
+
+/// The launch params variable name is interned, so the pointer to it stays
+/// valid for as long as the returned options are used.
+impl From<&PipelineCompileOptions> for sys::OptixPipelineCompileOptions {
+    fn from(o: &PipelineCompileOptions) -> sys::OptixPipelineCompileOptions {
+        sys::OptixPipelineCompileOptions {
+            usesMotionBlur: o.uses_motion_blur as i32,
+            traversableGraphFlags: o.traversable_graph_flags.bits(),
+            numPayloadValues: o.num_payload_values,
+            numAttributeValues: o.num_attribute_values,
+            exceptionFlags: o.exception_flags.bits(),
+            pipelineLaunchParamsVariableName: o
+                .pipeline_launch_params_variable_name
+                .as_char_ptr(),
+            #[cfg(optix_7_3)]
+            usesPrimitiveTypeFlags: o.uses_primitive_type_flags.bits(),
+        }
+    }
 }
 
 pub struct Module {
     pub(crate) module: sys::OptixModule,
+    // built-in intersection modules belong to the device context and must
+    // not be destroyed
+    pub(crate) builtin: bool,
+    // empty for built-in modules
+    ptx: String,
+    pipeline_compile_options: PipelineCompileOptions,
+    context: super::Ref<SharedContext>,
+}
+
+impl Module {
+    /// The PTX the module was created from.
+    pub fn ptx(&self) -> &str {
+        &self.ptx
+    }
+
+    /// The pipeline compile options the module was compiled with, which
+    /// every module in a pipeline must share with the pipeline.
+    pub fn pipeline_compile_options(&self) -> &PipelineCompileOptions {
+        &self.pipeline_compile_options
+    }
+
+    /// The entry functions in the module's PTX, in the order they appear.
+    /// Built-in intersection modules have none.
+    pub fn entry_points(&self) -> Vec<EntryPoint> {
+        parse_entry_points(&self.ptx)
+    }
+}
+
+// A module is immutable once created and OptiX allows it to be used from any
+// thread.
+unsafe impl Send for Module {}
+unsafe impl Sync for Module {}
+
+impl Drop for Module {
+    fn drop(&mut self) {
+        if !self.builtin {
+            unsafe {
+                backend::optix_module_destroy(self.module);
+            }
+            self.context.modules.destroyed();
+        }
+    }
 }
 
 pub type ModuleRef = super::Ref<Module>;
@@ -86,62 +302,128 @@ impl DeviceContext {
         module_compile_options: ModuleCompileOptions,
         pipeline_compile_options: &PipelineCompileOptions,
         ptx: &str,
-    ) -> Result<(ModuleRef, String)> {
+    ) -> Result<(ModuleRef, CompileLog)> {
         let cptx = CString::new(ptx).unwrap();
-        let mut log = [0u8; 4096];
-        let mut log_len = log.len();
 
-        let launch_param = CString::new(
-            pipeline_compile_options
-                .pipeline_launch_params_variable_name
-                .as_str(),
-        )
-        .unwrap();
-
-        let popt = sys::OptixPipelineCompileOptions {
-            usesMotionBlur: if pipeline_compile_options.uses_motion_blur {
-                1
-            } else {
-                0
+        let popt: sys::OptixPipelineCompileOptions =
+            pipeline_compile_options.into();
+
+        #[allow(unused_mut)]
+        let mut mopt: sys::OptixModuleCompileOptions =
+            (&module_compile_options).into();
+
+        #[cfg(optix_7_4)]
+        let payload_semantics: Vec<Vec<u32>> = module_compile_options
+            .payload_types
+            .iter()
+            .map(|t| t.semantics_bits())
+            .collect();
+        #[cfg(optix_7_4)]
+        let mut payload_types: Vec<sys::OptixPayloadType> = payload_semantics
+            .iter()
+            .map(|s| sys::OptixPayloadType {
+                numPayloadValues: s.len() as u32,
+                payloadSemantics: s.as_ptr(),
+            })
+            .collect();
+        #[cfg(optix_7_4)]
+        {
+            mopt.numPayloadTypes = payload_types.len() as u32;
+            mopt.payloadTypes = payload_types.as_mut_ptr();
+        }
+
+        let (res, module, log) = create_with_log(
+            |log, log_len| unsafe {
+                let mut module = std::ptr::null_mut();
+                let res = backend::optix_module_create_from_ptx(
+                    self.ctx,
+                    &mopt,
+                    &popt,
+                    cptx.as_ptr(),
+                    cptx.as_bytes().len(),
+                    log,
+                    log_len,
+                    &mut module,
+                );
+                (res, module)
             },
-            traversableGraphFlags: pipeline_compile_options
-                .traversable_graph_flags
-                .bits(),
-            numPayloadValues: pipeline_compile_options.num_payload_values,
-            numAttributeValues: pipeline_compile_options.num_attribute_values,
-            exceptionFlags: pipeline_compile_options.exception_flags.bits(),
-            pipelineLaunchParamsVariableName: launch_param.as_ptr(),
-        };
+            |module| unsafe {
+                backend::optix_module_destroy(module);
+            },
+        );
+
+        if res != sys::OptixResult::OPTIX_SUCCESS {
+            return Err(Error::ModuleCreationFailed {
+                source: res.into(),
+                log,
+                ptx_size: ptx.len(),
+            });
+        }
+
+        self.shared.modules.created();
+        let module = super::Ref::new(Module {
+            module,
+            builtin: false,
+            ptx: ptx.to_string(),
+            pipeline_compile_options: pipeline_compile_options.clone(),
+            context: super::Ref::clone(&self.shared),
+        });
+        Ok((module, log))
+    }
+
+    /// Returns a module containing the built-in intersection program for
+    /// `options.primitive_type`, for use as the `is` program of a hit group.
+    #[cfg(optix_7_3)]
+    pub fn builtin_is_module_get(
+        &mut self,
+        module_compile_options: ModuleCompileOptions,
+        pipeline_compile_options: &PipelineCompileOptions,
+        options: BuiltinISOptions,
+    ) -> Result<ModuleRef> {
+        let popt: sys::OptixPipelineCompileOptions =
+            pipeline_compile_options.into();
+
+        let mopt: sys::OptixModuleCompileOptions =
+            (&module_compile_options).into();
+
+        // zero-initialize so that fields added in later versions (such as
+        // the build and end cap flags) get their defaults
+        let mut is_options: sys::OptixBuiltinISOptions =
+            unsafe { std::mem::zeroed() };
+        is_options.builtinISModuleType = options.primitive_type as u32;
+        is_options.usesMotionBlur = options.uses_motion_blur as i32;
 
-        let mopt = module_compile_options.into();
         let mut module = std::ptr::null_mut();
         let res = unsafe {
-            sys::optixModuleCreateFromPTX(
+            backend::optix_builtin_is_module_get(
                 self.ctx,
                 &mopt,
                 &popt,
-                cptx.as_ptr(),
-                cptx.as_bytes().len(),
-                log.as_mut_ptr() as *mut i8,
-                &mut log_len,
+                &is_options,
                 &mut module,
             )
         };
 
-        let log = CStr::from_bytes_with_nul(&log[0..log_len])
-            .unwrap()
-            .to_string_lossy()
-            .into_owned();
-
         if res != sys::OptixResult::OPTIX_SUCCESS {
-            return Err(Error::ModuleCreationFailed {
+            return Err(Error::BuiltinISModuleGetFailed {
                 source: res.into(),
-                log,
+                primitive_type: options.primitive_type,
             });
         }
 
-        let module = super::Ref::new(Module { module });
-        // self.modules.push(super::Ref::clone(&module));
-        Ok((module, log))
+        Ok(super::Ref::new(Module {
+            module,
+            builtin: true,
+            ptx: String::new(),
+            pipeline_compile_options: pipeline_compile_options.clone(),
+            context: super::Ref::clone(&self.shared),
+        }))
     }
 }
+
+#[cfg(optix_7_3)]
+#[derive(Debug, Hash, PartialEq, Copy, Clone)]
+pub struct BuiltinISOptions {
+    pub primitive_type: PrimitiveType,
+    pub uses_motion_blur: bool,
+}
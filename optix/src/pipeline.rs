@@ -1,18 +1,20 @@
+use crate::backend;
 use optix_sys as sys;
 
 use super::error::Error;
 type Result<T, E = Error> = std::result::Result<T, E>;
 
-use super::device_context::DeviceContext;
+use super::compile_log::{create_with_log, CompileLog};
+use super::device_context::{DeviceContext, DeviceProperties, SharedContext};
 use super::module::{CompileDebugLevel, PipelineCompileOptions};
 use super::program_group::ProgramGroupRef;
-
-use std::ffi::CStr;
+use super::stack_size::{PipelineStackSize, StackSizes};
 
 #[derive(Debug, Hash, PartialEq, Copy, Clone)]
 pub struct PipelineLinkOptions {
     pub max_trace_depth: u32,
     pub debug_level: CompileDebugLevel,
+    #[cfg(not(optix_7_3))]
     pub override_uses_motion_blur: bool,
 }
 
@@ -21,6 +23,7 @@ impl From<PipelineLinkOptions> for sys::OptixPipelineLinkOptions {
         sys::OptixPipelineLinkOptions {
             maxTraceDepth: o.max_trace_depth,
             debugLevel: o.debug_level as u32,
+            #[cfg(not(optix_7_3))]
             overrideUsesMotionBlur: o.override_uses_motion_blur as i32,
         }
     }
@@ -28,80 +31,112 @@ impl From<PipelineLinkOptions> for sys::OptixPipelineLinkOptions {
 
 pub struct Pipeline {
     pub(crate) pipeline: sys::OptixPipeline,
+    // the program groups linked into the pipeline, which are dropped after
+    // it is destroyed
+    program_groups: Vec<ProgramGroupRef>,
+    context: super::Ref<SharedContext>,
 }
 
+// OptiX allows a pipeline to be launched from any thread. Setting the stack
+// size must not race with launches, so it requires the only reference to the
+// pipeline (see `DeviceContext::pipeline_set_stack_size`).
+unsafe impl Send for Pipeline {}
+unsafe impl Sync for Pipeline {}
+
 impl Drop for Pipeline {
     fn drop(&mut self) {
         unsafe {
-            sys::optixPipelineDestroy(self.pipeline);
+            backend::optix_pipeline_destroy(self.pipeline);
         }
+        self.context.pipelines.destroyed();
     }
 }
 
 pub type PipelineRef = super::Ref<Pipeline>;
 
+impl Pipeline {
+    /// The stack sizes needed by the program groups linked into the
+    /// pipeline, accumulated over all of them.
+    pub fn stack_sizes(&self) -> Result<StackSizes> {
+        let mut stack_sizes = StackSizes::default();
+        for pg in &self.program_groups {
+            stack_sizes.accumulate(&pg.stack_sizes()?);
+        }
+        Ok(stack_sizes)
+    }
+}
+
 impl DeviceContext {
     pub fn pipeline_create(
         &mut self,
         pipeline_compile_options: &PipelineCompileOptions,
         link_options: PipelineLinkOptions,
         program_groups: &[ProgramGroupRef],
-    ) -> Result<(PipelineRef, String)> {
-        let popt = sys::OptixPipelineCompileOptions {
-            usesMotionBlur: if pipeline_compile_options.uses_motion_blur {
-                1
-            } else {
-                0
-            },
-            traversableGraphFlags: pipeline_compile_options
-                .traversable_graph_flags
-                .bits(),
-            numPayloadValues: pipeline_compile_options.num_payload_values,
-            numAttributeValues: pipeline_compile_options.num_attribute_values,
-            exceptionFlags: pipeline_compile_options.exception_flags.bits(),
-            pipelineLaunchParamsVariableName: unsafe {
-                pipeline_compile_options
-                    .pipeline_launch_params_variable_name
-                    .as_char_ptr()
-            },
-        };
+    ) -> Result<(PipelineRef, CompileLog)> {
+        // OptiX requires all modules to be compiled with the options of the
+        // pipeline they are linked into, but only reports a mismatch as a
+        // generic link error
+        for pg in program_groups {
+            for pgm in pg.desc.program_group_modules() {
+                let fields = pgm
+                    .module
+                    .pipeline_compile_options()
+                    .differences(pipeline_compile_options);
+                if !fields.is_empty() {
+                    return Err(Error::PipelineCompileOptionsMismatch {
+                        entry_function: pgm.entry_function_name.to_string(),
+                        fields,
+                    });
+                }
+            }
+        }
+
+        let popt: sys::OptixPipelineCompileOptions =
+            pipeline_compile_options.into();
 
+        DeviceProperties::check(
+            "max_trace_depth",
+            link_options.max_trace_depth as u64,
+            self.shared.properties.max_trace_depth,
+        )?;
         let link_options: sys::OptixPipelineLinkOptions = link_options.into();
 
         let pgs: Vec<sys::OptixProgramGroup> =
             program_groups.iter().map(|pg| pg.pg).collect();
 
-        let mut log = [0u8; 4096];
-        let mut log_len = log.len();
-
-        let mut pipeline: sys::OptixPipeline = std::ptr::null_mut();
-
-        let res = unsafe {
-            sys::optixPipelineCreate(
-                self.ctx,
-                &popt,
-                &link_options,
-                pgs.as_ptr(),
-                pgs.len() as u32,
-                log.as_mut_ptr() as *mut i8,
-                &mut log_len,
-                &mut pipeline,
-            )
-        };
-
-        let log = CStr::from_bytes_with_nul(&log[0..log_len])
-            .unwrap()
-            .to_string_lossy()
-            .into_owned();
+        let (res, pipeline, log) = create_with_log(
+            |log, log_len| unsafe {
+                let mut pipeline: sys::OptixPipeline = std::ptr::null_mut();
+                let res = backend::optix_pipeline_create(
+                    self.ctx,
+                    &popt,
+                    &link_options,
+                    pgs.as_ptr(),
+                    pgs.len() as u32,
+                    log,
+                    log_len,
+                    &mut pipeline,
+                );
+                (res, pipeline)
+            },
+            |pipeline| unsafe {
+                backend::optix_pipeline_destroy(pipeline);
+            },
+        );
 
         if res != sys::OptixResult::OPTIX_SUCCESS {
             return Err(Error::PipelineCreationFailed {
                 source: res.into(),
                 log,
+                num_program_groups: program_groups.len(),
             });
         }
-        let pipeline = super::Ref::new(Pipeline { pipeline });
-        self.pipelines.push(super::Ref::clone(&pipeline));
+        self.shared.pipelines.created();
+        let pipeline = super::Ref::new(Pipeline {
+            pipeline,
+            program_groups: program_groups.to_vec(),
+            context: super::Ref::clone(&self.shared),
+        });
         Ok((pipeline, log))
     }
 
@@ -135,8 +170,14 @@ impl DeviceContext {
     ///   graph
     /// passed to trace
     ///
-    /// # Panics
-    /// If the FFI call to optixPipelineSetStackSize returns an error
+    /// The stack size can't be changed while the pipeline may be launched,
+    /// so `pipeline` must be the only reference to it, e.g. by setting the
+    /// stack size before the pipeline is cloned.
+    ///
+    /// # Errors
+    /// If `pipeline` is shared, `max_traversable_graph_depth` exceeds the
+    /// device limit or the FFI call to optixPipelineSetStackSize returns an
+    /// error
     pub fn pipeline_set_stack_size(
         &self,
         pipeline: &mut PipelineRef,
@@ -144,9 +185,16 @@ impl DeviceContext {
         direct_callable_stack_size_from_state: u32,
         continuation_stack_size: u32,
         max_traversable_graph_depth: u32,
-    ) {
+    ) -> Result<()> {
+        DeviceProperties::check(
+            "max_traversable_graph_depth",
+            max_traversable_graph_depth as u64,
+            self.shared.properties.max_traversable_graph_depth,
+        )?;
+        let pipeline =
+            super::Ref::get_mut(pipeline).ok_or(Error::PipelineShared)?;
         let res = unsafe {
-            sys::optixPipelineSetStackSize(
+            backend::optix_pipeline_set_stack_size(
                 pipeline.pipeline,
                 direct_callable_stack_size_from_traversable,
                 direct_callable_stack_size_from_state,
@@ -155,7 +203,203 @@ impl DeviceContext {
             )
         };
         if res != sys::OptixResult::OPTIX_SUCCESS {
-            panic!("optixPipelineSetStackSize failed");
+            return Err(Error::PipelineSetStackSizeFailed {
+                source: res.into(),
+                direct_callable_stack_size_from_traversable,
+                direct_callable_stack_size_from_state,
+                continuation_stack_size,
+                max_traversable_graph_depth,
+            });
+        }
+
+        Ok(())
+    }
+
+    /// Compute the stack sizes for a pipeline from the stack sizes of its
+    /// program groups and the given call depths, and set them.
+    ///
+    /// See `StackSizes::compute()` for how the sizes are computed and
+    /// `pipeline_set_stack_size()` for the meaning of
+    /// `max_traversable_graph_depth`. Returns the stack sizes that were set.
+    pub fn pipeline_compute_stack_size(
+        &self,
+        pipeline: &mut PipelineRef,
+        max_trace_depth: u32,
+        max_cc_depth: u32,
+        max_dc_depth: u32,
+        max_traversable_graph_depth: u32,
+    ) -> Result<PipelineStackSize> {
+        let stack_size = pipeline.stack_sizes()?.compute(
+            max_trace_depth,
+            max_cc_depth,
+            max_dc_depth,
+        );
+        self.pipeline_set_stack_size(
+            pipeline,
+            stack_size.direct_callable_stack_size_from_traversal,
+            stack_size.direct_callable_stack_size_from_state,
+            stack_size.continuation_stack_size,
+            max_traversable_graph_depth,
+        )?;
+        Ok(stack_size)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::PipelineLinkOptions;
+    use crate::backend::mock::{fixtures, HandleKind, MockBackend};
+    use crate::error::Error;
+    use crate::{
+        CompileDebugLevel, PipelineCompileOptions, ProgramGroupDesc,
+        ProgramGroupModule, StackSizes,
+    };
+    use optix_sys as sys;
+
+    #[test]
+    fn link_options_checked_against_device_limits() {
+        let mock = MockBackend::install();
+        let mut ctx = fixtures::device_context();
+        let link_options = PipelineLinkOptions {
+            // the mock reports a maximum trace depth of 31
+            max_trace_depth: 32,
+            debug_level: CompileDebugLevel::None,
+            #[cfg(not(optix_7_3))]
+            override_uses_motion_blur: false,
+        };
+        match ctx.pipeline_create(
+            &fixtures::pipeline_compile_options(),
+            link_options,
+            &[],
+        ) {
+            Err(Error::DeviceLimitExceeded {
+                limit: "max_trace_depth",
+                value: 32,
+                max: 31,
+            }) => (),
+            Err(e) => panic!("unexpected error {}", e),
+            Ok(_) => panic!("pipeline creation should have failed"),
+        }
+        assert_eq!(mock.live_handles(HandleKind::Pipeline), 0);
+    }
+
+    #[test]
+    fn compile_options_must_match_modules() {
+        let mock = MockBackend::install();
+        let mut ctx = fixtures::device_context();
+        let module = fixtures::module(&mut ctx);
+        let (pg, _) = ctx
+            .program_group_create(ProgramGroupDesc::Miss(ProgramGroupModule {
+                module,
+                entry_function_name: ustr::ustr("__miss__radiance"),
+            }))
+            .unwrap();
+
+        let pipeline_compile_options = PipelineCompileOptions {
+            num_payload_values: 4,
+            pipeline_launch_params_variable_name: ustr::ustr("launch"),
+            ..fixtures::pipeline_compile_options()
+        };
+        let link_options = PipelineLinkOptions {
+            max_trace_depth: 1,
+            debug_level: CompileDebugLevel::None,
+            #[cfg(not(optix_7_3))]
+            override_uses_motion_blur: false,
+        };
+        match ctx.pipeline_create(
+            &pipeline_compile_options,
+            link_options,
+            &[pg],
+        ) {
+            Err(Error::PipelineCompileOptionsMismatch {
+                entry_function,
+                fields,
+            }) => {
+                assert_eq!(entry_function, "__miss__radiance");
+                assert_eq!(
+                    fields,
+                    [
+                        "num_payload_values",
+                        "pipeline_launch_params_variable_name"
+                    ]
+                );
+            }
+            r => panic!("unexpected result {:?}", r.err()),
+        }
+        assert_eq!(mock.live_handles(HandleKind::Pipeline), 0);
+    }
+
+    #[test]
+    fn compute_stack_size() {
+        let mock = MockBackend::install();
+        let mut ctx = fixtures::device_context();
+        let module = fixtures::module(&mut ctx);
+        let entry = |name| ProgramGroupModule {
+            module: module.clone(),
+            entry_function_name: ustr::ustr(name),
+        };
+        let (pgs, _) = ctx
+            .program_groups_create(&[
+                ProgramGroupDesc::Raygen(entry("__raygen__main")),
+                ProgramGroupDesc::Hitgroup {
+                    ch: Some(entry("__closesthit__radiance")),
+                    ah: None,
+                    is: None,
+                },
+            ])
+            .unwrap();
+        let stack_sizes = |css_rg, css_ch| sys::OptixStackSizes {
+            cssRG: css_rg,
+            cssMS: 0,
+            cssCH: css_ch,
+            cssAH: 0,
+            cssIS: 0,
+            cssCC: 0,
+            dssDC: 0,
+        };
+        mock.set_program_group_stack_sizes(pgs[0].pg, stack_sizes(96, 0));
+        mock.set_program_group_stack_sizes(pgs[1].pg, stack_sizes(0, 32));
+
+        let link_options = PipelineLinkOptions {
+            max_trace_depth: 2,
+            debug_level: CompileDebugLevel::None,
+            #[cfg(not(optix_7_3))]
+            override_uses_motion_blur: false,
+        };
+        let (mut pipeline, _) = ctx
+            .pipeline_create(
+                &fixtures::pipeline_compile_options(),
+                link_options,
+                &pgs,
+            )
+            .unwrap();
+        assert_eq!(
+            pipeline.stack_sizes().unwrap(),
+            StackSizes {
+                css_rg: 96,
+                css_ch: 32,
+                ..Default::default()
+            }
+        );
+
+        let stack_size = ctx
+            .pipeline_compute_stack_size(&mut pipeline, 2, 0, 0, 2)
+            .unwrap();
+        assert_eq!(stack_size.continuation_stack_size, 96 + 2 * 32);
+        assert_eq!(
+            mock.stack_size(pipeline.pipeline),
+            Some([0, 0, 96 + 2 * 32, 2])
+        );
+
+        // a launch may be using another reference to the pipeline
+        let shared = pipeline.clone();
+        match ctx.pipeline_compute_stack_size(&mut pipeline, 1, 0, 0, 1) {
+            Err(Error::PipelineShared) => (),
+            r => panic!("expected PipelineShared, got {:?}", r),
         }
+        assert_eq!(
+            mock.stack_size(shared.pipeline),
+            Some([0, 0, 96 + 2 * 32, 2])
+        );
     }
 }
@@ -1,12 +1,18 @@
+use crate::backend;
 use optix_sys as sys;
 
 use super::error::Error;
 type Result<T, E = Error> = std::result::Result<T, E>;
 
-use super::device_context::DeviceContext;
+use super::compile_log::{create_with_log, CompileLog};
+use super::cuda::nvrtc::LoweredNames;
+use super::device_context::{DeviceContext, SharedContext};
+use super::entry_point::{function_name, ProgramKind};
 use super::module::ModuleRef;
+#[cfg(optix_7_4)]
+use super::module::PayloadType;
+use super::stack_size::StackSizes;
 
-use std::ffi::CStr;
 use ustr::Ustr;
 
 #[derive(Clone)]
@@ -15,6 +21,37 @@ pub struct ProgramGroupModule {
     pub entry_function_name: Ustr,
 }
 
+impl ProgramGroupModule {
+    pub fn new(module: &ModuleRef, entry_function_name: &str) -> Self {
+        ProgramGroupModule {
+            module: ModuleRef::clone(module),
+            entry_function_name: ustr::ustr(entry_function_name),
+        }
+    }
+
+    /// Use the function named by the C++ expression `expression` in
+    /// `module`, e.g. `__closesthit__radiance<float>`. The expression must
+    /// have been added to the NVRTC program the module was compiled from
+    /// with `Program::add_name_expression()`, and `lowered_names` come from
+    /// that program.
+    ///
+    /// # Errors
+    /// If `lowered_names` doesn't have `expression`.
+    pub fn from_name_expression(
+        module: &ModuleRef,
+        lowered_names: &LoweredNames,
+        expression: &str,
+    ) -> Result<Self> {
+        match lowered_names.get(expression) {
+            Some(name) => Ok(ProgramGroupModule::new(module, name)),
+            None => Err(Error::UnknownNameExpression {
+                expression: expression.to_string(),
+            }),
+        }
+    }
+}
+
+#[derive(Clone)]
 pub enum ProgramGroupDesc {
     Raygen(ProgramGroupModule),
     Miss(ProgramGroupModule),
@@ -29,11 +66,167 @@ pub enum ProgramGroupDesc {
     },
 }
 
+impl ProgramGroupDesc {
+    /// The kind of program group this describes, e.g. "hitgroup".
+    pub fn kind_name(&self) -> &'static str {
+        match self {
+            ProgramGroupDesc::Raygen(_) => "raygen",
+            ProgramGroupDesc::Miss(_) => "miss",
+            ProgramGroupDesc::Hitgroup { .. } => "hitgroup",
+            ProgramGroupDesc::Callables { .. } => "callables",
+        }
+    }
+
+    /// The programs in the group.
+    pub fn program_group_modules(&self) -> Vec<&ProgramGroupModule> {
+        match self {
+            ProgramGroupDesc::Raygen(pgm) | ProgramGroupDesc::Miss(pgm) => {
+                vec![pgm]
+            }
+            ProgramGroupDesc::Hitgroup { ch, ah, is } => {
+                [ch, ah, is].iter().filter_map(|m| m.as_ref()).collect()
+            }
+            ProgramGroupDesc::Callables { dc, cc } => {
+                [dc, cc].iter().filter_map(|m| m.as_ref()).collect()
+            }
+        }
+    }
+
+    /// The names of all the entry functions in the group.
+    pub fn entry_function_names(&self) -> Vec<String> {
+        self.program_group_modules()
+            .iter()
+            .map(|m| m.entry_function_name.to_string())
+            .collect()
+    }
+
+    /// Check that the entry function names have the prefix OptiX expects for
+    /// the kind of program they are used as, e.g. `__miss__` for a miss
+    /// program. For mangled names the prefix is checked on the unqualified
+    /// name. Built-in intersection programs have no name to check.
+    pub fn validate(&self) -> Result<()> {
+        let check = |pgm: Option<&ProgramGroupModule>, kind: ProgramKind| {
+            let prefix = kind.prefix();
+            match pgm {
+                Some(pgm)
+                    if !pgm.module.builtin
+                        && !function_name(&pgm.entry_function_name)
+                            .starts_with(prefix) =>
+                {
+                    Err(Error::InvalidEntryFunctionName {
+                        name: pgm.entry_function_name.to_string(),
+                        prefix,
+                    })
+                }
+                _ => Ok(()),
+            }
+        };
+        match self {
+            ProgramGroupDesc::Raygen(pgm) => {
+                check(Some(pgm), ProgramKind::Raygen)
+            }
+            ProgramGroupDesc::Miss(pgm) => check(Some(pgm), ProgramKind::Miss),
+            ProgramGroupDesc::Hitgroup { ch, ah, is } => {
+                check(ch.as_ref(), ProgramKind::ClosestHit)?;
+                check(ah.as_ref(), ProgramKind::AnyHit)?;
+                check(is.as_ref(), ProgramKind::Intersection)
+            }
+            ProgramGroupDesc::Callables { dc, cc } => {
+                check(dc.as_ref(), ProgramKind::DirectCallable)?;
+                check(cc.as_ref(), ProgramKind::ContinuationCallable)
+            }
+        }
+    }
+
+    /// The OptiX description of the group. The module handles and entry
+    /// function names it points to are owned by `self`.
+    fn to_sys(&self) -> sys::OptixProgramGroupDesc {
+        // entry function names are interned, so their pointers stay valid.
+        // OptiX requires built-in intersection programs to have no name.
+        let single =
+            |pgm: &ProgramGroupModule| sys::OptixProgramGroupSingleModule {
+                module: pgm.module.module,
+                entryFunctionName: if pgm.module.builtin {
+                    std::ptr::null()
+                } else {
+                    pgm.entry_function_name.as_char_ptr()
+                },
+            };
+        let optional = |pgm: &Option<ProgramGroupModule>| match pgm {
+            Some(pgm) => {
+                let s = single(pgm);
+                (s.module, s.entryFunctionName)
+            }
+            None => (std::ptr::null_mut(), std::ptr::null()),
+        };
+
+        let (kind, group) = match self {
+            ProgramGroupDesc::Raygen(pgm) => (
+                sys::OptixProgramGroupKind::OPTIX_PROGRAM_GROUP_KIND_RAYGEN,
+                sys::OptixProgramGroupDesc__bindgen_ty_1 {
+                    raygen: single(pgm),
+                },
+            ),
+            ProgramGroupDesc::Miss(pgm) => (
+                sys::OptixProgramGroupKind::OPTIX_PROGRAM_GROUP_KIND_MISS,
+                sys::OptixProgramGroupDesc__bindgen_ty_1 { miss: single(pgm) },
+            ),
+            ProgramGroupDesc::Hitgroup { ch, ah, is } => {
+                let (module_ch, efn_ch) = optional(ch);
+                let (module_ah, efn_ah) = optional(ah);
+                let (module_is, efn_is) = optional(is);
+                (
+                    sys::OptixProgramGroupKind::OPTIX_PROGRAM_GROUP_KIND_HITGROUP,
+                    sys::OptixProgramGroupDesc__bindgen_ty_1 {
+                        hitgroup: sys::OptixProgramGroupHitgroup {
+                            moduleCH: module_ch,
+                            entryFunctionNameCH: efn_ch,
+                            moduleAH: module_ah,
+                            entryFunctionNameAH: efn_ah,
+                            moduleIS: module_is,
+                            entryFunctionNameIS: efn_is,
+                        },
+                    },
+                )
+            }
+            ProgramGroupDesc::Callables { dc, cc } => {
+                let (module_dc, efn_dc) = optional(dc);
+                let (module_cc, efn_cc) = optional(cc);
+                (
+                    sys::OptixProgramGroupKind::OPTIX_PROGRAM_GROUP_KIND_CALLABLES,
+                    sys::OptixProgramGroupDesc__bindgen_ty_1 {
+                        callables: sys::OptixProgramGroupCallables {
+                            moduleDC: module_dc,
+                            entryFunctionNameDC: efn_dc,
+                            moduleCC: module_cc,
+                            entryFunctionNameCC: efn_cc,
+                        },
+                    },
+                )
+            }
+        };
+
+        sys::OptixProgramGroupDesc {
+            kind,
+            __bindgen_anon_1: group,
+            flags: 0,
+        }
+    }
+}
+
 pub struct ProgramGroup {
     pub(crate) pg: sys::OptixProgramGroup,
-    _desc: ProgramGroupDesc,
+    // holds on to the modules the group's programs come from, which are
+    // dropped after the group is destroyed
+    pub(crate) desc: ProgramGroupDesc,
+    context: super::Ref<SharedContext>,
 }
 
+// A program group is immutable once created and OptiX allows it to be used
+// from any thread.
+unsafe impl Send for ProgramGroup {}
+unsafe impl Sync for ProgramGroup {}
+
 impl PartialEq for ProgramGroup {
     fn eq(&self, rhs: &ProgramGroup) -> bool {
         self.pg == rhs.pg
@@ -46,237 +239,350 @@ impl ProgramGroup {
     pub fn sys_ptr(&self) -> sys::OptixProgramGroup {
         self.pg
     }
+
+    /// The stack sizes needed by the programs in the group.
+    pub fn stack_sizes(&self) -> Result<StackSizes> {
+        let mut stack_sizes: sys::OptixStackSizes =
+            unsafe { std::mem::zeroed() };
+        let res = unsafe {
+            backend::optix_program_group_get_stack_size(
+                self.pg,
+                &mut stack_sizes,
+            )
+        };
+        if res != sys::OptixResult::OPTIX_SUCCESS {
+            return Err(Error::ProgramGroupGetStackSizeFailed {
+                source: res.into(),
+            });
+        }
+        Ok(stack_sizes.into())
+    }
 }
 
 impl Drop for ProgramGroup {
     fn drop(&mut self) {
         unsafe {
-            sys::optixProgramGroupDestroy(self.pg);
+            backend::optix_program_group_destroy(self.pg);
         }
+        self.context.program_groups.destroyed();
     }
 }
 
+fn default_program_group_options() -> sys::OptixProgramGroupOptions {
+    #[cfg(not(optix_7_4))]
+    let pg_options = sys::OptixProgramGroupOptions { placeholder: 0 };
+    #[cfg(optix_7_4)]
+    let pg_options = sys::OptixProgramGroupOptions {
+        payloadType: std::ptr::null_mut(),
+    };
+    pg_options
+}
+
 impl DeviceContext {
     pub fn program_group_create(
         &mut self,
         desc: ProgramGroupDesc,
-    ) -> Result<(ProgramGroupRef, String)> {
-        let pg_options = sys::OptixProgramGroupOptions { placeholder: 0 };
+    ) -> Result<(ProgramGroupRef, CompileLog)> {
+        let pg_options = default_program_group_options();
+        self.program_group_create_with_options(desc, &pg_options)
+    }
 
-        let mut log = [0u8; 4096];
-        let mut log_len = log.len();
+    /// Create a program group whose programs use `payload_type`, which must
+    /// be one of the payload types declared by the modules they come from.
+    #[cfg(optix_7_4)]
+    pub fn program_group_create_with_payload_type(
+        &mut self,
+        desc: ProgramGroupDesc,
+        payload_type: &PayloadType,
+    ) -> Result<(ProgramGroupRef, CompileLog)> {
+        let semantics = payload_type.semantics_bits();
+        let mut payload_type = sys::OptixPayloadType {
+            numPayloadValues: semantics.len() as u32,
+            payloadSemantics: semantics.as_ptr(),
+        };
+        let pg_options = sys::OptixProgramGroupOptions {
+            payloadType: &mut payload_type,
+        };
+
+        self.program_group_create_with_options(desc, &pg_options)
+    }
 
-        let mut pg = std::ptr::null_mut();
+    /// Create a program group for each of `descs` with a single call to
+    /// OptiX, which produces a single log for all of them.
+    ///
+    /// # Errors
+    /// If the entry function names of a description don't match the kind of
+    /// program they are used as (see `ProgramGroupDesc::validate()`), in
+    /// which case nothing is created, or if optixProgramGroupCreate fails
+    pub fn program_groups_create(
+        &mut self,
+        descs: &[ProgramGroupDesc],
+    ) -> Result<(Vec<ProgramGroupRef>, CompileLog)> {
+        let pg_options = default_program_group_options();
+        self.program_groups_create_with_options(descs.to_vec(), &pg_options)
+    }
 
-        unsafe {
-            match &desc {
-                ProgramGroupDesc::Raygen(ProgramGroupModule {
-                    module,
-                    entry_function_name,
-                }) => {
-                    let pg_desc = sys::OptixProgramGroupDesc {
-                    kind:
-                        sys::OptixProgramGroupKind::OPTIX_PROGRAM_GROUP_KIND_RAYGEN,
-                    __bindgen_anon_1: sys::OptixProgramGroupDesc__bindgen_ty_1 {
-                        raygen: sys::OptixProgramGroupSingleModule {
-                            module: module.module,
-                            entryFunctionName: entry_function_name.as_char_ptr(),
-                        },
-                    },
-                    flags: 0,
-                };
-                    let res = sys::optixProgramGroupCreate(
-                        self.ctx,
-                        &pg_desc,
-                        1,
-                        &pg_options,
-                        log.as_mut_ptr() as *mut i8,
-                        &mut log_len,
-                        &mut pg,
-                    );
-
-                    let log = CStr::from_bytes_with_nul(&log[0..log_len])
-                        .unwrap()
-                        .to_string_lossy()
-                        .into_owned();
-
-                    if res != sys::OptixResult::OPTIX_SUCCESS {
-                        return Err(Error::ProgramGroupCreationFailed {
-                            source: res.into(),
-                            log,
-                        });
-                    }
+    fn program_group_create_with_options(
+        &mut self,
+        desc: ProgramGroupDesc,
+        pg_options: &sys::OptixProgramGroupOptions,
+    ) -> Result<(ProgramGroupRef, CompileLog)> {
+        let (mut pgs, log) =
+            self.program_groups_create_with_options(vec![desc], pg_options)?;
+        Ok((pgs.remove(0), log))
+    }
 
-                    let pg = super::Ref::new(ProgramGroup { pg, _desc: desc });
-                    // self.program_groups.push(super::Ref::clone(&pg));
-                    Ok((pg, log))
-                }
-                ProgramGroupDesc::Miss(ProgramGroupModule {
-                    module,
-                    entry_function_name,
-                }) => {
-                    let pg_desc = sys::OptixProgramGroupDesc {
-                    kind:
-                        sys::OptixProgramGroupKind::OPTIX_PROGRAM_GROUP_KIND_MISS,
-                    __bindgen_anon_1: sys::OptixProgramGroupDesc__bindgen_ty_1 {
-                        miss: sys::OptixProgramGroupSingleModule {
-                            module: module.module,
-                            entryFunctionName: entry_function_name.as_char_ptr(),
-                        },
-                    },
-                    flags: 0,
-                };
-                    let res = sys::optixProgramGroupCreate(
-                        self.ctx,
-                        &pg_desc,
-                        1,
-                        &pg_options,
-                        log.as_mut_ptr() as *mut i8,
-                        &mut log_len,
-                        &mut pg,
-                    );
-
-                    let log = CStr::from_bytes_with_nul(&log[0..log_len])
-                        .unwrap()
-                        .to_string_lossy()
-                        .into_owned();
-
-                    if res != sys::OptixResult::OPTIX_SUCCESS {
-                        return Err(Error::ProgramGroupCreationFailed {
-                            source: res.into(),
-                            log,
-                        });
-                    }
+    fn program_groups_create_with_options(
+        &mut self,
+        descs: Vec<ProgramGroupDesc>,
+        pg_options: &sys::OptixProgramGroupOptions,
+    ) -> Result<(Vec<ProgramGroupRef>, CompileLog)> {
+        for (index, desc) in descs.iter().enumerate() {
+            desc.validate()
+                .map_err(|e| Error::InvalidProgramGroupDesc {
+                    index,
+                    source: Box::new(e),
+                })?;
+        }
 
-                    let pg = super::Ref::new(ProgramGroup { pg, _desc: desc });
-                    // self.program_groups.push(super::Ref::clone(&pg));
-                    Ok((pg, log))
+        let pg_descs: Vec<sys::OptixProgramGroupDesc> =
+            descs.iter().map(|desc| desc.to_sys()).collect();
+
+        let (res, pgs, log) = create_with_log(
+            |log, log_len| unsafe {
+                let mut pgs = vec![std::ptr::null_mut(); pg_descs.len()];
+                let res = backend::optix_program_group_create(
+                    self.ctx,
+                    pg_descs.as_ptr(),
+                    pg_descs.len() as u32,
+                    pg_options,
+                    log,
+                    log_len,
+                    pgs.as_mut_ptr(),
+                );
+                (res, pgs)
+            },
+            |pgs| unsafe {
+                for pg in pgs {
+                    backend::optix_program_group_destroy(pg);
                 }
-                ProgramGroupDesc::Hitgroup { ch, ah, is } => {
-                    let mut efn_ch_ptr = std::ptr::null();
-                    let mut efn_ah_ptr = std::ptr::null();
-                    let mut efn_is_ptr = std::ptr::null();
-
-                    let module_ch = if let Some(pg_ch) = &ch {
-                        efn_ch_ptr = pg_ch.entry_function_name.as_char_ptr();
-                        pg_ch.module.module
-                    } else {
-                        std::ptr::null_mut()
-                    };
-
-                    let module_ah = if let Some(pg_ah) = &ah {
-                        efn_ah_ptr = pg_ah.entry_function_name.as_char_ptr();
-                        pg_ah.module.module
-                    } else {
-                        std::ptr::null_mut()
-                    };
-
-                    let module_is = if let Some(pg_is) = &is {
-                        efn_is_ptr = pg_is.entry_function_name.as_char_ptr();
-                        pg_is.module.module
-                    } else {
-                        std::ptr::null_mut()
-                    };
-
-                    let pg_desc = sys::OptixProgramGroupDesc {
-                    kind:
-                        sys::OptixProgramGroupKind::OPTIX_PROGRAM_GROUP_KIND_HITGROUP,
-                    __bindgen_anon_1: sys::OptixProgramGroupDesc__bindgen_ty_1 {
-                        hitgroup: sys::OptixProgramGroupHitgroup {
-                            moduleCH: module_ch,
-                            entryFunctionNameCH: efn_ch_ptr,
-                            moduleAH: module_ah,
-                            entryFunctionNameAH: efn_ah_ptr,
-                            moduleIS: module_is,
-                            entryFunctionNameIS: efn_is_ptr,
-                        },
-                    },
-                    flags: 0,
-                };
-                    let res = sys::optixProgramGroupCreate(
-                        self.ctx,
-                        &pg_desc,
-                        1,
-                        &pg_options,
-                        log.as_mut_ptr() as *mut i8,
-                        &mut log_len,
-                        &mut pg,
-                    );
-
-                    let log = CStr::from_bytes_with_nul(&log[0..log_len])
-                        .unwrap()
-                        .to_string_lossy()
-                        .into_owned();
-
-                    if res != sys::OptixResult::OPTIX_SUCCESS {
-                        return Err(Error::ProgramGroupCreationFailed {
-                            source: res.into(),
-                            log,
-                        });
-                    }
+            },
+        );
+
+        if res != sys::OptixResult::OPTIX_SUCCESS {
+            return Err(match descs.as_slice() {
+                [desc] => Error::ProgramGroupCreationFailed {
+                    source: res.into(),
+                    log,
+                    kind: desc.kind_name(),
+                    entry_functions: desc.entry_function_names(),
+                },
+                _ => Error::ProgramGroupsCreationFailed {
+                    source: res.into(),
+                    log,
+                    num_program_groups: descs.len(),
+                },
+            });
+        }
 
-                    let pg = super::Ref::new(ProgramGroup { pg, _desc: desc });
-                    // self.program_groups.push(super::Ref::clone(&pg));
-                    Ok((pg, log))
-                }
-                ProgramGroupDesc::Callables { dc, cc } => {
-                    let (module_dc, efn_dc) = if let Some(pg_dc) = &dc {
-                        (
-                            pg_dc.module.module,
-                            pg_dc.entry_function_name.as_char_ptr(),
-                        )
-                    } else {
-                        (std::ptr::null_mut(), std::ptr::null())
-                    };
-
-                    let (module_cc, efn_cc) = if let Some(pg_cc) = &cc {
-                        (
-                            pg_cc.module.module,
-                            pg_cc.entry_function_name.as_char_ptr(),
-                        )
-                    } else {
-                        (std::ptr::null_mut(), std::ptr::null())
-                    };
-
-                    let pg_desc = sys::OptixProgramGroupDesc {
-                    kind:
-                        sys::OptixProgramGroupKind::OPTIX_PROGRAM_GROUP_KIND_CALLABLES,
-                    __bindgen_anon_1: sys::OptixProgramGroupDesc__bindgen_ty_1 {
-                        callables: sys::OptixProgramGroupCallables {
-                            moduleDC: module_dc,
-                            entryFunctionNameDC: efn_dc,
-                            moduleCC: module_cc,
-                            entryFunctionNameCC: efn_cc,
-                        },
-                        },
-                    flags: 0,
-                    };
-
-                    let res = sys::optixProgramGroupCreate(
-                        self.ctx,
-                        &pg_desc,
-                        1,
-                        &pg_options,
-                        log.as_mut_ptr() as *mut i8,
-                        &mut log_len,
-                        &mut pg,
-                    );
-
-                    let log = CStr::from_bytes_with_nul(&log[0..log_len])
-                        .unwrap()
-                        .to_string_lossy()
-                        .into_owned();
-
-                    if res != sys::OptixResult::OPTIX_SUCCESS {
-                        return Err(Error::ProgramGroupCreationFailed {
-                            source: res.into(),
-                            log,
-                        });
+        let pgs = pgs
+            .into_iter()
+            .zip(descs)
+            .map(|(pg, desc)| {
+                self.shared.program_groups.created();
+                super::Ref::new(ProgramGroup {
+                    pg,
+                    desc,
+                    context: super::Ref::clone(&self.shared),
+                })
+            })
+            .collect();
+        Ok((pgs, log))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{ProgramGroupDesc, ProgramGroupModule};
+    use crate::backend::mock::{fixtures, HandleKind, MockBackend};
+    use crate::cuda::nvrtc::LoweredNames;
+    use crate::error::Error;
+    use optix_sys as sys;
+
+    #[test]
+    fn creation_failure_names_entry_functions() {
+        let mock = MockBackend::install();
+        let mut ctx = fixtures::device_context();
+        let module = fixtures::module(&mut ctx);
+        let entry = |name| {
+            Some(ProgramGroupModule {
+                module: module.clone(),
+                entry_function_name: ustr::ustr(name),
+            })
+        };
+
+        mock.fail_optix(
+            "optix_program_group_create",
+            sys::OptixResult::OPTIX_ERROR_INVALID_FUNCTION_USE,
+        );
+        let desc = ProgramGroupDesc::Hitgroup {
+            ch: entry("__closesthit__radiance"),
+            ah: None,
+            is: entry("__intersection__sphere"),
+        };
+        match ctx.program_group_create(desc) {
+            Err(e @ Error::ProgramGroupCreationFailed { .. }) => {
+                assert!(e.to_string().starts_with(
+                    "Creation of hitgroup program group with entry functions \
+                     [__closesthit__radiance, __intersection__sphere] \
+                     failed: OPTIX_ERROR_INVALID_FUNCTION_USE"
+                ));
+            }
+            Err(e) => panic!("unexpected error {}", e),
+            Ok(_) => panic!("program group creation should have failed"),
+        }
+    }
+
+    #[test]
+    fn batch_create() {
+        let mock = MockBackend::install();
+        let mut ctx = fixtures::device_context();
+        let module = fixtures::module(&mut ctx);
+        let entry = |name| ProgramGroupModule {
+            module: module.clone(),
+            entry_function_name: ustr::ustr(name),
+        };
+        mock.set_compile_log("Info: Compiled 3 program groups\n");
+
+        let descs = vec![
+            ProgramGroupDesc::Raygen(entry("__raygen__main")),
+            ProgramGroupDesc::Miss(entry("__miss__radiance")),
+            ProgramGroupDesc::Callables {
+                dc: Some(entry("__direct_callable__shade")),
+                cc: None,
+            },
+        ];
+        let (pgs, log) = ctx.program_groups_create(&descs).unwrap();
+        assert_eq!(pgs.len(), 3);
+        assert_eq!(log.diagnostics().len(), 1);
+        assert_eq!(mock.live_handles(HandleKind::ProgramGroup), 3);
+        assert_eq!(ctx.object_counts().program_groups.created, 3);
+
+        // a miss program used as a closest hit program is rejected before
+        // anything is created
+        let descs = vec![
+            ProgramGroupDesc::Raygen(entry("__raygen__main")),
+            ProgramGroupDesc::Hitgroup {
+                ch: Some(entry("__miss__radiance")),
+                ah: Some(entry("__anyhit__shadow")),
+                is: None,
+            },
+        ];
+        match ctx.program_groups_create(&descs) {
+            Err(Error::InvalidProgramGroupDesc { index: 1, source }) => {
+                match *source {
+                    Error::InvalidEntryFunctionName { name, prefix } => {
+                        assert_eq!(name, "__miss__radiance");
+                        assert_eq!(prefix, "__closesthit__");
                     }
+                    e => panic!("unexpected error {}", e),
+                }
+            }
+            r => panic!("unexpected result {:?}", r.err()),
+        }
+        assert_eq!(mock.live_handles(HandleKind::ProgramGroup), 3);
+    }
+
+    #[test]
+    fn entry_from_name_expression() {
+        let _mock = MockBackend::install();
+        let mut ctx = fixtures::device_context();
+        let module = fixtures::module(&mut ctx);
+        let mut lowered_names = LoweredNames::new();
+        lowered_names.insert(
+            "__closesthit__radiance<float>",
+            "_Z22__closesthit__radianceIfEvv",
+        );
+
+        let ch = ProgramGroupModule::from_name_expression(
+            &module,
+            &lowered_names,
+            "__closesthit__radiance<float>",
+        )
+        .unwrap();
+        assert_eq!(
+            ch.entry_function_name.as_str(),
+            "_Z22__closesthit__radianceIfEvv"
+        );
+        match ProgramGroupModule::from_name_expression(
+            &module,
+            &lowered_names,
+            "__closesthit__radiance<double>",
+        ) {
+            Err(Error::UnknownNameExpression { expression }) => {
+                assert_eq!(expression, "__closesthit__radiance<double>")
+            }
+            _ => panic!("the name expression should be unknown"),
+        }
 
-                    let pg = super::Ref::new(ProgramGroup { pg, _desc: desc });
-                    Ok((pg, log))
+        // the prefix is checked on the unmangled name
+        let desc = ProgramGroupDesc::Hitgroup {
+            ch: Some(ch.clone()),
+            ah: None,
+            is: None,
+        };
+        assert!(desc.validate().is_ok());
+        let desc = ProgramGroupDesc::Miss(ch);
+        assert!(matches!(
+            desc.validate(),
+            Err(Error::InvalidEntryFunctionName { .. })
+        ));
+        ctx.program_group_create(ProgramGroupDesc::Hitgroup {
+            ch: Some(ProgramGroupModule::new(
+                &module,
+                "_ZN6shader22__closesthit__radianceEv",
+            )),
+            ah: None,
+            is: None,
+        })
+        .unwrap();
+    }
+    #[test]
+    fn validate_checks_every_slot() {
+        let _mock = MockBackend::install();
+        let mut ctx = fixtures::device_context();
+        let module = fixtures::module(&mut ctx);
+        // the entry in slot `wrong` gets a raygen name
+        let entry = |wrong: bool, name| {
+            let name = if wrong { "__raygen__a" } else { name };
+            Some(ProgramGroupModule::new(&module, name))
+        };
+        let hitgroup = |slot| ProgramGroupDesc::Hitgroup {
+            ch: entry(slot == 0, "__closesthit__a"),
+            ah: entry(slot == 1, "__anyhit__a"),
+            is: entry(slot == 2, "__intersection__a"),
+        };
+        let callables = |slot| ProgramGroupDesc::Callables {
+            dc: entry(slot == 0, "__direct_callable__a"),
+            cc: entry(slot == 1, "__continuation_callable__a"),
+        };
+
+        assert!(hitgroup(3).validate().is_ok());
+        assert!(callables(2).validate().is_ok());
+        let invalid = [
+            (hitgroup(0), "__closesthit__"),
+            (hitgroup(1), "__anyhit__"),
+            (hitgroup(2), "__intersection__"),
+            (callables(0), "__direct_callable__"),
+            (callables(1), "__continuation_callable__"),
+        ];
+        for (desc, expected) in &invalid {
+            match desc.validate() {
+                Err(Error::InvalidEntryFunctionName { name, prefix }) => {
+                    assert_eq!(name, "__raygen__a");
+                    assert_eq!(prefix, *expected);
                 }
+                r => panic!("expected InvalidEntryFunctionName, got {:?}", r),
             }
         }
     }
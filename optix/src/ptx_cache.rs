@@ -0,0 +1,542 @@
+//! Caching the PTX compiled from CUDA source on disk, so that it's only
+//! compiled again when something that goes into the compilation changes.
+//!
+//! Entries are addressed by a hash of the source, the name and contents of
+//! every header, the compile options and the compiler version, and stored
+//! as `<hash>.ptx` in the cache directory. The headers include those the
+//! source includes from the `include_dir()`s of the options, which are found
+//! with an `IncludeResolver`. Problems reading or writing the cache are
+//! logged and otherwise treated as misses, so the cache never causes a
+//! compilation to fail.
+//!
+//! ```no_run
+//! use optix::cuda::nvrtc::{Arch, CompileOptions};
+//!
+//! # fn main() -> Result<(), optix::cuda::nvrtc::Error> {
+//! # let source = "";
+//! let mut cache = optix::PtxCache::new("target/ptx-cache")?
+//!     .max_size(64 << 20);
+//! let options = CompileOptions::new().arch(Arch::Compute(70));
+//! let ptx = cache.compile_nvrtc(source, "devicePrograms", &[], &options)?;
+//! # Ok(())
+//! # }
+//! ```
+use super::cuda::nvrtc::{self, CompileOptions, Header};
+use super::include_resolver::IncludeResolver;
+
+use std::ffi::OsStr;
+use std::fs;
+use std::io::{self, Write};
+use std::path::{Path, PathBuf};
+use std::sync::atomic::{AtomicUsize, Ordering};
+use std::time::SystemTime;
+
+const EXTENSION: &str = "ptx";
+
+/// How a `PtxCache` has been used since it was created.
+#[derive(Debug, Default, Copy, Clone, PartialEq, Eq, Hash)]
+pub struct CacheStats {
+    pub hits: u64,
+    pub misses: u64,
+    /// The number of entries removed to keep the cache within its size
+    pub evictions: u64,
+}
+
+/// A directory of PTX, keyed by everything it was compiled from.
+pub struct PtxCache {
+    dir: PathBuf,
+    compiler_version: String,
+    max_size: Option<u64>,
+    stats: CacheStats,
+}
+
+impl PtxCache {
+    /// Create a cache storing PTX compiled by the NVRTC that's loaded in
+    /// `dir`, which is created when the first entry is written.
+    ///
+    /// # Errors
+    /// If the NVRTC version can't be queried.
+    pub fn new<P: AsRef<Path>>(dir: P) -> nvrtc::Result<PtxCache> {
+        let (major, minor) = nvrtc::version()?;
+        Ok(PtxCache::with_compiler_version(
+            dir,
+            format!("nvrtc {}.{}", major, minor),
+        ))
+    }
+
+    /// Create a cache in `dir` for PTX compiled by some other compiler,
+    /// identified by `version`. Entries from a different version are never
+    /// used.
+    pub fn with_compiler_version<P: AsRef<Path>, S: Into<String>>(
+        dir: P,
+        version: S,
+    ) -> PtxCache {
+        PtxCache {
+            dir: dir.as_ref().to_path_buf(),
+            compiler_version: version.into(),
+            max_size: None,
+            stats: CacheStats::default(),
+        }
+    }
+
+    /// Remove the least recently used entries whenever the total size of
+    /// the cache would be more than `bytes`. By default the cache grows
+    /// without bounds.
+    pub fn max_size(mut self, bytes: u64) -> PtxCache {
+        self.max_size = Some(bytes);
+        self
+    }
+
+    pub fn dir(&self) -> &Path {
+        &self.dir
+    }
+
+    pub fn stats(&self) -> CacheStats {
+        self.stats
+    }
+
+    /// The key an entry compiled from these inputs is stored under, or
+    /// `None` if a header the source includes from the include directories
+    /// of `options` can't be read, in which case nothing is cached.
+    pub fn key(
+        &self,
+        source: &str,
+        headers: &[Header],
+        options: &CompileOptions,
+    ) -> Option<String> {
+        // the headers given are found first, as NVRTC does
+        let includes = options
+            .include_dirs()
+            .iter()
+            .fold(IncludeResolver::new(), |includes, dir| {
+                includes.search_path(dir)
+            });
+        let includes = headers.iter().fold(includes, |includes, header| {
+            includes.virtual_file(header.name.clone(), header.contents.clone())
+        });
+        let resolved = match includes.resolve(source, None) {
+            Ok(resolved) => resolved,
+            Err(e) => {
+                log::warn!("Not caching PTX: {}", e);
+                return None;
+            }
+        };
+        let found: Vec<&Header> = resolved
+            .headers
+            .iter()
+            .filter(|found| headers.iter().all(|h| h.name != found.name))
+            .collect();
+
+        let mut hasher = Fnv128::new();
+        hasher.write_str(&self.compiler_version);
+        hasher.write_str(source);
+        let options = options.to_strings();
+        hasher.write_u64(options.len() as u64);
+        for option in &options {
+            hasher.write_str(option);
+        }
+        hasher.write_u64(headers.len() as u64);
+        for header in headers {
+            hasher.write_str(&header.name);
+            hasher.write_str(&header.contents);
+        }
+        hasher.write_u64(found.len() as u64);
+        for header in found {
+            hasher.write_str(&header.name);
+            hasher.write_str(&header.contents);
+        }
+        Some(format!("{:032x}", hasher.finish()))
+    }
+
+    /// Look up the PTX for `source` compiled with `headers` and `options`,
+    /// calling `compile` to produce and store it if it isn't in the cache.
+    ///
+    /// # Errors
+    /// Only the errors returned by `compile`, which are not cached.
+    pub fn get_or_compile<F, E>(
+        &mut self,
+        source: &str,
+        headers: &[Header],
+        options: &CompileOptions,
+        compile: F,
+    ) -> Result<String, E>
+    where
+        F: FnOnce() -> Result<String, E>,
+    {
+        let path = match self.key(source, headers, options) {
+            Some(key) => self.path(&key),
+            None => {
+                self.stats.misses += 1;
+                return compile();
+            }
+        };
+        match fs::read_to_string(&path) {
+            Ok(ptx) => {
+                self.stats.hits += 1;
+                // the modification time is the last use for eviction
+                if let Err(e) = touch(&path) {
+                    log::warn!("Could not touch {}: {}", path.display(), e);
+                }
+                return Ok(ptx);
+            }
+            Err(e) if e.kind() != io::ErrorKind::NotFound => {
+                log::warn!("Could not read {}: {}", path.display(), e);
+            }
+            Err(_) => (),
+        }
+
+        self.stats.misses += 1;
+        let ptx = compile()?;
+        match self.write(&path, &ptx) {
+            Ok(()) => self.evict(&path),
+            Err(e) => {
+                log::warn!("Could not write {}: {}", path.display(), e)
+            }
+        }
+        Ok(ptx)
+    }
+
+    /// Look up the PTX for `source`, compiling it with NVRTC if it isn't in
+    /// the cache.
+    ///
+    /// # Errors
+    /// If compilation fails.
+    pub fn compile_nvrtc(
+        &mut self,
+        source: &str,
+        name: &str,
+        headers: &[Header],
+        options: &CompileOptions,
+    ) -> nvrtc::Result<String> {
+        self.get_or_compile(source, headers, options, || {
+            let mut program = nvrtc::Program::new(source, name, headers)?;
+            program.compile(options)?;
+            program.get_ptx()
+        })
+    }
+
+    /// Remove every entry from the cache.
+    pub fn clear(&mut self) -> io::Result<()> {
+        for (path, _, _) in self.entries()? {
+            fs::remove_file(path)?;
+        }
+        Ok(())
+    }
+
+    /// The total size of the entries in the cache, in bytes.
+    pub fn size(&self) -> io::Result<u64> {
+        Ok(self.entries()?.iter().map(|(_, size, _)| size).sum())
+    }
+
+    fn path(&self, key: &str) -> PathBuf {
+        self.dir.join(key).with_extension(EXTENSION)
+    }
+
+    /// Write `ptx` to a temporary file and rename it to `path`, so that
+    /// other processes never see a partly written entry.
+    fn write(&self, path: &Path, ptx: &str) -> io::Result<()> {
+        static COUNTER: AtomicUsize = AtomicUsize::new(0);
+        fs::create_dir_all(&self.dir)?;
+        let tmp = path.with_extension(format!(
+            "{}.{}-{}.tmp",
+            EXTENSION,
+            std::process::id(),
+            COUNTER.fetch_add(1, Ordering::Relaxed)
+        ));
+        let result = fs::File::create(&tmp)
+            .and_then(|mut file| {
+                file.write_all(ptx.as_bytes())?;
+                file.sync_all()
+            })
+            .and_then(|_| fs::rename(&tmp, path));
+        if result.is_err() {
+            let _ = fs::remove_file(&tmp);
+        }
+        result
+    }
+
+    /// Remove the least recently used entries other than `keep` until the
+    /// cache is within its maximum size.
+    fn evict(&mut self, keep: &Path) {
+        let max_size = match self.max_size {
+            Some(max_size) => max_size,
+            None => return,
+        };
+        let mut entries = match self.entries() {
+            Ok(entries) => entries,
+            Err(e) => {
+                log::warn!("Could not list {}: {}", self.dir.display(), e);
+                return;
+            }
+        };
+        let mut size: u64 = entries.iter().map(|(_, size, _)| size).sum();
+        entries.sort_by_key(|(_, _, modified)| *modified);
+        for (path, entry_size, _) in entries {
+            if size <= max_size {
+                break;
+            }
+            if path == keep {
+                continue;
+            }
+            match fs::remove_file(&path) {
+                Ok(()) => {
+                    size -= entry_size;
+                    self.stats.evictions += 1;
+                }
+                Err(e) => {
+                    log::warn!("Could not remove {}: {}", path.display(), e)
+                }
+            }
+        }
+    }
+
+    /// The path, size and modification time of every entry.
+    fn entries(&self) -> io::Result<Vec<(PathBuf, u64, SystemTime)>> {
+        let dir = match fs::read_dir(&self.dir) {
+            Ok(dir) => dir,
+            Err(e) if e.kind() == io::ErrorKind::NotFound => {
+                return Ok(Vec::new())
+            }
+            Err(e) => return Err(e),
+        };
+        let mut entries = Vec::new();
+        for entry in dir {
+            let path = entry?.path();
+            if path.extension() == Some(OsStr::new(EXTENSION)) {
+                let metadata = fs::metadata(&path)?;
+                entries.push((path, metadata.len(), metadata.modified()?));
+            }
+        }
+        Ok(entries)
+    }
+}
+
+fn touch(path: &Path) -> io::Result<()> {
+    fs::File::options()
+        .write(true)
+        .open(path)?
+        .set_modified(SystemTime::now())
+}
+
+/// 128-bit FNV-1a, which unlike `std`'s hashers is the same across Rust
+/// versions and platforms, as needed for keys stored on disk.
+struct Fnv128(u128);
+
+impl Fnv128 {
+    const OFFSET_BASIS: u128 = 0x6c62272e07bb014262b821756295c58d;
+    const PRIME: u128 = 0x0000000001000000000000000000013b;
+
+    fn new() -> Fnv128 {
+        Fnv128(Fnv128::OFFSET_BASIS)
+    }
+
+    fn write(&mut self, bytes: &[u8]) {
+        for byte in bytes {
+            self.0 ^= u128::from(*byte);
+            self.0 = self.0.wrapping_mul(Fnv128::PRIME);
+        }
+    }
+
+    fn write_u64(&mut self, n: u64) {
+        self.write(&n.to_le_bytes());
+    }
+
+    /// Write `s` prefixed with its length, so that e.g. ("ab", "c") and
+    /// ("a", "bc") hash differently.
+    fn write_str(&mut self, s: &str) {
+        self.write_u64(s.len() as u64);
+        self.write(s.as_bytes());
+    }
+
+    fn finish(&self) -> u128 {
+        self.0
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{CacheStats, Fnv128, PtxCache};
+    use crate::cuda::nvrtc::{Arch, CompileOptions, Header};
+    use std::cell::Cell;
+    use std::path::PathBuf;
+    use std::time::{Duration, SystemTime};
+
+    fn cache_dir(name: &str) -> PathBuf {
+        let dir = std::env::temp_dir().join(format!(
+            "optix-rs-ptx-cache-{}-{}",
+            name,
+            std::process::id()
+        ));
+        let _ = std::fs::remove_dir_all(&dir);
+        dir
+    }
+
+    fn header(contents: &str) -> Header {
+        Header {
+            name: "params.h".into(),
+            contents: contents.into(),
+        }
+    }
+
+    #[test]
+    fn fnv() {
+        // test vectors from the FNV reference implementation
+        let hash = |s: &str| {
+            let mut hasher = Fnv128::new();
+            hasher.write(s.as_bytes());
+            hasher.finish()
+        };
+        assert_eq!(hash(""), 0x6c62272e07bb014262b821756295c58d);
+        assert_eq!(hash("a"), 0xd228cb696f1a8caf78912b704e4a8964);
+    }
+
+    #[test]
+    fn keys() {
+        let cache = PtxCache::with_compiler_version("unused", "nvrtc 11.4");
+        let options = CompileOptions::new().arch(Arch::Compute(70));
+        let key = cache.key("source", &[header("a")], &options);
+        assert_eq!(key.as_ref().map(String::len), Some(32));
+        assert_eq!(key, cache.key("source", &[header("a")], &options));
+
+        // everything that goes into compilation changes the key
+        assert_ne!(key, cache.key("source2", &[header("a")], &options));
+        assert_ne!(key, cache.key("source", &[header("b")], &options));
+        assert_ne!(key, cache.key("source", &[], &options));
+        assert_ne!(
+            key,
+            cache.key("source", &[header("a")], &CompileOptions::new())
+        );
+        let other = PtxCache::with_compiler_version("unused", "nvrtc 11.5");
+        assert_ne!(key, other.key("source", &[header("a")], &options));
+    }
+
+    #[test]
+    fn keys_include_found_headers() {
+        let dir = cache_dir("includes");
+        let include = dir.join("include");
+        std::fs::create_dir_all(&include).unwrap();
+        std::fs::write(include.join("common.h"), "#define A 1").unwrap();
+        let cache = PtxCache::with_compiler_version(&dir, "test");
+        let options = CompileOptions::new().include_dir(&include);
+        let source = "#include \"common.h\"\n#include <nested/common.h>";
+
+        let key = cache.key(source, &[], &options);
+        assert!(key.is_some());
+        std::fs::write(include.join("common.h"), "#define A 2").unwrap();
+        let changed = cache.key(source, &[], &options);
+        assert_ne!(key, changed);
+
+        // headers passed explicitly take the place of the files
+        let explicit = Header {
+            name: "common.h".into(),
+            contents: "#define A 3".into(),
+        };
+        let key = cache.key(source, std::slice::from_ref(&explicit), &options);
+        std::fs::write(include.join("common.h"), "#define A 4").unwrap();
+        assert_eq!(key, cache.key(source, &[explicit], &options));
+
+        // a header that can't be read can't be keyed on
+        std::fs::create_dir_all(include.join("nested")).unwrap();
+        std::fs::write(include.join("nested/common.h"), [0xff, 0xfe]).unwrap();
+        assert_eq!(cache.key(source, &[], &options), None);
+
+        std::fs::remove_dir_all(&dir).unwrap();
+    }
+
+    #[test]
+    fn hits_and_misses() {
+        let dir = cache_dir("hits");
+        let mut cache = PtxCache::with_compiler_version(&dir, "test");
+        let options = CompileOptions::new();
+        let compiled = Cell::new(0);
+        let mut compile = |source: &str| {
+            cache.get_or_compile(source, &[], &options, || {
+                compiled.set(compiled.get() + 1);
+                if source == "broken" {
+                    Err("error: expected a declaration".to_string())
+                } else {
+                    Ok(format!("// compiled from {}", source))
+                }
+            })
+        };
+
+        assert_eq!(compile("a").unwrap(), "// compiled from a");
+        assert_eq!(compile("a").unwrap(), "// compiled from a");
+        assert_eq!(compile("b").unwrap(), "// compiled from b");
+        assert!(compile("broken").is_err());
+        assert!(compile("broken").is_err());
+        assert_eq!(compiled.get(), 4);
+        assert_eq!(
+            cache.stats(),
+            CacheStats {
+                hits: 1,
+                misses: 4,
+                evictions: 0,
+            }
+        );
+
+        // entries persist, and failures aren't stored
+        let mut cache = PtxCache::with_compiler_version(&dir, "test");
+        let ptx = cache
+            .get_or_compile("b", &[], &options, || -> Result<_, ()> {
+                panic!("should be cached")
+            })
+            .unwrap();
+        assert_eq!(ptx, "// compiled from b");
+        assert_eq!(
+            cache.size().unwrap(),
+            2 * "// compiled from a".len() as u64
+        );
+        // and no temporary files are left behind
+        assert_eq!(std::fs::read_dir(&dir).unwrap().count(), 2);
+
+        cache.clear().unwrap();
+        assert_eq!(cache.size().unwrap(), 0);
+        std::fs::remove_dir_all(&dir).unwrap();
+    }
+
+    #[test]
+    fn evict_least_recently_used() {
+        let dir = cache_dir("evict");
+        let entry_size = "// compiled from a".len() as u64;
+        let mut cache = PtxCache::with_compiler_version(&dir, "test")
+            .max_size(2 * entry_size);
+        let options = CompileOptions::new();
+        let compile = |cache: &mut PtxCache, source: &str| {
+            cache
+                .get_or_compile(source, &[], &options, || {
+                    Ok::<_, ()>(format!("// compiled from {}", source))
+                })
+                .unwrap();
+            // make the order of uses visible on file systems with coarse
+            // timestamps
+            let uses = cache.stats().hits + cache.stats().misses;
+            let path = cache.path(&cache.key(source, &[], &options).unwrap());
+            std::fs::File::options()
+                .write(true)
+                .open(path)
+                .unwrap()
+                .set_modified(
+                    SystemTime::now() - Duration::from_secs(100 - uses),
+                )
+                .unwrap();
+        };
+
+        compile(&mut cache, "a");
+        compile(&mut cache, "b");
+        // using a makes b the least recently used
+        compile(&mut cache, "a");
+        compile(&mut cache, "c");
+        assert_eq!(cache.stats().evictions, 1);
+        assert_eq!(cache.size().unwrap(), 2 * entry_size);
+
+        let misses = cache.stats().misses;
+        compile(&mut cache, "a");
+        compile(&mut cache, "c");
+        assert_eq!(cache.stats().misses, misses);
+        compile(&mut cache, "b");
+        assert_eq!(cache.stats().misses, misses + 1);
+
+        std::fs::remove_dir_all(&dir).unwrap();
+    }
+}
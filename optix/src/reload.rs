@@ -0,0 +1,667 @@
+//! Rebuilding a pipeline when its CUDA source changes, so that device
+//! programs can be edited while an application is running.
+//!
+//! ```no_run
+//! use optix::cuda::nvrtc::{Arch, CompileOptions};
+//!
+//! # fn main() -> Result<(), optix::Error> {
+//! # let mut ctx: optix::DeviceContext = unimplemented!();
+//! # let module_compile_options = unimplemented!();
+//! # let pipeline_compile_options = unimplemented!();
+//! # let link_options = unimplemented!();
+//! let mut reloader = optix::PipelineReloader::new(
+//!     "src/devicePrograms.cu",
+//!     module_compile_options,
+//!     pipeline_compile_options,
+//!     link_options,
+//! )
+//! .header("src/launch_params.h")
+//! .compute_stack_size(1, 0, 0, 1)
+//! .nvrtc_options(
+//!     CompileOptions::new()
+//!         .arch(Arch::Compute(70))
+//!         .include_dir("/opt/optix/include"),
+//! )
+//! .log_callback(|log| eprintln!("{}", log));
+//!
+//! loop {
+//!     // keeps the previous pipeline if the new source doesn't compile
+//!     if let Err(e) = reloader.reload_if_changed(&mut ctx) {
+//!         eprintln!("{}", e);
+//!     }
+//!     if let Some(programs) = reloader.programs() {
+//!         // launch programs.pipeline
+//!     }
+//! }
+//! # }
+//! ```
+use super::cuda::nvrtc::{self, CompileOptions, Header};
+use super::device_context::DeviceContext;
+use super::error::Error;
+use super::include_resolver::IncludeResolver;
+use super::module::{ModuleCompileOptions, ModuleRef, PipelineCompileOptions};
+use super::pipeline::{PipelineLinkOptions, PipelineRef};
+use super::program_group::{ProgramGroupDesc, ProgramGroupRef};
+use super::shader_binding_table::{pack_header, SbtRecordHeader};
+use super::stack_size::PipelineStackSize;
+use super::CompileLog;
+type Result<T, E = Error> = std::result::Result<T, E>;
+
+use std::path::{Path, PathBuf};
+use std::time::SystemTime;
+
+/// Compiles CUDA source with the given file name and headers to PTX,
+/// returning the PTX and the compiler's log. Compilers other than NVRTC can
+/// report a failed compilation with `nvrtc::Error::from_log()`.
+pub type PtxCompiler = Box<
+    dyn FnMut(&str, &str, &[Header]) -> nvrtc::Result<(String, String)> + Send,
+>;
+
+/// A compiler that compiles with NVRTC, passing it `options`.
+pub fn nvrtc_compiler(options: CompileOptions) -> PtxCompiler {
+    Box::new(move |source, name, headers| {
+        let mut program = nvrtc::Program::new(source, name, headers)?;
+        program.compile(&options)?;
+        Ok((program.get_ptx()?, program.get_program_log()?))
+    })
+}
+
+/// The logs from one attempt at rebuilding a pipeline, as passed to the
+/// callback set with `PipelineReloader::log_callback()`. Stages that weren't
+/// reached have no log.
+#[derive(Debug, Clone, Default)]
+pub struct ReloadLog {
+    pub ptx: Option<String>,
+    pub module: Option<CompileLog>,
+    pub program_groups: Option<CompileLog>,
+    pub pipeline: Option<CompileLog>,
+}
+
+impl std::fmt::Display for ReloadLog {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        let logs = [
+            ("PTX compilation", self.ptx.as_deref()),
+            ("module", self.module.as_deref()),
+            ("program groups", self.program_groups.as_deref()),
+            ("pipeline", self.pipeline.as_deref()),
+        ];
+        for (stage, log) in logs.iter() {
+            if let Some(log) = log.filter(|log| !log.trim().is_empty()) {
+                writeln!(f, "{}:\n{}", stage, log.trim_end())?;
+            }
+        }
+        Ok(())
+    }
+}
+
+/// Everything built from the source by the last successful reload.
+pub struct ReloadedPrograms {
+    pub module: ModuleRef,
+    /// One group for each program in the module, as made by
+    /// `ProgramGroupDesc::from_entry_points()`
+    pub program_groups: Vec<ProgramGroupRef>,
+    /// The SBT record header of each of `program_groups`, to patch into the
+    /// records of an existing shader binding table
+    pub sbt_headers: Vec<SbtRecordHeader>,
+    pub pipeline: PipelineRef,
+}
+
+type LogCallback = Box<dyn FnMut(&ReloadLog) + Send>;
+
+/// How the stack size of each rebuilt pipeline is set, as the arguments to
+/// `DeviceContext::pipeline_set_stack_size()` or
+/// `DeviceContext::pipeline_compute_stack_size()`.
+#[derive(Debug, Copy, Clone)]
+enum StackSizeSetting {
+    Fixed {
+        stack_size: PipelineStackSize,
+        max_traversable_graph_depth: u32,
+    },
+    Computed {
+        max_trace_depth: u32,
+        max_cc_depth: u32,
+        max_dc_depth: u32,
+        max_traversable_graph_depth: u32,
+    },
+}
+
+/// Watches a CUDA source file and the headers it includes, and rebuilds the
+/// module, program groups and pipeline from it when any of them changes.
+///
+/// The headers are found with an `IncludeResolver`, looking in the virtual
+/// headers, next to the source, and in the directories added with
+/// `include_dir()`. Changes are found by polling the modification times of
+/// the files, so call `reload_if_changed()` regularly, e.g. once per frame.
+/// If a rebuild fails the programs from the previous successful build stay
+/// in use.
+pub struct PipelineReloader {
+    source: PathBuf,
+    headers: Vec<PathBuf>,
+    includes: IncludeResolver,
+    module_compile_options: ModuleCompileOptions,
+    pipeline_compile_options: PipelineCompileOptions,
+    link_options: PipelineLinkOptions,
+    compiler: PtxCompiler,
+    log_callback: Option<LogCallback>,
+    stack_size: Option<StackSizeSetting>,
+    // the files the last build depended on, and their modification times
+    // when they were read
+    watched: Vec<(PathBuf, Option<SystemTime>)>,
+    programs: Option<ReloadedPrograms>,
+}
+
+impl PipelineReloader {
+    /// Create a reloader for the CUDA source file `source`, compiled with
+    /// NVRTC without any options until changed with `nvrtc_options()` or
+    /// `compiler()`. Nothing is built until the first call to `reload()` or
+    /// `reload_if_changed()`.
+    pub fn new<P: AsRef<Path>>(
+        source: P,
+        module_compile_options: ModuleCompileOptions,
+        pipeline_compile_options: PipelineCompileOptions,
+        link_options: PipelineLinkOptions,
+    ) -> PipelineReloader {
+        PipelineReloader {
+            source: source.as_ref().to_path_buf(),
+            headers: Vec::new(),
+            includes: IncludeResolver::new(),
+            module_compile_options,
+            pipeline_compile_options,
+            link_options,
+            compiler: nvrtc_compiler(CompileOptions::new()),
+            log_callback: None,
+            stack_size: None,
+            watched: Vec::new(),
+            programs: None,
+        }
+    }
+
+    /// Watch the header file at `path` and pass it to the compiler, which
+    /// finds it by its file name, e.g. `#include "launch_params.h"`, whether
+    /// the source includes it or not.
+    pub fn header<P: AsRef<Path>>(mut self, path: P) -> PipelineReloader {
+        self.headers.push(path.as_ref().to_path_buf());
+        self
+    }
+
+    /// Look for included headers in `dir`, after the directory of the file
+    /// that includes them.
+    pub fn include_dir<P: AsRef<Path>>(mut self, dir: P) -> PipelineReloader {
+        self.includes = self.includes.search_path(dir);
+        self
+    }
+
+    /// Provide a header that isn't read from a file, such as one generated
+    /// from Rust types, to be included by its name.
+    pub fn virtual_header(mut self, header: Header) -> PipelineReloader {
+        self.includes =
+            self.includes.virtual_file(header.name, header.contents);
+        self
+    }
+
+    /// Compile with NVRTC, passing it `options`.
+    pub fn nvrtc_options(self, options: CompileOptions) -> PipelineReloader {
+        self.compiler(nvrtc_compiler(options))
+    }
+
+    /// Compile with `compiler` instead of NVRTC.
+    pub fn compiler(mut self, compiler: PtxCompiler) -> PipelineReloader {
+        self.compiler = compiler;
+        self
+    }
+
+    /// Call `cb` with the logs of every rebuild, whether it succeeds or not.
+    pub fn log_callback<F>(mut self, cb: F) -> PipelineReloader
+    where
+        F: FnMut(&ReloadLog) + Send + 'static,
+    {
+        self.log_callback = Some(Box::new(cb));
+        self
+    }
+
+    /// Set the stack size of every rebuilt pipeline to `stack_size`, before
+    /// it is shared. See `DeviceContext::pipeline_set_stack_size()`.
+    /// Otherwise OptiX's default stack size is used.
+    pub fn stack_size(
+        mut self,
+        stack_size: PipelineStackSize,
+        max_traversable_graph_depth: u32,
+    ) -> PipelineReloader {
+        self.stack_size = Some(StackSizeSetting::Fixed {
+            stack_size,
+            max_traversable_graph_depth,
+        });
+        self
+    }
+
+    /// Compute the stack size of every rebuilt pipeline from the stack sizes
+    /// of its programs and these call depths, and set it before the pipeline
+    /// is shared. See `DeviceContext::pipeline_compute_stack_size()`.
+    pub fn compute_stack_size(
+        mut self,
+        max_trace_depth: u32,
+        max_cc_depth: u32,
+        max_dc_depth: u32,
+        max_traversable_graph_depth: u32,
+    ) -> PipelineReloader {
+        self.stack_size = Some(StackSizeSetting::Computed {
+            max_trace_depth,
+            max_cc_depth,
+            max_dc_depth,
+            max_traversable_graph_depth,
+        });
+        self
+    }
+
+    /// The programs from the last successful rebuild, if there was one.
+    pub fn programs(&self) -> Option<&ReloadedPrograms> {
+        self.programs.as_ref()
+    }
+
+    /// Whether the source or any of the headers have been modified since
+    /// they were last read, or have never been read.
+    pub fn is_stale(&self) -> bool {
+        self.watched.is_empty()
+            || self
+                .watched
+                .iter()
+                .any(|(path, modified)| modification_time(path) != *modified)
+    }
+
+    /// The files the last build read, or tried to read: the source, and the
+    /// headers it includes, directly or indirectly.
+    pub fn dependencies(&self) -> impl Iterator<Item = &Path> {
+        self.watched.iter().map(|(path, _)| path.as_path())
+    }
+
+    /// Rebuild if `is_stale()`. Returns whether a rebuild succeeded.
+    ///
+    /// # Errors
+    /// See `reload()`. The files aren't read again until they change.
+    pub fn reload_if_changed(
+        &mut self,
+        ctx: &mut DeviceContext,
+    ) -> Result<bool> {
+        if self.is_stale() {
+            self.reload(ctx).map(|_| true)
+        } else {
+            Ok(false)
+        }
+    }
+
+    /// Read the source and headers and rebuild everything from them.
+    ///
+    /// # Errors
+    /// If a file can't be read, compilation to PTX fails, or creating the
+    /// module, program groups or pipeline fails. The programs from the
+    /// previous build are kept in every case.
+    pub fn reload(
+        &mut self,
+        ctx: &mut DeviceContext,
+    ) -> Result<&ReloadedPrograms> {
+        // taken before reading so that a change made while reading is
+        // picked up by the next call. The headers the source includes are
+        // added once they're found.
+        self.watched = std::iter::once(&self.source)
+            .chain(&self.headers)
+            .map(|path| (path.clone(), modification_time(path)))
+            .collect();
+
+        let mut log = ReloadLog::default();
+        let result = self.build(ctx, &mut log);
+        if let Some(cb) = &mut self.log_callback {
+            cb(&log);
+        }
+        self.programs = Some(result?);
+        Ok(self.programs.as_ref().unwrap())
+    }
+
+    // ModuleCompileOptions is only Copy before 7.4
+    #[allow(clippy::clone_on_copy)]
+    fn build(
+        &mut self,
+        ctx: &mut DeviceContext,
+        log: &mut ReloadLog,
+    ) -> Result<ReloadedPrograms> {
+        let source = read(&self.source)?;
+        let resolved = self.includes.resolve(&source, self.source.parent())?;
+        let mut headers = resolved.headers;
+        for path in resolved.dependencies {
+            if self.watched.iter().all(|(p, _)| *p != path) {
+                let modified = modification_time(&path);
+                self.watched.push((path, modified));
+            }
+        }
+        for path in &self.headers {
+            let name = path
+                .file_name()
+                .map(|name| name.to_string_lossy().into_owned())
+                .unwrap_or_default();
+            // takes the place of a header found under the same name
+            headers.retain(|h| h.name != name);
+            headers.push(Header {
+                name,
+                contents: read(path)?,
+            });
+        }
+
+        // diagnostics name the source by the name it's compiled under
+        let name = self
+            .source
+            .file_name()
+            .map(|name| name.to_string_lossy().into_owned())
+            .unwrap_or_default();
+        let ptx = match (self.compiler)(&source, &name, &headers) {
+            Ok((ptx, ptx_log)) => {
+                log.ptx = Some(ptx_log);
+                ptx
+            }
+            Err(source) => {
+                log.ptx = Some(source.to_string());
+                return Err(Error::PtxCompilationFailed {
+                    path: self.source.clone(),
+                    source,
+                });
+            }
+        };
+
+        let (module, module_log) = ctx
+            .module_create_from_ptx(
+                self.module_compile_options.clone(),
+                &self.pipeline_compile_options,
+                &ptx,
+            )
+            .inspect_err(|e| log.module = failure_log(e))?;
+        log.module = Some(module_log);
+
+        let descs =
+            ProgramGroupDesc::from_entry_points(std::slice::from_ref(&module));
+        let (program_groups, pg_log) = ctx
+            .program_groups_create(&descs)
+            .inspect_err(|e| log.program_groups = failure_log(e))?;
+        log.program_groups = Some(pg_log);
+
+        let (mut pipeline, pipeline_log) = ctx
+            .pipeline_create(
+                &self.pipeline_compile_options,
+                self.link_options,
+                &program_groups,
+            )
+            .inspect_err(|e| log.pipeline = failure_log(e))?;
+        log.pipeline = Some(pipeline_log);
+
+        // has to happen while `pipeline` is the only reference to it
+        match self.stack_size {
+            Some(StackSizeSetting::Fixed {
+                stack_size,
+                max_traversable_graph_depth,
+            }) => ctx.pipeline_set_stack_size(
+                &mut pipeline,
+                stack_size.direct_callable_stack_size_from_traversal,
+                stack_size.direct_callable_stack_size_from_state,
+                stack_size.continuation_stack_size,
+                max_traversable_graph_depth,
+            )?,
+            Some(StackSizeSetting::Computed {
+                max_trace_depth,
+                max_cc_depth,
+                max_dc_depth,
+                max_traversable_graph_depth,
+            }) => {
+                ctx.pipeline_compute_stack_size(
+                    &mut pipeline,
+                    max_trace_depth,
+                    max_cc_depth,
+                    max_dc_depth,
+                    max_traversable_graph_depth,
+                )?;
+            }
+            None => (),
+        }
+
+        let sbt_headers = program_groups
+            .iter()
+            .map(pack_header)
+            .collect::<Result<Vec<_>>>()?;
+
+        Ok(ReloadedPrograms {
+            module,
+            program_groups,
+            sbt_headers,
+            pipeline,
+        })
+    }
+}
+
+fn modification_time(path: &Path) -> Option<SystemTime> {
+    std::fs::metadata(path).and_then(|m| m.modified()).ok()
+}
+
+/// The log of a failed module, program group or pipeline creation.
+fn failure_log(e: &Error) -> Option<CompileLog> {
+    match e {
+        Error::ModuleCreationFailed { log, .. }
+        | Error::ProgramGroupCreationFailed { log, .. }
+        | Error::ProgramGroupsCreationFailed { log, .. }
+        | Error::PipelineCreationFailed { log, .. } => Some(log.clone()),
+        _ => None,
+    }
+}
+
+fn read(path: &Path) -> Result<String> {
+    std::fs::read_to_string(path).map_err(|source| Error::ReadSourceFailed {
+        path: path.to_path_buf(),
+        source,
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{PipelineReloader, PipelineStackSize, ReloadLog};
+    use crate::backend::mock::{fixtures, HandleKind, MockBackend};
+    use crate::cuda::nvrtc;
+    use crate::error::Error;
+    use crate::{CompileDebugLevel, PipelineLinkOptions};
+    use std::fs;
+    use std::path::Path;
+    use std::sync::{Arc, Mutex};
+    use std::time::{Duration, SystemTime};
+
+    const PTX: &str = ".visible .entry __raygen__main()\n{\n\tret;\n}\n\
+                       .visible .entry __miss__radiance()\n{\n\tret;\n}\n";
+
+    /// Write `contents` to `path` with a modification time `secs` seconds in
+    /// the future, so that the change is seen even on file systems with
+    /// coarse timestamps.
+    fn write(path: &Path, contents: &str, secs: u64) {
+        fs::write(path, contents).unwrap();
+        fs::File::options()
+            .write(true)
+            .open(path)
+            .unwrap()
+            .set_modified(SystemTime::now() + Duration::from_secs(secs))
+            .unwrap();
+    }
+
+    #[test]
+    fn reload_when_changed() {
+        let mock = MockBackend::install();
+        let mut ctx = fixtures::device_context();
+        let dir = std::env::temp_dir()
+            .join(format!("optix-rs-reload-{}", std::process::id()));
+        fs::create_dir_all(&dir).unwrap();
+        let source = dir.join("programs.cu");
+        let header = dir.join("params.h");
+        write(&source, PTX, 0);
+        write(&header, "struct Params {};", 0);
+
+        let logs: Arc<Mutex<Vec<ReloadLog>>> = Arc::default();
+        let link_options = PipelineLinkOptions {
+            max_trace_depth: 1,
+            debug_level: CompileDebugLevel::None,
+            #[cfg(not(optix_7_3))]
+            override_uses_motion_blur: false,
+        };
+        let mut reloader = PipelineReloader::new(
+            &source,
+            fixtures::module_compile_options(),
+            fixtures::pipeline_compile_options(),
+            link_options,
+        )
+        .header(&header)
+        // the source is PTX already, unless it's broken
+        .compiler(Box::new(|source, name, headers| {
+            assert_eq!(name, "programs.cu");
+            assert_eq!(headers[0].name, "params.h");
+            if source.starts_with("broken") {
+                Err(nvrtc::Error::from_log(
+                    "programs.cu(1): error: expected a declaration",
+                ))
+            } else {
+                Ok((source.to_string(), String::new()))
+            }
+        }))
+        .log_callback({
+            let logs = Arc::clone(&logs);
+            move |log| logs.lock().unwrap().push(log.clone())
+        });
+
+        assert!(reloader.is_stale());
+        assert!(reloader.reload_if_changed(&mut ctx).unwrap());
+        assert!(!reloader.reload_if_changed(&mut ctx).unwrap());
+        let programs = reloader.programs().unwrap();
+        assert_eq!(programs.program_groups.len(), 2);
+        assert_eq!(programs.sbt_headers.len(), 2);
+        let first = programs.pipeline.clone();
+
+        // a broken edit keeps the previous pipeline, and isn't compiled
+        // again until the next edit
+        write(&source, "broken", 10);
+        match reloader.reload_if_changed(&mut ctx) {
+            Err(Error::PtxCompilationFailed { source, .. }) => {
+                let diagnostics = source.diagnostics();
+                assert_eq!(diagnostics.len(), 1);
+                assert_eq!(diagnostics[0].file.as_deref(), Some("programs.cu"));
+                assert_eq!(diagnostics[0].message, "expected a declaration");
+            }
+            r => panic!("unexpected result {:?}", r.err()),
+        }
+        assert!(Arc::ptr_eq(&reloader.programs().unwrap().pipeline, &first));
+        assert!(!reloader.is_stale());
+        {
+            let logs = logs.lock().unwrap();
+            assert_eq!(logs.len(), 2);
+            let ptx_log = logs[1].ptx.as_ref().unwrap();
+            assert!(ptx_log.contains("expected a declaration"));
+            assert!(logs[1].module.is_none());
+        }
+
+        write(&source, PTX, 20);
+        assert!(reloader.reload_if_changed(&mut ctx).unwrap());
+        assert!(!Arc::ptr_eq(&reloader.programs().unwrap().pipeline, &first));
+        drop(first);
+        assert_eq!(mock.live_handles(HandleKind::Pipeline), 1);
+        assert_eq!(mock.live_handles(HandleKind::Module), 1);
+
+        fs::remove_dir_all(&dir).unwrap();
+    }
+
+    #[test]
+    fn reload_when_included_header_changes() {
+        let _mock = MockBackend::install();
+        let mut ctx = fixtures::device_context();
+        let dir = std::env::temp_dir()
+            .join(format!("optix-rs-reload-includes-{}", std::process::id()));
+        let include = dir.join("include");
+        fs::create_dir_all(&include).unwrap();
+        let source = dir.join("programs.cu");
+        let nested = include.join("nested.h");
+        write(&source, &format!("#include \"common.h\"\n{}", PTX), 0);
+        write(&include.join("common.h"), "#include \"nested.h\"\n", 0);
+        write(&nested, "struct Params {};", 0);
+
+        let link_options = PipelineLinkOptions {
+            max_trace_depth: 1,
+            debug_level: CompileDebugLevel::None,
+            #[cfg(not(optix_7_3))]
+            override_uses_motion_blur: false,
+        };
+        let mut reloader = PipelineReloader::new(
+            &source,
+            fixtures::module_compile_options(),
+            fixtures::pipeline_compile_options(),
+            link_options,
+        )
+        .include_dir(&include)
+        // leaves out the preprocessor directives
+        .compiler(Box::new(|source, _, headers| {
+            let names: Vec<&str> =
+                headers.iter().map(|h| h.name.as_str()).collect();
+            assert_eq!(names, ["common.h", "nested.h"]);
+            let ptx = source.lines().filter(|l| !l.starts_with('#'));
+            Ok((ptx.collect::<Vec<_>>().join("\n"), String::new()))
+        }));
+
+        assert!(reloader.reload_if_changed(&mut ctx).unwrap());
+        assert_eq!(reloader.dependencies().count(), 3);
+        assert!(!reloader.is_stale());
+        write(&nested, "struct Params { int frame; };", 10);
+        assert!(reloader.is_stale());
+        assert!(reloader.reload_if_changed(&mut ctx).unwrap());
+
+        fs::remove_dir_all(&dir).unwrap();
+    }
+
+    #[test]
+    fn reload_sets_stack_size() {
+        let mock = MockBackend::install();
+        let mut ctx = fixtures::device_context();
+        let dir = std::env::temp_dir()
+            .join(format!("optix-rs-reload-stack-{}", std::process::id()));
+        fs::create_dir_all(&dir).unwrap();
+        let source = dir.join("programs.cu");
+        write(&source, PTX, 0);
+
+        let link_options = PipelineLinkOptions {
+            max_trace_depth: 2,
+            debug_level: CompileDebugLevel::None,
+            #[cfg(not(optix_7_3))]
+            override_uses_motion_blur: false,
+        };
+        let reloader = |source: &Path| {
+            PipelineReloader::new(
+                source,
+                fixtures::module_compile_options(),
+                fixtures::pipeline_compile_options(),
+                link_options,
+            )
+            .compiler(Box::new(|source, _, _| {
+                Ok((source.to_string(), String::new()))
+            }))
+        };
+
+        let stack_size = PipelineStackSize {
+            direct_callable_stack_size_from_traversal: 16,
+            direct_callable_stack_size_from_state: 32,
+            continuation_stack_size: 1024,
+        };
+        let mut fixed = reloader(&source).stack_size(stack_size, 2);
+        let pipeline = &fixed.reload(&mut ctx).unwrap().pipeline;
+        assert_eq!(mock.stack_size(pipeline.pipeline), Some([16, 32, 1024, 2]));
+
+        // the mock's program groups have no stack, so only the depth is set
+        let mut computed = reloader(&source).compute_stack_size(2, 0, 0, 1);
+        let pipeline = &computed.reload(&mut ctx).unwrap().pipeline;
+        assert_eq!(mock.stack_size(pipeline.pipeline), Some([0, 0, 0, 1]));
+
+        // a depth over the device limit fails the rebuild
+        let mut too_deep = reloader(&source).stack_size(stack_size, 1000);
+        assert!(matches!(
+            too_deep.reload(&mut ctx),
+            Err(Error::DeviceLimitExceeded { .. })
+        ));
+        assert!(too_deep.programs().is_none());
+
+        fs::remove_dir_all(&dir).unwrap();
+    }
+}
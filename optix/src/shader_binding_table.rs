@@ -1,6 +1,10 @@
 use super::cuda::{self, Allocator};
+use crate::backend;
 use optix_sys as sys;
 
+use super::error::Error;
+type Result<T, E = Error> = std::result::Result<T, E>;
+
 use super::{DeviceShareable, ProgramGroupRef};
 
 pub trait SbtData {}
@@ -57,7 +61,7 @@ where
         rec_rg: SbtRecord<T>,
         tag: u64,
         allocator: &'a AllocT,
-    ) -> ShaderBindingTableBuilder<'a, 't, AllocT>
+    ) -> Result<ShaderBindingTableBuilder<'a, 't, AllocT>>
     where
         T: DeviceShareable + SbtData + 't,
     {
@@ -73,20 +77,24 @@ where
         rec_rg: SbtRecord<T>,
         tag: u64,
         allocator: &'a AllocT,
-    ) -> ShaderBindingTableBuilder<'a, 't, AllocT>
+    ) -> Result<ShaderBindingTableBuilder<'a, 't, AllocT>>
     where
         AllocT: Allocator,
         T: 't + DeviceShareable + SbtData,
     {
-        let rec_rg_d = rec_rg.to_device_record();
-        ShaderBindingTableBuilder {
+        let rec_rg_d = rec_rg.to_device_record()?;
+        Ok(ShaderBindingTableBuilder {
             rg: cuda::Buffer::with_data(
                 std::slice::from_ref(&rec_rg_d),
                 sys::OptixSbtRecordAlignment,
                 tag,
                 allocator,
             )
-            .unwrap(),
+            .map_err(|source| Error::SbtRecordUploadFailed {
+                source,
+                kind: "raygen",
+                count: 1,
+            })?,
             rec_rg: Box::new(rec_rg),
             ex: None,
             rec_ex: None,
@@ -102,7 +110,7 @@ where
             cl_stride: 0,
             cl_count: 0,
             rec_cl: Vec::new(),
-        }
+        })
     }
 
     pub fn exception_record<T>(
@@ -110,11 +118,11 @@ where
         rec_ex: SbtRecord<T>,
         tag: u64,
         allocator: &'a AllocT,
-    ) -> ShaderBindingTableBuilder<'a, 't, AllocT>
+    ) -> Result<ShaderBindingTableBuilder<'a, 't, AllocT>>
     where
         T: DeviceShareable + SbtData + 't,
     {
-        let rec_ex_d = rec_ex.to_device_record();
+        let rec_ex_d = rec_ex.to_device_record()?;
         self.ex = Some(
             cuda::Buffer::with_data(
                 std::slice::from_ref(&rec_ex_d),
@@ -122,11 +130,15 @@ where
                 tag,
                 allocator,
             )
-            .unwrap(),
+            .map_err(|source| Error::SbtRecordUploadFailed {
+                source,
+                kind: "exception",
+                count: 1,
+            })?,
         );
         self.rec_ex = Some(Box::new(rec_ex));
 
-        self
+        Ok(self)
     }
 
     pub fn miss_records<T>(
@@ -134,12 +146,14 @@ where
         rec_miss: Vec<SbtRecord<T>>,
         tag: u64,
         allocator: &'a AllocT,
-    ) -> ShaderBindingTableBuilder<'a, 't, AllocT>
+    ) -> Result<ShaderBindingTableBuilder<'a, 't, AllocT>>
     where
         T: DeviceShareable + SbtData + 't,
     {
-        let rec_miss_d: Vec<SbtRecordDevice<T::Target>> =
-            rec_miss.iter().map(|r| r.to_device_record()).collect();
+        let rec_miss_d = rec_miss
+            .iter()
+            .map(|r| r.to_device_record())
+            .collect::<Result<Vec<_>>>()?;
         self.ms = Some(
             cuda::Buffer::with_data(
                 &rec_miss_d,
@@ -147,7 +161,11 @@ where
                 tag,
                 allocator,
             )
-            .unwrap(),
+            .map_err(|source| Error::SbtRecordUploadFailed {
+                source,
+                kind: "miss",
+                count: rec_miss.len(),
+            })?,
         );
         self.ms_stride =
             std::mem::size_of::<SbtRecordDevice<T::Target>>() as u32;
@@ -156,7 +174,7 @@ where
             self.rec_ms.push(Box::new(r));
         }
 
-        self
+        Ok(self)
     }
 
     pub fn hitgroup_records<T>(
@@ -164,12 +182,14 @@ where
         rec_hg: Vec<SbtRecord<T>>,
         tag: u64,
         allocator: &'a AllocT,
-    ) -> ShaderBindingTableBuilder<'a, 't, AllocT>
+    ) -> Result<ShaderBindingTableBuilder<'a, 't, AllocT>>
     where
         T: DeviceShareable + SbtData + 't,
     {
-        let rec_hg_d: Vec<SbtRecordDevice<T::Target>> =
-            rec_hg.iter().map(|r| r.to_device_record()).collect();
+        let rec_hg_d = rec_hg
+            .iter()
+            .map(|r| r.to_device_record())
+            .collect::<Result<Vec<_>>>()?;
         self.hg = Some(
             cuda::Buffer::with_data(
                 &rec_hg_d,
@@ -177,7 +197,11 @@ where
                 tag,
                 allocator,
             )
-            .unwrap(),
+            .map_err(|source| Error::SbtRecordUploadFailed {
+                source,
+                kind: "hitgroup",
+                count: rec_hg.len(),
+            })?,
         );
         self.hg_stride =
             std::mem::size_of::<SbtRecordDevice<T::Target>>() as u32;
@@ -186,7 +210,7 @@ where
             self.rec_hg.push(Box::new(r));
         }
 
-        self
+        Ok(self)
     }
 
     pub fn callables_records<T>(
@@ -194,12 +218,14 @@ where
         rec_cl: Vec<SbtRecord<T>>,
         tag: u64,
         allocator: &'a AllocT,
-    ) -> ShaderBindingTableBuilder<'a, 't, AllocT>
+    ) -> Result<ShaderBindingTableBuilder<'a, 't, AllocT>>
     where
         T: DeviceShareable + SbtData + 't,
     {
-        let rec_cl_d: Vec<SbtRecordDevice<T::Target>> =
-            rec_cl.iter().map(|r| r.to_device_record()).collect();
+        let rec_cl_d = rec_cl
+            .iter()
+            .map(|r| r.to_device_record())
+            .collect::<Result<Vec<_>>>()?;
         self.cl = Some(
             cuda::Buffer::with_data(
                 &rec_cl_d,
@@ -207,7 +233,11 @@ where
                 tag,
                 allocator,
             )
-            .unwrap(),
+            .map_err(|source| Error::SbtRecordUploadFailed {
+                source,
+                kind: "callables",
+                count: rec_cl.len(),
+            })?,
         );
         self.cl_stride =
             std::mem::size_of::<SbtRecordDevice<T::Target>>() as u32;
@@ -216,7 +246,7 @@ where
             self.rec_cl.push(Box::new(r));
         }
 
-        self
+        Ok(self)
     }
 
     pub fn build(self) -> ShaderBindingTable<'a, 't, AllocT> {
@@ -283,29 +313,125 @@ where
         }
     }
 
-    pub fn to_device_record(&self) -> SbtRecordDevice<T::Target> {
-        let mut rec = SbtRecordDevice {
-            header: [0u8; 32],
+    pub fn to_device_record(&self) -> Result<SbtRecordDevice<T::Target>> {
+        Ok(SbtRecordDevice {
+            header: pack_header(&self.program_group)?,
             data: self.data.to_device(),
-        };
+        })
+    }
+}
 
-        let res = unsafe {
-            optix_sys::optixSbtRecordPackHeader(
-                self.program_group.sys_ptr(),
-                rec.header.as_mut_ptr() as *mut std::os::raw::c_void,
-            )
-        };
-        if res != optix_sys::OptixResult::OPTIX_SUCCESS {
-            panic!("optixSbtRecordPackHeader failed");
-        }
+/// The header identifying a program group at the start of an SBT record.
+pub type SbtRecordHeader = [u8; sys::OptixSbtRecordHeaderSize];
 
-        rec
+/// Pack the SBT record header for `program_group`, e.g. to update the
+/// records of an existing table after the program group was recreated.
+pub fn pack_header(program_group: &ProgramGroupRef) -> Result<SbtRecordHeader> {
+    let mut header = [0u8; sys::OptixSbtRecordHeaderSize];
+    let res = unsafe {
+        backend::optix_sbt_record_pack_header(
+            program_group.sys_ptr(),
+            header.as_mut_ptr() as *mut std::os::raw::c_void,
+        )
+    };
+    if res != optix_sys::OptixResult::OPTIX_SUCCESS {
+        return Err(Error::SbtRecordPackHeaderFailed { source: res.into() });
     }
+    Ok(header)
 }
 
 #[repr(C)]
 #[repr(align(16))]
 pub struct SbtRecordDevice<T> {
-    header: [u8; 32],
+    header: SbtRecordHeader,
     data: T,
 }
+
+#[cfg(test)]
+mod tests {
+    use super::{SbtRecord, ShaderBindingTable};
+    use crate::backend::mock::{fixtures, MockBackend};
+    use crate::cuda;
+    use crate::error::Error;
+    use crate::{ProgramGroupDesc, ProgramGroupModule};
+    use optix_sys as sys;
+
+    #[test]
+    fn pack_records() {
+        let mock = MockBackend::install();
+        let mut ctx = fixtures::device_context();
+        let module = fixtures::module(&mut ctx);
+        let entry = |name| ProgramGroupModule {
+            module: module.clone(),
+            entry_function_name: ustr::ustr(name),
+        };
+        let (pg_rg, _) = ctx
+            .program_group_create(ProgramGroupDesc::Raygen(entry("__raygen__")))
+            .unwrap();
+        let (pg_ms, _) = ctx
+            .program_group_create(ProgramGroupDesc::Miss(entry("__miss__")))
+            .unwrap();
+
+        let alloc = cuda::Mallocator::new();
+        let sbt = ShaderBindingTable::new(
+            SbtRecord::new(7i32, pg_rg.clone()),
+            0,
+            &alloc,
+        )
+        .unwrap()
+        .miss_records(
+            vec![
+                SbtRecord::new(1i32, pg_ms.clone()),
+                SbtRecord::new(2i32, pg_ms.clone()),
+            ],
+            0,
+            &alloc,
+        )
+        .unwrap()
+        .build();
+
+        // the mock packs the program group handle into the header
+        let rg = mock.read(sbt.sbt.raygenRecord, 36).unwrap();
+        assert_eq!(&rg[..8], &(pg_rg.sys_ptr() as u64).to_le_bytes());
+        assert_eq!(&rg[32..36], &7i32.to_ne_bytes());
+
+        assert_eq!(sbt.sbt.missRecordCount, 2);
+        assert_eq!(sbt.sbt.missRecordStrideInBytes, 48);
+        let ms = mock.read(sbt.sbt.missRecordBase + 48, 36).unwrap();
+        assert_eq!(&ms[..8], &(pg_ms.sys_ptr() as u64).to_le_bytes());
+        assert_eq!(&ms[32..36], &2i32.to_ne_bytes());
+        assert_eq!(sbt.sbt.hitgroupRecordBase, 0);
+    }
+
+    #[test]
+    fn pack_header_failure() {
+        let mock = MockBackend::install();
+        let mut ctx = fixtures::device_context();
+        let module = fixtures::module(&mut ctx);
+        let (pg, _) = ctx
+            .program_group_create(ProgramGroupDesc::Raygen(
+                ProgramGroupModule {
+                    module,
+                    entry_function_name: ustr::ustr("__raygen__"),
+                },
+            ))
+            .unwrap();
+
+        mock.fail_optix(
+            "optix_sbt_record_pack_header",
+            sys::OptixResult::OPTIX_ERROR_INVALID_VALUE,
+        );
+        let alloc = cuda::Mallocator::new();
+        match ShaderBindingTable::new(SbtRecord::new(0i32, pg), 0, &alloc) {
+            Err(Error::SbtRecordPackHeaderFailed { source }) => {
+                assert_eq!(
+                    source.result(),
+                    sys::OptixResult::OPTIX_ERROR_INVALID_VALUE
+                );
+            }
+            Err(e) => panic!("unexpected error {}", e),
+            Ok(_) => panic!("packing the header should have failed"),
+        }
+        assert_eq!(mock.allocation_count(), 0);
+    }
+}
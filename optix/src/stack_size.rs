@@ -0,0 +1,181 @@
+//! Computing pipeline stack sizes from the stack sizes of the programs in it,
+//! as done by the helpers in the SDK's `optix_stack_size.h`.
+//!
+//! Collect the stack sizes of all program groups in a pipeline with
+//! `StackSizes::accumulate()` (or use `Pipeline::stack_sizes()`, which does
+//! this for the groups linked into the pipeline), then turn them into the
+//! arguments for optixPipelineSetStackSize with `StackSizes::compute()`.
+//! `DeviceContext::pipeline_compute_stack_size()` does all of this and
+//! applies the result.
+use optix_sys as sys;
+
+/// The stack sizes needed by each kind of program, in bytes, as returned by
+/// optixProgramGroupGetStackSize. For more than one program group this is the
+/// maximum over all of them.
+#[derive(Debug, Default, Copy, Clone, PartialEq, Eq, Hash)]
+pub struct StackSizes {
+    /// Continuation stack size of ray generation programs
+    pub css_rg: u32,
+    /// Continuation stack size of miss programs
+    pub css_ms: u32,
+    /// Continuation stack size of closest hit programs
+    pub css_ch: u32,
+    /// Continuation stack size of any hit programs
+    pub css_ah: u32,
+    /// Continuation stack size of intersection programs
+    pub css_is: u32,
+    /// Continuation stack size of continuation callable programs
+    pub css_cc: u32,
+    /// Direct stack size of direct callable programs
+    pub dss_dc: u32,
+}
+
+impl From<sys::OptixStackSizes> for StackSizes {
+    fn from(s: sys::OptixStackSizes) -> StackSizes {
+        StackSizes {
+            css_rg: s.cssRG,
+            css_ms: s.cssMS,
+            css_ch: s.cssCH,
+            css_ah: s.cssAH,
+            css_is: s.cssIS,
+            css_cc: s.cssCC,
+            dss_dc: s.dssDC,
+        }
+    }
+}
+
+/// The stack sizes to set on a pipeline with optixPipelineSetStackSize.
+#[derive(Debug, Default, Copy, Clone, PartialEq, Eq, Hash)]
+pub struct PipelineStackSize {
+    /// Direct stack size for direct callables invoked from IS or AH
+    pub direct_callable_stack_size_from_traversal: u32,
+    /// Direct stack size for direct callables invoked from RG, MS or CH
+    pub direct_callable_stack_size_from_state: u32,
+    pub continuation_stack_size: u32,
+}
+
+impl StackSizes {
+    /// Take the maximum of each stack size with those in `other`, as
+    /// `optixUtilAccumulateStackSizes` does.
+    pub fn accumulate(&mut self, other: &StackSizes) {
+        self.css_rg = self.css_rg.max(other.css_rg);
+        self.css_ms = self.css_ms.max(other.css_ms);
+        self.css_ch = self.css_ch.max(other.css_ch);
+        self.css_ah = self.css_ah.max(other.css_ah);
+        self.css_is = self.css_is.max(other.css_is);
+        self.css_cc = self.css_cc.max(other.css_cc);
+        self.dss_dc = self.dss_dc.max(other.dss_dc);
+    }
+
+    /// Compute the pipeline stack sizes for programs with these stack sizes,
+    /// as `optixUtilComputeStackSizes` does.
+    ///
+    /// # Arguments
+    /// * `max_trace_depth` - The maximum depth of recursive optixTrace calls
+    /// * `max_cc_depth` - The maximum depth of call trees of continuation
+    ///   callables
+    /// * `max_dc_depth` - The maximum depth of call trees of direct callables
+    ///
+    /// The result is an upper bound that assumes any program may call any
+    /// other program at every depth, so it is correct but not necessarily
+    /// optimal.
+    pub fn compute(
+        &self,
+        max_trace_depth: u32,
+        max_cc_depth: u32,
+        max_dc_depth: u32,
+    ) -> PipelineStackSize {
+        let css_cc_tree = max_cc_depth * self.css_cc;
+        let css_ch_or_ms_plus_cc_tree =
+            self.css_ch.max(self.css_ms) + css_cc_tree;
+
+        // direct callables are only called from within a call tree, so the
+        // same size is needed from traversal and from state
+        let dss_dc_tree = max_dc_depth * self.dss_dc;
+
+        // every trace but the last can recurse into CH or MS, the last one
+        // may instead go through IS and AH
+        let continuation_stack_size = self.css_rg
+            + css_cc_tree
+            + (max_trace_depth.max(1) - 1) * css_ch_or_ms_plus_cc_tree
+            + max_trace_depth.min(1)
+                * css_ch_or_ms_plus_cc_tree.max(self.css_is + self.css_ah);
+
+        PipelineStackSize {
+            direct_callable_stack_size_from_traversal: dss_dc_tree,
+            direct_callable_stack_size_from_state: dss_dc_tree,
+            continuation_stack_size,
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{PipelineStackSize, StackSizes};
+
+    fn sizes() -> StackSizes {
+        StackSizes {
+            css_rg: 80,
+            css_ms: 16,
+            css_ch: 48,
+            css_ah: 8,
+            css_is: 24,
+            css_cc: 32,
+            dss_dc: 64,
+        }
+    }
+
+    #[test]
+    fn accumulate_takes_maximum() {
+        let mut total = StackSizes::default();
+        total.accumulate(&sizes());
+        assert_eq!(total, sizes());
+
+        total.accumulate(&StackSizes {
+            css_ch: 96,
+            dss_dc: 16,
+            ..Default::default()
+        });
+        assert_eq!(total.css_ch, 96);
+        assert_eq!(total.dss_dc, 64);
+        assert_eq!(total.css_rg, 80);
+    }
+
+    #[test]
+    fn compute() {
+        // no callables and a single trace: RG plus the larger of CH/MS and
+        // IS + AH
+        assert_eq!(
+            sizes().compute(1, 0, 0),
+            PipelineStackSize {
+                direct_callable_stack_size_from_traversal: 0,
+                direct_callable_stack_size_from_state: 0,
+                continuation_stack_size: 80 + 48,
+            }
+        );
+
+        // two levels of recursion and two levels of callables
+        let cc_tree = 2 * 32;
+        assert_eq!(
+            sizes().compute(2, 2, 2),
+            PipelineStackSize {
+                direct_callable_stack_size_from_traversal: 2 * 64,
+                direct_callable_stack_size_from_state: 2 * 64,
+                continuation_stack_size: 80
+                    + cc_tree
+                    + (48 + cc_tree)
+                    + (48 + cc_tree),
+            }
+        );
+
+        // IS + AH can be larger than CH
+        let sizes = StackSizes {
+            css_is: 100,
+            ..sizes()
+        };
+        assert_eq!(sizes.compute(1, 0, 0).continuation_stack_size, 80 + 108);
+
+        // a pipeline that never traces only needs RG and its callables
+        assert_eq!(sizes.compute(0, 1, 0).continuation_stack_size, 80 + 32);
+    }
+}